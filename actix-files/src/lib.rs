@@ -22,7 +22,6 @@ use actix_web::{
 };
 use mime_guess::from_ext;
 
-mod chunked;
 mod directory;
 mod encoding;
 mod error;
@@ -32,12 +31,13 @@ mod path_buf;
 mod range;
 mod service;
 
-pub use crate::chunked::ChunkedReadFile;
 pub use crate::directory::Directory;
 pub use crate::files::Files;
 pub use crate::named::NamedFile;
 pub use crate::range::HttpRange;
 pub use crate::service::FilesService;
+#[doc(hidden)]
+pub use actix_web::dev::ChunkedReadFile;
 
 use self::directory::{directory_listing, DirectoryRenderer};
 use self::error::FilesError;
@@ -1,6 +1,7 @@
 use std::{
     fmt, mem,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -9,7 +10,7 @@ use futures_core::Stream;
 
 use crate::error::Error;
 
-use super::{BodySize, BodyStream, MessageBody, SizedStream};
+use super::{shared_body::shared_bytes, BodySize, BodyStream, MessageBody, SizedStream};
 
 /// Represents various types of HTTP message body.
 pub enum Body {
@@ -120,6 +121,18 @@ impl<'a> From<&'a String> for Body {
     }
 }
 
+impl From<Arc<str>> for Body {
+    fn from(s: Arc<str>) -> Body {
+        Body::Bytes(shared_bytes(s))
+    }
+}
+
+impl From<Arc<[u8]>> for Body {
+    fn from(s: Arc<[u8]>) -> Body {
+        Body::Bytes(shared_bytes(s))
+    }
+}
+
 impl From<Bytes> for Body {
     fn from(s: Bytes) -> Body {
         Body::Bytes(s)
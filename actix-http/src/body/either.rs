@@ -0,0 +1,97 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use pin_project::pin_project;
+
+use crate::error::Error;
+
+use super::{Body, BodySize, MessageBody};
+
+/// A body that is one of two body types.
+///
+/// Lets middleware replace the body of some responses while passing others through with their
+/// original body type, without boxing them. The right side defaults to [`Body`], which can hold
+/// any body.
+#[pin_project(project = EitherBodyProj)]
+#[derive(Debug, Clone)]
+pub enum EitherBody<L, R = Body> {
+    /// A body of the first type.
+    Left {
+        #[pin]
+        body: L,
+    },
+
+    /// A body of the second type.
+    Right {
+        #[pin]
+        body: R,
+    },
+}
+
+impl<L, R> EitherBody<L, R> {
+    /// Creates a body of the first type.
+    pub fn left(body: L) -> Self {
+        EitherBody::Left { body }
+    }
+
+    /// Creates a body of the second type.
+    pub fn right(body: R) -> Self {
+        EitherBody::Right { body }
+    }
+}
+
+impl<L, R> MessageBody for EitherBody<L, R>
+where
+    L: MessageBody,
+    R: MessageBody,
+{
+    fn size(&self) -> BodySize {
+        match self {
+            EitherBody::Left { body } => body.size(),
+            EitherBody::Right { body } => body.size(),
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Error>>> {
+        match self.project() {
+            EitherBodyProj::Left { body } => body.poll_next(cx),
+            EitherBodyProj::Right { body } => body.poll_next(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future::poll_fn;
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_either_body() {
+        let mut body = EitherBody::<&'static str, Body>::left("left");
+        assert_eq!(body.size(), BodySize::Sized(4));
+        assert_eq!(
+            poll_fn(|cx| Pin::new(&mut body).poll_next(cx))
+                .await
+                .unwrap()
+                .unwrap(),
+            "left"
+        );
+
+        let mut body = EitherBody::<&'static str, Body>::right(Body::from("right"));
+        assert_eq!(body.size(), BodySize::Sized(5));
+        assert_eq!(
+            poll_fn(|cx| Pin::new(&mut body).poll_next(cx))
+                .await
+                .unwrap()
+                .unwrap(),
+            "right"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+
+use crate::error::{ContentLengthError, Error};
+
+use super::{BodySize, MessageBody};
+
+/// Streaming body wrapper that enforces a declared content length.
+///
+/// The wrapped body is sent with a `Content-Length` header. If it yields more or fewer bytes than
+/// declared, the stream fails with a [`ContentLengthError`], which closes the connection instead
+/// of leaving the client with a mis-framed response.
+pub(crate) struct ExactLength<B> {
+    body: B,
+    expected: u64,
+    actual: u64,
+}
+
+impl<B> ExactLength<B>
+where
+    B: MessageBody + Unpin,
+{
+    pub(crate) fn new(expected: u64, body: B) -> Self {
+        ExactLength {
+            body,
+            expected,
+            actual: 0,
+        }
+    }
+}
+
+impl<B> MessageBody for ExactLength<B>
+where
+    B: MessageBody + Unpin,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.expected)
+    }
+
+    /// Empty chunks are skipped, like in [`SizedStream`](super::SizedStream), to prevent the
+    /// transmission being ended on a zero-length chunk.
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Error>>> {
+        let this = self.get_mut();
+
+        loop {
+            let chunk = match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                Some(Ok(chunk)) if chunk.is_empty() => continue,
+
+                Some(Ok(chunk)) => {
+                    this.actual += chunk.len() as u64;
+
+                    if this.actual > this.expected {
+                        log::error!(
+                            "Response body exceeds declared content length of {} bytes",
+                            this.expected
+                        );
+
+                        Some(Err(ContentLengthError::Overrun {
+                            expected: this.expected,
+                        }
+                        .into()))
+                    } else {
+                        Some(Ok(chunk))
+                    }
+                }
+
+                None if this.actual < this.expected => {
+                    log::error!(
+                        "Response body ended after {} of {} declared bytes",
+                        this.actual,
+                        this.expected
+                    );
+
+                    Some(Err(ContentLengthError::Underrun {
+                        expected: this.expected,
+                        actual: this.actual,
+                    }
+                    .into()))
+                }
+
+                item => item,
+            };
+
+            return Poll::Ready(chunk);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{future::poll_fn, stream};
+
+    use super::*;
+    use crate::body::{Body, BodyStream};
+
+    fn body(chunks: &'static [&'static str]) -> Body {
+        Body::from_message(BodyStream::new(stream::iter(
+            chunks
+                .iter()
+                .map(|chunk| Ok::<_, Error>(Bytes::from_static(chunk.as_bytes()))),
+        )))
+    }
+
+    async fn next<B: MessageBody + Unpin>(body: &mut B) -> Option<Result<Bytes, Error>> {
+        poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)).await
+    }
+
+    #[actix_rt::test]
+    async fn test_exact() {
+        let mut body = ExactLength::new(10, body(&["01234", "", "56789"]));
+        assert_eq!(body.size(), BodySize::Sized(10));
+
+        assert_eq!(next(&mut body).await.unwrap().unwrap(), "01234");
+        assert_eq!(next(&mut body).await.unwrap().unwrap(), "56789");
+        assert!(next(&mut body).await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_overrun() {
+        let mut body = ExactLength::new(4, body(&["0123", "4"]));
+
+        assert_eq!(next(&mut body).await.unwrap().unwrap(), "0123");
+        let err = next(&mut body).await.unwrap().unwrap_err();
+        assert!(matches!(
+            err.as_error::<ContentLengthError>(),
+            Some(ContentLengthError::Overrun { expected: 4 })
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn test_underrun() {
+        let mut body = ExactLength::new(10, body(&["0123"]));
+
+        assert_eq!(next(&mut body).await.unwrap().unwrap(), "0123");
+        let err = next(&mut body).await.unwrap().unwrap_err();
+        assert!(matches!(
+            err.as_error::<ContentLengthError>(),
+            Some(ContentLengthError::Underrun {
+                expected: 10,
+                actual: 4
+            })
+        ));
+    }
+}
@@ -3,21 +3,27 @@
 #[allow(clippy::module_inception)]
 mod body;
 mod body_stream;
+mod either;
+mod exact_length;
 mod message_body;
 mod response_body;
+mod shared_body;
 mod size;
 mod sized_stream;
 
 pub use self::body::Body;
 pub use self::body_stream::BodyStream;
+pub use self::either::EitherBody;
+pub(crate) use self::exact_length::ExactLength;
 pub use self::message_body::MessageBody;
 pub use self::response_body::ResponseBody;
+pub use self::shared_body::SharedBody;
 pub use self::size::BodySize;
 pub use self::sized_stream::SizedStream;
 
 #[cfg(test)]
 mod tests {
-    use std::pin::Pin;
+    use std::{pin::Pin, sync::Arc};
 
     use actix_rt::pin;
     use bytes::{Bytes, BytesMut};
@@ -139,6 +145,28 @@ mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_shared() {
+        let text = Arc::<str>::from("test");
+        let b = SharedBody::new(Arc::clone(&text));
+        pin!(b);
+
+        assert_eq!(b.size(), BodySize::Sized(4));
+        let chunk = poll_fn(|cx| b.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chunk, Bytes::from("test"));
+        assert_eq!(chunk.as_ptr(), text.as_ptr());
+        assert!(poll_fn(|cx| b.as_mut().poll_next(cx)).await.is_none());
+
+        let b = Body::from(Arc::<[u8]>::from(&b""[..]));
+        pin!(b);
+
+        assert_eq!(b.size(), BodySize::Sized(0));
+        assert!(poll_fn(|cx| b.as_mut().poll_next(cx)).await.is_none());
+    }
+
     #[actix_rt::test]
     async fn test_unit() {
         assert_eq!(().size(), BodySize::Empty);
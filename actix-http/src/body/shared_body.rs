@@ -0,0 +1,77 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+
+use crate::error::Error;
+
+use super::{BodySize, MessageBody};
+
+/// Body backed by a reference counted buffer.
+///
+/// The shared buffer is never copied; the chunk written to the connection keeps a reference to
+/// it until the write completes.
+pub struct SharedBody<T: ?Sized> {
+    buf: Arc<T>,
+    done: bool,
+}
+
+impl<T> SharedBody<T>
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    /// Creates body that yields the contents of `buf` as a single chunk.
+    pub fn new(buf: Arc<T>) -> Self {
+        SharedBody { buf, done: false }
+    }
+
+    /// Returns reference to the shared buffer.
+    pub fn get_ref(&self) -> &Arc<T> {
+        &self.buf
+    }
+}
+
+impl<T> MessageBody for SharedBody<T>
+where
+    T: AsRef<[u8]> + Send + Sync + ?Sized + 'static,
+{
+    fn size(&self) -> BodySize {
+        BodySize::Sized((*self.buf).as_ref().len() as u64)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Error>>> {
+        let this = self.get_mut();
+
+        if this.done || (*this.buf).as_ref().is_empty() {
+            Poll::Ready(None)
+        } else {
+            this.done = true;
+            Poll::Ready(Some(Ok(shared_bytes(Arc::clone(&this.buf)))))
+        }
+    }
+}
+
+/// Creates `Bytes` pointing into the shared buffer, keeping a reference to it.
+pub(crate) fn shared_bytes<T>(buf: Arc<T>) -> Bytes
+where
+    T: AsRef<[u8]> + Send + Sync + ?Sized + 'static,
+{
+    Bytes::from_owner(SharedBuf(buf))
+}
+
+struct SharedBuf<T: ?Sized>(Arc<T>);
+
+impl<T> AsRef<[u8]> for SharedBuf<T>
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    fn as_ref(&self) -> &[u8] {
+        (*self.0).as_ref()
+    }
+}
@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 use std::rc::Rc;
+use std::time::Duration;
 use std::{fmt, net};
 
 use actix_codec::Framed;
@@ -10,6 +11,7 @@ use crate::config::{KeepAlive, ServiceConfig};
 use crate::error::Error;
 use crate::h1::{Codec, ExpectHandler, H1Service, UpgradeHandler};
 use crate::h2::H2Service;
+use crate::header::ResponseHeaderLimits;
 use crate::request::Request;
 use crate::response::Response;
 use crate::service::HttpService;
@@ -21,8 +23,13 @@ use crate::{ConnectCallback, Extensions};
 /// builder-like pattern.
 pub struct HttpServiceBuilder<T, S, X = ExpectHandler, U = UpgradeHandler> {
     keep_alive: KeepAlive,
+    keep_alive_timeout: Option<Duration>,
     client_timeout: u64,
     client_disconnect: u64,
+    payload_rate: u64,
+    payload_rate_window: u64,
+    max_chunk_size: usize,
+    response_header_limits: Option<ResponseHeaderLimits>,
     secure: bool,
     local_addr: Option<net::SocketAddr>,
     expect: X,
@@ -42,8 +49,13 @@ where
     pub fn new() -> Self {
         HttpServiceBuilder {
             keep_alive: KeepAlive::Timeout(5),
+            keep_alive_timeout: None,
             client_timeout: 5000,
             client_disconnect: 0,
+            payload_rate: 0,
+            payload_rate_window: 1000,
+            max_chunk_size: 0,
+            response_header_limits: None,
             secure: false,
             local_addr: None,
             expect: ExpectHandler,
@@ -77,6 +89,20 @@ where
         self
     }
 
+    /// Set how long an idle keep-alive connection is kept open.
+    ///
+    /// Once a response has been sent, the connection is closed if the next request does not start
+    /// within this time. Connections with a request in progress are not affected. Unlike
+    /// [`client_timeout`](Self::client_timeout), which limits how long receiving a request head
+    /// may take, this only applies while the connection is idle.
+    ///
+    /// Overrides the duration of [`KeepAlive::Timeout`] with sub-second precision. Has no effect
+    /// if keep-alive is disabled; a zero duration lets the OS decide when to close the connection.
+    pub fn keep_alive_timeout(mut self, timeout: Duration) -> Self {
+        self.keep_alive_timeout = Some(timeout);
+        self
+    }
+
     /// Set connection secure state
     pub fn secure(mut self) -> Self {
         self.secure = true;
@@ -89,11 +115,13 @@ where
         self
     }
 
-    /// Set server client timeout in milliseconds for first request.
+    /// Set server client timeout in milliseconds for reading request heads.
     ///
-    /// Defines a timeout for reading client request header. If a client does not transmit
-    /// the entire set headers within this time, the request is terminated with
-    /// the 408 (Request Time-out) error.
+    /// Defines the total time a client has to transmit an entire request head. It is counted from
+    /// connection start for the first request and from the first received byte of the head for
+    /// following requests, so trickling headers a byte at a time does not extend it. If the head
+    /// is not complete within this time, the request is terminated with the 408 (Request
+    /// Time-out) error.
     ///
     /// To disable timeout set value to 0.
     ///
@@ -116,6 +144,47 @@ where
         self
     }
 
+    /// Set minimum rate, in bytes per second, at which clients must send request payloads.
+    ///
+    /// The rate is measured over consecutive windows of `window` milliseconds while a request
+    /// payload is being received. Windows in which the service is not reading the payload are
+    /// not counted. If fewer bytes arrive within a window, the request is terminated with the
+    /// 408 (Request Time-out) error and the connection is closed.
+    ///
+    /// To disable the minimum rate set value to 0.
+    ///
+    /// By default there is no minimum rate.
+    pub fn client_payload_rate(mut self, bytes_per_sec: u64, window: u64) -> Self {
+        self.payload_rate = bytes_per_sec;
+        self.payload_rate_window = window;
+        self
+    }
+
+    /// Set maximum size of the chunks of HTTP/1 responses sent with chunked transfer encoding.
+    ///
+    /// Body data written in larger pieces is split into multiple chunks of at most this many
+    /// bytes. This only changes the framing on the wire; the body sent stays the same.
+    ///
+    /// To disable the limit set value to 0.
+    ///
+    /// By default chunk sizes are not limited.
+    pub fn max_chunk_size(mut self, size: usize) -> Self {
+        self.max_chunk_size = size;
+        self
+    }
+
+    /// Set limits enforced on the headers of responses before they are encoded.
+    ///
+    /// Duplicates of singleton headers are folded into one value and responses with too many or
+    /// too large headers are truncated or replaced with a 500 response, as configured. See
+    /// [`ResponseHeaderLimits`] for details.
+    ///
+    /// By default response headers are sent as set by the service.
+    pub fn response_header_limits(mut self, limits: ResponseHeaderLimits) -> Self {
+        self.response_header_limits = Some(limits);
+        self
+    }
+
     /// Provide service for `EXPECT: 100-Continue` support.
     ///
     /// Service get called with request that contains `EXPECT` header.
@@ -131,8 +200,13 @@ where
     {
         HttpServiceBuilder {
             keep_alive: self.keep_alive,
+            keep_alive_timeout: self.keep_alive_timeout,
             client_timeout: self.client_timeout,
             client_disconnect: self.client_disconnect,
+            payload_rate: self.payload_rate,
+            payload_rate_window: self.payload_rate_window,
+            max_chunk_size: self.max_chunk_size,
+            response_header_limits: self.response_header_limits,
             secure: self.secure,
             local_addr: self.local_addr,
             expect: expect.into_factory(),
@@ -156,8 +230,13 @@ where
     {
         HttpServiceBuilder {
             keep_alive: self.keep_alive,
+            keep_alive_timeout: self.keep_alive_timeout,
             client_timeout: self.client_timeout,
             client_disconnect: self.client_disconnect,
+            payload_rate: self.payload_rate,
+            payload_rate_window: self.payload_rate_window,
+            max_chunk_size: self.max_chunk_size,
+            response_header_limits: self.response_header_limits,
             secure: self.secure,
             local_addr: self.local_addr,
             expect: self.expect,
@@ -195,7 +274,11 @@ where
             self.client_disconnect,
             self.secure,
             self.local_addr,
-        );
+        )
+        .with_payload_rate(self.payload_rate, self.payload_rate_window)
+        .with_max_chunk_size(self.max_chunk_size)
+        .with_response_header_limits(self.response_header_limits)
+        .with_keep_alive_timeout(self.keep_alive_timeout);
 
         H1Service::with_config(cfg, service.into_factory())
             .expect(self.expect)
@@ -219,7 +302,11 @@ where
             self.client_disconnect,
             self.secure,
             self.local_addr,
-        );
+        )
+        .with_payload_rate(self.payload_rate, self.payload_rate_window)
+        .with_max_chunk_size(self.max_chunk_size)
+        .with_response_header_limits(self.response_header_limits)
+        .with_keep_alive_timeout(self.keep_alive_timeout);
 
         H2Service::with_config(cfg, service.into_factory())
             .on_connect_ext(self.on_connect_ext)
@@ -241,7 +328,11 @@ where
             self.client_disconnect,
             self.secure,
             self.local_addr,
-        );
+        )
+        .with_payload_rate(self.payload_rate, self.payload_rate_window)
+        .with_max_chunk_size(self.max_chunk_size)
+        .with_response_header_limits(self.response_header_limits)
+        .with_keep_alive_timeout(self.keep_alive_timeout);
 
         HttpService::with_config(cfg, service.into_factory())
             .expect(self.expect)
@@ -1,4 +1,4 @@
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::time::Duration;
 
 const DEFAULT_H2_CONN_WINDOW: u32 = 1024 * 1024 * 2; // 2MB
@@ -11,10 +11,12 @@ pub(crate) struct ConnectorConfig {
     pub(crate) conn_lifetime: Duration,
     pub(crate) conn_keep_alive: Duration,
     pub(crate) disconnect_timeout: Option<Duration>,
+    pub(crate) connect_backoff: Option<(Duration, Duration)>,
     pub(crate) limit: usize,
     pub(crate) conn_window_size: u32,
     pub(crate) stream_window_size: u32,
     pub(crate) local_address: Option<IpAddr>,
+    pub(crate) resolve_overrides: Vec<(String, SocketAddr)>,
 }
 
 impl Default for ConnectorConfig {
@@ -24,10 +26,12 @@ impl Default for ConnectorConfig {
             conn_lifetime: Duration::from_secs(75),
             conn_keep_alive: Duration::from_secs(15),
             disconnect_timeout: Some(Duration::from_millis(3000)),
+            connect_backoff: None,
             limit: 100,
             conn_window_size: DEFAULT_H2_CONN_WINDOW,
             stream_window_size: DEFAULT_H2_STREAM_WINDOW,
             local_address: None,
+            resolve_overrides: Vec::new(),
         }
     }
 }
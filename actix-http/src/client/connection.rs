@@ -1,5 +1,7 @@
+use std::cell::Cell;
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::{Context, Poll};
 use std::{fmt, io, time};
 
@@ -31,6 +33,7 @@ pub(crate) enum ConnectionType<Io> {
 pub(crate) struct H2Connection {
     handle: JoinHandle<()>,
     sender: SendRequest<Bytes>,
+    closed: Rc<Cell<bool>>,
 }
 
 impl H2Connection {
@@ -41,11 +44,31 @@ impl H2Connection {
     where
         Io: AsyncRead + AsyncWrite + Unpin + 'static,
     {
-        let handle = actix_rt::spawn(async move {
-            let _ = connection.await;
+        let closed = Rc::new(Cell::new(false));
+        let handle = actix_rt::spawn({
+            let closed = closed.clone();
+            async move {
+                let _ = connection.await;
+                closed.set(true);
+            }
         });
 
-        Self { handle, sender }
+        Self {
+            handle,
+            sender,
+            closed,
+        }
+    }
+
+    /// Returns true once the connection task has finished or the peer went away.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    /// Flag marking the connection as closed. Stays usable after the connection has been
+    /// released to the pool, so that the pool stops handing it out.
+    pub(crate) fn closed(&self) -> Rc<Cell<bool>> {
+        self.closed.clone()
     }
 }
 
@@ -92,6 +115,34 @@ pub trait Connection {
         'static,
         Result<(ResponseHead, Framed<Self::Io, ClientCodec>), SendRequestError>,
     >;
+
+    /// Send upgrade request, returns Response and Framed if protocol was switched.
+    ///
+    /// Unlike [`open_tunnel`](Self::open_tunnel), a response refusing the upgrade is returned
+    /// with its payload and the connection is released to the pool once the payload is read.
+    fn open_upgrade<H: Into<RequestHeadType> + 'static>(
+        self,
+        head: H,
+    ) -> LocalBoxFuture<'static, Result<UpgradeResponse<Self::Io>, SendRequestError>>
+    where
+        Self: Sized,
+        Self::Io: 'static,
+    {
+        let fut = self.open_tunnel(head);
+        Box::pin(async move {
+            let (head, framed) = fut.await?;
+            Ok(UpgradeResponse::Tunnel(head, framed))
+        })
+    }
+}
+
+/// Response to a request sent with [`Connection::open_upgrade`].
+pub enum UpgradeResponse<Io> {
+    /// Protocol was switched; the connection belongs to the returned Framed.
+    Tunnel(ResponseHead, Framed<Io, ClientCodec>),
+
+    /// Upgrade was refused; the response is read like any other response.
+    Refused(ResponseHead, Payload),
 }
 
 pub(crate) trait ConnectionLifetime: AsyncRead + AsyncWrite + 'static {
@@ -184,6 +235,28 @@ impl<T: AsyncRead + AsyncWrite + Unpin> IoConnection<T> {
             }
         }
     }
+
+    /// Send upgrade request, keeping the connection pooled if the upgrade is refused
+    async fn open_upgrade<H: Into<RequestHeadType>>(
+        mut self,
+        head: H,
+    ) -> Result<UpgradeResponse<T>, SendRequestError> {
+        match self.io.take().unwrap() {
+            ConnectionType::H1(io) => {
+                h1proto::open_upgrade(io, head.into(), self.created, self.pool).await
+            }
+            ConnectionType::H2(io) => {
+                if let Some(mut pool) = self.pool.take() {
+                    pool.release(IoConnection::new(
+                        ConnectionType::H2(io),
+                        self.created,
+                        None,
+                    ));
+                }
+                Err(SendRequestError::TunnelNotSupported)
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -237,6 +310,35 @@ where
             }),
         }
     }
+
+    fn open_upgrade<H: Into<RequestHeadType> + 'static>(
+        self,
+        head: H,
+    ) -> LocalBoxFuture<'static, Result<UpgradeResponse<Self::Io>, SendRequestError>>
+    {
+        match self {
+            EitherIoConnection::A(con) => Box::pin(async {
+                Ok(match con.open_upgrade(head).await? {
+                    UpgradeResponse::Tunnel(head, framed) => {
+                        UpgradeResponse::Tunnel(head, framed.into_map_io(EitherIo::A))
+                    }
+                    UpgradeResponse::Refused(head, payload) => {
+                        UpgradeResponse::Refused(head, payload)
+                    }
+                })
+            }),
+            EitherIoConnection::B(con) => Box::pin(async {
+                Ok(match con.open_upgrade(head).await? {
+                    UpgradeResponse::Tunnel(head, framed) => {
+                        UpgradeResponse::Tunnel(head, framed.into_map_io(EitherIo::B))
+                    }
+                    UpgradeResponse::Refused(head, payload) => {
+                        UpgradeResponse::Refused(head, payload)
+                    }
+                })
+            }),
+        }
+    }
 }
 
 #[pin_project(project = EitherIoProj)]
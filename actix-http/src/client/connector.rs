@@ -2,8 +2,10 @@ use std::{
     fmt,
     future::Future,
     marker::PhantomData,
-    net::IpAddr,
+    mem,
+    net::{IpAddr, SocketAddr},
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -21,6 +23,7 @@ use super::config::ConnectorConfig;
 use super::connection::{Connection, EitherIoConnection};
 use super::error::ConnectError;
 use super::pool::{ConnectionPool, Protocol};
+use super::tls::{TlsHandshake, TlsHandshakeHook};
 use super::Connect;
 
 #[cfg(feature = "openssl")]
@@ -58,6 +61,10 @@ pub struct Connector<T, U> {
     config: ConnectorConfig,
     #[allow(dead_code)]
     ssl: SslConnector,
+    #[allow(dead_code)]
+    tls_session_cache: Option<usize>,
+    #[allow(dead_code)]
+    tls_handshake_hook: Option<TlsHandshakeHook>,
     _phantom: PhantomData<U>,
 }
 
@@ -78,6 +85,8 @@ impl Connector<(), ()> {
             ssl: Self::build_ssl(vec![b"h2".to_vec(), b"http/1.1".to_vec()]),
             connector: new_connector(resolver::resolver()),
             config: ConnectorConfig::default(),
+            tls_session_cache: None,
+            tls_handshake_hook: None,
             _phantom: PhantomData,
         }
     }
@@ -132,6 +141,8 @@ impl<T, U> Connector<T, U> {
             connector,
             config: self.config,
             ssl: self.ssl,
+            tls_session_cache: self.tls_session_cache,
+            tls_handshake_hook: self.tls_handshake_hook,
             _phantom: PhantomData,
         }
     }
@@ -243,26 +254,97 @@ where
         self
     }
 
+    /// Set backoff of connection attempts to hosts that could not be connected to.
+    ///
+    /// After a failed connection attempt, further attempts to the same host fail right away with
+    /// [`ConnectError::Backoff`] until the backoff period elapses. The period starts at `initial`
+    /// and doubles with each consecutive failure, up to `max`. Attempts that fail while a period
+    /// is running, such as concurrent attempts to the same host, do not count as further
+    /// failures. A successful connection resets the period, and so does not failing again for
+    /// `max` after it ended. Requests that can use a pooled connection are not affected.
+    ///
+    /// By default connection attempts are not backed off.
+    pub fn connect_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.config.connect_backoff = Some((initial, max));
+        self
+    }
+
     /// Set local IP Address the connector would use for establishing connection.
     pub fn local_address(mut self, addr: IpAddr) -> Self {
         self.config.local_address = Some(addr);
         self
     }
 
+    /// Connect to `addr` for requests to `host` instead of resolving the host name.
+    ///
+    /// Can be called multiple times for the same host to register several addresses, which are
+    /// tried in the order they were added, like the addresses returned by a DNS lookup. The
+    /// `Host` header and TLS server name still use `host`, so certificates are validated against
+    /// it. An address set on an individual request takes precedence.
+    ///
+    /// ```rust,ignore
+    /// use actix_http::client::Connector;
+    ///
+    /// let connector = Connector::new()
+    ///     .resolve_to("api.example.com", "10.0.0.7:443".parse().unwrap())
+    ///     .finish();
+    /// ```
+    pub fn resolve_to(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.config
+            .resolve_overrides
+            .push((host.to_ascii_lowercase(), addr));
+        self
+    }
+
+    /// Set the number of TLS sessions kept for resumption of later connections.
+    ///
+    /// Sessions are cached per connector and per host and port, so they are only resumed with the
+    /// client identity they were established with. Set to 0 to disable resumption. By default
+    /// openssl keeps 32 sessions and rustls uses the session storage of its client config.
+    pub fn tls_session_cache(mut self, capacity: usize) -> Self {
+        self.tls_session_cache = Some(capacity);
+        self
+    }
+
+    /// Set a function called after each completed TLS handshake.
+    ///
+    /// Receives the host, handshake duration and whether a cached session was resumed, which
+    /// makes it a place to collect handshake metrics.
+    ///
+    /// ```rust,ignore
+    /// use actix_http::client::Connector;
+    ///
+    /// let connector = Connector::new()
+    ///     .on_tls_handshake(|hs| log::debug!("{} resumed: {:?}", hs.host(), hs.resumed()))
+    ///     .finish();
+    /// ```
+    pub fn on_tls_handshake<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&TlsHandshake) + 'static,
+    {
+        self.tls_handshake_hook = Some(TlsHandshakeHook::new(f));
+        self
+    }
+
     /// Finish configuration process and create connector service.
     /// The Connector builder always concludes by calling `finish()` last in
     /// its combinator chain.
     pub fn finish(
-        self,
+        mut self,
     ) -> impl Service<Connect, Response = impl Connection, Error = ConnectError> + Clone
     {
         let local_address = self.config.local_address;
         let timeout = self.config.timeout;
+        let overrides = Rc::new(ResolveOverrides(mem::take(
+            &mut self.config.resolve_overrides,
+        )));
+        #[cfg(any(feature = "openssl", feature = "rustls"))]
+        let ssl_overrides = Rc::clone(&overrides);
 
         let tcp_service = TimeoutService::new(
             timeout,
             apply_fn(self.connector.clone(), move |msg: Connect, srv| {
-                let mut req = TcpConnect::new(msg.uri).set_addr(msg.addr);
+                let mut req = overrides.apply(msg);
 
                 if let Some(local_addr) = local_address {
                     req = req.set_local_addr(local_addr);
@@ -304,18 +386,24 @@ where
 
         #[cfg(any(feature = "openssl", feature = "rustls"))]
         {
+            #[cfg(feature = "rustls")]
             const H2: &[u8] = b"h2";
-            use actix_service::{boxed::service, pipeline};
             #[cfg(feature = "openssl")]
-            use actix_tls::connect::ssl::openssl::OpensslConnector;
+            use super::tls::{OpensslHandshake, OpensslSessions};
+            use actix_service::{boxed::service, pipeline};
             #[cfg(feature = "rustls")]
             use actix_tls::connect::ssl::rustls::{RustlsConnector, Session};
+            #[cfg(feature = "rustls")]
+            use std::time::Instant;
+
+            let tls_session_cache = self.tls_session_cache;
+            let hook = self.tls_handshake_hook;
 
             let ssl_service = TimeoutService::new(
                 timeout,
                 pipeline(
                     apply_fn(self.connector.clone(), move |msg: Connect, srv| {
-                        let mut req = TcpConnect::new(msg.uri).set_addr(msg.addr);
+                        let mut req = ssl_overrides.apply(msg);
 
                         if let Some(local_addr) = local_address {
                             req = req.set_local_addr(local_addr);
@@ -327,42 +415,63 @@ where
                 )
                 .and_then(match self.ssl {
                     #[cfg(feature = "openssl")]
-                    SslConnector::Openssl(ssl) => service(
-                        OpensslConnector::service(ssl)
-                            .map(|stream| {
-                                let sock = stream.into_parts().0;
-                                let h2 = sock
-                                    .ssl()
-                                    .selected_alpn_protocol()
-                                    .map(|protos| protos.windows(2).any(|w| w == H2))
-                                    .unwrap_or(false);
-                                if h2 {
-                                    (Box::new(sock) as Box<dyn Io>, Protocol::Http2)
-                                } else {
-                                    (Box::new(sock) as Box<dyn Io>, Protocol::Http1)
-                                }
-                            })
-                            .map_err(ConnectError::from),
-                    ),
+                    SslConnector::Openssl(ssl) => service(OpensslHandshake {
+                        connector: ssl,
+                        sessions: match tls_session_cache.unwrap_or(32) {
+                            0 => None,
+                            capacity => Some(Rc::new(OpensslSessions::new(capacity))),
+                        },
+                        hook,
+                    }),
                     #[cfg(feature = "rustls")]
-                    SslConnector::Rustls(ssl) => service(
-                        RustlsConnector::service(ssl)
-                            .map_err(ConnectError::from)
-                            .map(|stream| {
-                                let sock = stream.into_parts().0;
-                                let h2 = sock
-                                    .get_ref()
-                                    .1
-                                    .get_alpn_protocol()
-                                    .map(|protos| protos.windows(2).any(|w| w == H2))
-                                    .unwrap_or(false);
-                                if h2 {
-                                    (Box::new(sock) as Box<dyn Io>, Protocol::Http2)
-                                } else {
-                                    (Box::new(sock) as Box<dyn Io>, Protocol::Http1)
+                    SslConnector::Rustls(mut ssl) => {
+                        if let Some(capacity) = tls_session_cache {
+                            let mut config = ClientConfig::clone(&ssl);
+                            config.session_persistence = if capacity == 0 {
+                                Arc::new(tls_rustls::NoClientSessionStorage {})
+                            } else {
+                                tls_rustls::ClientSessionMemoryCache::new(capacity)
+                            };
+                            ssl = Arc::new(config);
+                        }
+
+                        // rustls does not report whether the session was resumed
+                        let handshake = apply_fn(
+                            RustlsConnector::service(ssl),
+                            move |conn: TcpConnection<Uri, U>, srv| {
+                                let host = conn.host().to_owned();
+                                let hook = hook.clone();
+                                let start = Instant::now();
+                                let fut = srv.call(conn);
+
+                                async move {
+                                    let res = fut.await;
+                                    if let (Ok(_), Some(hook)) = (&res, hook) {
+                                        let elapsed = start.elapsed();
+                                        hook.call(&TlsHandshake::new(
+                                            host, None, elapsed,
+                                        ));
+                                    }
+                                    res
                                 }
-                            }),
-                    ),
+                            },
+                        );
+
+                        service(handshake.map_err(ConnectError::from).map(|stream| {
+                            let sock = stream.into_parts().0;
+                            let h2 = sock
+                                .get_ref()
+                                .1
+                                .get_alpn_protocol()
+                                .map(|protos| protos.windows(2).any(|w| w == H2))
+                                .unwrap_or(false);
+                            if h2 {
+                                (Box::new(sock) as Box<dyn Io>, Protocol::Http2)
+                            } else {
+                                (Box::new(sock) as Box<dyn Io>, Protocol::Http1)
+                            }
+                        }))
+                    }
                 }),
             )
             .map_err(|e| match e {
@@ -381,6 +490,34 @@ where
     }
 }
 
+/// Static host to address mapping set with [`Connector::resolve_to`].
+struct ResolveOverrides(Vec<(String, SocketAddr)>);
+
+impl ResolveOverrides {
+    /// Creates TCP connect request, using override addresses for the host if there are any.
+    fn apply(&self, msg: Connect) -> TcpConnect<Uri> {
+        if msg.addr.is_some() || self.0.is_empty() {
+            return TcpConnect::new(msg.uri).set_addr(msg.addr);
+        }
+
+        let addrs = match msg.uri.host() {
+            Some(host) => self
+                .0
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case(host))
+                .map(|(_, addr)| *addr)
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        if addrs.is_empty() {
+            TcpConnect::new(msg.uri)
+        } else {
+            TcpConnect::new(msg.uri).set_addrs(addrs)
+        }
+    }
+}
+
 struct InnerConnector<S1, S2, Io1, Io2>
 where
     S1: Service<Connect, Response = (Io1, Protocol), Error = ConnectError> + 'static,
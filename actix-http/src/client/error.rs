@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, time::Duration};
 
 use derive_more::{Display, From};
 
@@ -44,6 +44,19 @@ pub enum ConnectError {
     #[display(fmt = "Connector received `Connect` method with unresolved host")]
     Unresolved,
 
+    /// Connecting was not attempted because earlier attempts to the host failed and its backoff
+    /// period has not elapsed
+    #[display(
+        fmt = "Connecting to {} is backed off after failures, retry after {:?}",
+        authority,
+        retry_after
+    )]
+    #[from(ignore)]
+    Backoff {
+        authority: String,
+        retry_after: Duration,
+    },
+
     /// Connection io error
     #[display(fmt = "{}", _0)]
     Io(io::Error),
@@ -109,12 +122,30 @@ pub enum SendRequestError {
     #[display(fmt = "Timeout while waiting for response")]
     Timeout,
 
+    /// HTTP/2 peer refused the request or shut the connection down before processing it, so it
+    /// is safe to send again on a new connection
+    #[display(fmt = "Request was not processed before the HTTP/2 connection went away")]
+    GoAwayUnprocessed,
+
     /// Tunnels are not supported for HTTP/2 connection
     #[display(fmt = "Tunnels are not supported for http2 connection")]
     TunnelNotSupported,
 
     /// Error sending request body
     Body(Error),
+
+    /// Request was rejected without being sent because the circuit breaker for the authority
+    /// is open
+    #[display(
+        fmt = "Circuit breaker for {} is open, retry after {:?}",
+        authority,
+        retry_after
+    )]
+    #[from(ignore)]
+    CircuitOpen {
+        authority: String,
+        retry_after: Duration,
+    },
 }
 
 impl std::error::Error for SendRequestError {}
@@ -126,7 +157,11 @@ impl ResponseError for SendRequestError {
             SendRequestError::Connect(ConnectError::Timeout) => {
                 StatusCode::GATEWAY_TIMEOUT
             }
+            SendRequestError::Connect(ConnectError::Backoff { .. }) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             SendRequestError::Connect(_) => StatusCode::BAD_REQUEST,
+            SendRequestError::CircuitOpen { .. } => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
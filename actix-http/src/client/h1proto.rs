@@ -14,10 +14,13 @@ use crate::error::PayloadError;
 use crate::h1;
 use crate::header::HeaderMap;
 use crate::http::header::{IntoHeaderValue, HOST};
+use crate::http::{Method, StatusCode};
 use crate::message::{RequestHeadType, ResponseHead};
 use crate::payload::{Payload, PayloadStream};
 
-use super::connection::{ConnectionLifetime, ConnectionType, IoConnection};
+use super::connection::{
+    ConnectionLifetime, ConnectionType, IoConnection, UpgradeResponse,
+};
 use super::error::{ConnectError, SendRequestError};
 use super::pool::Acquired;
 use crate::body::{BodySize, MessageBody};
@@ -85,14 +88,15 @@ where
         return Err(SendRequestError::from(ConnectError::Disconnected));
     };
 
+    let keepalive = framed.codec_ref().keepalive();
+
     match framed.codec_ref().message_type() {
         h1::MessageType::None => {
-            let force_close = !framed.codec_ref().keepalive();
-            release_connection(framed, force_close);
+            release_connection(framed, !keepalive);
             Ok((head, Payload::None))
         }
         _ => {
-            let pl: PayloadStream = PlStream::new(framed_inner).boxed_local();
+            let pl: PayloadStream = PlStream::new(framed_inner, keepalive).boxed_local();
             Ok((head, pl.into()))
         }
     }
@@ -118,6 +122,49 @@ where
     }
 }
 
+pub(crate) async fn open_upgrade<T>(
+    io: T,
+    head: RequestHeadType,
+    created: time::Instant,
+    pool: Option<Acquired<T>>,
+) -> Result<UpgradeResponse<T>, SendRequestError>
+where
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
+{
+    let connect = head.as_ref().method == Method::CONNECT;
+    let (head, framed) = open_tunnel(io, head).await?;
+
+    let switched = if connect {
+        head.status.is_success()
+    } else {
+        head.status == StatusCode::SWITCHING_PROTOCOLS
+    };
+
+    if switched {
+        return Ok(UpgradeResponse::Tunnel(head, framed));
+    }
+
+    // upgrade was refused; the codec still expects an upgrade so keep-alive is taken from the
+    // response head
+    let keepalive = head.keep_alive();
+    let mut framed = framed.into_map_io(|io| H1Connection {
+        created,
+        pool,
+        io: Some(io),
+    });
+
+    match framed.codec_ref().message_type() {
+        h1::MessageType::None => {
+            release_connection(Pin::new(&mut framed), !keepalive);
+            Ok(UpgradeResponse::Refused(head, Payload::None))
+        }
+        _ => {
+            let pl: PayloadStream = PlStream::new(framed, keepalive).boxed_local();
+            Ok(UpgradeResponse::Refused(head, pl.into()))
+        }
+    }
+}
+
 /// send request body to the peer
 pub(crate) async fn send_body<T, B>(
     body: B,
@@ -244,14 +291,16 @@ impl<T: AsyncRead + AsyncWrite + Unpin + 'static> AsyncWrite for H1Connection<T>
 pub(crate) struct PlStream<Io> {
     #[pin]
     framed: Option<Framed<Io, h1::ClientPayloadCodec>>,
+    keepalive: bool,
 }
 
 impl<Io: ConnectionLifetime> PlStream<Io> {
-    fn new(framed: Framed<Io, h1::ClientCodec>) -> Self {
+    fn new(framed: Framed<Io, h1::ClientCodec>, keepalive: bool) -> Self {
         let framed = framed.into_map_codec(|codec| codec.into_payload_codec());
 
         PlStream {
             framed: Some(framed),
+            keepalive,
         }
     }
 }
@@ -272,8 +321,7 @@ impl<Io: ConnectionLifetime> Stream for PlStream<Io> {
                     Poll::Ready(Some(Ok(chunk)))
                 } else {
                     let framed = this.framed.as_mut().as_pin_mut().unwrap();
-                    let force_close = !framed.codec_ref().keepalive();
-                    release_connection(framed, force_close);
+                    release_connection(framed, !*this.keepalive);
                     Poll::Ready(None)
                 }
             }
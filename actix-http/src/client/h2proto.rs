@@ -7,7 +7,7 @@ use bytes::Bytes;
 use futures_util::future::poll_fn;
 use h2::{
     client::{Builder, Connection, SendRequest},
-    SendStream,
+    Reason, SendStream,
 };
 use http::header::{HeaderValue, CONNECTION, CONTENT_LENGTH, TRANSFER_ENCODING};
 use http::{request::Request, Method, Version};
@@ -97,22 +97,44 @@ where
 
     let res = poll_fn(|cx| io.poll_ready(cx)).await;
     if let Err(e) = res {
-        release(io, pool, created, e.is_io());
-        return Err(SendRequestError::from(e));
+        let close = e.is_io() || is_go_away(&e);
+        release(io, pool, created, close);
+        return Err(classify(e));
     }
 
     let resp = match io.send_request(req, eof) {
         Ok((fut, send)) => {
+            // the connection is shared with other requests from here on; it is only marked as
+            // closed if the peer goes away, so the pool drops it instead of handing it out
+            let closed = io.closed();
             release(io, pool, created, false);
 
-            if !eof {
-                send_body(body, send).await?;
+            let res = if eof {
+                Ok(())
+            } else {
+                send_body(body, send).await
+            };
+
+            let res = match res {
+                // peer may respond before reading the whole body; its response is still used
+                Ok(()) | Err(SendRequestError::H2(_)) => fut.await,
+                Err(e) => return Err(e),
+            };
+
+            match res {
+                Ok(resp) => resp,
+                Err(e) => {
+                    if is_go_away(&e) {
+                        closed.set(true);
+                    }
+                    return Err(classify(e));
+                }
             }
-            fut.await.map_err(SendRequestError::from)?
         }
         Err(e) => {
-            release(io, pool, created, e.is_io());
-            return Err(e.into());
+            let close = e.is_io() || is_go_away(&e);
+            release(io, pool, created, close);
+            return Err(classify(e));
         }
     };
 
@@ -172,6 +194,28 @@ async fn send_body<B: MessageBody>(
     }
 }
 
+/// Returns true if the error was caused by a graceful GOAWAY from the peer.
+///
+/// h2 fails streams above the last stream id of a GOAWAY frame with the frame's error code, which
+/// is `NO_ERROR` for a graceful shutdown. Streams are never reset with `NO_ERROR` before their
+/// response has started, so these streams were not processed by the peer.
+fn is_go_away(err: &h2::Error) -> bool {
+    err.reason() == Some(Reason::NO_ERROR)
+}
+
+/// Returns true if the peer did not process the stream the error was reported on.
+fn is_unprocessed(err: &h2::Error) -> bool {
+    is_go_away(err) || err.reason() == Some(Reason::REFUSED_STREAM)
+}
+
+fn classify(err: h2::Error) -> SendRequestError {
+    if is_unprocessed(&err) {
+        SendRequestError::GoAwayUnprocessed
+    } else {
+        SendRequestError::H2(err)
+    }
+}
+
 /// release SendRequest object
 fn release<T: AsyncRead + AsyncWrite + Unpin + 'static>(
     io: H2Connection,
@@ -202,3 +246,103 @@ where
         .enable_push(false);
     builder.handshake(io)
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net;
+
+    use actix_rt::net::TcpStream;
+
+    use super::*;
+    use crate::body::Body;
+    use crate::message::RequestHead;
+
+    /// Starts a server that sends GOAWAY with a last stream id of 0 once it has received the
+    /// headers of the first request, then keeps the connection open until the client leaves.
+    fn go_away_stub() -> net::SocketAddr {
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        std::thread::spawn(move || {
+            let (mut io, _) = listener.accept().unwrap();
+
+            // empty SETTINGS frame
+            io.write_all(&[0, 0, 0, 0x4, 0, 0, 0, 0, 0]).unwrap();
+
+            let mut preface = [0; 24];
+            io.read_exact(&mut preface).unwrap();
+
+            loop {
+                let mut frame = [0; 9];
+                io.read_exact(&mut frame).unwrap();
+
+                let len = (frame[0] as usize) << 16
+                    | (frame[1] as usize) << 8
+                    | frame[2] as usize;
+                let mut payload = vec![0; len];
+                io.read_exact(&mut payload).unwrap();
+
+                match frame[3] {
+                    // acknowledge SETTINGS
+                    0x4 if frame[4] & 0x1 == 0 => {
+                        io.write_all(&[0, 0, 0, 0x4, 0x1, 0, 0, 0, 0]).unwrap()
+                    }
+                    // HEADERS
+                    0x1 => break,
+                    _ => {}
+                }
+            }
+
+            // GOAWAY, last stream id 0 and NO_ERROR
+            io.write_all(&[0, 0, 8, 0x7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+                .unwrap();
+
+            while let Ok(n) = io.read(&mut [0; 1024]) {
+                if n == 0 {
+                    break;
+                }
+            }
+        });
+
+        addr
+    }
+
+    #[actix_rt::test]
+    async fn test_go_away_unprocessed() {
+        let addr = go_away_stub();
+
+        let tcp = TcpStream::connect(addr).await.unwrap();
+        let (sender, connection) =
+            handshake(tcp, &ConnectorConfig::default()).await.unwrap();
+        let conn = H2Connection::new(sender, connection);
+        let closed = conn.closed();
+
+        let mut head = RequestHead::default();
+        head.uri = format!("http://{}/", addr).parse().unwrap();
+
+        let res = send_request::<TcpStream, _>(
+            conn,
+            head.into(),
+            Body::from_slice(b"replayable"),
+            time::Instant::now(),
+            None,
+        )
+        .await;
+
+        assert!(matches!(res, Err(SendRequestError::GoAwayUnprocessed)));
+        assert!(closed.get());
+    }
+
+    #[test]
+    fn test_unprocessed_reasons() {
+        assert!(is_unprocessed(&h2::Error::from(Reason::NO_ERROR)));
+        assert!(is_unprocessed(&h2::Error::from(Reason::REFUSED_STREAM)));
+        assert!(!is_unprocessed(&h2::Error::from(Reason::CANCEL)));
+
+        assert!(matches!(
+            classify(Reason::INTERNAL_ERROR.into()),
+            SendRequestError::H2(_)
+        ));
+    }
+}
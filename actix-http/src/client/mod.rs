@@ -9,18 +9,21 @@ mod error;
 mod h1proto;
 mod h2proto;
 mod pool;
+mod tls;
 
 pub use actix_tls::connect::{
     Connect as TcpConnect, ConnectError as TcpConnectError, Connection as TcpConnection,
 };
 
-pub use self::connection::Connection;
+pub use self::connection::{Connection, UpgradeResponse};
 pub use self::connector::Connector;
 pub use self::error::{ConnectError, FreezeRequestError, InvalidUrl, SendRequestError};
-pub use self::pool::Protocol;
+pub use self::pool::{ConnectionLease, Protocol};
+pub use self::tls::TlsHandshake;
 
 #[derive(Clone)]
 pub struct Connect {
     pub uri: Uri,
     pub addr: Option<std::net::SocketAddr>,
+    pub lease: Option<ConnectionLease>,
 }
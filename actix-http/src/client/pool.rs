@@ -1,17 +1,20 @@
-//! Client connection pooling keyed on the authority part of the connection URI.
+//! Client connection pooling keyed on the authority part of the connection URI, the peer
+//! address override and the connection lease, if any.
 
 use std::collections::VecDeque;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::ops::Deref;
 use std::pin::Pin;
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use std::{cell::RefCell, io};
 
 use actix_codec::{AsyncRead, AsyncWrite};
-use actix_rt::time::{sleep, Sleep};
+use actix_rt::time::{self, sleep, Sleep};
 use actix_service::Service;
 use ahash::AHashMap;
 use futures_core::future::LocalBoxFuture;
@@ -33,14 +36,108 @@ pub enum Protocol {
     Http2,
 }
 
+/// Reservation of the pooled connections used by a sequence of requests.
+///
+/// Connections opened for requests carrying a lease are returned to the pool under that lease and
+/// are only picked up by later requests carrying the same lease. Sending requests with a lease one
+/// after another, each waiting for the previous response body to be read, therefore sends them all
+/// on the same connection, as long as the server keeps it alive. Concurrent requests with the same
+/// lease open extra connections.
+///
+/// When the lease and all its clones are dropped, its connections go back to the shared pool.
+#[derive(Debug, Clone)]
+pub struct ConnectionLease {
+    id: u64,
+    alive: Arc<()>,
+}
+
+impl ConnectionLease {
+    /// Creates a new lease.
+    pub fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        ConnectionLease {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            alive: Arc::new(()),
+        }
+    }
+
+    fn key(&self) -> LeaseKey {
+        LeaseKey {
+            id: self.id,
+            alive: Arc::downgrade(&self.alive),
+        }
+    }
+}
+
+impl Default for ConnectionLease {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lease part of a pool key; compares by lease id.
+#[derive(Clone, Debug)]
+struct LeaseKey {
+    id: u64,
+    alive: Weak<()>,
+}
+
+impl LeaseKey {
+    /// Returns true once the lease has been dropped.
+    fn is_returned(&self) -> bool {
+        self.alive.strong_count() == 0
+    }
+}
+
+impl PartialEq for LeaseKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for LeaseKey {}
+
+impl std::hash::Hash for LeaseKey {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+/// Pool key; connections made to an explicit peer address are kept apart from ones that were
+/// resolved from the authority, and connections under a lease are kept apart from all others.
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub(crate) struct Key {
     authority: Authority,
+    addr: Option<SocketAddr>,
+    lease: Option<LeaseKey>,
 }
 
-impl From<Authority> for Key {
-    fn from(authority: Authority) -> Key {
-        Key { authority }
+impl Key {
+    /// Returns the key connections go back to once the lease has been dropped.
+    fn unleased(&self) -> Key {
+        Key {
+            authority: self.authority.clone(),
+            addr: self.addr,
+            lease: None,
+        }
+    }
+}
+
+/// Moves the connections of dropped leases back to the shared pool.
+fn reclaim_returned_leases<Io>(map: &mut AHashMap<Key, VecDeque<PooledConnection<Io>>>) {
+    let returned = map
+        .keys()
+        .filter(|key| matches!(key.lease, Some(ref lease) if lease.is_returned()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for key in returned {
+        if let Some(conns) = map.remove(&key) {
+            map.entry(key.unleased())
+                .or_insert_with(VecDeque::new)
+                .extend(conns);
+        }
     }
 }
 
@@ -116,9 +213,70 @@ where
 {
     config: ConnectorConfig,
     available: RefCell<AHashMap<Key, VecDeque<PooledConnection<Io>>>>,
+    backoff: RefCell<AHashMap<Key, Backoff>>,
     permits: Arc<Semaphore>,
 }
 
+impl<Io> ConnectionPoolInnerPriv<Io>
+where
+    Io: AsyncWrite + Unpin + 'static,
+{
+    /// Returns the time left until connecting to the host of `key` may be attempted again.
+    fn backoff_remaining(&self, key: &Key) -> Option<Duration> {
+        let now = time::Instant::now();
+
+        self.backoff
+            .borrow()
+            .get(key)
+            .filter(|backoff| backoff.until > now)
+            .map(|backoff| backoff.until - now)
+    }
+
+    /// Records the outcome of a connection attempt to the host of `key`.
+    fn record_connect(&self, key: &Key, connected: bool) {
+        let (initial, max) = match self.config.connect_backoff {
+            Some(backoff) => backoff,
+            None => return,
+        };
+
+        let mut backoffs = self.backoff.borrow_mut();
+
+        if connected {
+            backoffs.remove(key);
+            return;
+        }
+
+        let now = time::Instant::now();
+
+        // hosts that have not failed again for `max` after their period start over
+        backoffs.retain(|_, backoff| backoff.until + max > now);
+
+        let backoff = backoffs.entry(key.clone()).or_insert(Backoff {
+            failures: 0,
+            until: now,
+        });
+
+        // attempts started before the current period began failed for the same reason
+        if backoff.until > now {
+            return;
+        }
+
+        // period doubles with each consecutive failure
+        let period = initial
+            .checked_mul(1 << backoff.failures.min(31))
+            .map_or(max, |period| period.min(max));
+
+        backoff.failures = backoff.failures.saturating_add(1);
+        backoff.until = now + period;
+    }
+}
+
+/// Consecutive connection failures to a host and the end of its backoff period.
+struct Backoff {
+    failures: u32,
+    until: time::Instant,
+}
+
 impl<S, Io> ConnectionPool<S, Io>
 where
     Io: AsyncWrite + Unpin + 'static,
@@ -136,11 +294,13 @@ where
     pub(crate) fn new(connector: S, config: ConnectorConfig) -> Self {
         let permits = Arc::new(Semaphore::new(config.limit));
         let available = RefCell::new(AHashMap::default());
+        let backoff = RefCell::new(AHashMap::default());
         let connector = Rc::new(connector);
 
         let inner = ConnectionPoolInner(Rc::new(ConnectionPoolInnerPriv {
             config,
             available,
+            backoff,
             permits,
         }));
 
@@ -177,7 +337,11 @@ where
 
         Box::pin(async move {
             let key = if let Some(authority) = req.uri.authority() {
-                authority.clone().into()
+                Key {
+                    authority: authority.clone(),
+                    addr: req.addr,
+                    lease: req.lease.as_ref().map(ConnectionLease::key),
+                }
             } else {
                 return Err(ConnectError::Unresolved);
             };
@@ -195,6 +359,7 @@ where
 
                 // check if there is idle connection for given key.
                 let mut map = inner.available.borrow_mut();
+                reclaim_returned_leases(&mut map);
 
                 if let Some(conns) = map.get_mut(&key) {
                     let now = Instant::now();
@@ -222,6 +387,14 @@ where
                                     ConnectionState::Live => conn = Some(c),
                                 }
                             } else {
+                                // drop HTTP/2 connections the peer has sent GOAWAY on
+                                if let ConnectionType::H2(ref io) = c.conn {
+                                    if io.is_closed() {
+                                        inner.close(c.conn);
+                                        continue;
+                                    }
+                                }
+
                                 conn = Some(c);
                             }
 
@@ -233,6 +406,18 @@ where
                 conn
             };
 
+            // backoff is kept per host, whatever the lease
+            let backoff_key = key.unleased();
+
+            if conn.is_none() {
+                if let Some(retry_after) = inner.backoff_remaining(&backoff_key) {
+                    return Err(ConnectError::Backoff {
+                        authority: key.authority.as_str().to_owned(),
+                        retry_after,
+                    });
+                }
+            }
+
             // construct acquired. It's used to put Io type back to pool/ close the Io type.
             // permit is carried with the whole lifecycle of Acquired.
             let acquired = Some(Acquired { key, inner, permit });
@@ -241,23 +426,23 @@ where
             match conn {
                 Some(conn) => Ok(IoConnection::new(conn.conn, conn.created, acquired)),
                 None => {
-                    let (io, proto) = connector.call(req).await?;
-
-                    if proto == Protocol::Http1 {
-                        Ok(IoConnection::new(
-                            ConnectionType::H1(io),
-                            Instant::now(),
-                            acquired,
-                        ))
-                    } else {
-                        let config = &acquired.as_ref().unwrap().inner.config;
-                        let (sender, connection) = handshake(io, config).await?;
-                        Ok(IoConnection::new(
-                            ConnectionType::H2(H2Connection::new(sender, connection)),
-                            Instant::now(),
-                            acquired,
-                        ))
+                    let inner = &acquired.as_ref().unwrap().inner;
+
+                    let conn = async {
+                        let (io, proto) = connector.call(req).await?;
+
+                        if proto == Protocol::Http1 {
+                            Ok::<_, ConnectError>(ConnectionType::H1(io))
+                        } else {
+                            let (sender, connection) =
+                                handshake(io, &inner.config).await?;
+                            Ok(ConnectionType::H2(H2Connection::new(sender, connection)))
+                        }
                     }
+                    .await;
+
+                    inner.record_connect(&backoff_key, conn.is_ok());
+                    Ok(IoConnection::new(conn?, Instant::now(), acquired))
                 }
             }
         })
@@ -371,10 +556,15 @@ where
         let (io, created) = conn.into_inner();
         let Acquired { key, inner, .. } = self;
 
+        let key = match key.lease {
+            Some(ref lease) if lease.is_returned() => key.unleased(),
+            _ => key.clone(),
+        };
+
         inner
             .available
             .borrow_mut()
-            .entry(key.clone())
+            .entry(key)
             .or_insert_with(VecDeque::new)
             .push_back(PooledConnection {
                 conn: io,
@@ -482,6 +672,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -522,6 +713,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -564,6 +756,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -603,6 +796,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://crates.io"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -616,6 +810,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://google.com"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -626,6 +821,89 @@ mod test {
         release(conn);
     }
 
+    #[actix_rt::test]
+    async fn test_pool_addr_key() {
+        let generated = Rc::new(Cell::new(0));
+        let generated_clone = generated.clone();
+
+        let connector = TestPoolConnector { generated };
+
+        let config = ConnectorConfig::default();
+
+        let pool = super::ConnectionPool::new(connector, config);
+
+        let req = Connect {
+            uri: Uri::from_static("https://crates.io"),
+            addr: None,
+            lease: None,
+        };
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(1, generated_clone.get());
+        release(conn);
+
+        // same authority but a pinned address does not reuse the resolved connection
+        let pinned = Connect {
+            uri: Uri::from_static("https://crates.io"),
+            addr: Some("127.0.0.1:443".parse().unwrap()),
+            lease: None,
+        };
+        let conn = pool.call(pinned.clone()).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn);
+
+        let conn = pool.call(pinned).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn);
+
+        let conn = pool.call(req).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn);
+    }
+
+    #[actix_rt::test]
+    async fn test_pool_lease_key() {
+        let generated = Rc::new(Cell::new(0));
+        let generated_clone = generated.clone();
+
+        let connector = TestPoolConnector { generated };
+
+        let config = ConnectorConfig::default();
+
+        let pool = super::ConnectionPool::new(connector, config);
+
+        let lease = ConnectionLease::new();
+        let leased = Connect {
+            uri: Uri::from_static("https://crates.io"),
+            addr: None,
+            lease: Some(lease.clone()),
+        };
+        let conn = pool.call(leased.clone()).await.unwrap();
+        assert_eq!(1, generated_clone.get());
+        release(conn);
+
+        // leased connection is not handed to other requests
+        let req = Connect {
+            uri: Uri::from_static("https://crates.io"),
+            addr: None,
+            lease: None,
+        };
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn);
+
+        let conn = pool.call(leased).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn);
+
+        // once returned, both connections are shared
+        drop(lease);
+        let conn1 = pool.call(req.clone()).await.unwrap();
+        let conn2 = pool.call(req.clone()).await.unwrap();
+        assert_eq!(2, generated_clone.get());
+        release(conn1);
+        release(conn2);
+    }
+
     #[actix_rt::test]
     async fn test_pool_drop() {
         let generated = Rc::new(Cell::new(0));
@@ -640,6 +918,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://crates.io"),
             addr: None,
+            lease: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -649,6 +928,7 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://google.com"),
             addr: None,
+            lease: None,
         };
         let conn = pool.call(req.clone()).await.unwrap();
         assert_eq!(2, generated_clone.get());
@@ -675,4 +955,146 @@ mod test {
         }
         assert_eq!(0, generated_clone.get());
     }
+
+    /// Connector that fails while `failing` is set, counting connection attempts.
+    struct FlakyConnector {
+        attempts: Rc<Cell<usize>>,
+        failing: Rc<Cell<bool>>,
+        generated: Rc<Cell<usize>>,
+    }
+
+    impl Service<Connect> for FlakyConnector {
+        type Response = (TestStream, Protocol);
+        type Error = ConnectError;
+        type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        actix_service::always_ready!();
+
+        fn call(&self, _: Connect) -> Self::Future {
+            self.attempts.set(self.attempts.get() + 1);
+
+            if self.failing.get() {
+                // yield so that attempts started together are in flight at the same time
+                return Box::pin(async {
+                    actix_rt::task::yield_now().await;
+                    let err = io::Error::from(io::ErrorKind::ConnectionRefused);
+                    Err(ConnectError::Io(err))
+                });
+            }
+
+            self.generated.set(self.generated.get() + 1);
+            let generated = self.generated.clone();
+            Box::pin(async { Ok((TestStream(generated), Protocol::Http1)) })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_pool_connect_backoff() {
+        tokio::time::pause();
+
+        let attempts = Rc::new(Cell::new(0));
+        let failing = Rc::new(Cell::new(true));
+
+        let connector = FlakyConnector {
+            attempts: attempts.clone(),
+            failing: failing.clone(),
+            generated: Rc::new(Cell::new(0)),
+        };
+
+        let config = ConnectorConfig {
+            connect_backoff: Some((Duration::from_millis(200), Duration::from_secs(1))),
+            ..Default::default()
+        };
+
+        let pool = super::ConnectionPool::new(connector, config);
+
+        let req = Connect {
+            uri: Uri::from_static("http://localhost"),
+            addr: None,
+            lease: None,
+        };
+
+        let other = Connect {
+            uri: Uri::from_static("http://other"),
+            addr: None,
+            lease: None,
+        };
+
+        // concurrent failures count once
+        let (res1, res2) =
+            futures_util::future::join(pool.call(req.clone()), pool.call(req.clone()))
+                .await;
+        assert!(matches!(res1.err().unwrap(), ConnectError::Io(_)));
+        assert!(matches!(res2.err().unwrap(), ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 2);
+
+        // repeated attempts fail fast without connecting
+        for _ in 0..3 {
+            let err = pool.call(req.clone()).await.err().unwrap();
+            match err {
+                ConnectError::Backoff {
+                    authority,
+                    retry_after,
+                } => {
+                    assert_eq!(authority, "localhost");
+                    assert_eq!(retry_after, Duration::from_millis(200));
+                }
+                err => panic!("unexpected error: {}", err),
+            }
+        }
+        assert_eq!(attempts.get(), 2);
+
+        // other hosts are not backed off
+        let err = pool.call(other.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 3);
+
+        // period doubles after the next failure
+        actix_rt::time::sleep(Duration::from_millis(250)).await;
+        let err = pool.call(req.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 4);
+
+        actix_rt::time::sleep(Duration::from_millis(250)).await;
+        let err = pool.call(req.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Backoff { .. }));
+        assert_eq!(attempts.get(), 4);
+
+        // host recovers once the period has elapsed
+        failing.set(false);
+        actix_rt::time::sleep(Duration::from_millis(200)).await;
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(attempts.get(), 5);
+        drop(conn);
+
+        // success resets the backoff
+        failing.set(true);
+        let err = pool.call(req.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 6);
+
+        actix_rt::time::sleep(Duration::from_millis(250)).await;
+        let err = pool.call(req.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 7);
+        assert_eq!(pool.inner.backoff.borrow().len(), 2);
+
+        // entries of hosts that stopped failing are pruned and start over
+        actix_rt::time::sleep(Duration::from_secs(2)).await;
+        let err = pool.call(other).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 8);
+        assert_eq!(pool.inner.backoff.borrow().len(), 1);
+
+        let err = pool.call(req.clone()).await.err().unwrap();
+        assert!(matches!(err, ConnectError::Io(_)));
+        assert_eq!(attempts.get(), 9);
+
+        match pool.call(req).await.err().unwrap() {
+            ConnectError::Backoff { retry_after, .. } => {
+                assert_eq!(retry_after, Duration::from_millis(200))
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
 }
@@ -0,0 +1,267 @@
+//! TLS session resumption and handshake reporting for the client connector.
+
+use std::{fmt, rc::Rc, time::Duration};
+
+/// Details of a completed client TLS handshake.
+///
+/// Passed to the hook set with [`Connector::on_tls_handshake`](super::Connector::on_tls_handshake).
+#[derive(Debug, Clone)]
+pub struct TlsHandshake {
+    host: String,
+    resumed: Option<bool>,
+    duration: Duration,
+}
+
+impl TlsHandshake {
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    pub(crate) fn new(host: String, resumed: Option<bool>, duration: Duration) -> Self {
+        TlsHandshake {
+            host,
+            resumed,
+            duration,
+        }
+    }
+
+    /// Host name the connection was made to.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns true if a cached session was resumed, false for a full handshake.
+    ///
+    /// Returns `None` when the TLS backend can not tell, which is the case for rustls.
+    pub fn resumed(&self) -> Option<bool> {
+        self.resumed
+    }
+
+    /// Time taken by the handshake, excluding the TCP connect.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct TlsHandshakeHook(Rc<dyn Fn(&TlsHandshake)>);
+
+impl TlsHandshakeHook {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(&TlsHandshake) + 'static,
+    {
+        TlsHandshakeHook(Rc::new(f))
+    }
+
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    pub(crate) fn call(&self, handshake: &TlsHandshake) {
+        (self.0)(handshake)
+    }
+}
+
+impl fmt::Debug for TlsHandshakeHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TlsHandshakeHook")
+    }
+}
+
+#[cfg(feature = "openssl")]
+pub(crate) use self::openssl::{OpensslHandshake, OpensslSessions};
+
+#[cfg(feature = "openssl")]
+mod openssl {
+    use std::{
+        cell::RefCell,
+        collections::VecDeque,
+        io,
+        pin::Pin,
+        rc::Rc,
+        task::{Context, Poll},
+        time::Instant,
+    };
+
+    use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
+    use actix_service::Service;
+    use actix_tls::connect::Connection as TcpConnection;
+    use futures_core::future::LocalBoxFuture;
+    use http::Uri;
+    use tls_openssl::{
+        error::ErrorStack,
+        ssl::{SslConnector, SslSession},
+    };
+    use tokio_openssl::SslStream;
+
+    use super::{TlsHandshake, TlsHandshakeHook};
+    use crate::client::{connector::Io, error::ConnectError, pool::Protocol};
+
+    const H2: &[u8] = b"h2";
+
+    /// Client sessions of one connector, keyed by the authority they were made with.
+    ///
+    /// Each connector has its own cache and with it its own client identity, so sessions are never
+    /// offered with a different client certificate than they were established with.
+    pub(crate) struct OpensslSessions {
+        capacity: usize,
+        sessions: RefCell<VecDeque<(String, SslSession)>>,
+    }
+
+    impl OpensslSessions {
+        pub(crate) fn new(capacity: usize) -> Self {
+            OpensslSessions {
+                capacity,
+                sessions: RefCell::new(VecDeque::with_capacity(capacity)),
+            }
+        }
+
+        fn get(&self, authority: &str) -> Option<SslSession> {
+            self.sessions
+                .borrow()
+                .iter()
+                .find(|(key, _)| key == authority)
+                .map(|(_, session)| session.clone())
+        }
+
+        fn put(&self, authority: &str, session: SslSession) {
+            if self.capacity == 0 {
+                return;
+            }
+
+            let mut sessions = self.sessions.borrow_mut();
+            sessions.retain(|(key, _)| key != authority);
+
+            if sessions.len() == self.capacity {
+                sessions.pop_front();
+            }
+
+            sessions.push_back((authority.to_owned(), session));
+        }
+    }
+
+    /// Performs client TLS handshakes, offering cached sessions for resumption.
+    pub(crate) struct OpensslHandshake {
+        pub(crate) connector: SslConnector,
+        pub(crate) sessions: Option<Rc<OpensslSessions>>,
+        pub(crate) hook: Option<TlsHandshakeHook>,
+    }
+
+    impl<U> Service<TcpConnection<Uri, U>> for OpensslHandshake
+    where
+        U: Io + 'static,
+    {
+        type Response = (Box<dyn Io>, Protocol);
+        type Error = ConnectError;
+        type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        actix_service::always_ready!();
+
+        fn call(&self, conn: TcpConnection<Uri, U>) -> Self::Future {
+            let (io, uri) = conn.into_parts();
+            let host = uri.host().unwrap_or_default().to_owned();
+            let authority = format!("{}:{}", host, uri.port_u16().unwrap_or(443));
+
+            let connector = self.connector.clone();
+            let sessions = self.sessions.clone();
+            let hook = self.hook.clone();
+
+            Box::pin(async move {
+                let start = Instant::now();
+
+                let mut ssl = connector
+                    .configure()
+                    .and_then(|config| config.into_ssl(&host))
+                    .map_err(ssl_error)?;
+
+                if let Some(session) = sessions.as_ref().and_then(|s| s.get(&authority))
+                {
+                    // SAFETY: the session was created by a connection of this same connector
+                    unsafe { ssl.set_session(&session) }.map_err(ssl_error)?;
+                }
+
+                let mut stream = SslStream::new(ssl, io).map_err(ssl_error)?;
+                Pin::new(&mut stream)
+                    .connect()
+                    .await
+                    .map_err(ConnectError::SslError)?;
+
+                let ssl = stream.ssl();
+
+                if let Some(ref hook) = hook {
+                    let resumed = Some(ssl.session_reused());
+                    hook.call(&TlsHandshake::new(host, resumed, start.elapsed()));
+                }
+
+                let h2 = ssl
+                    .selected_alpn_protocol()
+                    .map(|protos| protos.windows(2).any(|w| w == H2))
+                    .unwrap_or(false);
+
+                let proto = if h2 { Protocol::Http2 } else { Protocol::Http1 };
+
+                let io: Box<dyn Io> = match sessions {
+                    Some(sessions) => Box::new(SessionCapture {
+                        stream,
+                        authority,
+                        sessions,
+                    }),
+                    None => Box::new(stream),
+                };
+
+                Ok((io, proto))
+            })
+        }
+    }
+
+    fn ssl_error(err: ErrorStack) -> ConnectError {
+        ConnectError::SslError(err.into())
+    }
+
+    /// TLS stream that stores its session in the cache once the connection is closed.
+    ///
+    /// TLS 1.3 servers send session tickets after the handshake, so the session is only taken
+    /// when the connection is dropped, after all tickets have been read.
+    struct SessionCapture<U: Io> {
+        stream: SslStream<U>,
+        authority: String,
+        sessions: Rc<OpensslSessions>,
+    }
+
+    impl<U: Io> Drop for SessionCapture<U> {
+        fn drop(&mut self) {
+            if let Some(session) = self.stream.ssl().session() {
+                self.sessions.put(&self.authority, session.to_owned());
+            }
+        }
+    }
+
+    impl<U: Io> AsyncRead for SessionCapture<U> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_read(cx, buf)
+        }
+    }
+
+    impl<U: Io> AsyncWrite for SessionCapture<U> {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut self.stream).poll_write(cx, buf)
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_flush(cx)
+        }
+
+        fn poll_shutdown(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<io::Result<()>> {
+            Pin::new(&mut self.stream).poll_shutdown(cx)
+        }
+    }
+}
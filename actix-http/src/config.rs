@@ -11,6 +11,10 @@ use actix_rt::{
 use bytes::BytesMut;
 use time::OffsetDateTime;
 
+use crate::body::ResponseBody;
+use crate::header::ResponseHeaderLimits;
+use crate::response::Response;
+
 /// "Sun, 06 Nov 1994 08:49:37 GMT".len()
 const DATE_VALUE_LENGTH: usize = 29;
 
@@ -41,13 +45,35 @@ impl From<Option<usize>> for KeepAlive {
     }
 }
 
+/// Keep-alive behavior of the connection a response is sent on.
+///
+/// Inserted into the extensions of a response, it overrides the service's [`KeepAlive`] setting for
+/// that response on HTTP/1 connections. The HTTP/2 dispatcher ignores it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KeepAlivePolicy {
+    /// Close the connection once the response is sent.
+    Close,
+
+    /// Wait the given number of seconds for the next request after the response is sent,
+    /// advertising the timeout with a `Keep-Alive: timeout=<secs>` header.
+    ///
+    /// A timeout of zero closes the connection. The policy can not keep a connection alive on its
+    /// own: it has no effect when the client asked to close the connection or when the service's
+    /// keep-alive is disabled or left to the OS.
+    Timeout(usize),
+}
+
 /// Http service configuration
 pub struct ServiceConfig(Rc<Inner>);
 
 struct Inner {
     keep_alive: Option<Duration>,
+    idle_timeout: Option<Duration>,
     client_timeout: u64,
     client_disconnect: u64,
+    payload_rate: Option<(u64, Duration)>,
+    max_chunk_size: Option<usize>,
+    response_header_limits: Option<ResponseHeaderLimits>,
     ka_enabled: bool,
     secure: bool,
     local_addr: Option<std::net::SocketAddr>,
@@ -88,15 +114,72 @@ impl ServiceConfig {
 
         ServiceConfig(Rc::new(Inner {
             keep_alive,
+            idle_timeout: None,
             ka_enabled,
             client_timeout,
             client_disconnect,
+            payload_rate: None,
+            max_chunk_size: None,
+            response_header_limits: None,
             secure,
             local_addr,
             date_service: DateService::new(),
         }))
     }
 
+    /// Set minimum rate, in bytes per second, at which request payloads must be received,
+    /// measured over windows of `window` milliseconds.
+    ///
+    /// Must be called before the config is cloned.
+    pub(crate) fn with_payload_rate(mut self, rate: u64, window: u64) -> Self {
+        let inner = Rc::get_mut(&mut self.0).expect("ServiceConfig is shared");
+        inner.payload_rate = if rate != 0 && window != 0 {
+            Some((rate, Duration::from_millis(window)))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Set maximum size of chunks written with chunked transfer encoding; 0 disables the limit.
+    ///
+    /// Must be called before the config is cloned.
+    pub(crate) fn with_max_chunk_size(mut self, size: usize) -> Self {
+        let inner = Rc::get_mut(&mut self.0).expect("ServiceConfig is shared");
+        inner.max_chunk_size = if size != 0 { Some(size) } else { None };
+        self
+    }
+
+    /// Set limits enforced on response headers, if any.
+    ///
+    /// Must be called before the config is cloned.
+    pub(crate) fn with_response_header_limits(
+        mut self,
+        limits: Option<ResponseHeaderLimits>,
+    ) -> Self {
+        let inner = Rc::get_mut(&mut self.0).expect("ServiceConfig is shared");
+        inner.response_header_limits = limits;
+        self
+    }
+
+    /// Set the timeout for idle keep-alive connections, if keep-alive is enabled and `timeout` is
+    /// set. Also replaces the keep-alive duration.
+    ///
+    /// Must be called before the config is cloned.
+    pub(crate) fn with_keep_alive_timeout(mut self, timeout: Option<Duration>) -> Self {
+        let inner = Rc::get_mut(&mut self.0).expect("ServiceConfig is shared");
+        if let (true, Some(timeout)) = (inner.ka_enabled, timeout) {
+            if timeout.as_nanos() > 0 {
+                inner.keep_alive = Some(timeout);
+                inner.idle_timeout = Some(timeout);
+            } else {
+                // zero timeout relies on the OS, like `KeepAlive::Os`
+                inner.keep_alive = None;
+            }
+        }
+        self
+    }
+
     /// Returns true if connection is secure (HTTPS)
     #[inline]
     pub fn secure(&self) -> bool {
@@ -115,6 +198,12 @@ impl ServiceConfig {
         self.0.keep_alive
     }
 
+    /// Timeout for idle keep-alive connections, if set with sub-second precision.
+    #[inline]
+    pub(crate) fn idle_timeout(&self) -> Option<Duration> {
+        self.0.idle_timeout
+    }
+
     /// Return state of connection keep-alive functionality
     #[inline]
     pub fn keep_alive_enabled(&self) -> bool {
@@ -134,6 +223,40 @@ impl ServiceConfig {
         }
     }
 
+    /// Time allowed for receiving a complete request head, if configured.
+    #[inline]
+    pub(crate) fn client_timeout(&self) -> Option<Duration> {
+        match self.0.client_timeout {
+            0 => None,
+            delay => Some(Duration::from_millis(delay)),
+        }
+    }
+
+    /// Minimum request payload rate in bytes per second and the window it is measured over.
+    #[inline]
+    pub(crate) fn payload_rate(&self) -> Option<(u64, Duration)> {
+        self.0.payload_rate
+    }
+
+    /// Maximum size of chunks written with chunked transfer encoding, if limited.
+    #[inline]
+    pub(crate) fn max_chunk_size(&self) -> Option<usize> {
+        self.0.max_chunk_size
+    }
+
+    /// Applies the configured response header limits to a response about to be encoded.
+    #[inline]
+    pub(crate) fn limit_response_headers<B>(
+        &self,
+        res: Response<()>,
+        body: ResponseBody<B>,
+    ) -> (Response<()>, ResponseBody<B>) {
+        match self.0.response_header_limits {
+            Some(ref limits) => limits.enforce(res, body),
+            None => (res, body),
+        }
+    }
+
     /// Client timeout for first request.
     pub fn client_timer_expire(&self) -> Option<Instant> {
         let delay = self.0.client_timeout;
@@ -0,0 +1,151 @@
+use std::{
+    cell::RefCell,
+    fmt, mem,
+    rc::Rc,
+    task::{Context, Waker},
+};
+
+use bytes::BytesMut;
+
+use crate::header::HeaderMap;
+
+/// Handle for sending `103 Early Hints` interim responses ahead of a request's final response.
+///
+/// Early hints usually carry `Link` headers that let the client start preloading resources while
+/// the final response is still being produced. The HTTP/1 dispatcher attaches a handle to the
+/// extensions of every HTTP/1.1 request; it writes queued hints to the connection as soon as it
+/// gets to run and before the final response head.
+///
+/// Hints can not be sent to HTTP/1.0 clients, over HTTP/2, or once the final response has been
+/// started; [`send`](Self::send) returns false in those cases. The default handle is detached and
+/// never sends anything.
+#[derive(Clone, Default)]
+pub struct EarlyHints {
+    inner: Option<Rc<RefCell<Inner>>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    pending: Vec<HeaderMap>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl EarlyHints {
+    pub(crate) fn new() -> Self {
+        EarlyHints {
+            inner: Some(Rc::new(RefCell::new(Inner::default()))),
+        }
+    }
+
+    /// Queues a `103 Early Hints` response with the given headers.
+    ///
+    /// Returns false if the hint will not be sent.
+    pub fn send(&self, headers: HeaderMap) -> bool {
+        let inner = match self.inner {
+            Some(ref inner) => inner,
+            None => return false,
+        };
+
+        let waker = {
+            let mut inner = inner.borrow_mut();
+
+            if inner.closed {
+                return false;
+            }
+
+            inner.pending.push(headers);
+            inner.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+
+        true
+    }
+
+    /// Returns true if hints can still be sent.
+    pub fn is_open(&self) -> bool {
+        matches!(self.inner, Some(ref inner) if !inner.borrow().closed)
+    }
+
+    /// Writes queued hints to `buf`, registering the current task to be woken by later hints.
+    pub(crate) fn poll_write(&self, cx: &mut Context<'_>, buf: &mut BytesMut) {
+        if let Some(ref inner) = self.inner {
+            inner.borrow_mut().waker = Some(cx.waker().clone());
+            self.write(buf);
+        }
+    }
+
+    /// Writes queued hints to `buf` and refuses any further hints.
+    pub(crate) fn close(&self, buf: &mut BytesMut) {
+        self.write(buf);
+
+        if let Some(ref inner) = self.inner {
+            let mut inner = inner.borrow_mut();
+            inner.closed = true;
+            inner.waker = None;
+        }
+    }
+
+    fn write(&self, buf: &mut BytesMut) {
+        let pending = match self.inner {
+            Some(ref inner) => mem::take(&mut inner.borrow_mut().pending),
+            None => return,
+        };
+
+        for headers in pending {
+            buf.extend_from_slice(b"HTTP/1.1 103 Early Hints\r\n");
+
+            for (name, value) in headers.iter() {
+                buf.extend_from_slice(name.as_str().as_bytes());
+                buf.extend_from_slice(b": ");
+                buf.extend_from_slice(value.as_bytes());
+                buf.extend_from_slice(b"\r\n");
+            }
+
+            buf.extend_from_slice(b"\r\n");
+        }
+    }
+}
+
+impl fmt::Debug for EarlyHints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EarlyHints")
+            .field("open", &self.is_open())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{HeaderValue, LINK};
+
+    #[test]
+    fn test_write_hints() {
+        let hints = EarlyHints::new();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_static("</style.css>; rel=preload"));
+        assert!(hints.send(headers));
+
+        let mut buf = BytesMut::new();
+        hints.close(&mut buf);
+        assert_eq!(
+            &buf[..],
+            b"HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\n"
+        );
+
+        assert!(!hints.is_open());
+        assert!(!hints.send(HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_detached() {
+        let hints = EarlyHints::default();
+        assert!(!hints.is_open());
+        assert!(!hints.send(HeaderMap::new()));
+    }
+}
@@ -14,7 +14,7 @@ use flate2::write::{GzDecoder, ZlibDecoder};
 use futures_core::{ready, Stream};
 
 use crate::{
-    encoding::Writer,
+    encoding::{LimitExceeded, Writer},
     error::{BlockingError, PayloadError},
     http::header::{ContentEncoding, HeaderMap, CONTENT_ENCODING},
 };
@@ -26,6 +26,9 @@ pub struct Decoder<S> {
     stream: S,
     eof: bool,
     fut: Option<JoinHandle<Result<(Option<Bytes>, ContentDecoder), io::Error>>>,
+
+    /// Number of bytes of an unencoded payload that can still be yielded, if limited.
+    remaining: Option<usize>,
 }
 
 impl<S> Decoder<S>
@@ -53,7 +56,23 @@ where
             stream,
             fut: None,
             eof: false,
+            remaining: None,
+        }
+    }
+
+    /// Set inflated size limit, the maximum number of decoded bytes the stream yields.
+    ///
+    /// The limit is enforced while decoding, so a small compressed payload can not inflate to more
+    /// than roughly the limit in memory. The stream fails with `PayloadError::Overflow` once the
+    /// decoded payload grows past the limit. Decoded chunks are only yielded, not deserialized;
+    /// buffering them is up to the consumer of the stream.
+    pub fn limit(mut self, limit: usize) -> Self {
+        match self.decoder {
+            Some(ref mut decoder) => decoder.writer_mut().remaining = Some(limit),
+            None => self.remaining = Some(limit),
         }
+
+        self
     }
 
     /// Construct decoder based on headers.
@@ -82,8 +101,9 @@ where
     ) -> Poll<Option<Self::Item>> {
         loop {
             if let Some(ref mut fut) = self.fut {
-                let (chunk, decoder) =
-                    ready!(Pin::new(fut).poll(cx)).map_err(|_| BlockingError)??;
+                let (chunk, decoder) = ready!(Pin::new(fut).poll(cx))
+                    .map_err(|_| BlockingError::Gone)?
+                    .map_err(decode_error)?;
 
                 self.decoder = Some(decoder);
                 self.fut.take();
@@ -103,7 +123,8 @@ where
                 Some(Ok(chunk)) => {
                     if let Some(mut decoder) = self.decoder.take() {
                         if chunk.len() < MAX_CHUNK_SIZE_DECODE_IN_PLACE {
-                            let chunk = decoder.feed_data(chunk)?;
+                            let chunk =
+                                decoder.feed_data(chunk).map_err(decode_error)?;
                             self.decoder = Some(decoder);
 
                             if let Some(chunk) = chunk {
@@ -118,6 +139,14 @@ where
 
                         continue;
                     } else {
+                        if let Some(ref mut remaining) = self.remaining {
+                            if chunk.len() > *remaining {
+                                return Poll::Ready(Some(Err(PayloadError::Overflow)));
+                            }
+
+                            *remaining -= chunk.len();
+                        }
+
                         return Poll::Ready(Some(Ok(chunk)));
                     }
                 }
@@ -129,7 +158,7 @@ where
                         match decoder.feed_eof() {
                             Ok(Some(res)) => Poll::Ready(Some(Ok(res))),
                             Ok(None) => Poll::Ready(None),
-                            Err(err) => Poll::Ready(Some(Err(err.into()))),
+                            Err(err) => Poll::Ready(Some(Err(decode_error(err)))),
                         }
                     } else {
                         Poll::Ready(None)
@@ -140,6 +169,15 @@ where
     }
 }
 
+/// Maps errors of a limited writer to `PayloadError::Overflow`.
+fn decode_error(err: io::Error) -> PayloadError {
+    if err.get_ref().map_or(false, |err| err.is::<LimitExceeded>()) {
+        PayloadError::Overflow
+    } else {
+        err.into()
+    }
+}
+
 enum ContentDecoder {
     Deflate(Box<ZlibDecoder<Writer>>),
     Gzip(Box<GzDecoder<Writer>>),
@@ -147,6 +185,14 @@ enum ContentDecoder {
 }
 
 impl ContentDecoder {
+    fn writer_mut(&mut self) -> &mut Writer {
+        match self {
+            ContentDecoder::Br(ref mut decoder) => decoder.get_mut(),
+            ContentDecoder::Gzip(ref mut decoder) => decoder.get_mut(),
+            ContentDecoder::Deflate(ref mut decoder) => decoder.get_mut(),
+        }
+    }
+
     fn feed_eof(&mut self) -> io::Result<Option<Bytes>> {
         match self {
             ContentDecoder::Br(ref mut decoder) => match decoder.flush() {
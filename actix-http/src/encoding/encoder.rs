@@ -17,7 +17,7 @@ use pin_project::pin_project;
 use crate::{
     body::{Body, BodySize, MessageBody, ResponseBody},
     http::{
-        header::{ContentEncoding, CONTENT_ENCODING},
+        header::{ContentEncoding, CONTENT_ENCODING, CONTENT_LENGTH},
         HeaderValue, StatusCode,
     },
     Error, ResponseHead,
@@ -68,7 +68,10 @@ impl<B: MessageBody> Encoder<B> {
         if can_encode {
             // Modify response body only if encoder is not None
             if let Some(enc) = ContentEncoder::encoder(encoding) {
+                // the size of the encoded body is unknown; any content length set for the
+                // original body is stale and the response is sent with chunked framing instead
                 update_head(encoding, head);
+                head.headers_mut().remove(CONTENT_LENGTH);
                 head.no_chunking(false);
                 return ResponseBody::Body(Encoder {
                     body,
@@ -144,8 +147,8 @@ impl<B: MessageBody> MessageBody for Encoder<B> {
             }
 
             if let Some(ref mut fut) = this.fut {
-                let mut encoder =
-                    ready!(Pin::new(fut).poll(cx)).map_err(|_| BlockingError)??;
+                let mut encoder = ready!(Pin::new(fut).poll(cx))
+                    .map_err(|_| BlockingError::Gone)??;
 
                 let chunk = encoder.take();
                 *this.encoder = Some(encoder);
@@ -1,6 +1,6 @@
 //! Content-Encoding support.
 
-use std::io;
+use std::{fmt, io};
 
 use bytes::{Bytes, BytesMut};
 
@@ -12,12 +12,16 @@ pub use self::encoder::Encoder;
 
 pub(self) struct Writer {
     buf: BytesMut,
+
+    /// Number of bytes that can still be written, if limited.
+    remaining: Option<usize>,
 }
 
 impl Writer {
     fn new() -> Writer {
         Writer {
             buf: BytesMut::with_capacity(8192),
+            remaining: None,
         }
     }
 
@@ -28,6 +32,14 @@ impl Writer {
 
 impl io::Write for Writer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(ref mut remaining) = self.remaining {
+            if buf.len() > *remaining {
+                return Err(io::Error::new(io::ErrorKind::Other, LimitExceeded));
+            }
+
+            *remaining -= buf.len();
+        }
+
         self.buf.extend_from_slice(buf);
         Ok(buf.len())
     }
@@ -36,3 +48,15 @@ impl io::Write for Writer {
         Ok(())
     }
 }
+
+/// Error returned by a limited [`Writer`] that is given more bytes than it can hold.
+#[derive(Debug)]
+pub(self) struct LimitExceeded;
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("decoded payload exceeds limit")
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
@@ -4,6 +4,7 @@ use std::cell::RefCell;
 use std::io::Write;
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
+use std::time::Duration;
 use std::{fmt, io, result};
 
 use actix_codec::{Decoder, Encoder};
@@ -81,6 +82,14 @@ pub trait ResponseError: fmt::Debug + fmt::Display {
         resp.set_body(Body::from(buf))
     }
 
+    /// Returns true if the error's response is meant to be shown to clients as is.
+    ///
+    /// Servers that hide the details of server errors from clients leave the responses of
+    /// user-facing errors unchanged. Not user-facing by default.
+    fn is_user_facing(&self) -> bool {
+        false
+    }
+
     downcast_get_type_id!();
 }
 
@@ -301,13 +310,83 @@ impl From<httparse::Error> for ParseError {
 
 /// A set of errors that can occur running blocking tasks in thread pool.
 #[derive(Debug, Display)]
-#[display(fmt = "Blocking thread pool is gone")]
-pub struct BlockingError;
+pub enum BlockingError {
+    /// Blocking thread pool is gone or the task panicked.
+    #[display(fmt = "Blocking thread pool is gone")]
+    Gone,
+
+    /// Task was rejected because the blocking task queue is full or the task was queued for
+    /// longer than allowed.
+    #[display(fmt = "Blocking thread pool is overloaded")]
+    Overloaded {
+        /// Suggested delay before the client retries its request.
+        retry_after: Duration,
+    },
+}
 
 impl std::error::Error for BlockingError {}
 
-/// `InternalServerError` for `BlockingError`
-impl ResponseError for BlockingError {}
+/// Returns [`StatusCode::SERVICE_UNAVAILABLE`] with a `Retry-After` header for
+/// [`BlockingError::Overloaded`] and [`StatusCode::INTERNAL_SERVER_ERROR`] otherwise.
+impl ResponseError for BlockingError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BlockingError::Gone => StatusCode::INTERNAL_SERVER_ERROR,
+            BlockingError::Overloaded { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    fn error_response(&self) -> Response {
+        let mut resp = Response::new(self.status_code());
+        let mut buf = BytesMut::new();
+        let _ = write!(Writer(&mut buf), "{}", self);
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+
+        if let BlockingError::Overloaded { retry_after } = self {
+            // round up to whole seconds; a zero delay would invite an immediate retry
+            let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            resp.headers_mut()
+                .insert(header::RETRY_AFTER, header::HeaderValue::from(secs.max(1)));
+        }
+
+        resp.set_body(Body::from(buf))
+    }
+}
+
+/// A set of errors that can occur when a response body does not match its declared length.
+#[derive(Debug, Display)]
+pub enum ContentLengthError {
+    /// Body is longer than the declared content length.
+    #[display(
+        fmt = "Response body exceeds declared content length of {} bytes",
+        expected
+    )]
+    Overrun {
+        /// Declared content length.
+        expected: u64,
+    },
+
+    /// Body ended before reaching the declared content length.
+    #[display(
+        fmt = "Response body ended after {} of {} declared bytes",
+        actual,
+        expected
+    )]
+    Underrun {
+        /// Declared content length.
+        expected: u64,
+        /// Number of bytes in the body.
+        actual: u64,
+    },
+}
+
+impl std::error::Error for ContentLengthError {}
+
+/// Return `InternalServerError` for `ContentLengthError`
+impl ResponseError for ContentLengthError {}
 
 #[derive(Display, Debug)]
 /// A set of errors that can occur during payload parsing
@@ -331,6 +410,10 @@ pub enum PayloadError {
     #[display(fmt = "Payload length is unknown.")]
     UnknownLength,
 
+    /// Shared budget for buffering payloads is exhausted.
+    #[display(fmt = "Payload buffering budget is exhausted.")]
+    BudgetExhausted,
+
     /// HTTP/2 payload error.
     #[display(fmt = "{}", _0)]
     Http2Payload(h2::Error),
@@ -348,6 +431,7 @@ impl std::error::Error for PayloadError {
             PayloadError::EncodingCorrupted => None,
             PayloadError::Overflow => None,
             PayloadError::UnknownLength => None,
+            PayloadError::BudgetExhausted => None,
             PayloadError::Http2Payload(err) => Some(err as &dyn std::error::Error),
             PayloadError::Io(err) => Some(err as &dyn std::error::Error),
         }
@@ -389,6 +473,7 @@ impl ResponseError for PayloadError {
     fn status_code(&self) -> StatusCode {
         match *self {
             PayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            PayloadError::BudgetExhausted => StatusCode::SERVICE_UNAVAILABLE,
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -575,6 +660,11 @@ where
             }
         }
     }
+
+    /// Errors created with a predefined response are user-facing.
+    fn is_user_facing(&self) -> bool {
+        matches!(self.status, InternalErrorType::Response(_))
+    }
 }
 
 /// Helper function that creates wrapper of any error and generate *BAD
@@ -983,6 +1073,20 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
 
+    #[test]
+    fn test_blocking_error_response() {
+        let resp: Response = BlockingError::Gone.error_response();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp.headers().get(header::RETRY_AFTER).is_none());
+
+        let err = BlockingError::Overloaded {
+            retry_after: Duration::from_millis(1500),
+        };
+        let resp: Response = err.error_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "2");
+    }
+
     #[cfg(feature = "cookies")]
     #[test]
     fn test_cookie_parse() {
@@ -240,6 +240,7 @@ impl MessageType for Request {
         head.uri = uri;
         head.method = method;
         head.version = ver;
+        head.wire_stats.header_bytes = len;
 
         Ok(Some((msg, decoder)))
     }
@@ -353,7 +354,9 @@ impl HeaderIndex {
 #[derive(Debug, Clone, PartialEq)]
 /// Http payload item
 pub enum PayloadItem {
+    /// Chunk of payload data.
     Chunk(Bytes),
+    /// End of the payload.
     Eof,
 }
 
@@ -375,7 +378,7 @@ impl PayloadDecoder {
 
     pub fn chunked() -> PayloadDecoder {
         PayloadDecoder {
-            kind: Kind::Chunked(ChunkedState::Size, 0),
+            kind: Kind::Chunked(ChunkedDecoder::new()),
         }
     }
 
@@ -390,7 +393,7 @@ enum Kind {
     /// integer.
     Length(u64),
     /// A Reader used when Transfer-Encoding is `chunked`.
-    Chunked(ChunkedState, u64),
+    Chunked(ChunkedDecoder),
     /// A Reader used for responses that don't indicate a length or chunked.
     ///
     /// Note: This should only used for `Response`s. It is illegal for a
@@ -408,20 +411,115 @@ enum Kind {
     Eof,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-enum ChunkedState {
+/// Position of a [`ChunkedDecoder`] within the chunked transfer coding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum ChunkedState {
+    /// Reading hex digits of a chunk size.
     Size,
+    /// Reading white space after a chunk size.
     SizeLws,
+    /// Skipping a chunk extension.
     Extension,
+    /// Expecting the LF ending a chunk size line.
     SizeLf,
+    /// Reading chunk data.
     Body,
+    /// Expecting the CR after chunk data.
     BodyCr,
+    /// Expecting the LF after chunk data.
     BodyLf,
+    /// Expecting the CR ending the last chunk.
     EndCr,
+    /// Expecting the LF ending the last chunk.
     EndLf,
+    /// The payload is complete.
     End,
 }
 
+/// Resumable decoder of the chunked transfer coding.
+///
+/// Each call to `decode` consumes what it can of the buffer and keeps its position in its
+/// [`ChunkedState`], so the payload may be split at any byte. Malformed input fails with an
+/// `InvalidInput` error; no input makes the decoder panic. Chunk extensions are skipped and
+/// trailers are not supported.
+///
+/// # Examples
+/// ```
+/// use actix_codec::Decoder;
+/// use actix_http::h1::{ChunkedDecoder, ChunkedState, PayloadItem};
+/// use bytes::BytesMut;
+///
+/// let mut decoder = ChunkedDecoder::new();
+/// let mut buf = BytesMut::from(&b"4\r\nda"[..]);
+///
+/// match decoder.decode(&mut buf).unwrap() {
+///     Some(PayloadItem::Chunk(data)) => assert_eq!(data, "da"),
+///     _ => panic!(),
+/// }
+/// assert_eq!(decoder.state(), ChunkedState::Body);
+/// assert_eq!(decoder.remaining(), 2);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ChunkedDecoder {
+    state: ChunkedState,
+    size: u64,
+}
+
+impl Default for ChunkedDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkedDecoder {
+    /// Creates decoder expecting the size line of the first chunk.
+    pub fn new() -> Self {
+        ChunkedDecoder {
+            state: ChunkedState::Size,
+            size: 0,
+        }
+    }
+
+    /// Returns current position within the chunked transfer coding.
+    pub fn state(&self) -> ChunkedState {
+        self.state
+    }
+
+    /// Returns size read so far while reading a chunk size, or bytes of the chunk data left
+    /// while reading it.
+    pub fn remaining(&self) -> u64 {
+        self.size
+    }
+}
+
+impl Decoder for ChunkedDecoder {
+    type Item = PayloadItem;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let mut buf = None;
+            // advances the chunked state
+            self.state = match self.state.step(src, &mut self.size, &mut buf) {
+                Poll::Pending => return Ok(None),
+                Poll::Ready(Ok(state)) => state,
+                Poll::Ready(Err(e)) => return Err(e),
+            };
+            if self.state == ChunkedState::End {
+                trace!("End of chunked stream");
+                return Ok(Some(PayloadItem::Eof));
+            }
+            if let Some(buf) = buf {
+                return Ok(Some(PayloadItem::Chunk(buf)));
+            }
+            if src.is_empty() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
 impl Decoder for PayloadDecoder {
     type Item = PayloadItem;
     type Error = io::Error;
@@ -448,27 +546,7 @@ impl Decoder for PayloadDecoder {
                     Ok(Some(PayloadItem::Chunk(buf)))
                 }
             }
-            Kind::Chunked(ref mut state, ref mut size) => {
-                loop {
-                    let mut buf = None;
-                    // advances the chunked state
-                    *state = match state.step(src, size, &mut buf) {
-                        Poll::Pending => return Ok(None),
-                        Poll::Ready(Ok(state)) => state,
-                        Poll::Ready(Err(e)) => return Err(e),
-                    };
-                    if *state == ChunkedState::End {
-                        trace!("End of chunked stream");
-                        return Ok(Some(PayloadItem::Eof));
-                    }
-                    if let Some(buf) = buf {
-                        return Ok(Some(PayloadItem::Chunk(buf)));
-                    }
-                    if src.is_empty() {
-                        return Ok(None);
-                    }
-                }
-            }
+            Kind::Chunked(ref mut decoder) => decoder.decode(src),
             Kind::Eof => {
                 if src.is_empty() {
                     Ok(None)
@@ -519,19 +597,10 @@ impl ChunkedState {
         size: &mut u64,
     ) -> Poll<Result<ChunkedState, io::Error>> {
         let radix = 16;
-        match byte!(rdr) {
-            b @ b'0'..=b'9' => {
-                *size *= radix;
-                *size += u64::from(b - b'0');
-            }
-            b @ b'a'..=b'f' => {
-                *size *= radix;
-                *size += u64::from(b + 10 - b'a');
-            }
-            b @ b'A'..=b'F' => {
-                *size *= radix;
-                *size += u64::from(b + 10 - b'A');
-            }
+        let digit = match byte!(rdr) {
+            b @ b'0'..=b'9' => b - b'0',
+            b @ b'a'..=b'f' => b + 10 - b'a',
+            b @ b'A'..=b'F' => b + 10 - b'A',
             b'\t' | b' ' => return Poll::Ready(Ok(ChunkedState::SizeLws)),
             b';' => return Poll::Ready(Ok(ChunkedState::Extension)),
             b'\r' => return Poll::Ready(Ok(ChunkedState::SizeLf)),
@@ -541,7 +610,21 @@ impl ChunkedState {
                     "Invalid chunk size line: Invalid Size",
                 )));
             }
+        };
+
+        match size
+            .checked_mul(radix)
+            .and_then(|size| size.checked_add(u64::from(digit)))
+        {
+            Some(new_size) => *size = new_size,
+            None => {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Invalid chunk size line: Size is too big",
+                )));
+            }
         }
+
         Poll::Ready(Ok(ChunkedState::Size))
     }
 
@@ -1231,6 +1314,144 @@ mod tests {
         assert!(msg.eof());
     }
 
+    #[test]
+    fn test_chunked_size_overflow() {
+        let mut decoder = PayloadDecoder::chunked();
+        let mut buf = BytesMut::from(&b"fffffffffffffffff\r\n"[..]);
+        assert!(decoder.decode(&mut buf).is_err());
+
+        let mut decoder = PayloadDecoder::chunked();
+        let mut buf = BytesMut::from(&b"ffffffffffffffff\r\n"[..]);
+        assert!(decoder.decode(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_chunked_states() {
+        use ChunkedState::*;
+
+        let mut decoder = ChunkedDecoder::new();
+        let mut buf = BytesMut::new();
+        let mut states = Vec::new();
+        let mut body = Vec::new();
+
+        // one byte at a time, so every state is observed
+        for byte in b"a;x\r\n0123456789\r\n0\r\n\r\n" {
+            buf.extend_from_slice(&[*byte]);
+
+            match decoder.decode(&mut buf).unwrap() {
+                Some(PayloadItem::Chunk(data)) => body.extend_from_slice(&data),
+                Some(PayloadItem::Eof) => assert_eq!(decoder.state(), End),
+                None => {}
+            }
+
+            if states.is_empty() {
+                assert_eq!(decoder.remaining(), 10);
+            }
+            states.push(decoder.state());
+        }
+
+        // the LF of the size line and all data bytes but the last leave the decoder in `Body`
+        let mut expected = vec![Size, Extension, Extension, SizeLf];
+        expected.extend(std::iter::repeat(Body).take(10));
+        expected.extend(vec![BodyCr, BodyLf, Size, Size, SizeLf, EndCr, EndLf, End]);
+
+        assert_eq!(states, expected);
+        assert_eq!(body, b"0123456789");
+        assert!(buf.is_empty());
+    }
+
+    /// Decodes `chunks` fed one after another, returning the body or the first error.
+    fn decode_chunked(chunks: &[&[u8]]) -> io::Result<Option<Vec<u8>>> {
+        let mut decoder = PayloadDecoder::chunked();
+        let mut buf = BytesMut::new();
+        let mut body = Vec::new();
+
+        for chunk in chunks {
+            buf.extend_from_slice(chunk);
+
+            while let Some(item) = decoder.decode(&mut buf)? {
+                match item {
+                    PayloadItem::Chunk(data) => body.extend_from_slice(&data),
+                    PayloadItem::Eof => return Ok(Some(body)),
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    #[test]
+    fn test_chunked_random_splits() {
+        let payload: &[u8] =
+            b"4\r\ndata\r\n1;ext=\"x\"\r\n\r\r\nA \r\n0123456789\r\n0\r\n\r\n";
+        let expected = b"data\r0123456789".to_vec();
+
+        assert_eq!(decode_chunked(&[payload]).unwrap().unwrap(), expected);
+
+        for split in 0..=payload.len() {
+            let (a, b) = payload.split_at(split);
+            assert_eq!(decode_chunked(&[a, b]).unwrap().unwrap(), expected);
+        }
+
+        // simple linear congruential generator keeps the split points reproducible
+        let mut seed: u64 = 0x9e37_79b9;
+        let mut next = |max: usize| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as usize % max
+        };
+
+        for _ in 0..200 {
+            let mut chunks = Vec::new();
+            let mut pos = 0;
+
+            while pos < payload.len() {
+                let end = std::cmp::min(pos + 1 + next(6), payload.len());
+                chunks.push(&payload[pos..end]);
+                pos = end;
+            }
+
+            assert_eq!(decode_chunked(&chunks).unwrap().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_chunked_malformed_no_panic() {
+        let cases: &[&[u8]] = &[
+            b"\r\r\n",
+            b"4\r\r",
+            b"4\r\ndata\r\r",
+            b"4\r\ndata\n",
+            b"0\r\n\r\r",
+            b"0\r\nx",
+            b"1 1\r\n",
+            b"-1\r\n",
+        ];
+
+        for case in cases {
+            for split in 0..=case.len() {
+                let (a, b) = case.split_at(split);
+                assert!(decode_chunked(&[a, b]).is_err(), "{:?}", case);
+            }
+        }
+
+        let mut seed: u64 = 0x85eb_ca6b;
+        let mut next = || {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as u8
+        };
+
+        // arbitrary input, biased towards the bytes of the chunked framing
+        let alphabet = b"0123456789abcdefF \t;\r\nx";
+        for _ in 0..500 {
+            let len = usize::from(next() % 32);
+            let input: Vec<u8> = (0..len)
+                .map(|_| alphabet[usize::from(next()) % alphabet.len()])
+                .collect();
+
+            let _ = decode_chunked(&[&input]);
+        }
+    }
+
     #[test]
     fn test_response_http10_read_until_eof() {
         let mut buf = BytesMut::from(&"HTTP/1.0 200 Ok\r\n\r\ntest data"[..]);
@@ -1,4 +1,5 @@
 use std::{
+    cmp,
     collections::VecDeque,
     fmt,
     future::Future,
@@ -6,27 +7,31 @@ use std::{
     pin::Pin,
     rc::Rc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use actix_codec::{AsyncRead, AsyncWrite, Decoder, Encoder, Framed, FramedParts};
-use actix_rt::time::{sleep_until, Instant, Sleep};
+use actix_rt::time::{sleep, sleep_until, Instant, Sleep};
 use actix_service::Service;
 use bitflags::bitflags;
 use bytes::{Buf, BytesMut};
-use futures_core::ready;
+use futures_core::{future::LocalBoxFuture, ready};
 use log::{error, trace};
 use pin_project::pin_project;
 
 use crate::body::{Body, BodySize, MessageBody, ResponseBody};
-use crate::config::ServiceConfig;
+use crate::config::{KeepAlivePolicy, ServiceConfig};
 use crate::error::{DispatchError, Error};
 use crate::error::{ParseError, PayloadError};
+use crate::http::{HeaderName, HeaderValue, StatusCode, Version};
+use crate::message::ConnectionType;
 use crate::request::Request;
 use crate::response::Response;
 use crate::service::HttpFlow;
-use crate::OnConnectData;
+use crate::{EarlyHints, HttpMessage, OnConnectData};
 
 use super::codec::Codec;
+use super::handover::{OnUpgrade, Upgraded};
 use super::payload::{Payload, PayloadSender, PayloadStatus};
 use super::{Message, MessageType};
 
@@ -76,6 +81,7 @@ where
 {
     Normal(#[pin] InnerDispatcher<T, S, B, X, U>),
     Upgrade(#[pin] U::Future),
+    Handover(#[pin] LocalBoxFuture<'static, ()>),
 }
 
 #[pin_project(project = InnerDispatcherProj)]
@@ -99,10 +105,24 @@ where
     state: State<S, B, X>,
     payload: Option<PayloadSender>,
     messages: VecDeque<DispatcherMessage>,
+    on_upgrade: Option<OnUpgrade>,
+    early_hints: Option<EarlyHints>,
 
     ka_expire: Instant,
     #[pin]
     ka_timer: Option<Sleep>,
+    ka_override: Option<Duration>,
+    #[pin]
+    idle_timer: Option<Sleep>,
+
+    head_deadline: Option<Instant>,
+    payload_window: Option<PayloadWindow>,
+    #[pin]
+    req_timer: Option<Sleep>,
+
+    conn_start: Instant,
+    preface_wait: Option<u64>,
+    head_start: Option<Instant>,
 
     io: Option<T>,
     read_buf: BytesMut,
@@ -110,6 +130,21 @@ where
     codec: Codec,
 }
 
+/// Bytes of request payload received since the start of the current rate window.
+struct PayloadWindow {
+    start: Instant,
+    bytes: u64,
+}
+
+impl PayloadWindow {
+    fn new() -> Self {
+        PayloadWindow {
+            start: Instant::now(),
+            bytes: 0,
+        }
+    }
+}
+
 enum DispatcherMessage {
     Item(Request),
     Upgrade(Request),
@@ -148,7 +183,7 @@ enum PollResponse {
 
 impl<T, S, B, X, U> Dispatcher<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error>,
     S::Response: Into<Response<B>>,
@@ -178,6 +213,11 @@ where
             None => (config.now(), None),
         };
 
+        // the first request head has to arrive within client timeout of connection start
+        let head_deadline = config
+            .client_timeout()
+            .map(|timeout| Instant::now() + timeout);
+
         Dispatcher {
             inner: DispatcherState::Normal(InnerDispatcher {
                 read_buf: BytesMut::with_capacity(HW_BUFFER_SIZE),
@@ -186,6 +226,8 @@ where
                 state: State::None,
                 error: None,
                 messages: VecDeque::new(),
+                on_upgrade: None,
+                early_hints: None,
                 io: Some(io),
                 codec: Codec::new(config),
                 flow,
@@ -194,6 +236,14 @@ where
                 peer_addr,
                 ka_expire,
                 ka_timer,
+                ka_override: None,
+                idle_timer: None,
+                head_deadline,
+                payload_window: None,
+                req_timer: None,
+                conn_start: Instant::now(),
+                preface_wait: None,
+                head_start: None,
             }),
 
             #[cfg(test)]
@@ -204,7 +254,7 @@ where
 
 impl<T, S, B, X, U> InnerDispatcher<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error>,
     S::Response: Into<Response<B>>,
@@ -272,8 +322,45 @@ where
         message: Response<()>,
         body: ResponseBody<B>,
     ) -> Result<(), DispatchError> {
-        let size = body.size();
         let mut this = self.project();
+
+        let (mut message, body) =
+            this.codec.config().limit_response_headers(message, body);
+        let size = body.size();
+
+        // hints queued by the service go out before the final response head
+        if let Some(hints) = this.early_hints.take() {
+            hints.close(&mut this.write_buf);
+        }
+
+        if message.status() == StatusCode::SWITCHING_PROTOCOLS {
+            *this.on_upgrade = message.extensions_mut().remove::<OnUpgrade>();
+        }
+
+        // keep-alive policy of the response overrides the service setting
+        *this.ka_override = None;
+        let policy = message.extensions().get::<KeepAlivePolicy>().copied();
+        match policy {
+            Some(KeepAlivePolicy::Timeout(secs)) if secs > 0 => {
+                let forced = matches!(
+                    message.head().ctype(),
+                    Some(ConnectionType::Close) | Some(ConnectionType::Upgrade)
+                );
+
+                if this.ka_timer.is_some() && this.codec.keepalive() && !forced {
+                    message.headers_mut().insert(
+                        HeaderName::from_static("keep-alive"),
+                        HeaderValue::from_str(&format!("timeout={}", secs)).unwrap(),
+                    );
+                    *this.ka_override = Some(Duration::from_secs(secs as u64));
+                }
+            }
+            Some(_) => message
+                .head_mut()
+                .set_connection_type(ConnectionType::Close),
+            None => {}
+        }
+
         this.codec
             .encode(Message::Item((message, size)), &mut this.write_buf)
             .map_err(|err| {
@@ -285,12 +372,53 @@ where
 
         this.flags.set(Flags::KEEPALIVE, this.codec.keepalive());
         match size {
-            BodySize::None | BodySize::Empty => this.state.set(State::None),
+            BodySize::None | BodySize::Empty => {
+                this.state.set(State::None);
+
+                // connection is idle from here on
+                if this.ka_timer.is_some() {
+                    let config = this.codec.config();
+                    match (config.idle_timeout(), *this.ka_override) {
+                        (Some(timeout), None) => {
+                            this.idle_timer.set(Some(sleep(timeout)))
+                        }
+                        (_, ka_override) => {
+                            if let Some(expire) = keep_alive_expire(config, ka_override)
+                            {
+                                *this.ka_expire = expire;
+                            }
+                        }
+                    }
+                }
+            }
             _ => this.state.set(State::SendPayload(body)),
         };
         Ok(())
     }
 
+    /// Calls service with the request, attaching an early hints handle for HTTP/1.1 clients.
+    fn call_service(self: Pin<&mut Self>, req: Request) {
+        let mut this = self.project();
+
+        if req.head().version >= Version::HTTP_11 {
+            let hints = EarlyHints::new();
+            req.extensions_mut().insert(hints.clone());
+            *this.early_hints = Some(hints);
+        }
+
+        let task = this.flow.service.call(req);
+        this.state.set(State::ServiceCall(task));
+    }
+
+    /// Writes early hints sent by the pending service call.
+    fn poll_early_hints(self: Pin<&mut Self>, cx: &mut Context<'_>) {
+        let this = self.project();
+
+        if let Some(hints) = this.early_hints {
+            hints.poll_write(cx, this.write_buf);
+        }
+    }
+
     fn send_continue(self: Pin<&mut Self>) {
         self.project()
             .write_buf
@@ -315,8 +443,7 @@ where
                             this.state.set(State::ExpectCall(task));
                         } else {
                             // the same as expect call.
-                            let task = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall(task));
+                            self.as_mut().call_service(req);
                         };
                     }
 
@@ -354,6 +481,8 @@ where
                     // service call pending and could be waiting for more chunk messages.
                     // (pipeline message limit and/or payload can_read limit)
                     Poll::Pending => {
+                        self.as_mut().poll_early_hints(cx);
+
                         // no new message is decoded and no new payload is feed.
                         // nothing to do except waiting for new incoming data from client.
                         if !self.as_mut().poll_request(cx)? {
@@ -381,6 +510,23 @@ where
                                 // payload stream finished.
                                 // set state to None and handle next message
                                 this.state.set(State::None);
+
+                                // connection is idle from here on
+                                if this.ka_timer.is_some() {
+                                    let config = this.codec.config();
+                                    match (config.idle_timeout(), *this.ka_override) {
+                                        (Some(timeout), None) => {
+                                            this.idle_timer.set(Some(sleep(timeout)))
+                                        }
+                                        (_, ka_override) => {
+                                            if let Some(expire) =
+                                                keep_alive_expire(config, ka_override)
+                                            {
+                                                *this.ka_expire = expire;
+                                            }
+                                        }
+                                    }
+                                }
                                 continue 'res;
                             }
 
@@ -402,8 +548,7 @@ where
                     Poll::Ready(Ok(req)) => {
                         this.write_buf
                             .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
-                        let fut = this.flow.service.call(req);
-                        this.state.set(State::ServiceCall(fut));
+                        self.as_mut().call_service(req);
                     }
                     // send expect error as response
                     Poll::Ready(Err(err)) => {
@@ -431,9 +576,7 @@ where
             this.state.set(State::ExpectCall(task));
         } else {
             // the same as above.
-            let mut this = self.as_mut().project();
-            let task = this.flow.service.call(req);
-            this.state.set(State::ServiceCall(task));
+            self.as_mut().call_service(req);
         };
 
         // eagerly poll the future for once(or twice if expect is resolved immediately).
@@ -444,9 +587,7 @@ where
                         // expect is resolved. continue loop and poll the service call branch.
                         Poll::Ready(Ok(req)) => {
                             self.as_mut().send_continue();
-                            let mut this = self.as_mut().project();
-                            let task = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall(task));
+                            self.as_mut().call_service(req);
                             continue;
                         }
                         // future is pending. return Ok(()) to notify that a new state is
@@ -474,7 +615,10 @@ where
                             self.send_response(res, body)
                         }
                         // see the comment on ExpectCall state branch's Pending.
-                        Poll::Pending => Ok(()),
+                        Poll::Pending => {
+                            self.poll_early_hints(cx);
+                            Ok(())
+                        }
                         // see the comment on ExpectCall state branch's Ready(Err(err)).
                         Poll::Ready(Err(err)) => {
                             let res: Response = err.into().into();
@@ -511,6 +655,21 @@ where
                     match msg {
                         Message::Item(mut req) => {
                             req.head_mut().peer_addr = *this.peer_addr;
+                            *this.head_deadline = None;
+
+                            // head may have been read and parsed within this poll
+                            let now = Instant::now();
+                            let head_start = this.head_start.take().unwrap_or(now);
+                            let preface_wait = head_start
+                                .saturating_duration_since(*this.conn_start)
+                                .as_micros()
+                                as u64;
+                            let preface_wait =
+                                *this.preface_wait.get_or_insert(preface_wait);
+
+                            let stats = &mut req.head_mut().wire_stats;
+                            stats.parse_micros = (now - head_start).as_micros() as u64;
+                            stats.preface_wait_micros = preface_wait;
 
                             // merge on_connect_ext data into request extensions
                             this.on_connect_data.merge_into(&mut req);
@@ -540,6 +699,11 @@ where
                                         req.replace_payload(crate::Payload::H1(pl));
                                     req = req1;
                                     *this.payload = Some(ps);
+
+                                    if this.codec.config().payload_rate().is_some() {
+                                        *this.payload_window =
+                                            Some(PayloadWindow::new());
+                                    }
                                 }
 
                                 // Request has no payload.
@@ -555,6 +719,10 @@ where
                             }
                         }
                         Message::Chunk(Some(chunk)) => {
+                            if let Some(ref mut window) = this.payload_window {
+                                window.bytes += chunk.len() as u64;
+                            }
+
                             if let Some(ref mut payload) = this.payload {
                                 payload.feed_data(chunk);
                             } else {
@@ -570,6 +738,8 @@ where
                             }
                         }
                         Message::Chunk(None) => {
+                            *this.payload_window = None;
+
                             if let Some(mut payload) = this.payload.take() {
                                 payload.feed_eof();
                             } else {
@@ -622,13 +792,125 @@ where
         }
 
         if updated && this.ka_timer.is_some() {
+            this.idle_timer.set(None);
+
             if let Some(expire) = this.codec.config().keep_alive_expire() {
                 *this.ka_expire = expire;
             }
         }
+
+        // start of a new request head has been received
+        if this.head_start.is_none()
+            && this.payload.is_none()
+            && !this.read_buf.is_empty()
+        {
+            let now = Instant::now();
+            *this.head_start = Some(now);
+
+            if this.head_deadline.is_none() {
+                if let Some(timeout) = this.codec.config().client_timeout() {
+                    *this.head_deadline = Some(now + timeout);
+                }
+            }
+        }
+
         Ok(updated)
     }
 
+    /// Enforce request head deadline and minimum payload rate.
+    fn poll_request_timer(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Result<(), DispatchError> {
+        let mut this = self.as_mut().project();
+        let now = Instant::now();
+
+        if matches!(*this.head_deadline, Some(deadline) if deadline <= now) {
+            trace!("Request head timeout");
+            *this.head_deadline = None;
+            this.messages.push_back(DispatcherMessage::Error(
+                Response::RequestTimeout().finish().drop_body(),
+            ));
+            this.flags.insert(Flags::STARTED | Flags::READ_DISCONNECT);
+            *this.error = Some(DispatchError::SlowRequestTimeout);
+        }
+
+        let payload_rate = this.codec.config().payload_rate();
+
+        if let (Some((rate, window)), Some(payload), Some(payload_window)) = (
+            payload_rate,
+            this.payload.as_ref(),
+            this.payload_window.as_mut(),
+        ) {
+            if payload_window.start + window <= now {
+                let min_bytes = rate * window.as_millis() as u64 / 1000;
+
+                // client is not to blame while the service is not reading
+                if payload.need_read(cx) == PayloadStatus::Pause
+                    || payload_window.bytes >= min_bytes
+                {
+                    *payload_window = PayloadWindow::new();
+                } else {
+                    trace!("Request payload rate below minimum");
+                    *this.payload_window = None;
+                    if let Some(mut payload) = this.payload.take() {
+                        payload.set_error(PayloadError::Incomplete(None));
+                    }
+                    this.flags.insert(Flags::READ_DISCONNECT);
+                    *this.error = Some(DispatchError::SlowRequestTimeout);
+
+                    let awaiting_response = matches!(
+                        *this.state,
+                        State::ExpectCall(_) | State::ServiceCall(_)
+                    );
+
+                    // respond with 408 unless a response is already being written
+                    if this.messages.is_empty() && awaiting_response {
+                        this.state.set(State::None);
+                        self.as_mut().send_response(
+                            Response::RequestTimeout().finish().drop_body(),
+                            ResponseBody::Other(Body::Empty),
+                        )?;
+                        this = self.as_mut().project();
+                    } else {
+                        return Err(DispatchError::SlowRequestTimeout);
+                    }
+                }
+            }
+        }
+
+        let window_end =
+            match (payload_rate, this.payload.as_ref(), &*this.payload_window) {
+                (Some((_, window)), Some(_), Some(payload_window)) => {
+                    Some(payload_window.start + window)
+                }
+                _ => None,
+            };
+
+        let deadline = match (*this.head_deadline, window_end) {
+            (Some(a), Some(b)) => Some(cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+
+        match deadline {
+            Some(deadline) => {
+                match this.req_timer.as_mut().as_pin_mut() {
+                    Some(timer) if timer.deadline() == deadline => {}
+                    Some(timer) => timer.reset(deadline),
+                    None => this.req_timer.set(Some(sleep_until(deadline))),
+                }
+
+                let timer = this.req_timer.as_pin_mut().unwrap();
+                if timer.poll(cx).is_ready() {
+                    return self.poll_request_timer(cx);
+                }
+            }
+            None => this.req_timer.set(None),
+        }
+
+        Ok(())
+    }
+
     /// keep-alive timer
     fn poll_keepalive(
         mut self: Pin<&mut Self>,
@@ -636,6 +918,33 @@ where
     ) -> Result<(), DispatchError> {
         let mut this = self.as_mut().project();
 
+        // idle timer is precise, keep-alive timeouts can be shorter than the resolution of the
+        // date service clock used by the keep-alive timer
+        if let Some(timer) = this.idle_timer.as_mut().as_pin_mut() {
+            if timer.poll(cx).is_ready() {
+                this.idle_timer.set(None);
+
+                if !this.flags.contains(Flags::SHUTDOWN)
+                    && this.state.is_empty()
+                    && this.write_buf.is_empty()
+                {
+                    trace!("Keep-alive idle timeout, close connection");
+                    this.flags.insert(Flags::SHUTDOWN);
+
+                    // start shutdown timeout
+                    if let Some(deadline) = this.codec.config().client_disconnect_timer()
+                    {
+                        this.ka_timer.set(Some(sleep_until(deadline)));
+                        return self.poll_keepalive(cx);
+                    } else {
+                        // no shutdown timeout, drop socket
+                        this.flags.insert(Flags::WRITE_DISCONNECT);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
         // when a branch is not explicit return early it's meant to fall through
         // and return as Ok(())
         match this.ka_timer.as_mut().as_pin_mut() {
@@ -666,7 +975,9 @@ where
                     } else if timer.deadline() >= *this.ka_expire {
                         // have no task at hand.
                         if this.state.is_empty() && this.write_buf.is_empty() {
-                            if this.flags.contains(Flags::STARTED) {
+                            if this.idle_timer.is_some() {
+                                // idle connection is closed by the idle timer
+                            } else if this.flags.contains(Flags::STARTED) {
                                 trace!("Keep-alive timeout, close connection");
                                 this.flags.insert(Flags::SHUTDOWN);
 
@@ -820,11 +1131,21 @@ where
         let framed = Framed::from_parts(parts);
         this.flow.upgrade.as_ref().unwrap().call((req, framed))
     }
+
+    /// hand connection over to upgrade handler of the response.
+    fn handover(self: Pin<&mut Self>) -> LocalBoxFuture<'static, ()> {
+        let this = self.project();
+        if let Some(mut payload) = this.payload.take() {
+            payload.feed_eof();
+        }
+        let io = Upgraded::new(this.io.take().unwrap(), mem::take(this.read_buf));
+        this.on_upgrade.take().unwrap().call(io)
+    }
 }
 
 impl<T, S, B, X, U> Future for Dispatcher<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error>,
     S::Response: Into<Response<B>>,
@@ -874,6 +1195,8 @@ where
                         }
                     };
 
+                    inner.as_mut().poll_request_timer(cx)?;
+
                     loop {
                         // poll_response and populate write buffer.
                         // drain indicate if write buffer should be emptied before next run.
@@ -907,6 +1230,19 @@ where
                         return Poll::Ready(Ok(()));
                     }
 
+                    // switching protocols response is written, hand connection over.
+                    if inner.on_upgrade.is_some()
+                        && inner.state.is_empty()
+                        && inner.write_buf.is_empty()
+                    {
+                        let handover = inner.handover();
+                        self.as_mut()
+                            .project()
+                            .inner
+                            .set(DispatcherState::Handover(handover));
+                        return self.poll(cx);
+                    }
+
                     let is_empty = inner.state.is_empty();
 
                     let inner_p = inner.as_mut().project();
@@ -942,10 +1278,23 @@ where
                 error!("Upgrade handler error: {}", e);
                 DispatchError::Upgrade
             }),
+            DispatcherStateProj::Handover(fut) => fut.poll(cx).map(Ok),
         }
     }
 }
 
+/// Returns when an idle connection times out, using the keep-alive timeout of the last response's
+/// policy if it set one.
+fn keep_alive_expire(
+    config: &ServiceConfig,
+    ka_override: Option<Duration>,
+) -> Option<Instant> {
+    match ka_override {
+        Some(timeout) => Some(Instant::now() + timeout),
+        None => config.keep_alive_expire(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str;
@@ -338,6 +338,7 @@ impl<T: MessageType> MessageEncoder<T> {
                 BodySize::Stream => {
                     if message.chunked() && !stream {
                         TransferEncoding::chunked()
+                            .max_chunk_size(config.max_chunk_size())
                     } else {
                         TransferEncoding::eof()
                     }
@@ -357,6 +358,7 @@ impl<T: MessageType> MessageEncoder<T> {
 #[derive(Debug)]
 pub(crate) struct TransferEncoding {
     kind: TransferEncodingKind,
+    max_chunk_size: Option<usize>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -378,6 +380,7 @@ impl TransferEncoding {
     pub fn empty() -> TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Length(0),
+            max_chunk_size: None,
         }
     }
 
@@ -385,6 +388,7 @@ impl TransferEncoding {
     pub fn eof() -> TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Eof,
+            max_chunk_size: None,
         }
     }
 
@@ -392,6 +396,7 @@ impl TransferEncoding {
     pub fn chunked() -> TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Chunked(false),
+            max_chunk_size: None,
         }
     }
 
@@ -399,9 +404,17 @@ impl TransferEncoding {
     pub fn length(len: u64) -> TransferEncoding {
         TransferEncoding {
             kind: TransferEncodingKind::Length(len),
+            max_chunk_size: None,
         }
     }
 
+    /// Limit size of chunks written by a chunked encoder, splitting larger messages.
+    #[inline]
+    pub fn max_chunk_size(mut self, size: Option<usize>) -> TransferEncoding {
+        self.max_chunk_size = size;
+        self
+    }
+
     /// Encode message. Return `EOF` state of encoder
     #[inline]
     pub fn encode(&mut self, msg: &[u8], buf: &mut BytesMut) -> io::Result<bool> {
@@ -420,12 +433,16 @@ impl TransferEncoding {
                     *eof = true;
                     buf.extend_from_slice(b"0\r\n\r\n");
                 } else {
-                    writeln!(helpers::Writer(buf), "{:X}\r", msg.len())
-                        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                    let size = self.max_chunk_size.unwrap_or_else(|| msg.len());
 
-                    buf.reserve(msg.len() + 2);
-                    buf.extend_from_slice(msg);
-                    buf.extend_from_slice(b"\r\n");
+                    for chunk in msg.chunks(size) {
+                        writeln!(helpers::Writer(buf), "{:X}\r", chunk.len())
+                            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+                        buf.reserve(chunk.len() + 2);
+                        buf.extend_from_slice(chunk);
+                        buf.extend_from_slice(b"\r\n");
+                    }
                 }
                 Ok(*eof)
             }
@@ -529,6 +546,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_chunked_te_max_chunk_size() {
+        let mut bytes = BytesMut::new();
+        let mut enc = TransferEncoding::chunked().max_chunk_size(Some(4));
+        assert!(!enc.encode(b"0123456789", &mut bytes).unwrap());
+        assert!(!enc.encode(b"abcd", &mut bytes).unwrap());
+        assert!(enc.encode(b"", &mut bytes).unwrap());
+        assert_eq!(
+            bytes.split().freeze(),
+            Bytes::from_static(
+                b"4\r\n0123\r\n4\r\n4567\r\n2\r\n89\r\n4\r\nabcd\r\n0\r\n\r\n"
+            )
+        );
+    }
+
     #[actix_rt::test]
     async fn test_camel_case() {
         let mut bytes = BytesMut::with_capacity(2048);
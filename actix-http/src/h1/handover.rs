@@ -0,0 +1,101 @@
+use std::{
+    cmp, fmt,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
+use bytes::BytesMut;
+use futures_core::future::LocalBoxFuture;
+use futures_util::future::FutureExt as _;
+
+/// Connection handed over to a response's upgrade handler.
+///
+/// Reading yields the bytes received after the request, followed by data read from the
+/// connection. See [`ResponseBuilder::on_upgrade`](crate::ResponseBuilder::on_upgrade).
+pub struct Upgraded {
+    io: Box<dyn UpgradedIo>,
+    read_buf: BytesMut,
+}
+
+trait UpgradedIo: AsyncRead + AsyncWrite + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> UpgradedIo for T {}
+
+impl Upgraded {
+    pub(crate) fn new<T>(io: T, read_buf: BytesMut) -> Self
+    where
+        T: AsyncRead + AsyncWrite + Unpin + 'static,
+    {
+        Upgraded {
+            io: Box::new(io),
+            read_buf,
+        }
+    }
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded")
+            .field("buffered", &self.read_buf.len())
+            .finish()
+    }
+}
+
+impl AsyncRead for Upgraded {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        if !this.read_buf.is_empty() {
+            let len = cmp::min(this.read_buf.len(), buf.remaining());
+            buf.put_slice(&this.read_buf.split_to(len));
+            return Poll::Ready(Ok(()));
+        }
+
+        Pin::new(&mut this.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+/// Upgrade handler stored in response extensions until the response has been written.
+pub(crate) struct OnUpgrade(Box<dyn FnOnce(Upgraded) -> LocalBoxFuture<'static, ()>>);
+
+impl OnUpgrade {
+    pub(crate) fn new<F, Fut>(f: F) -> Self
+    where
+        F: FnOnce(Upgraded) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        OnUpgrade(Box::new(move |io| f(io).boxed_local()))
+    }
+
+    pub(crate) fn call(self, io: Upgraded) -> LocalBoxFuture<'static, ()> {
+        (self.0)(io)
+    }
+}
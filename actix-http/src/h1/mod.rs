@@ -7,6 +7,7 @@ mod decoder;
 mod dispatcher;
 mod encoder;
 mod expect;
+mod handover;
 mod payload;
 mod service;
 mod upgrade;
@@ -14,8 +15,11 @@ mod utils;
 
 pub use self::client::{ClientCodec, ClientPayloadCodec};
 pub use self::codec::Codec;
+pub use self::decoder::{ChunkedDecoder, ChunkedState, PayloadItem};
 pub use self::dispatcher::Dispatcher;
 pub use self::expect::ExpectHandler;
+pub(crate) use self::handover::OnUpgrade;
+pub use self::handover::Upgraded;
 pub use self::payload::Payload;
 pub use self::service::{H1Service, H1ServiceHandler};
 pub use self::upgrade::UpgradeHandler;
@@ -43,6 +43,7 @@ where
     peer_addr: Option<net::SocketAddr>,
     ka_expire: Instant,
     ka_timer: Option<Sleep>,
+    preface_wait_micros: u64,
     _phantom: PhantomData<B>,
 }
 
@@ -61,6 +62,7 @@ where
         config: ServiceConfig,
         timeout: Option<Sleep>,
         peer_addr: Option<net::SocketAddr>,
+        handshake_start: Instant,
     ) -> Self {
         // let keepalive = config.keep_alive_enabled();
         // let flags = if keepalive {
@@ -86,6 +88,7 @@ where
             on_connect_data,
             ka_expire,
             ka_timer,
+            preface_wait_micros: handshake_start.elapsed().as_micros() as u64,
             _phantom: PhantomData,
         }
     }
@@ -132,6 +135,13 @@ where
                     head.headers = parts.headers.into();
                     head.peer_addr = this.peer_addr;
 
+                    head.wire_stats.header_bytes = head
+                        .headers
+                        .iter()
+                        .map(|(name, value)| name.as_str().len() + value.len())
+                        .sum();
+                    head.wire_stats.preface_wait_micros = this.preface_wait_micros;
+
                     // merge on_connect_ext data into request extensions
                     this.on_connect_data.merge_into(&mut req);
 
@@ -254,6 +264,7 @@ where
                 match ready!(call.poll(cx)) {
                     Ok(res) => {
                         let (res, body) = res.into().replace_body(());
+                        let (res, body) = this.config.limit_response_headers(res, body);
 
                         let mut send = send.take().unwrap();
                         let mut size = body.size();
@@ -281,6 +292,7 @@ where
                     Err(e) => {
                         let res: Response = e.into().into();
                         let (res, body) = res.replace_body(());
+                        let (res, body) = this.config.limit_response_headers(res, body);
 
                         let mut send = send.take().unwrap();
                         let mut size = body.size();
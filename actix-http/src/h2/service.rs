@@ -5,7 +5,7 @@ use std::task::{Context, Poll};
 use std::{net, rc::Rc};
 
 use actix_codec::{AsyncRead, AsyncWrite};
-use actix_rt::net::TcpStream;
+use actix_rt::{net::TcpStream, time::Instant};
 use actix_service::{
     fn_factory, fn_service, pipeline_factory, IntoServiceFactory, Service,
     ServiceFactory,
@@ -308,6 +308,7 @@ where
                 addr,
                 on_connect_data,
                 server::handshake(io),
+                Instant::now(),
             ),
         }
     }
@@ -325,6 +326,7 @@ where
         Option<net::SocketAddr>,
         OnConnectData,
         Handshake<T, Bytes>,
+        Instant,
     ),
 }
 
@@ -360,6 +362,7 @@ where
                 ref peer_addr,
                 ref mut on_connect_data,
                 ref mut handshake,
+                started,
             ) => match ready!(Pin::new(handshake).poll(cx)) {
                 Ok(conn) => {
                     let on_connect_data = std::mem::take(on_connect_data);
@@ -370,6 +373,7 @@ where
                         config.take().unwrap(),
                         None,
                         *peer_addr,
+                        started,
                     ));
                     self.poll(cx)
                 }
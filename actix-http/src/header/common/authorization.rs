@@ -0,0 +1,254 @@
+use std::{fmt, str::FromStr};
+
+use http::header::InvalidHeaderValue;
+
+use crate::{
+    error::ParseError,
+    header::{self, from_one_raw_str, Header, HeaderName, HeaderValue, IntoHeaderValue},
+    HttpMessage,
+};
+
+/// `Authorization` header, defined in [RFC7235](https://tools.ietf.org/html/rfc7235#section-4.2)
+///
+/// The `Authorization` header field allows a user agent to authenticate itself with an origin
+/// server -- usually, but not necessarily, after receiving a 401 (Unauthorized) response. Its
+/// value consists of credentials containing the authentication information of the user agent
+/// for the realm of the resource being requested.
+///
+/// The `Basic` ([RFC7617](https://tools.ietf.org/html/rfc7617)) and `Bearer`
+/// ([RFC6750](https://tools.ietf.org/html/rfc6750)) schemes are parsed into their credentials.
+/// Any other scheme is kept as-is. Scheme names are matched case-insensitively.
+///
+/// # ABNF
+/// ```text
+/// Authorization = credentials
+/// credentials   = auth-scheme [ 1*SP ( token68 / #auth-param ) ]
+/// ```
+///
+/// # Example values
+/// * `Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==`
+/// * `Bearer mF_9.B5f-4.1JqM`
+/// * `Digest username="Mufasa", realm="testrealm@host.com"`
+///
+/// # Examples
+/// ```
+/// use actix_http::http::header::{Authorization, Header};
+/// use actix_http::test::TestRequest;
+///
+/// let req = TestRequest::default()
+///     .insert_header(("authorization", "Bearer mF_9.B5f-4.1JqM"))
+///     .finish();
+///
+/// assert_eq!(
+///     Authorization::parse(&req).unwrap(),
+///     Authorization::Bearer("mF_9.B5f-4.1JqM".to_owned())
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Authorization {
+    /// Credentials of the `Basic` scheme, decoded from their base64 representation.
+    Basic {
+        /// User-id part of the credentials.
+        user_id: String,
+        /// Password part of the credentials.
+        password: String,
+    },
+
+    /// Token of the `Bearer` scheme.
+    Bearer(String),
+
+    /// Credentials of any other scheme.
+    Other {
+        /// Scheme name, as sent by the client.
+        scheme: String,
+        /// Raw `token68` or `auth-param` list following the scheme name.
+        params: String,
+    },
+}
+
+impl Authorization {
+    /// Returns the scheme name of these credentials.
+    pub fn scheme(&self) -> &str {
+        match self {
+            Authorization::Basic { .. } => "Basic",
+            Authorization::Bearer(_) => "Bearer",
+            Authorization::Other { scheme, .. } => scheme,
+        }
+    }
+}
+
+/// Checks value against the `token68` grammar:
+/// `1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="`
+fn is_token68(val: &str) -> bool {
+    let trimmed = val.trim_end_matches('=');
+
+    !trimmed.is_empty()
+        && trimmed.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/')
+        })
+}
+
+impl FromStr for Authorization {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let val = val.trim();
+
+        let (scheme, params) = match val.find(' ') {
+            Some(idx) => (&val[..idx], val[idx..].trim_start()),
+            None => (val, ""),
+        };
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            if !is_token68(params) {
+                return Err(ParseError::Header);
+            }
+
+            let decoded = base64::decode(params).map_err(|_| ParseError::Header)?;
+            let decoded = String::from_utf8(decoded).map_err(|_| ParseError::Header)?;
+
+            let idx = decoded.find(':').ok_or(ParseError::Header)?;
+
+            Ok(Authorization::Basic {
+                user_id: decoded[..idx].to_owned(),
+                password: decoded[idx + 1..].to_owned(),
+            })
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            if !is_token68(params) {
+                return Err(ParseError::Header);
+            }
+
+            Ok(Authorization::Bearer(params.to_owned()))
+        } else if !scheme.is_empty() {
+            Ok(Authorization::Other {
+                scheme: scheme.to_owned(),
+                params: params.to_owned(),
+            })
+        } else {
+            Err(ParseError::Header)
+        }
+    }
+}
+
+impl fmt::Display for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Authorization::Basic { user_id, password } => {
+                let credentials = format!("{}:{}", user_id, password);
+                write!(f, "Basic {}", base64::encode(credentials))
+            }
+            Authorization::Bearer(token) => write!(f, "Bearer {}", token),
+            Authorization::Other { scheme, params } if params.is_empty() => {
+                f.write_str(scheme)
+            }
+            Authorization::Other { scheme, params } => {
+                write!(f, "{} {}", scheme, params)
+            }
+        }
+    }
+}
+
+impl IntoHeaderValue for Authorization {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        HeaderValue::from_str(&self.to_string())
+    }
+}
+
+impl Header for Authorization {
+    fn name() -> HeaderName {
+        header::AUTHORIZATION
+    }
+
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(Self::name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    fn parse(val: &'static str) -> Result<Authorization, ParseError> {
+        let req = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, val))
+            .finish();
+        Authorization::parse(&req)
+    }
+
+    #[test]
+    fn test_basic() {
+        assert_eq!(
+            parse("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==").unwrap(),
+            Authorization::Basic {
+                user_id: "Aladdin".to_owned(),
+                password: "open sesame".to_owned(),
+            }
+        );
+
+        // scheme is case-insensitive; password may contain colons
+        assert_eq!(
+            parse("bAsIc dXNlcjpwYXNzOndvcmQ=").unwrap(),
+            Authorization::Basic {
+                user_id: "user".to_owned(),
+                password: "pass:word".to_owned(),
+            }
+        );
+
+        // malformed base64
+        assert!(parse("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=!").is_err());
+        assert!(parse("Basic ****").is_err());
+        // missing colon separator
+        assert!(parse("Basic dXNlcg==").is_err());
+        // invalid utf-8
+        assert!(parse("Basic /w==").is_err());
+        assert!(parse("Basic").is_err());
+    }
+
+    #[test]
+    fn test_bearer() {
+        assert_eq!(
+            parse("Bearer mF_9.B5f-4.1JqM").unwrap(),
+            Authorization::Bearer("mF_9.B5f-4.1JqM".to_owned())
+        );
+        assert_eq!(
+            parse("BEARER a~b+c/d==").unwrap(),
+            Authorization::Bearer("a~b+c/d==".to_owned())
+        );
+
+        assert!(parse("Bearer").is_err());
+        assert!(parse("Bearer ==").is_err());
+        assert!(parse("Bearer a=b").is_err());
+        assert!(parse("Bearer two tokens").is_err());
+    }
+
+    #[test]
+    fn test_other() {
+        assert_eq!(
+            parse(r#"Digest username="Mufasa", realm="test""#).unwrap(),
+            Authorization::Other {
+                scheme: "Digest".to_owned(),
+                params: r#"username="Mufasa", realm="test""#.to_owned(),
+            }
+        );
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let auth = Authorization::Basic {
+            user_id: "Aladdin".to_owned(),
+            password: "open sesame".to_owned(),
+        };
+        assert_eq!(
+            auth.try_into_value().unwrap(),
+            HeaderValue::from_static("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==")
+        );
+
+        let auth = Authorization::Bearer("abc".to_owned());
+        assert_eq!(auth.to_string(), "Bearer abc");
+    }
+}
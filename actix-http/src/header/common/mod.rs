@@ -12,6 +12,7 @@ pub use self::accept_charset::AcceptCharset;
 pub use self::accept::Accept;
 pub use self::accept_language::AcceptLanguage;
 pub use self::allow::Allow;
+pub use self::authorization::Authorization;
 pub use self::cache_control::{CacheControl, CacheDirective};
 pub use self::content_disposition::{
     ContentDisposition, DispositionParam, DispositionType,
@@ -338,6 +339,7 @@ mod accept_charset;
 mod accept;
 mod accept_language;
 mod allow;
+mod authorization;
 mod cache_control;
 mod content_disposition;
 mod content_language;
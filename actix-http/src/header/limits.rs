@@ -0,0 +1,359 @@
+//! Limits on the headers of responses, see [`ResponseHeaderLimits`].
+
+use http::{
+    header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, LOCATION},
+    StatusCode,
+};
+
+use crate::{
+    body::{Body, ResponseBody},
+    header::HeaderMap,
+    response::Response,
+};
+
+/// Returns true for headers that may occur only once in a response.
+///
+/// Duplicates of these headers are folded into one value by [`ResponseHeaderLimits`].
+pub fn is_singleton(name: &HeaderName) -> bool {
+    matches!(*name, CONTENT_TYPE | CONTENT_LENGTH | LOCATION)
+}
+
+/// What happens to a response whose headers exceed the [`ResponseHeaderLimits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderLimitAction {
+    /// Headers are dropped until the rest fit; the response is sent otherwise unchanged.
+    ///
+    /// The first value of each header is kept before any further value of a header, so duplicates
+    /// are dropped first.
+    Truncate,
+
+    /// The response is replaced by an empty `500 Internal Server Error` response.
+    InternalError,
+}
+
+/// Which value is kept when a singleton header occurs several times in a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateHeaderPolicy {
+    /// Keep the value that was added first.
+    KeepFirst,
+
+    /// Keep the value that was added last, like repeated calls to `HeaderMap::insert` would.
+    KeepLast,
+}
+
+/// Limits enforced on response headers before they are encoded.
+///
+/// Set with [`HttpServiceBuilder::response_header_limits`]. Limits are applied in two steps:
+///
+/// 1. Duplicates of singleton headers (`Content-Type`, `Content-Length` and `Location`) are folded
+///    into one value according to the [`DuplicateHeaderPolicy`]. A warning is logged when the
+///    duplicates have different values.
+/// 2. If the response then has more header values than `max_count`, or their encoded size is
+///    larger than `max_bytes`, the [`HeaderLimitAction`] is taken and an error is logged. The
+///    size of a header is the length of its name and value plus 4 bytes for the separator and
+///    line break, whatever the protocol.
+///
+/// Headers added by the dispatcher itself, such as `Date`, are not counted.
+///
+/// [`HttpServiceBuilder::response_header_limits`]: crate::HttpServiceBuilder::response_header_limits
+///
+/// # Examples
+/// ```
+/// use actix_http::http::header::{
+///     DuplicateHeaderPolicy, HeaderLimitAction, ResponseHeaderLimits,
+/// };
+///
+/// let limits = ResponseHeaderLimits::new()
+///     .max_count(100)
+///     .max_bytes(16 * 1024)
+///     .on_exceeded(HeaderLimitAction::InternalError)
+///     .duplicates(DuplicateHeaderPolicy::KeepFirst);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseHeaderLimits {
+    max_count: Option<usize>,
+    max_bytes: Option<usize>,
+    on_exceeded: HeaderLimitAction,
+    duplicates: DuplicateHeaderPolicy,
+}
+
+impl Default for ResponseHeaderLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseHeaderLimits {
+    /// Constructs limits that only fold singleton headers, keeping the last value.
+    ///
+    /// Headers are truncated once a limit is set and exceeded.
+    pub fn new() -> Self {
+        ResponseHeaderLimits {
+            max_count: None,
+            max_bytes: None,
+            on_exceeded: HeaderLimitAction::Truncate,
+            duplicates: DuplicateHeaderPolicy::KeepLast,
+        }
+    }
+
+    /// Set maximum number of header values in a response.
+    pub fn max_count(mut self, count: usize) -> Self {
+        self.max_count = Some(count);
+        self
+    }
+
+    /// Set maximum encoded size of the headers of a response, in bytes.
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Set what happens to responses exceeding the limits.
+    pub fn on_exceeded(mut self, action: HeaderLimitAction) -> Self {
+        self.on_exceeded = action;
+        self
+    }
+
+    /// Set which value of a duplicated singleton header is kept.
+    pub fn duplicates(mut self, policy: DuplicateHeaderPolicy) -> Self {
+        self.duplicates = policy;
+        self
+    }
+
+    /// Applies the limits to a response about to be encoded.
+    ///
+    /// Returns the empty 500 response to send instead if the limits are exceeded and configured
+    /// to fail.
+    pub(crate) fn enforce<B>(
+        &self,
+        mut res: Response<()>,
+        body: ResponseBody<B>,
+    ) -> (Response<()>, ResponseBody<B>) {
+        if self.apply(res.headers_mut()) {
+            (res, body)
+        } else {
+            let res = Response::new(StatusCode::INTERNAL_SERVER_ERROR).drop_body();
+            (res, ResponseBody::Other(Body::Empty))
+        }
+    }
+
+    /// Folds singleton headers and truncates `headers` to the limits.
+    ///
+    /// Returns false if the limits are exceeded and the response must not be sent.
+    fn apply(&self, headers: &mut HeaderMap) -> bool {
+        self.fold_duplicates(headers);
+
+        let count = headers.len();
+        let bytes = headers
+            .iter()
+            .map(|(name, value)| entry_size(name, value))
+            .sum::<usize>();
+
+        if !self.exceeded(count, bytes) {
+            return true;
+        }
+
+        match self.on_exceeded {
+            HeaderLimitAction::InternalError => {
+                log::error!(
+                    "Response headers exceed limits ({} headers, {} bytes); sending 500 response",
+                    count,
+                    bytes
+                );
+                false
+            }
+
+            HeaderLimitAction::Truncate => {
+                let dropped = self.truncate(headers);
+                log::error!(
+                    "Response headers exceed limits ({} headers, {} bytes); dropped {} headers",
+                    count,
+                    bytes,
+                    dropped
+                );
+                true
+            }
+        }
+    }
+
+    fn fold_duplicates(&self, headers: &mut HeaderMap) {
+        let duplicated = headers
+            .keys()
+            .filter(|name| is_singleton(name) && headers.get_all(*name).nth(1).is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for name in duplicated {
+            let mut values = headers.get_all(&name);
+            let first = values.next().unwrap();
+
+            let (value, conflict) = match self.duplicates {
+                DuplicateHeaderPolicy::KeepFirst => {
+                    (first, values.any(|value| value != first))
+                }
+                DuplicateHeaderPolicy::KeepLast => {
+                    let last = values.last().unwrap();
+                    (last, headers.get_all(&name).any(|value| value != last))
+                }
+            };
+
+            if conflict {
+                log::warn!(
+                    "Response has conflicting {} headers; keeping {:?}",
+                    name,
+                    value
+                );
+            }
+
+            let value = value.clone();
+            headers.insert(name, value);
+        }
+    }
+
+    fn exceeded(&self, count: usize, bytes: usize) -> bool {
+        self.max_count.map_or(false, |max| count > max)
+            || self.max_bytes.map_or(false, |max| bytes > max)
+    }
+
+    /// Drops headers that do not fit the limits, returning the number of header values dropped.
+    fn truncate(&self, headers: &mut HeaderMap) -> usize {
+        let mut kept = HeaderMap::with_capacity(headers.len_keys());
+        let mut count = 0;
+        let mut bytes = 0;
+        let mut dropped = 0;
+
+        {
+            let first = headers
+                .keys()
+                .filter_map(|name| headers.get(name).map(|value| (name, value)));
+            let rest = headers.keys().flat_map(|name| {
+                headers
+                    .get_all(name)
+                    .skip(1)
+                    .map(move |value| (name, value))
+            });
+
+            for (name, value) in first.chain(rest) {
+                let size = entry_size(name, value);
+
+                if self.exceeded(count + 1, bytes + size) {
+                    dropped += 1;
+                    continue;
+                }
+
+                count += 1;
+                bytes += size;
+                kept.append(name.clone(), value.clone());
+            }
+        }
+
+        *headers = kept;
+        dropped
+    }
+}
+
+/// Size of a header as `name: value\r\n`.
+fn entry_size(name: &HeaderName, value: &HeaderValue) -> usize {
+    name.as_str().len() + value.len() + 4
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::{CACHE_CONTROL, SET_COOKIE};
+
+    use super::*;
+
+    fn pathological() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+
+        headers.append(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        headers.append(LOCATION, HeaderValue::from_static("/a"));
+        headers.append(CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+        for i in 0..1000 {
+            headers.append(SET_COOKIE, HeaderValue::from(i));
+            headers.append(LOCATION, HeaderValue::from_static("/a"));
+        }
+
+        headers.append(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        headers.append(CONTENT_LENGTH, HeaderValue::from_static("3"));
+
+        headers
+    }
+
+    #[test]
+    fn test_fold_duplicates() {
+        let mut headers = pathological();
+        assert!(ResponseHeaderLimits::new().apply(&mut headers));
+        assert_eq!(headers.get_all(CONTENT_TYPE).count(), 1);
+        assert_eq!(headers.get(CONTENT_TYPE).unwrap(), "text/html");
+        assert_eq!(headers.get_all(LOCATION).count(), 1);
+        assert_eq!(headers.get(CONTENT_LENGTH).unwrap(), "3");
+
+        // non-singleton headers are left alone
+        assert_eq!(headers.get_all(SET_COOKIE).count(), 1000);
+
+        let mut headers = pathological();
+        let limits =
+            ResponseHeaderLimits::new().duplicates(DuplicateHeaderPolicy::KeepFirst);
+        assert!(limits.apply(&mut headers));
+        assert_eq!(headers.get(CONTENT_TYPE).unwrap(), "text/plain");
+        assert_eq!(headers.get(LOCATION).unwrap(), "/a");
+        assert_eq!(headers.len(), 1004);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut headers = pathological();
+        let limits = ResponseHeaderLimits::new().max_count(10);
+        assert!(limits.apply(&mut headers));
+        assert_eq!(headers.len(), 10);
+
+        // one value of every header is kept before duplicates
+        assert_eq!(headers.get(CONTENT_TYPE).unwrap(), "text/html");
+        assert_eq!(headers.get(CONTENT_LENGTH).unwrap(), "3");
+        assert_eq!(headers.get(LOCATION).unwrap(), "/a");
+        assert_eq!(headers.get(CACHE_CONTROL).unwrap(), "no-store");
+        assert_eq!(headers.get(SET_COOKIE).unwrap(), "0");
+        assert_eq!(headers.get_all(SET_COOKIE).count(), 6);
+
+        let mut headers = pathological();
+        let limits = ResponseHeaderLimits::new().max_bytes(100);
+        assert!(limits.apply(&mut headers));
+        let bytes = headers
+            .iter()
+            .map(|(name, value)| entry_size(name, value))
+            .sum::<usize>();
+        assert!(bytes <= 100);
+        assert!(headers.contains_key(CACHE_CONTROL));
+
+        // within limits after folding
+        let mut headers = pathological();
+        headers.remove(SET_COOKIE);
+        let limits = ResponseHeaderLimits::new().max_count(4);
+        assert!(limits.apply(&mut headers));
+        assert_eq!(headers.len(), 4);
+    }
+
+    #[test]
+    fn test_internal_error() {
+        let limits = ResponseHeaderLimits::new()
+            .max_count(100)
+            .on_exceeded(HeaderLimitAction::InternalError);
+
+        let mut res = Response::new(StatusCode::OK).drop_body();
+        *res.headers_mut() = pathological();
+        let (res, body) =
+            limits.enforce(res, ResponseBody::<Body>::Other(Body::from("abc")));
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.headers().is_empty());
+        assert!(matches!(body, ResponseBody::Other(Body::Empty)));
+
+        let mut res = Response::new(StatusCode::OK).drop_body();
+        res.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let (res, _) =
+            limits.enforce(res, ResponseBody::<Body>::Other(Body::from("abc")));
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -14,6 +14,7 @@ use crate::HttpMessage;
 mod as_name;
 mod into_pair;
 mod into_value;
+mod limits;
 mod utils;
 
 mod common;
@@ -27,6 +28,9 @@ pub use self::shared::*;
 pub use self::as_name::AsHeaderName;
 pub use self::into_pair::IntoHeaderPair;
 pub use self::into_value::IntoHeaderValue;
+pub use self::limits::{
+    is_singleton, DuplicateHeaderPolicy, HeaderLimitAction, ResponseHeaderLimits,
+};
 #[doc(hidden)]
 pub use self::map::GetAll;
 pub use self::map::HeaderMap;
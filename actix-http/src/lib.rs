@@ -35,6 +35,7 @@ pub mod body;
 mod builder;
 pub mod client;
 mod config;
+mod early_hints;
 #[cfg(feature = "compress")]
 pub mod encoding;
 mod extensions;
@@ -59,11 +60,14 @@ pub mod ws;
 pub use cookie;
 
 pub use self::builder::HttpServiceBuilder;
-pub use self::config::{KeepAlive, ServiceConfig};
+pub use self::config::{KeepAlive, KeepAlivePolicy, ServiceConfig};
+pub use self::early_hints::EarlyHints;
 pub use self::error::{Error, ResponseError, Result};
 pub use self::extensions::Extensions;
 pub use self::http_message::HttpMessage;
-pub use self::message::{Message, RequestHead, RequestHeadType, ResponseHead};
+pub use self::message::{
+    Message, RequestHead, RequestHeadType, RequestWireStats, ResponseHead,
+};
 pub use self::payload::{Payload, PayloadStream};
 pub use self::request::Request;
 pub use self::response::{Response, ResponseBuilder};
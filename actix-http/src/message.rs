@@ -41,6 +41,31 @@ pub trait Head: Default + 'static {
         F: FnOnce(&MessagePool<Self>) -> R;
 }
 
+/// Measurements of how a request head was received, recorded by the server dispatchers.
+///
+/// Recording costs a few integer stores per request; all values are zero for requests that were
+/// not received by a server dispatcher.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestWireStats {
+    /// Size of the request head.
+    ///
+    /// For HTTP/1 this is the raw size of the request line and headers, including line breaks.
+    /// For HTTP/2 it is the decoded size of the header names and values, since the size of the
+    /// compressed header block is not available.
+    pub header_bytes: usize,
+
+    /// Time between receiving the first bytes of the request head and having parsed all of it.
+    ///
+    /// Always zero for HTTP/2, where the head arrives already parsed.
+    pub parse_micros: u64,
+
+    /// Time the connection waited for the client's first bytes after being accepted.
+    ///
+    /// For HTTP/2 this is the duration of the connection preface exchange. The same value is
+    /// reported for every request on a connection.
+    pub preface_wait_micros: u64,
+}
+
 #[derive(Debug)]
 pub struct RequestHead {
     pub uri: Uri,
@@ -49,6 +74,7 @@ pub struct RequestHead {
     pub headers: HeaderMap,
     pub extensions: RefCell<Extensions>,
     pub peer_addr: Option<net::SocketAddr>,
+    pub wire_stats: RequestWireStats,
     flags: Flags,
 }
 
@@ -61,6 +87,7 @@ impl Default for RequestHead {
             headers: HeaderMap::with_capacity(16),
             flags: Flags::empty(),
             peer_addr: None,
+            wire_stats: RequestWireStats::default(),
             extensions: RefCell::new(Extensions::new()),
         }
     }
@@ -69,6 +96,7 @@ impl Default for RequestHead {
 impl Head for RequestHead {
     fn clear(&mut self) {
         self.flags = Flags::empty();
+        self.wire_stats = RequestWireStats::default();
         self.headers.clear();
         self.extensions.get_mut().clear();
     }
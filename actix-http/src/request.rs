@@ -9,7 +9,7 @@ use http::{header, Method, Uri, Version};
 
 use crate::extensions::Extensions;
 use crate::header::HeaderMap;
-use crate::message::{Message, RequestHead};
+use crate::message::{Message, RequestHead, RequestWireStats};
 use crate::payload::{Payload, PayloadStream};
 use crate::HttpMessage;
 
@@ -168,6 +168,12 @@ impl<P> Request<P> {
     pub fn peer_addr(&self) -> Option<net::SocketAddr> {
         self.head().peer_addr
     }
+
+    /// Measurements of how the request head was received.
+    #[inline]
+    pub fn wire_stats(&self) -> RequestWireStats {
+        self.head().wire_stats
+    }
 }
 
 impl<P> fmt::Debug for Request<P> {
@@ -15,9 +15,10 @@ use bytes::{Bytes, BytesMut};
 use futures_core::Stream;
 use serde::Serialize;
 
-use crate::body::{Body, BodyStream, MessageBody, ResponseBody};
-use crate::error::Error;
+use crate::body::{Body, BodySize, BodyStream, ExactLength, MessageBody, ResponseBody};
+use crate::error::{ContentLengthError, Error};
 use crate::extensions::Extensions;
+use crate::h1::{OnUpgrade, Upgraded};
 use crate::header::{IntoHeaderPair, IntoHeaderValue};
 use crate::http::header::{self, HeaderName};
 use crate::http::{Error as HttpError, HeaderMap, StatusCode};
@@ -331,6 +332,7 @@ impl<'a> Iterator for CookieIter<'a> {
 pub struct ResponseBuilder {
     head: Option<BoxedResponseHead>,
     err: Option<HttpError>,
+    content_length: Option<u64>,
     #[cfg(feature = "cookies")]
     cookies: Option<CookieJar>,
 }
@@ -342,6 +344,7 @@ impl ResponseBuilder {
         ResponseBuilder {
             head: Some(BoxedResponseHead::new(status)),
             err: None,
+            content_length: None,
             #[cfg(feature = "cookies")]
             cookies: None,
         }
@@ -486,6 +489,24 @@ impl ResponseBuilder {
         self
     }
 
+    /// Set a handler that takes over the connection once this response has been sent.
+    ///
+    /// The handler is only called for `101 Switching Protocols` responses on HTTP/1.1
+    /// connections; use it together with [`upgrade`](Self::upgrade) to switch the connection to
+    /// another protocol. The connection is closed when the future returned by the handler
+    /// completes.
+    pub fn on_upgrade<F, Fut>(&mut self, f: F) -> &mut Self
+    where
+        F: FnOnce(Upgraded) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        if let Some(parts) = parts(&mut self.head, &self.err) {
+            let handler = OnUpgrade::new(f);
+            parts.extensions.borrow_mut().insert(handler);
+        }
+        self
+    }
+
     /// Force close connection, even if it is marked as keep-alive
     #[inline]
     pub fn force_close(&mut self) -> &mut Self {
@@ -495,10 +516,24 @@ impl ResponseBuilder {
         self
     }
 
+    /// Set the exact length of the response body.
+    ///
+    /// Buffered bodies whose size differs from `len` produce an error response instead.
+    /// Streaming bodies are sent with a `Content-Length` header instead of chunked transfer
+    /// encoding; a stream that yields more or fewer bytes than `len` fails with
+    /// [`ContentLengthError`](crate::error::ContentLengthError) and the connection is closed.
+    #[inline]
+    pub fn content_length(&mut self, len: u64) -> &mut Self {
+        self.content_length = Some(len);
+        self
+    }
+
     /// Disable chunked transfer encoding for HTTP/1.1 streaming responses.
+    ///
+    /// The body length is enforced as described in [`content_length`](Self::content_length).
     #[inline]
     pub fn no_chunking(&mut self, len: u64) -> &mut Self {
-        self.insert_header((header::CONTENT_LENGTH, len));
+        self.content_length(len);
 
         if let Some(parts) = parts(&mut self.head, &self.err) {
             parts.no_chunking(true);
@@ -625,7 +660,53 @@ impl ResponseBuilder {
     ///
     /// `ResponseBuilder` can not be used after this call.
     pub fn body<B: Into<Body>>(&mut self, body: B) -> Response {
-        self.message_body(body.into())
+        let mut body = body.into();
+
+        if let Some(expected) = self.content_length.take() {
+            let actual = match body.size() {
+                BodySize::Stream => {
+                    body = Body::from_message(ExactLength::new(expected, body));
+                    expected
+                }
+                BodySize::Sized(len) => len,
+                BodySize::Empty => 0,
+                BodySize::None => expected,
+            };
+
+            if actual > expected {
+                return Error::from(ContentLengthError::Overrun { expected }).into();
+            } else if actual < expected {
+                return Error::from(ContentLengthError::Underrun { expected, actual })
+                    .into();
+            }
+        }
+
+        let len = match body.size() {
+            BodySize::Sized(len) => Some(len),
+            BodySize::Empty => Some(0),
+            BodySize::None | BodySize::Stream => None,
+        };
+
+        if let (Some(len), Some(parts)) = (len, parts(&mut self.head, &self.err)) {
+            // content length is computed from the body; drop a user-set value that disagrees
+            if let Some(value) = parts.headers.get(header::CONTENT_LENGTH) {
+                let user_len = value
+                    .to_str()
+                    .ok()
+                    .and_then(|v| v.trim().parse::<u64>().ok());
+
+                if user_len != Some(len) {
+                    log::warn!(
+                        "Removing Content-Length header {:?} that conflicts with body size {}",
+                        value,
+                        len
+                    );
+                    parts.headers.remove(header::CONTENT_LENGTH);
+                }
+            }
+        }
+
+        self.message_body(body)
     }
 
     /// Set a body and generate `Response`.
@@ -708,6 +789,7 @@ impl ResponseBuilder {
         ResponseBuilder {
             head: self.head.take(),
             err: self.err.take(),
+            content_length: self.content_length.take(),
             #[cfg(feature = "cookies")]
             cookies: self.cookies.take(),
         }
@@ -749,6 +831,7 @@ impl<B> From<Response<B>> for ResponseBuilder {
         ResponseBuilder {
             head: Some(res.head),
             err: None,
+            content_length: None,
             #[cfg(feature = "cookies")]
             cookies: jar,
         }
@@ -793,6 +876,7 @@ impl<'a> From<&'a ResponseHead> for ResponseBuilder {
         ResponseBuilder {
             head: Some(msg),
             err: None,
+            content_length: None,
             #[cfg(feature = "cookies")]
             cookies: jar,
         }
@@ -1166,4 +1250,53 @@ mod tests {
         assert!(headers.contains(&HeaderValue::from_static("application/octet-stream")));
         assert!(headers.contains(&HeaderValue::from_static("application/json")));
     }
+
+    #[test]
+    fn test_content_length_buffered_mismatch() {
+        let res = Response::Ok().content_length(5).body("hello");
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().size(), BodySize::Sized(5));
+
+        let res = Response::Ok().content_length(10).body("hello");
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(matches!(
+            res.error().unwrap().as_error::<ContentLengthError>(),
+            Some(ContentLengthError::Underrun {
+                expected: 10,
+                actual: 5
+            })
+        ));
+
+        let res = Response::Ok().content_length(2).body("hello");
+        assert!(matches!(
+            res.error().unwrap().as_error::<ContentLengthError>(),
+            Some(ContentLengthError::Overrun { expected: 2 })
+        ));
+
+        let res = Response::Ok().content_length(1).finish();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_content_length_stream() {
+        let stream = futures_util::stream::once(async { Ok::<_, Error>(Bytes::new()) });
+        let res = Response::Ok().no_chunking(10).streaming(Box::pin(stream));
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().size(), BodySize::Sized(10));
+        assert!(!res.head().chunked());
+    }
+
+    #[test]
+    fn test_conflicting_content_length_header() {
+        let res = Response::Ok()
+            .insert_header((header::CONTENT_LENGTH, "100"))
+            .body("hello");
+        assert!(!res.headers().contains_key(header::CONTENT_LENGTH));
+
+        let res = Response::Ok()
+            .insert_header((header::CONTENT_LENGTH, "5"))
+            .body("hello");
+        assert_eq!(res.headers().get(header::CONTENT_LENGTH).unwrap(), "5");
+    }
 }
@@ -4,7 +4,7 @@ use std::task::{Context, Poll};
 use std::{fmt, net, rc::Rc};
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
-use actix_rt::net::TcpStream;
+use actix_rt::{net::TcpStream, time::Instant};
 use actix_service::{pipeline_factory, IntoServiceFactory, Service, ServiceFactory};
 use bytes::Bytes;
 use futures_core::{ready, Future};
@@ -314,7 +314,7 @@ mod rustls {
 impl<T, S, B, X, U> ServiceFactory<(T, Protocol, Option<net::SocketAddr>)>
     for HttpService<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: ServiceFactory<Request, Config = ()>,
     S::Error: Into<Error> + 'static,
     S::InitError: fmt::Debug,
@@ -374,7 +374,7 @@ where
 
 impl<T, S, B, X, U> Future for HttpServiceResponse<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: ServiceFactory<Request>,
     S::Error: Into<Error> + 'static,
     S::InitError: fmt::Debug,
@@ -493,7 +493,7 @@ where
 impl<T, S, B, X, U> Service<(T, Protocol, Option<net::SocketAddr>)>
     for HttpServiceHandler<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error> + 'static,
     S::Future: 'static,
@@ -567,6 +567,7 @@ where
                     self.flow.clone(),
                     on_connect_data,
                     peer_addr,
+                    Instant::now(),
                 ))),
             },
 
@@ -591,7 +592,7 @@ where
     S: Service<Request>,
     S::Future: 'static,
     S::Error: Into<Error>,
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     B: MessageBody,
     X: Service<Request, Response = Request>,
     X::Error: Into<Error>,
@@ -607,6 +608,7 @@ where
             Rc<HttpFlow<S, X, U>>,
             OnConnectData,
             Option<net::SocketAddr>,
+            Instant,
         )>,
     ),
 }
@@ -614,7 +616,7 @@ where
 #[pin_project]
 pub struct HttpServiceHandlerResponse<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error> + 'static,
     S::Future: 'static,
@@ -631,7 +633,7 @@ where
 
 impl<T, S, B, X, U> Future for HttpServiceHandlerResponse<T, S, B, X, U>
 where
-    T: AsyncRead + AsyncWrite + Unpin,
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
     S: Service<Request>,
     S::Error: Into<Error> + 'static,
     S::Future: 'static,
@@ -651,7 +653,7 @@ where
             StateProj::H2Handshake(data) => {
                 match ready!(Pin::new(&mut data.as_mut().unwrap().0).poll(cx)) {
                     Ok(conn) => {
-                        let (_, cfg, srv, on_connect_data, peer_addr) =
+                        let (_, cfg, srv, on_connect_data, peer_addr, started) =
                             data.take().unwrap();
                         self.as_mut().project().state.set(State::H2(Dispatcher::new(
                             srv,
@@ -660,6 +662,7 @@ where
                             cfg,
                             None,
                             peer_addr,
+                            started,
                         )));
                         self.poll(cx)
                     }
@@ -8,7 +8,7 @@ use actix_http::error::{ErrorBadRequest, PayloadError};
 use actix_http::http::header::{self, HeaderName, HeaderValue};
 use actix_http::http::{Method, StatusCode, Version};
 use actix_http::HttpMessage;
-use actix_http::{body, Error, HttpService, Request, Response};
+use actix_http::{body, EarlyHints, Error, HttpService, Request, Response};
 use actix_http_test::test_server;
 use actix_service::{fn_service, ServiceFactoryExt};
 use bytes::{Bytes, BytesMut};
@@ -77,6 +77,31 @@ async fn test_h2() -> io::Result<()> {
     Ok(())
 }
 
+#[actix_rt::test]
+async fn test_h2_early_hints() -> io::Result<()> {
+    let srv = test_server(move || {
+        HttpService::build()
+            .h2(|req: Request| {
+                // early hints are not supported over HTTP/2
+                let hints = req
+                    .extensions()
+                    .get::<EarlyHints>()
+                    .cloned()
+                    .unwrap_or_default();
+                assert!(!hints.send(header::HeaderMap::new()));
+
+                ok::<_, Error>(Response::Ok().finish())
+            })
+            .openssl(tls_config())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let response = srv.sget("/").send().await.unwrap();
+    assert!(response.status().is_success());
+    Ok(())
+}
+
 #[actix_rt::test]
 async fn test_h2_1() -> io::Result<()> {
     let srv = test_server(move || {
@@ -12,7 +12,8 @@ use regex::Regex;
 
 use actix_http::HttpMessage;
 use actix_http::{
-    body, error, http, http::header, Error, HttpService, KeepAlive, Request, Response,
+    body, error, http, http::header, EarlyHints, Error, HttpService, KeepAlive, Request,
+    Response,
 };
 
 #[actix_rt::test]
@@ -114,6 +115,97 @@ async fn test_expect_continue_h1() {
     assert!(data.starts_with("HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n"));
 }
 
+#[actix_rt::test]
+async fn test_early_hints_h1() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(fn_service(|req: Request| {
+                let hints = req
+                    .extensions()
+                    .get::<EarlyHints>()
+                    .cloned()
+                    .unwrap_or_default();
+
+                let mut headers = header::HeaderMap::new();
+                headers.insert(
+                    header::LINK,
+                    header::HeaderValue::from_static("</style.css>; rel=preload"),
+                );
+                let sent = hints.send(headers);
+                assert_eq!(sent, req.version() == http::Version::HTTP_11);
+
+                sleep(Duration::from_millis(20))
+                    .then(move |_| ok::<_, ()>(Response::Ok().finish()))
+            }))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with(
+        "HTTP/1.1 103 Early Hints\r\nlink: </style.css>; rel=preload\r\n\r\nHTTP/1.1 200 OK\r\n"
+    ));
+
+    // HTTP/1.0 clients do not get a handle
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.0\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(!data.contains("103 Early Hints"));
+}
+
+#[actix_rt::test]
+async fn test_wire_stats_h1() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(fn_service(|req: Request| {
+                let stats = req.wire_stats();
+                let body = format!(
+                    "{} {} {}",
+                    stats.header_bytes, stats.parse_micros, stats.preface_wait_micros
+                );
+                ok::<_, ()>(Response::Ok().body(body))
+            }))
+            .tcp()
+    })
+    .await;
+
+    let stats = |data: String| -> Vec<u64> {
+        let body = data.rsplit("\r\n\r\n").next().unwrap();
+        body.split(' ').map(|n| n.parse().unwrap()).collect()
+    };
+
+    // minimal head sent in one go
+    let head = "GET / HTTP/1.1\r\nconnection: close\r\n\r\n";
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(head.as_bytes());
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    let minimal = stats(data);
+    assert_eq!(minimal[0], head.len() as u64);
+    assert!(minimal[1] < 50_000);
+
+    // bloated head sent in two parts after an initial delay
+    let head = format!(
+        "GET / HTTP/1.1\r\nconnection: close\r\nx-bloat: {}\r\n\r\n",
+        "a".repeat(4000)
+    );
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    thread::sleep(Duration::from_millis(30));
+    let _ = stream.write_all(&head.as_bytes()[..2000]);
+    thread::sleep(Duration::from_millis(60));
+    let _ = stream.write_all(&head.as_bytes()[2000..]);
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    let bloated = stats(data);
+    assert_eq!(bloated[0], head.len() as u64);
+    assert!(bloated[1] >= 50_000);
+    assert!(bloated[2] >= 20_000);
+}
+
 #[actix_rt::test]
 async fn test_chunked_payload() {
     let chunk_sizes = vec![32768, 32, 32768];
@@ -187,6 +279,86 @@ async fn test_slow_request() {
     assert!(data.starts_with("HTTP/1.1 408 Request Timeout"));
 }
 
+#[actix_rt::test]
+async fn test_trickled_request_head() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive(30)
+            .client_timeout(200)
+            .h1(|_| future::ok::<_, ()>(Response::Ok().finish()))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\nx-trickle: ");
+
+    // every byte arrives well within the timeout, but the head as a whole does not
+    let mut data = [0; 1024];
+    let mut len = 0;
+    for _ in 0..40 {
+        let _ = stream.write_all(b"x");
+        if let Ok(n) = stream.read(&mut data) {
+            len = n;
+            break;
+        }
+    }
+
+    let data = String::from_utf8_lossy(&data[..len]);
+    assert!(data.starts_with("HTTP/1.1 408 Request Timeout"));
+}
+
+#[actix_rt::test]
+async fn test_request_payload_rate() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive(30)
+            .client_timeout(200)
+            .client_payload_rate(10, 200)
+            .h1(|mut req: Request| async move {
+                let mut payload = req.take_payload();
+                let mut len = 0;
+                while let Some(chunk) = payload.next().await {
+                    len += chunk?.len();
+                }
+                Ok::<_, Error>(Response::Ok().body(len.to_string()))
+            })
+            .tcp()
+    })
+    .await;
+
+    // slow but steady upload of 20 bytes per second completes
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let _ = stream.write_all(b"POST /test HTTP/1.1\r\ncontent-length: 20\r\n\r\n");
+    for _ in 0..20 {
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(b"x").unwrap();
+    }
+
+    let mut data = [0; 1024];
+    let len = stream.read(&mut data).unwrap();
+    let data = String::from_utf8_lossy(&data[..len]);
+    assert!(data.starts_with("HTTP/1.1 200 OK"));
+    assert!(data.ends_with("\r\n\r\n20"));
+
+    // stalled upload is terminated
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let _ = stream.write_all(b"POST /test HTTP/1.1\r\ncontent-length: 20\r\n\r\nx");
+
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 408 Request Timeout"));
+}
+
 #[actix_rt::test]
 async fn test_http1_malformed_request() {
     let srv = test_server(|| {
@@ -246,6 +418,47 @@ async fn test_http1_keepalive_timeout() {
     assert_eq!(res, 0);
 }
 
+#[actix_rt::test]
+async fn test_http1_keepalive_idle_timeout() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive_timeout(Duration::from_millis(300))
+            .h1(fn_service(|req: Request| {
+                let delay = if req.path() == "/slow" { 600 } else { 0 };
+                sleep(Duration::from_millis(delay))
+                    .then(|_| future::ok::<_, ()>(Response::Ok().finish()))
+            }))
+            .tcp()
+    })
+    .await;
+
+    // idle connection is closed after the timeout
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+    thread::sleep(Duration::from_millis(600));
+
+    let mut data = vec![0; 1024];
+    let res = stream.read(&mut data).unwrap();
+    assert_eq!(res, 0);
+
+    // connection with a request in progress is kept open
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET /slow HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+
+    // and reused while within the timeout
+    thread::sleep(Duration::from_millis(100));
+    let _ = stream.write_all(b"GET /test HTTP/1.1\r\n\r\n");
+    let mut data = vec![0; 1024];
+    let _ = stream.read(&mut data);
+    assert_eq!(&data[..17], b"HTTP/1.1 200 OK\r\n");
+}
+
 #[actix_rt::test]
 async fn test_http1_keepalive_close() {
     let srv = test_server(|| {
@@ -553,6 +766,44 @@ async fn test_h1_body_length() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+#[actix_rt::test]
+async fn test_h1_body_length_mismatch_closes_connection() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .keep_alive(30)
+            .h1(|req: Request| {
+                let chunks: &[&'static [u8]] = match req.path() {
+                    "/over" => &[b"0123456789", b"overrun"],
+                    _ => &[b"01234"],
+                };
+                let body = futures_util::stream::iter(
+                    chunks
+                        .iter()
+                        .map(|chunk| Ok::<_, Error>(Bytes::from_static(chunk))),
+                );
+                ok::<_, ()>(Response::Ok().no_chunking(10).streaming(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    for path in &["/over", "/under"] {
+        let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let _ = stream.write_all(format!("GET {} HTTP/1.1\r\n\r\n", path).as_bytes());
+
+        // connection must be closed rather than kept alive with a mis-framed body
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).unwrap();
+
+        let data = String::from_utf8_lossy(&data);
+        assert!(!data.contains("overrun"));
+        assert!(!data.contains("transfer-encoding"));
+    }
+}
+
 #[actix_rt::test]
 async fn test_h1_body_chunked_explicit() {
     let mut srv = test_server(|| {
@@ -617,6 +868,98 @@ async fn test_h1_body_chunked_implicit() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+#[actix_rt::test]
+async fn test_h1_max_chunk_size() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .max_chunk_size(1024)
+            .h1(|_| {
+                let body = once(ok::<_, Error>(Bytes::from(vec![b'x'; 5000])));
+                ok::<_, ()>(Response::Ok().streaming(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+
+    let body = &data[data.find("\r\n\r\n").unwrap() + 4..];
+    let sizes = body
+        .trim_end_matches("\r\n")
+        .split("\r\n")
+        .step_by(2)
+        .map(|size| usize::from_str_radix(size, 16).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, [1024, 1024, 1024, 1024, 904, 0]);
+}
+
+fn duplicated_headers() -> Response {
+    let mut res = Response::Ok();
+    res.append_header((header::CONTENT_TYPE, "text/plain"));
+
+    for i in 0..500usize {
+        res.append_header((header::LOCATION, format!("/{}", i)));
+        res.append_header(("x-loop", i));
+    }
+
+    res.append_header((header::CONTENT_TYPE, "text/html"));
+    res.body(STR)
+}
+
+#[actix_rt::test]
+async fn test_h1_response_header_limits_truncate() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .response_header_limits(
+                header::ResponseHeaderLimits::new()
+                    .max_count(20)
+                    .duplicates(header::DuplicateHeaderPolicy::KeepFirst),
+            )
+            .h1(|_| ok::<_, ()>(duplicated_headers()))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+
+    let head = &data[..data.find("\r\n\r\n").unwrap()];
+    assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert_eq!(head.matches("content-type: ").count(), 1);
+    assert!(head.contains("content-type: text/plain\r\n"));
+    assert_eq!(head.matches("location: ").count(), 1);
+    assert!(head.contains("location: /0\r\n"));
+    assert_eq!(head.matches("x-loop: ").count(), 18);
+    assert!(data.ends_with(STR));
+}
+
+#[actix_rt::test]
+async fn test_h1_response_header_limits_internal_error() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .response_header_limits(
+                header::ResponseHeaderLimits::new()
+                    .max_bytes(4096)
+                    .on_exceeded(header::HeaderLimitAction::InternalError),
+            )
+            .h1(|_| ok::<_, ()>(duplicated_headers()))
+            .tcp()
+    })
+    .await;
+
+    let response = srv.get("/").send().await.unwrap();
+    assert_eq!(response.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!response.headers().contains_key("x-loop"));
+
+    let bytes = srv.load_body(response).await.unwrap();
+    assert!(bytes.is_empty());
+}
+
 #[actix_rt::test]
 async fn test_h1_response_http_error_handling() {
     let mut srv = test_server(|| {
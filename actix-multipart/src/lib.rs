@@ -5,7 +5,9 @@
 
 mod error;
 mod extractor;
+mod scanner;
 mod server;
 
 pub use self::error::MultipartError;
+pub use self::scanner::{BoundaryScanner, Scan, ScanState};
 pub use self::server::{Field, Multipart};
@@ -0,0 +1,285 @@
+//! Resumable scanner for the delimiters that end multipart body parts.
+
+use std::cmp;
+
+use crate::error::MultipartError;
+
+/// Longest boundary allowed by RFC 2046.
+pub(crate) const MAX_BOUNDARY_LEN: usize = 70;
+
+/// State of a [`BoundaryScanner`] between calls to [`scan`](BoundaryScanner::scan).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanState {
+    /// No delimiter has been started; the next byte is content unless it starts one.
+    Content,
+
+    /// Once the reported content is consumed, the buffer starts with this many bytes of a
+    /// delimiter. They are held back until enough data is received to decide.
+    Partial(usize),
+
+    /// Once the reported content is consumed, the buffer starts with a complete delimiter.
+    Delimiter,
+}
+
+/// Outcome of [`BoundaryScanner::scan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scan {
+    /// This many bytes at the start of the buffer are content and can be consumed.
+    Content(usize),
+
+    /// The buffer starts with a delimiter, which ends the body part.
+    Delimiter,
+
+    /// The buffer starts with part of a delimiter; more data is needed to decide.
+    NeedMore,
+}
+
+/// Finds the delimiter, `CRLF "--" boundary`, that ends a multipart body part.
+///
+/// The scanner does not consume any bytes. It is passed the unconsumed bytes of the body part
+/// each time more data is received, and the caller removes the content it reports before the
+/// next call. Bytes that may start a delimiter are held back, and matching resumes after the
+/// bytes already matched, so delimiters split across reads are found. No input makes it panic;
+/// a buffer that does not start with the held-back bytes only restarts matching.
+///
+/// # Examples
+/// ```
+/// use actix_multipart::{BoundaryScanner, Scan, ScanState};
+///
+/// let mut scanner = BoundaryScanner::new("abc").unwrap();
+///
+/// // the delimiter may start at the CR
+/// assert_eq!(scanner.scan(b"data\r\n-"), Scan::Content(4));
+/// assert_eq!(scanner.state(), ScanState::Partial(3));
+///
+/// assert_eq!(scanner.scan(b"\r\n-"), Scan::NeedMore);
+/// assert_eq!(scanner.scan(b"\r\n--abc\r\n"), Scan::Delimiter);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoundaryScanner {
+    delimiter: Vec<u8>,
+    state: ScanState,
+}
+
+impl BoundaryScanner {
+    /// Creates scanner for `boundary`, which must be 1 to 70 bytes long.
+    pub fn new(boundary: &str) -> Result<Self, MultipartError> {
+        if boundary.is_empty() || boundary.len() > MAX_BOUNDARY_LEN {
+            return Err(MultipartError::Boundary);
+        }
+
+        let mut delimiter = Vec::with_capacity(boundary.len() + 4);
+        delimiter.extend_from_slice(b"\r\n--");
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        Ok(BoundaryScanner {
+            delimiter,
+            state: ScanState::Content,
+        })
+    }
+
+    /// Returns state left by the last call to [`scan`](Self::scan).
+    pub fn state(&self) -> ScanState {
+        self.state
+    }
+
+    /// Returns length of the delimiter, including its leading CRLF.
+    pub fn delimiter_len(&self) -> usize {
+        self.delimiter.len()
+    }
+
+    /// Scans unconsumed bytes of the body part for the delimiter.
+    pub fn scan(&mut self, buf: &[u8]) -> Scan {
+        let mut pos = 0;
+
+        // resume the delimiter held back by an earlier call
+        let matched = match self.state {
+            ScanState::Content => None,
+            ScanState::Partial(matched) => Some(matched),
+            ScanState::Delimiter => Some(self.delimiter.len()),
+        };
+
+        if let Some(matched) = matched {
+            match self.match_at(buf, 0, matched) {
+                Some(state) => {
+                    self.state = state;
+                    return match state {
+                        ScanState::Delimiter => Scan::Delimiter,
+                        _ => Scan::NeedMore,
+                    };
+                }
+
+                // the held-back bytes are content after all
+                None => pos = cmp::min(1, buf.len()),
+            }
+        }
+
+        self.state = ScanState::Content;
+
+        while let Some(idx) = twoway::find_bytes(&buf[pos..], b"\r") {
+            let start = pos + idx;
+
+            match self.match_at(buf, start, 0) {
+                Some(state) if start > 0 => {
+                    // report the content first, the delimiter is matched on the next call
+                    self.state = state;
+                    return Scan::Content(start);
+                }
+                Some(state) => {
+                    self.state = state;
+                    return match state {
+                        ScanState::Delimiter => Scan::Delimiter,
+                        _ => Scan::NeedMore,
+                    };
+                }
+                None => pos = start + 1,
+            }
+        }
+
+        Scan::Content(buf.len())
+    }
+
+    /// Matches the delimiter at `start` of `buf`, skipping the first `matched` bytes of it.
+    ///
+    /// Returns `None` if the bytes at `start` can not be a delimiter.
+    fn match_at(&self, buf: &[u8], start: usize, matched: usize) -> Option<ScanState> {
+        let candidate = buf.get(start..)?;
+        let end = cmp::min(candidate.len(), self.delimiter.len());
+
+        if matched > end || candidate[matched..end] != self.delimiter[matched..end] {
+            None
+        } else if end == self.delimiter.len() {
+            Some(ScanState::Delimiter)
+        } else {
+            Some(ScanState::Partial(end))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `chunks` to a scanner like the multipart parser does, returning the content and
+    /// whether the delimiter was found.
+    fn scan_chunks(boundary: &str, chunks: &[&[u8]]) -> (Vec<u8>, bool) {
+        let mut scanner = BoundaryScanner::new(boundary).unwrap();
+        let mut buf = Vec::new();
+        let mut content = Vec::new();
+
+        for chunk in chunks {
+            buf.extend_from_slice(chunk);
+
+            loop {
+                match scanner.scan(&buf) {
+                    Scan::Delimiter => {
+                        assert!(buf.starts_with(b"\r\n--"));
+                        return (content, true);
+                    }
+                    Scan::NeedMore | Scan::Content(0) => break,
+                    Scan::Content(n) => {
+                        content.extend(buf.drain(..n));
+                    }
+                }
+            }
+        }
+
+        (content, false)
+    }
+
+    #[test]
+    fn test_invalid_boundary() {
+        assert!(BoundaryScanner::new("").is_err());
+        assert!(BoundaryScanner::new(&"b".repeat(MAX_BOUNDARY_LEN + 1)).is_err());
+        assert!(BoundaryScanner::new(&"b".repeat(MAX_BOUNDARY_LEN)).is_ok());
+    }
+
+    #[test]
+    fn test_states() {
+        let mut scanner = BoundaryScanner::new("b").unwrap();
+        assert_eq!(scanner.delimiter_len(), 5);
+        assert_eq!(scanner.state(), ScanState::Content);
+
+        assert_eq!(scanner.scan(b""), Scan::Content(0));
+        assert_eq!(scanner.scan(b"no delimiter"), Scan::Content(12));
+        assert_eq!(scanner.state(), ScanState::Content);
+
+        assert_eq!(scanner.scan(b"\r"), Scan::NeedMore);
+        assert_eq!(scanner.state(), ScanState::Partial(1));
+        assert_eq!(scanner.scan(b"\r\n-"), Scan::NeedMore);
+        assert_eq!(scanner.state(), ScanState::Partial(3));
+
+        // mismatch releases the CR as content and rescans from the next byte
+        assert_eq!(scanner.scan(b"\r\n-x"), Scan::Content(4));
+        assert_eq!(scanner.state(), ScanState::Content);
+
+        assert_eq!(scanner.scan(b"ab\r\n--b\r\n"), Scan::Content(2));
+        assert_eq!(scanner.state(), ScanState::Delimiter);
+        assert_eq!(scanner.scan(b"\r\n--b\r\n"), Scan::Delimiter);
+    }
+
+    #[test]
+    fn test_nested_candidates() {
+        // a CR inside a partial delimiter starts the real one
+        assert_eq!(
+            scan_chunks("b0", &[b"x\r\r\n--b0"]),
+            (b"x\r".to_vec(), true)
+        );
+        assert_eq!(
+            scan_chunks("b0", &[b"x\r\n--b\r", b"\n--b0"]),
+            (b"x\r\n--b".to_vec(), true)
+        );
+    }
+
+    #[test]
+    fn test_split_at_every_position() {
+        let body: &[u8] = b"first\r\r\n--b0\r\nvalue\r\n--b0undary\r\nrest";
+
+        for split in 0..=body.len() {
+            let (content, found) = scan_chunks("b0undary", &[&body[..split], &body[split..]]);
+            assert!(found, "delimiter not found with split at {}", split);
+            assert_eq!(content, b"first\r\r\n--b0\r\nvalue");
+        }
+
+        let chunks = body.chunks(1).collect::<Vec<_>>();
+        let (content, found) = scan_chunks("b0undary", &chunks);
+        assert!(found);
+        assert_eq!(content, b"first\r\r\n--b0\r\nvalue");
+    }
+
+    #[test]
+    fn test_precondition_violations() {
+        let mut scanner = BoundaryScanner::new("abc").unwrap();
+        assert_eq!(scanner.scan(b"\r\n--a"), Scan::NeedMore);
+
+        // buffers that do not start with the held-back bytes restart matching
+        assert_eq!(scanner.scan(b""), Scan::Content(0));
+        assert_eq!(scanner.scan(b"\r\n--abc"), Scan::Delimiter);
+        assert_eq!(scanner.scan(b"x"), Scan::Content(1));
+        assert_eq!(scanner.state(), ScanState::Content);
+    }
+
+    #[test]
+    fn test_random_input() {
+        // simple linear congruential generator keeps the inputs reproducible
+        let mut seed: u64 = 0x9e37_79b9;
+        let mut next = |max: usize| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as usize % max
+        };
+
+        for _ in 0..1000 {
+            let len = next(32);
+            let input = (0..len).map(|_| b"\r\n-ab"[next(5)]).collect::<Vec<_>>();
+            let split = next(len + 1);
+
+            let (content, found) = scan_chunks("ab", &[&input[..split], &input[split..]]);
+            let delimiter = twoway::find_bytes(&input, b"\r\n--ab");
+
+            assert_eq!(found, delimiter.is_some(), "input {:?}", input);
+            if let Some(idx) = delimiter {
+                assert_eq!(content, &input[..idx]);
+            }
+        }
+    }
+}
@@ -16,6 +16,7 @@ use actix_web::error::{ParseError, PayloadError};
 use actix_web::http::header::{self, ContentDisposition, HeaderMap, HeaderName, HeaderValue};
 
 use crate::error::MultipartError;
+use crate::scanner::{BoundaryScanner, Scan, MAX_BOUNDARY_LEN};
 
 const MAX_HEADERS: usize = 32;
 
@@ -73,7 +74,12 @@ impl Multipart {
             if let Ok(content_type) = content_type.to_str() {
                 if let Ok(ct) = content_type.parse::<mime::Mime>() {
                     if let Some(boundary) = ct.get_param(mime::BOUNDARY) {
-                        Ok(boundary.as_str().to_owned())
+                        let boundary = boundary.as_str();
+                        if boundary.is_empty() || boundary.len() > MAX_BOUNDARY_LEN {
+                            Err(MultipartError::Boundary)
+                        } else {
+                            Ok(boundary.to_owned())
+                        }
                     } else {
                         Err(MultipartError::Boundary)
                     }
@@ -209,30 +215,11 @@ impl InnerMultipart {
         payload: &mut PayloadBuffer,
         boundary: &str,
     ) -> Result<Option<bool>, MultipartError> {
-        let mut eof = false;
         loop {
             match payload.readline()? {
-                Some(chunk) => {
-                    if chunk.is_empty() {
-                        return Err(MultipartError::Boundary);
-                    }
-                    if chunk.len() < boundary.len() {
-                        continue;
-                    }
-                    if &chunk[..2] == b"--" && &chunk[2..chunk.len() - 2] == boundary.as_bytes()
-                    {
-                        break;
-                    } else {
-                        if chunk.len() < boundary.len() + 2 {
-                            continue;
-                        }
-                        let b: &[u8] = boundary.as_ref();
-                        if &chunk[..boundary.len()] == b
-                            && &chunk[boundary.len()..boundary.len() + 2] == b"--"
-                        {
-                            eof = true;
-                            break;
-                        }
+                Some(line) => {
+                    if let Some(eof) = InnerMultipart::match_boundary(&line, boundary) {
+                        return Ok(Some(eof));
                     }
                 }
                 None => {
@@ -244,7 +231,32 @@ impl InnerMultipart {
                 }
             }
         }
-        Ok(Some(eof))
+    }
+
+    /// Checks if `line` is a delimiter line of `boundary`.
+    ///
+    /// Returns `Some(true)` for the closing delimiter, `Some(false)` for other delimiters and
+    /// `None` if the line is not a delimiter.
+    fn match_boundary(line: &[u8], boundary: &str) -> Option<bool> {
+        let mut line = line;
+        if line.ends_with(b"\r\n") {
+            line = &line[..line.len() - 2];
+        } else if line.ends_with(b"\n") {
+            line = &line[..line.len() - 1];
+        }
+
+        let boundary = boundary.as_bytes();
+        if !line.starts_with(b"--") || !line[2..].starts_with(boundary) {
+            return None;
+        }
+
+        let rest = &line[boundary.len() + 2..];
+
+        match rest {
+            b"" => Some(false),
+            b"--" => Some(true),
+            _ => None,
+        }
     }
 
     fn poll(
@@ -350,7 +362,7 @@ impl InnerMultipart {
             } else {
                 let field = Rc::new(RefCell::new(InnerField::new(
                     self.payload.clone(),
-                    self.boundary.clone(),
+                    BoundaryScanner::new(&self.boundary)?,
                     &headers,
                 )?));
                 self.item = InnerMultipartItem::Field(Rc::clone(&field));
@@ -445,7 +457,7 @@ impl fmt::Debug for Field {
 
 struct InnerField {
     payload: Option<PayloadRef>,
-    boundary: String,
+    scanner: BoundaryScanner,
     eof: bool,
     length: Option<u64>,
 }
@@ -453,7 +465,7 @@ struct InnerField {
 impl InnerField {
     fn new(
         payload: PayloadRef,
-        boundary: String,
+        scanner: BoundaryScanner,
         headers: &HeaderMap,
     ) -> Result<InnerField, PayloadError> {
         let len = if let Some(len) = headers.get(&header::CONTENT_LENGTH) {
@@ -471,7 +483,7 @@ impl InnerField {
         };
 
         Ok(InnerField {
-            boundary,
+            scanner,
             payload: Some(payload),
             eof: false,
             length: len,
@@ -510,76 +522,24 @@ impl InnerField {
 
     /// Reads content chunk of body part with unknown length.
     /// The `Content-Length` header for body part is not necessary.
+    ///
+    /// Bytes that may be the start of a delimiter are left in the buffer, and the scanner resumes
+    /// matching them once more data is read, so delimiters split across payload chunks are found.
     fn read_stream(
         payload: &mut PayloadBuffer,
-        boundary: &str,
+        scanner: &mut BoundaryScanner,
     ) -> Poll<Option<Result<Bytes, MultipartError>>> {
-        let mut pos = 0;
-
-        let len = payload.buf.len();
-        if len == 0 {
-            return if payload.eof {
-                Poll::Ready(Some(Err(MultipartError::Incomplete)))
-            } else {
-                Poll::Pending
-            };
-        }
-
-        // check boundary
-        if len > 4 && payload.buf[0] == b'\r' {
-            let b_len = if &payload.buf[..2] == b"\r\n" && &payload.buf[2..4] == b"--" {
-                Some(4)
-            } else if &payload.buf[1..3] == b"--" {
-                Some(3)
-            } else {
-                None
-            };
-
-            if let Some(b_len) = b_len {
-                let b_size = boundary.len() + b_len;
-                if len < b_size {
-                    return Poll::Pending;
-                } else if &payload.buf[b_len..b_size] == boundary.as_bytes() {
-                    // found boundary
-                    return Poll::Ready(None);
-                }
-            }
-        }
-
-        loop {
-            return if let Some(idx) = twoway::find_bytes(&payload.buf[pos..], b"\r") {
-                let cur = pos + idx;
-
-                // check if we have enough data for boundary detection
-                if cur + 4 > len {
-                    if cur > 0 {
-                        Poll::Ready(Some(Ok(payload.buf.split_to(cur).freeze())))
-                    } else {
-                        Poll::Pending
-                    }
+        match scanner.scan(&payload.buf) {
+            Scan::Delimiter => Poll::Ready(None),
+            Scan::Content(0) | Scan::NeedMore => {
+                if payload.eof {
+                    // a partial delimiter can not be completed anymore
+                    Poll::Ready(Some(Err(MultipartError::Incomplete)))
                 } else {
-                    // check boundary
-                    if (&payload.buf[cur..cur + 2] == b"\r\n"
-                        && &payload.buf[cur + 2..cur + 4] == b"--")
-                        || (&payload.buf[cur..=cur] == b"\r"
-                            && &payload.buf[cur + 1..cur + 3] == b"--")
-                    {
-                        if cur != 0 {
-                            // return buffer
-                            Poll::Ready(Some(Ok(payload.buf.split_to(cur).freeze())))
-                        } else {
-                            pos = cur + 1;
-                            continue;
-                        }
-                    } else {
-                        // not boundary
-                        pos = cur + 1;
-                        continue;
-                    }
+                    Poll::Pending
                 }
-            } else {
-                Poll::Ready(Some(Ok(payload.buf.split().freeze())))
-            };
+            }
+            Scan::Content(len) => Poll::Ready(Some(Ok(payload.buf.split_to(len).freeze()))),
         }
     }
 
@@ -593,7 +553,7 @@ impl InnerField {
                 let res = if let Some(ref mut len) = self.length {
                     InnerField::read_len(&mut *payload, len)
                 } else {
-                    InnerField::read_stream(&mut *payload, &self.boundary)
+                    InnerField::read_stream(&mut *payload, &mut self.scanner)
                 };
 
                 match res {
@@ -1182,4 +1142,173 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    // Stream that returns the given chunks, returning Pending before each one
+    struct ChunkStream {
+        chunks: std::collections::VecDeque<Bytes>,
+        ready: bool,
+    }
+
+    impl ChunkStream {
+        fn new(chunks: Vec<Bytes>) -> ChunkStream {
+            ChunkStream {
+                chunks: chunks.into(),
+                ready: false,
+            }
+        }
+    }
+
+    impl Stream for ChunkStream {
+        type Item = Result<Bytes, PayloadError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            if !this.ready {
+                this.ready = true;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            this.ready = false;
+            Poll::Ready(this.chunks.pop_front().map(Ok))
+        }
+    }
+
+    /// Parses `chunks` as a multipart body, collecting field contents.
+    ///
+    /// Fails the test if parsing hangs instead of finishing with a result.
+    async fn parse_chunks(
+        boundary: &str,
+        chunks: Vec<Bytes>,
+    ) -> Result<Vec<Bytes>, MultipartError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(&format!("multipart/form-data; boundary={}", boundary))
+                .unwrap(),
+        );
+
+        let mut multipart = Multipart::new(&headers, ChunkStream::new(chunks));
+
+        let parse = async {
+            let mut fields = Vec::new();
+
+            while let Some(field) = multipart.next().await {
+                let mut field = field?;
+                let mut buf = BytesMut::new();
+
+                while let Some(chunk) = field.next().await {
+                    buf.extend_from_slice(&chunk?);
+                }
+
+                fields.push(buf.freeze());
+            }
+
+            Ok(fields)
+        };
+
+        actix_rt::time::timeout(std::time::Duration::from_secs(5), parse)
+            .await
+            .expect("multipart parser did not finish")
+    }
+
+    #[actix_rt::test]
+    async fn test_multipart_invalid_boundary_param() {
+        let long = "b".repeat(MAX_BOUNDARY_LEN + 1);
+
+        for boundary in &["\"\"", long.as_str()] {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(&format!("multipart/form-data; boundary={}", boundary))
+                    .unwrap(),
+            );
+
+            assert!(Multipart::boundary(&headers).is_err());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_multipart_short_boundary_lines() {
+        // preamble lines shorter than the delimiter used to be sliced out of bounds
+        let bodies: &[&[u8]] = &[
+            b"\n",
+            b"-\n",
+            b"--\n",
+            b"--a\n",
+            b"\r\n--\r\n",
+            b"x\r\n--abc--\r\n",
+        ];
+
+        for boundary in &["a", "ab", "abc"] {
+            for body in bodies {
+                let res = parse_chunks(boundary, vec![Bytes::from_static(body)]).await;
+                assert!(
+                    res.is_ok() || matches!(res, Err(MultipartError::Incomplete)),
+                    "unexpected result for {:?}: {:?}",
+                    body,
+                    res
+                );
+            }
+        }
+
+        let res = parse_chunks("abc", vec![Bytes::from_static(b"x\r\n--abc--\r\n")]).await;
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_multipart_trailing_cr() {
+        // field data ending in a CR that can never become a delimiter
+        let body = Bytes::from_static(b"--b\r\nContent-Type: text/plain\r\n\r\ndata\r");
+        match parse_chunks("b", vec![body]).await {
+            Err(MultipartError::Incomplete) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let body =
+            Bytes::from_static(b"--abcdef\r\nContent-Type: text/plain\r\n\r\ndata\r\n--abc");
+        match parse_chunks("abcdef", vec![body]).await {
+            Err(MultipartError::Incomplete) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_multipart_random_splits() {
+        let body: &[u8] = b"preamble\r\n\
+            --b0undary\r\n\
+            Content-Disposition: form-data; name=\"a\"\r\n\r\n\
+            first\r\r\n--b0\r\nvalue\r\n\
+            --b0undary\r\n\
+            Content-Disposition: form-data; name=\"b\"\r\nContent-Length: 6\r\n\r\n\
+            second\r\n\
+            --b0undary--\r\n";
+
+        let expected = vec![
+            Bytes::from_static(b"first\r\r\n--b0\r\nvalue"),
+            Bytes::from_static(b"second"),
+        ];
+
+        // simple linear congruential generator keeps the split points reproducible
+        let mut seed: u64 = 0x2545_f491;
+        let mut next = |max: usize| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) as usize % max
+        };
+
+        for _ in 0..200 {
+            let mut chunks = Vec::new();
+            let mut pos = 0;
+
+            while pos < body.len() {
+                let len = 1 + next(8);
+                let end = cmp::min(pos + len, body.len());
+                chunks.push(Bytes::copy_from_slice(&body[pos..end]));
+                pos = end;
+            }
+
+            let fields = parse_chunks("b0undary", chunks).await.unwrap();
+            assert_eq!(fields, expected);
+        }
+    }
 }
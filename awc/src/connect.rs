@@ -3,13 +3,17 @@ use std::{
     future::Future,
     io, net,
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll},
 };
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed, ReadBuf};
 use actix_http::{
     body::Body,
-    client::{Connect as ClientConnect, ConnectError, Connection, SendRequestError},
+    client::{
+        Connect as ClientConnect, ConnectError, Connection, ConnectionLease, SendRequestError,
+        UpgradeResponse,
+    },
     h1::ClientCodec,
     Payload, RequestHead, RequestHeadType, ResponseHead,
 };
@@ -58,12 +62,14 @@ impl ConnectResponse {
 }
 
 pub(crate) struct DefaultConnector<S> {
-    connector: S,
+    connector: Rc<S>,
 }
 
 impl<S> DefaultConnector<S> {
     pub(crate) fn new(connector: S) -> Self {
-        Self { connector }
+        Self {
+            connector: Rc::new(connector),
+        }
     }
 }
 
@@ -75,61 +81,125 @@ where
 {
     type Response = ConnectResponse;
     type Error = SendRequestError;
-    type Future = ConnectRequestFuture<S::Future, <S::Response as Connection>::Io>;
+    type Future = ConnectRequestFuture<S::Future, <S::Response as Connection>::Io, S>;
 
     actix_service::forward_ready!(connector);
 
     fn call(&self, req: ConnectRequest) -> Self::Future {
-        // connect to the host
-        let fut = match req {
-            ConnectRequest::Client(ref head, .., addr) => self.connector.call(ClientConnect {
-                uri: head.as_ref().uri.clone(),
-                addr,
-            }),
-            ConnectRequest::Tunnel(ref head, addr) => self.connector.call(ClientConnect {
-                uri: head.uri.clone(),
-                addr,
-            }),
+        let mut retry = None;
+
+        // requests with a body that can be sent again are kept for one retry on a new connection
+        let req = match req {
+            ConnectRequest::Client(head, body, addr) => match replay_body(&body) {
+                Some(replay) => {
+                    let (head, head_replay) = share_head(head);
+
+                    retry = Some(Retry {
+                        connector: Rc::clone(&self.connector),
+                        head: head_replay,
+                        body: replay,
+                        addr,
+                    });
+
+                    ConnectRequest::Client(head, body, addr)
+                }
+                None => ConnectRequest::Client(head, body, addr),
+            },
+            req => req,
         };
 
         ConnectRequestFuture::Connection {
-            fut,
+            fut: connect(&*self.connector, &req),
             req: Some(req),
+            retry,
         }
     }
 }
 
+fn connect<S>(connector: &S, req: &ConnectRequest) -> S::Future
+where
+    S: Service<ClientConnect>,
+{
+    // connect to the host
+    match *req {
+        ConnectRequest::Client(ref head, .., addr) => connector.call(ClientConnect {
+            uri: head.as_ref().uri.clone(),
+            addr,
+            lease: head.as_ref().extensions().get::<ConnectionLease>().cloned(),
+        }),
+        ConnectRequest::Tunnel(ref head, addr) => connector.call(ClientConnect {
+            uri: head.uri.clone(),
+            addr,
+            lease: head.extensions().get::<ConnectionLease>().cloned(),
+        }),
+    }
+}
+
+fn replay_body(body: &Body) -> Option<Body> {
+    match *body {
+        Body::None => Some(Body::None),
+        Body::Empty => Some(Body::Empty),
+        Body::Bytes(ref bytes) => Some(Body::Bytes(bytes.clone())),
+        Body::Message(_) => None,
+    }
+}
+
+/// Returns the head twice, moving an owned head behind an `Rc` so that it can be sent again.
+fn share_head(head: RequestHeadType) -> (RequestHeadType, RequestHeadType) {
+    let (head, extra_headers) = match head {
+        RequestHeadType::Owned(head) => (Rc::new(head), None),
+        RequestHeadType::Rc(head, extra_headers) => (head, extra_headers),
+    };
+
+    (
+        RequestHeadType::Rc(Rc::clone(&head), extra_headers.clone()),
+        RequestHeadType::Rc(head, extra_headers),
+    )
+}
+
+/// Request sent once more, on a new connection, if the HTTP/2 peer went away before processing
+/// the first attempt.
+pub(crate) struct Retry<S> {
+    connector: Rc<S>,
+    head: RequestHeadType,
+    body: Body,
+    addr: Option<net::SocketAddr>,
+}
+
 pin_project_lite::pin_project! {
     #[project = ConnectRequestProj]
-    pub(crate) enum ConnectRequestFuture<Fut, Io> {
+    pub(crate) enum ConnectRequestFuture<Fut, Io, S> {
         Connection {
             #[pin]
             fut: Fut,
-            req: Option<ConnectRequest>
+            req: Option<ConnectRequest>,
+            retry: Option<Retry<S>>
         },
         Client {
-            fut: LocalBoxFuture<'static, Result<(ResponseHead, Payload), SendRequestError>>
+            fut: LocalBoxFuture<'static, Result<(ResponseHead, Payload), SendRequestError>>,
+            retry: Option<Retry<S>>
         },
         Tunnel {
             fut: LocalBoxFuture<
                 'static,
-                Result<(ResponseHead, Framed<Io, ClientCodec>), SendRequestError>,
+                Result<UpgradeResponse<Io>, SendRequestError>,
             >,
         }
     }
 }
 
-impl<Fut, C, Io> Future for ConnectRequestFuture<Fut, Io>
+impl<Fut, C, Io, S> Future for ConnectRequestFuture<Fut, Io, S>
 where
     Fut: Future<Output = Result<C, ConnectError>>,
     C: Connection<Io = Io>,
     Io: AsyncRead + AsyncWrite + Unpin + 'static,
+    S: Service<ClientConnect, Future = Fut>,
 {
     type Output = Result<ConnectResponse, SendRequestError>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.as_mut().project() {
-            ConnectRequestProj::Connection { fut, req } => {
+            ConnectRequestProj::Connection { fut, req, retry } => {
                 let connection = ready!(fut.poll(cx))?;
                 let req = req.take().unwrap();
                 match req {
@@ -137,30 +207,52 @@ where
                         // send request
                         let fut = ConnectRequestFuture::Client {
                             fut: connection.send_request(head, body),
+                            retry: retry.take(),
                         };
                         self.as_mut().set(fut);
                     }
                     ConnectRequest::Tunnel(head, ..) => {
                         // send request
                         let fut = ConnectRequestFuture::Tunnel {
-                            fut: connection.open_tunnel(RequestHeadType::from(head)),
+                            fut: connection.open_upgrade(RequestHeadType::from(head)),
                         };
                         self.as_mut().set(fut);
                     }
                 }
                 self.poll(cx)
             }
-            ConnectRequestProj::Client { fut } => {
-                let (head, payload) = ready!(fut.as_mut().poll(cx))?;
-                Poll::Ready(Ok(ConnectResponse::Client(ClientResponse::new(
-                    head, payload,
-                ))))
-            }
-            ConnectRequestProj::Tunnel { fut } => {
-                let (head, framed) = ready!(fut.as_mut().poll(cx))?;
-                let framed = framed.into_map_io(|io| BoxedSocket(Box::new(Socket(io))));
-                Poll::Ready(Ok(ConnectResponse::Tunnel(head, framed)))
-            }
+            ConnectRequestProj::Client { fut, retry } => match ready!(fut.as_mut().poll(cx)) {
+                Ok((head, payload)) => Poll::Ready(Ok(ConnectResponse::Client(
+                    ClientResponse::new(head, payload),
+                ))),
+                Err(SendRequestError::GoAwayUnprocessed) if retry.is_some() => {
+                    let Retry {
+                        connector,
+                        head,
+                        body,
+                        addr,
+                    } = retry.take().unwrap();
+
+                    let req = ConnectRequest::Client(head, body, addr);
+                    let fut = ConnectRequestFuture::Connection {
+                        fut: connect(&*connector, &req),
+                        req: Some(req),
+                        retry: None,
+                    };
+                    self.as_mut().set(fut);
+                    self.poll(cx)
+                }
+                Err(err) => Poll::Ready(Err(err)),
+            },
+            ConnectRequestProj::Tunnel { fut } => match ready!(fut.as_mut().poll(cx))? {
+                UpgradeResponse::Tunnel(head, framed) => {
+                    let framed = framed.into_map_io(|io| BoxedSocket(Box::new(Socket(io))));
+                    Poll::Ready(Ok(ConnectResponse::Tunnel(head, framed)))
+                }
+                UpgradeResponse::Refused(head, payload) => Poll::Ready(Ok(
+                    ConnectResponse::Client(ClientResponse::new(head, payload)),
+                )),
+            },
         }
     }
 }
@@ -220,3 +312,113 @@ impl AsyncWrite for BoxedSocket {
         Pin::new(self.get_mut().0.as_write()).poll_shutdown(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use actix_http::{
+        body::{BodySize, BodyStream, MessageBody},
+        http::StatusCode,
+    };
+    use actix_service::fn_service;
+    use bytes::Bytes;
+    use futures_util::{future::ready, stream};
+
+    use super::*;
+
+    /// Connection that fails with `GoAwayUnprocessed` while `going_away` is set, recording the
+    /// size of every body sent on it.
+    struct TestConnection {
+        going_away: bool,
+        sent: Rc<RefCell<Vec<BodySize>>>,
+    }
+
+    impl Connection for TestConnection {
+        type Io = BoxedSocket;
+
+        fn send_request<B, H>(
+            self,
+            _: H,
+            body: B,
+        ) -> LocalBoxFuture<'static, Result<(ResponseHead, Payload), SendRequestError>>
+        where
+            B: MessageBody + 'static,
+            H: Into<RequestHeadType> + 'static,
+        {
+            self.sent.borrow_mut().push(body.size());
+
+            Box::pin(ready(if self.going_away {
+                Err(SendRequestError::GoAwayUnprocessed)
+            } else {
+                Ok((ResponseHead::new(StatusCode::OK), Payload::None))
+            }))
+        }
+
+        fn open_tunnel<H: Into<RequestHeadType> + 'static>(
+            self,
+            _: H,
+        ) -> LocalBoxFuture<
+            'static,
+            Result<(ResponseHead, Framed<Self::Io, ClientCodec>), SendRequestError>,
+        > {
+            Box::pin(ready(Err(SendRequestError::TunnelNotSupported)))
+        }
+    }
+
+    async fn send(
+        body: Body,
+        going_away: usize,
+    ) -> (Result<(), SendRequestError>, Vec<BodySize>) {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+
+        let connector = DefaultConnector::new(fn_service({
+            let sent = Rc::clone(&sent);
+            let connects = Rc::new(RefCell::new(0));
+
+            move |_: ClientConnect| {
+                *connects.borrow_mut() += 1;
+
+                ready(Ok::<_, ConnectError>(TestConnection {
+                    going_away: *connects.borrow() <= going_away,
+                    sent: Rc::clone(&sent),
+                }))
+            }
+        }));
+
+        let mut head = RequestHead::default();
+        head.uri = "http://localhost/".parse().unwrap();
+
+        let res = connector
+            .call(ConnectRequest::Client(head.into(), body, None))
+            .await
+            .map(|res| {
+                assert_eq!(res.into_client_response().status(), StatusCode::OK);
+            });
+
+        let sent = sent.borrow().clone();
+        (res, sent)
+    }
+
+    #[actix_rt::test]
+    async fn test_retry_go_away_unprocessed() {
+        let (res, sent) = send(Body::Bytes(Bytes::from_static(b"replay")), 1).await;
+        assert!(res.is_ok());
+        assert_eq!(sent, vec![BodySize::Sized(6), BodySize::Sized(6)]);
+
+        // retried only once
+        let (res, sent) = send(Body::Empty, 2).await;
+        assert!(matches!(res, Err(SendRequestError::GoAwayUnprocessed)));
+        assert_eq!(sent, vec![BodySize::Empty, BodySize::Empty]);
+    }
+
+    #[actix_rt::test]
+    async fn test_streaming_body_not_retried() {
+        let chunk = Ok::<_, actix_http::Error>(Bytes::from_static(b"stream"));
+        let body = Body::Message(Box::new(BodyStream::new(stream::once(ready(chunk)))));
+
+        let (res, sent) = send(body, 1).await;
+        assert!(matches!(res, Err(SendRequestError::GoAwayUnprocessed)));
+        assert_eq!(sent, vec![BodySize::Stream]);
+    }
+}
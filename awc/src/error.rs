@@ -9,15 +9,19 @@ pub use actix_http::ws::ProtocolError as WsProtocolError;
 use actix_http::ResponseError;
 use serde_json::error::Error as JsonError;
 
-use actix_http::http::{header::HeaderValue, StatusCode};
+use actix_http::http::header::HeaderValue;
 use derive_more::{Display, From};
 
+use crate::response::ClientResponse;
+
 /// Websocket client error
 #[derive(Debug, Display, From)]
 pub enum WsClientError {
-    /// Invalid response status
-    #[display(fmt = "Invalid response status")]
-    InvalidResponseStatus(StatusCode),
+    /// Server responded with a status other than `101 Switching Protocols`.
+    ///
+    /// The response, including its body, can be read as with any other request.
+    #[display(fmt = "Invalid response status: {}", "_0.status()")]
+    InvalidResponse(ClientResponse),
 
     /// Invalid upgrade header
     #[display(fmt = "Invalid upgrade header")]
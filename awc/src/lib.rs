@@ -99,7 +99,10 @@ use std::time::Duration;
 
 #[cfg(feature = "cookies")]
 pub use actix_http::cookie;
-pub use actix_http::{client::Connector, http};
+pub use actix_http::{
+    client::{ConnectionLease, Connector, TlsHandshake},
+    http,
+};
 
 use actix_http::{
     client::{TcpConnect, TcpConnectError, TcpConnection},
@@ -0,0 +1,521 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use actix_http::{
+    body::Body,
+    client::SendRequestError,
+    http::{
+        header::{self, HeaderMap, HeaderValue, HttpDate},
+        Method, StatusCode, Version,
+    },
+    Payload, RequestHead, RequestHeadType, ResponseHead,
+};
+use actix_service::Service;
+use bytes::{Bytes, BytesMut};
+use futures_util::{
+    future::{ready, LocalBoxFuture},
+    stream::{self, StreamExt as _},
+};
+
+use super::Transform;
+
+use crate::{
+    connect::{ConnectRequest, ConnectResponse},
+    response::ClientResponse,
+};
+
+/// Middleware that keeps responses to `GET` requests in memory, keyed by URL.
+///
+/// A `200 OK` response is stored when its `Cache-Control` `max-age` or its `Expires` header give
+/// it a freshness lifetime, or when it has an `ETag` to revalidate it with. While fresh, requests
+/// for the same URL are answered from the cache without being sent. Once stale, the request is
+/// sent with `If-None-Match` and a `304 Not Modified` response is answered with the stored body.
+///
+/// Responses with `Cache-Control: no-store`, a `Vary` header, or bodies larger than the
+/// configured maximum are not stored. `Cache-Control: no-cache` responses are stored but
+/// revalidated on every request. Requests that carry their own `Cache-Control`, `Range` or
+/// conditional headers bypass the cache. Stored bodies are kept as received, so they are
+/// decompressed again whenever they are served.
+///
+/// # Examples
+/// ```
+/// use awc::middleware::ResponseCache;
+///
+/// let client = awc::ClientBuilder::new()
+///     .wrap(ResponseCache::new().capacity(256))
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    capacity: usize,
+    max_body_size: usize,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResponseCache {
+    /// Creates response cache holding up to 128 responses of at most 1MB each.
+    pub fn new() -> Self {
+        Self {
+            capacity: 128,
+            max_body_size: 1024 * 1024,
+        }
+    }
+
+    /// Sets maximum number of stored responses.
+    ///
+    /// When full, the response stored first is evicted.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Sets maximum size of stored response bodies.
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.max_body_size = size;
+        self
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for ResponseCache
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = ResponseCacheService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        ResponseCacheService {
+            connector: service,
+            store: Rc::new(RefCell::new(Store {
+                capacity: self.capacity,
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// Service created by the [`ResponseCache`] middleware.
+pub struct ResponseCacheService<S> {
+    connector: S,
+    store: Rc<RefCell<Store>>,
+    max_body_size: usize,
+}
+
+impl<S> Service<ConnectRequest> for ResponseCacheService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = ConnectResponse;
+    type Error = SendRequestError;
+    type Future = LocalBoxFuture<'static, Result<ConnectResponse, SendRequestError>>;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, mut req: ConnectRequest) -> Self::Future {
+        let key = match req {
+            ConnectRequest::Client(ref head, ref body, _)
+                if is_cacheable(head.as_ref(), body) =>
+            {
+                head.as_ref().uri.to_string()
+            }
+            _ => return Box::pin(self.connector.call(req)),
+        };
+
+        let etag = match self.store.borrow().entries.get(&key) {
+            Some(entry) if Instant::now() < entry.fresh_until => {
+                return Box::pin(ready(Ok(ConnectResponse::Client(entry.response()))));
+            }
+            Some(entry) => entry.etag.clone(),
+            None => None,
+        };
+
+        if let (Some(etag), ConnectRequest::Client(head, ..)) = (&etag, &mut req) {
+            set_header(head, header::IF_NONE_MATCH, etag.clone());
+        }
+
+        let fut = self.connector.call(req);
+        let store = Rc::clone(&self.store);
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let mut res = match fut.await? {
+                ConnectResponse::Client(res) => res,
+                res => return Ok(res),
+            };
+
+            if res.status() == StatusCode::NOT_MODIFIED && etag.is_some() {
+                let cached = store.borrow_mut().revalidate(&key, res.headers());
+
+                // the stored response may have been evicted in the meantime
+                return Ok(ConnectResponse::Client(cached.unwrap_or(res)));
+            }
+
+            let lifetime = match storable(&res, max_body_size) {
+                Some(lifetime) => lifetime,
+                None => return Ok(ConnectResponse::Client(res)),
+            };
+
+            let mut payload = res.payload.take();
+            let mut body = BytesMut::new();
+
+            while let Some(item) = payload.next().await {
+                match item {
+                    Ok(chunk) if body.len() + chunk.len() <= max_body_size => {
+                        body.extend_from_slice(&chunk)
+                    }
+
+                    // too large to store or failed; hand over what was read with the rest
+                    item => {
+                        let head = stream::iter(vec![Ok(body.freeze()), item]);
+                        res.payload = Payload::Stream(Box::pin(head.chain(payload)));
+                        return Ok(ConnectResponse::Client(res));
+                    }
+                }
+            }
+
+            let body = body.freeze();
+            res.payload = body_payload(body.clone());
+
+            store.borrow_mut().insert(
+                key,
+                Entry {
+                    status: res.status(),
+                    version: res.version(),
+                    headers: res.headers().clone(),
+                    etag: res.headers().get(header::ETAG).cloned(),
+                    body,
+                    fresh_until: Instant::now() + lifetime,
+                },
+            );
+
+            Ok(ConnectResponse::Client(res))
+        })
+    }
+}
+
+/// Stored responses, evicted in insertion order.
+struct Store {
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    order: VecDeque<String>,
+}
+
+impl Store {
+    fn insert(&mut self, key: String, entry: Entry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.remove(&key).is_some() {
+            self.order.retain(|k| *k != key);
+        }
+
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+
+        self.order.push_back(key.clone());
+        self.entries.insert(key, entry);
+    }
+
+    /// Refreshes a stored response with the headers of a `304 Not Modified` response.
+    fn revalidate(&mut self, key: &str, headers: &HeaderMap) -> Option<ClientResponse> {
+        let entry = self.entries.get_mut(key)?;
+
+        for name in headers.keys() {
+            if *name != header::CONTENT_LENGTH {
+                entry.headers.remove(name);
+            }
+        }
+
+        for (name, value) in headers.iter() {
+            if *name != header::CONTENT_LENGTH {
+                entry.headers.append(name.clone(), value.clone());
+            }
+        }
+
+        let res = entry.response();
+
+        match freshness_lifetime(&entry.headers) {
+            Some(lifetime) => entry.fresh_until = Instant::now() + lifetime,
+            None => {
+                self.entries.remove(key);
+                self.order.retain(|k| k != key);
+            }
+        }
+
+        Some(res)
+    }
+}
+
+struct Entry {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    etag: Option<HeaderValue>,
+    body: Bytes,
+    fresh_until: Instant,
+}
+
+impl Entry {
+    fn response(&self) -> ClientResponse {
+        let mut head = ResponseHead::new(self.status);
+        head.version = self.version;
+        head.headers = self.headers.clone();
+
+        ClientResponse::new(head, body_payload(self.body.clone()))
+    }
+}
+
+fn body_payload(body: Bytes) -> Payload {
+    Payload::Stream(Box::pin(stream::once(ready(Ok(body)))))
+}
+
+/// Sets header on an outgoing request head, replacing any existing value.
+fn set_header(head: &mut RequestHeadType, name: header::HeaderName, value: HeaderValue) {
+    match head {
+        RequestHeadType::Owned(head) => {
+            head.headers.insert(name, value);
+        }
+        RequestHeadType::Rc(_, extra) => {
+            extra.get_or_insert_with(HeaderMap::new).insert(name, value);
+        }
+    }
+}
+
+/// Whether a request may be answered from, and its response stored in, the cache.
+fn is_cacheable(head: &RequestHead, body: &Body) -> bool {
+    let bypass = [
+        header::CACHE_CONTROL,
+        header::RANGE,
+        header::IF_NONE_MATCH,
+        header::IF_MODIFIED_SINCE,
+        header::AUTHORIZATION,
+    ];
+
+    head.method == Method::GET
+        && matches!(body, Body::None | Body::Empty)
+        && !bypass.iter().any(|name| head.headers.contains_key(name))
+}
+
+/// Returns how long a response stays fresh if it can be stored.
+fn storable(res: &ClientResponse, max_body_size: usize) -> Option<Duration> {
+    let headers = res.headers();
+
+    if res.status() != StatusCode::OK || headers.contains_key(header::VARY) {
+        return None;
+    }
+
+    let too_large = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<usize>().ok())
+        .map_or(false, |len| len > max_body_size);
+
+    if too_large {
+        return None;
+    }
+
+    let lifetime = freshness_lifetime(headers)?;
+
+    // stale responses without a validator are of no use
+    if lifetime == Duration::from_secs(0) && !headers.contains_key(header::ETAG) {
+        return None;
+    }
+
+    Some(lifetime)
+}
+
+/// Returns freshness lifetime given by the response headers, or `None` if it must not be stored.
+fn freshness_lifetime(headers: &HeaderMap) -> Option<Duration> {
+    let mut max_age = None;
+    let mut no_cache = false;
+
+    for value in headers.get_all(header::CACHE_CONTROL) {
+        let value = value.to_str().unwrap_or_default();
+
+        for directive in value.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+
+            if directive == "no-store" {
+                return None;
+            } else if directive == "no-cache" {
+                no_cache = true;
+            } else if let Some(secs) = directive.strip_prefix("max-age=") {
+                max_age = secs.trim_matches('"').parse::<u64>().ok();
+            }
+        }
+    }
+
+    if no_cache {
+        return Some(Duration::from_secs(0));
+    }
+
+    let age = headers
+        .get(header::AGE)
+        .and_then(|age| age.to_str().ok())
+        .and_then(|age| age.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    if let Some(max_age) = max_age {
+        return Some(Duration::from_secs(max_age.saturating_sub(age)));
+    }
+
+    let date = |name| {
+        headers
+            .get(name)
+            .and_then(|val: &HeaderValue| val.to_str().ok())
+            .and_then(|val| val.parse::<HttpDate>().ok())
+            .map(SystemTime::from)
+    };
+
+    match date(header::EXPIRES) {
+        Some(expires) => {
+            let now = date(header::DATE).unwrap_or_else(SystemTime::now);
+            Some(expires.duration_since(now).unwrap_or_default())
+        }
+
+        // no explicit lifetime, or an invalid `Expires` which means already expired
+        None => Some(Duration::from_secs(0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use actix_web::{test::start, web, App, HttpRequest, HttpResponse};
+
+    use super::*;
+    use crate::ClientBuilder;
+
+    #[actix_rt::test]
+    async fn test_fresh_response_from_cache() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let srv_hits = Arc::clone(&hits);
+
+        let srv = start(move || {
+            let hits = Arc::clone(&srv_hits);
+
+            App::new().service(web::resource("/").to(move || {
+                let n = hits.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    HttpResponse::Ok()
+                        .insert_header((header::CACHE_CONTROL, "public, max-age=60"))
+                        .body(format!("response {}", n))
+                }
+            }))
+        });
+
+        let client = ClientBuilder::new().wrap(ResponseCache::new()).finish();
+
+        for _ in 0..2 {
+            let mut res = client.get(srv.url("/")).send().await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.body().await.unwrap(), "response 0");
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // requests with their own cache directives go to the server
+        let mut res = client
+            .get(srv.url("/"))
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.body().await.unwrap(), "response 1");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_revalidate_stale_response() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let srv_hits = Arc::clone(&hits);
+
+        let srv = start(move || {
+            let hits = Arc::clone(&srv_hits);
+
+            App::new().service(web::resource("/").to(move |req: HttpRequest| {
+                hits.fetch_add(1, Ordering::SeqCst);
+
+                let matches = req
+                    .headers()
+                    .get(header::IF_NONE_MATCH)
+                    .map_or(false, |etag| etag == "\"v1\"");
+
+                async move {
+                    if matches {
+                        HttpResponse::NotModified()
+                            .insert_header((header::ETAG, "\"v1\""))
+                            .finish()
+                    } else {
+                        HttpResponse::Ok()
+                            .insert_header((header::CACHE_CONTROL, "no-cache"))
+                            .insert_header((header::ETAG, "\"v1\""))
+                            .body("versioned")
+                    }
+                }
+            }))
+        });
+
+        let client = ClientBuilder::new().wrap(ResponseCache::new()).finish();
+
+        for _ in 0..2 {
+            let mut res = client.get(srv.url("/")).send().await.unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.body().await.unwrap(), "versioned");
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_freshness_lifetime() {
+        let lifetime = |pairs: &[(header::HeaderName, &'static str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.append(name.clone(), HeaderValue::from_static(value));
+            }
+            freshness_lifetime(&headers)
+        };
+
+        assert_eq!(
+            lifetime(&[(header::CACHE_CONTROL, "max-age=60")]),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            lifetime(&[(header::CACHE_CONTROL, "max-age=60"), (header::AGE, "20")]),
+            Some(Duration::from_secs(40))
+        );
+        assert_eq!(
+            lifetime(&[(header::CACHE_CONTROL, "private, no-store")]),
+            None
+        );
+        assert_eq!(
+            lifetime(&[
+                (header::DATE, "Sun, 06 Nov 1994 08:49:37 GMT"),
+                (header::EXPIRES, "Sun, 06 Nov 1994 08:59:37 GMT"),
+            ]),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(
+            lifetime(&[(header::EXPIRES, "0")]),
+            Some(Duration::from_secs(0))
+        );
+    }
+}
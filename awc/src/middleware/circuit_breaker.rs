@@ -0,0 +1,739 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_http::{client::SendRequestError, RequestHead};
+use actix_service::Service;
+use futures_core::ready;
+
+use super::Transform;
+
+use crate::connect::{ConnectRequest, ConnectResponse};
+
+/// State of the circuit breaker of an authority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests are sent and their outcomes recorded.
+    Closed,
+
+    /// Requests fail fast without being sent.
+    Open,
+
+    /// A limited number of probe requests are sent to decide whether to close the circuit.
+    HalfOpen,
+}
+
+/// Thresholds of a [`CircuitBreaker`].
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    failure_rate: f64,
+    window: Duration,
+    min_requests: usize,
+    open_duration: Duration,
+    half_open_probes: usize,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_rate: 0.5,
+            window: Duration::from_secs(10),
+            min_requests: 10,
+            open_duration: Duration::from_secs(30),
+            half_open_probes: 1,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Creates config with default thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets share of failed requests, between 0 and 1, that opens the circuit.
+    ///
+    /// By default, the circuit opens when half of the requests fail.
+    pub fn failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = rate;
+        self
+    }
+
+    /// Sets duration of the sliding window over which the failure rate is computed.
+    ///
+    /// By default, the window is 10 seconds.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets minimum number of requests in the window before the circuit may open.
+    ///
+    /// By default, at least 10 requests are required.
+    pub fn min_requests(mut self, min_requests: usize) -> Self {
+        self.min_requests = min_requests;
+        self
+    }
+
+    /// Sets how long the circuit stays open before probe requests are allowed.
+    ///
+    /// By default, the circuit stays open for 30 seconds.
+    pub fn open_duration(mut self, duration: Duration) -> Self {
+        self.open_duration = duration;
+        self
+    }
+
+    /// Sets number of successful probe requests that close a half-open circuit.
+    ///
+    /// At most this many probes are in flight at once. By default, one probe is sent.
+    pub fn half_open_probes(mut self, probes: usize) -> Self {
+        self.half_open_probes = probes;
+        self
+    }
+}
+
+/// Circuit states shared by any number of [`CircuitBreaker`]s, keyed by authority.
+///
+/// Clients whose circuit breakers use the same registry see the same circuit for an authority.
+#[derive(Clone, Default)]
+pub struct CircuitBreakerRegistry {
+    circuits: Rc<RefCell<HashMap<String, Circuit>>>,
+    periods: Rc<Cell<u64>>,
+}
+
+impl CircuitBreakerRegistry {
+    /// Creates empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns current state of the circuit of an authority.
+    pub fn state(&self, authority: &str) -> CircuitState {
+        self.circuits
+            .borrow()
+            .get(authority)
+            .map_or(CircuitState::Closed, Circuit::state)
+    }
+}
+
+impl fmt::Debug for CircuitBreakerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let circuits = self.circuits.borrow();
+        f.debug_map()
+            .entries(circuits.iter().map(|(k, v)| (k, v.state())))
+            .finish()
+    }
+}
+
+enum Circuit {
+    Closed {
+        outcomes: VecDeque<(Instant, bool)>,
+    },
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        /// Identifies this half-open period among all periods of the registry.
+        period: u64,
+        in_flight: usize,
+        successes: usize,
+    },
+}
+
+impl Circuit {
+    fn closed() -> Self {
+        Circuit::Closed {
+            outcomes: VecDeque::new(),
+        }
+    }
+
+    fn state(&self) -> CircuitState {
+        match self {
+            Circuit::Closed { .. } => CircuitState::Closed,
+            Circuit::Open { .. } => CircuitState::Open,
+            Circuit::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+type OnTransition = Rc<dyn Fn(&str, CircuitState, CircuitState)>;
+
+/// Middleware that stops sending requests to failing upstreams.
+///
+/// Outcomes of requests are recorded per authority. A request fails when sending it returns an
+/// error or its response has a `5xx` status. Once the share of failed requests in the sliding
+/// window reaches the configured rate, the circuit opens and requests to that authority fail
+/// with [`SendRequestError::CircuitOpen`] without acquiring a connection. After the open
+/// duration, probe requests are let through; the circuit closes when enough of them succeed and
+/// opens again as soon as one fails.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use awc::middleware::{CircuitBreaker, CircuitBreakerConfig};
+///
+/// let client = awc::ClientBuilder::new()
+///     .wrap(
+///         CircuitBreaker::new()
+///             .authority(
+///                 "flaky.example.com",
+///                 CircuitBreakerConfig::new().open_duration(Duration::from_secs(5)),
+///             )
+///             .on_transition(|authority, from, to| {
+///                 log::warn!("circuit of {} went from {:?} to {:?}", authority, from, to);
+///             }),
+///     )
+///     .finish();
+/// ```
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    authorities: HashMap<String, CircuitBreakerConfig>,
+    registry: CircuitBreakerRegistry,
+    on_transition: Option<OnTransition>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreaker {
+    /// Creates circuit breaker with default thresholds and its own registry.
+    pub fn new() -> Self {
+        Self {
+            config: CircuitBreakerConfig::default(),
+            authorities: HashMap::new(),
+            registry: CircuitBreakerRegistry::new(),
+            on_transition: None,
+        }
+    }
+
+    /// Sets thresholds used for authorities without their own config.
+    pub fn config(mut self, config: CircuitBreakerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets thresholds for a single authority, e.g. `example.com` or `example.com:8080`.
+    pub fn authority(
+        mut self,
+        authority: impl Into<String>,
+        config: CircuitBreakerConfig,
+    ) -> Self {
+        self.authorities.insert(authority.into(), config);
+        self
+    }
+
+    /// Sets registry holding the circuit states, to share them with other clients.
+    pub fn registry(mut self, registry: CircuitBreakerRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Sets callback invoked with the authority, old state and new state on every transition.
+    pub fn on_transition<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, CircuitState, CircuitState) + 'static,
+    {
+        self.on_transition = Some(Rc::new(f));
+        self
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for CircuitBreaker
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = CircuitBreakerService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        CircuitBreakerService {
+            connector: service,
+            inner: Rc::new(Inner {
+                config: self.config,
+                authorities: self.authorities,
+                registry: self.registry,
+                on_transition: self.on_transition,
+            }),
+        }
+    }
+}
+
+struct Inner {
+    config: CircuitBreakerConfig,
+    authorities: HashMap<String, CircuitBreakerConfig>,
+    registry: CircuitBreakerRegistry,
+    on_transition: Option<OnTransition>,
+}
+
+impl Inner {
+    fn config(&self, authority: &str) -> &CircuitBreakerConfig {
+        self.authorities.get(authority).unwrap_or(&self.config)
+    }
+
+    /// Returns half-open period the request is a probe of, if any, or how long to wait if the
+    /// circuit is open.
+    fn acquire(&self, authority: &str) -> Result<Option<u64>, Duration> {
+        let config = self.config(authority);
+        let now = Instant::now();
+
+        let (res, transition) = {
+            let mut circuits = self.registry.circuits.borrow_mut();
+            let circuit = circuits
+                .entry(authority.to_owned())
+                .or_insert_with(Circuit::closed);
+
+            match circuit {
+                Circuit::Closed { .. } => (Ok(None), None),
+                Circuit::Open { until } if now < *until => (Err(*until - now), None),
+                Circuit::Open { .. } => {
+                    let period = self.registry.periods.get().wrapping_add(1);
+                    self.registry.periods.set(period);
+
+                    *circuit = Circuit::HalfOpen {
+                        period,
+                        in_flight: 1,
+                        successes: 0,
+                    };
+                    (Ok(Some(period)), Some(CircuitState::Open))
+                }
+                Circuit::HalfOpen {
+                    period, in_flight, ..
+                } => {
+                    if *in_flight < config.half_open_probes {
+                        *in_flight += 1;
+                        (Ok(Some(*period)), None)
+                    } else {
+                        // wait for in-flight probes to decide
+                        (Err(Duration::from_secs(0)), None)
+                    }
+                }
+            }
+        };
+
+        if let Some(from) = transition {
+            self.notify(authority, from, CircuitState::HalfOpen);
+        }
+
+        res
+    }
+
+    fn record(&self, authority: &str, probe: Option<u64>, success: bool) {
+        let config = self.config(authority);
+        let now = Instant::now();
+
+        let transition = {
+            let mut circuits = self.registry.circuits.borrow_mut();
+            let circuit = match circuits.get_mut(authority) {
+                Some(circuit) => circuit,
+                None => return,
+            };
+
+            match circuit {
+                Circuit::HalfOpen {
+                    period,
+                    in_flight,
+                    successes,
+                } if probe == Some(*period) => {
+                    *in_flight -= 1;
+
+                    if !success {
+                        *circuit = Circuit::Open {
+                            until: now + config.open_duration,
+                        };
+                        Some((CircuitState::HalfOpen, CircuitState::Open))
+                    } else {
+                        *successes += 1;
+
+                        if *successes >= config.half_open_probes {
+                            *circuit = Circuit::closed();
+                            Some((CircuitState::HalfOpen, CircuitState::Closed))
+                        } else {
+                            None
+                        }
+                    }
+                }
+
+                Circuit::Closed { outcomes } if probe.is_none() => {
+                    outcomes.push_back((now, success));
+
+                    while let Some((at, _)) = outcomes.front() {
+                        if now.duration_since(*at) > config.window {
+                            outcomes.pop_front();
+                        } else {
+                            break;
+                        }
+                    }
+
+                    let total = outcomes.len();
+                    let failures = outcomes.iter().filter(|(_, success)| !success).count();
+
+                    if total >= config.min_requests
+                        && failures as f64 >= config.failure_rate * total as f64
+                    {
+                        *circuit = Circuit::Open {
+                            until: now + config.open_duration,
+                        };
+                        Some((CircuitState::Closed, CircuitState::Open))
+                    } else {
+                        None
+                    }
+                }
+
+                // outcome of a request sent before the circuit changed state, including probes of
+                // an earlier half-open period
+                _ => None,
+            }
+        };
+
+        if let Some((from, to)) = transition {
+            self.notify(authority, from, to);
+        }
+    }
+
+    /// Frees the slot of a probe request that was dropped before completing.
+    ///
+    /// Probes of an earlier half-open period hold no slot of the current one.
+    fn release(&self, authority: &str, probe: u64) {
+        let mut circuits = self.registry.circuits.borrow_mut();
+
+        if let Some(Circuit::HalfOpen {
+            period, in_flight, ..
+        }) = circuits.get_mut(authority)
+        {
+            if *period == probe {
+                *in_flight -= 1;
+            }
+        }
+    }
+
+    fn notify(&self, authority: &str, from: CircuitState, to: CircuitState) {
+        if let Some(ref on_transition) = self.on_transition {
+            on_transition(authority, from, to);
+        }
+    }
+}
+
+pub struct CircuitBreakerService<S> {
+    connector: S,
+    inner: Rc<Inner>,
+}
+
+impl<S> Service<ConnectRequest> for CircuitBreakerService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = CircuitBreakerFuture<S::Future>;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let authority = match req {
+            ConnectRequest::Client(ref head, ..) => request_authority(head.as_ref()),
+            ConnectRequest::Tunnel(..) => None,
+        };
+
+        let outcome = match authority {
+            Some(authority) => match self.inner.acquire(&authority) {
+                Ok(probe) => Some(Outcome {
+                    inner: Rc::clone(&self.inner),
+                    authority,
+                    probe,
+                }),
+                Err(retry_after) => {
+                    return CircuitBreakerFuture {
+                        fut: None,
+                        outcome: None,
+                        err: Some(SendRequestError::CircuitOpen {
+                            authority,
+                            retry_after,
+                        }),
+                    }
+                }
+            },
+            None => None,
+        };
+
+        CircuitBreakerFuture {
+            fut: Some(self.connector.call(req)),
+            outcome,
+            err: None,
+        }
+    }
+}
+
+fn request_authority(head: &RequestHead) -> Option<String> {
+    head.uri
+        .authority()
+        .map(|authority| authority.as_str().to_owned())
+}
+
+/// Pending outcome of a request let through by the circuit breaker.
+struct Outcome {
+    inner: Rc<Inner>,
+    authority: String,
+    /// Half-open period the request is a probe of.
+    probe: Option<u64>,
+}
+
+impl Outcome {
+    fn finish(mut self, success: bool) {
+        let probe = self.probe.take();
+        self.inner.record(&self.authority, probe, success);
+    }
+}
+
+impl Drop for Outcome {
+    fn drop(&mut self) {
+        if let Some(probe) = self.probe {
+            self.inner.release(&self.authority, probe);
+        }
+    }
+}
+
+pin_project_lite::pin_project! {
+    pub struct CircuitBreakerFuture<Fut> {
+        #[pin]
+        fut: Option<Fut>,
+        outcome: Option<Outcome>,
+        err: Option<SendRequestError>,
+    }
+}
+
+impl<Fut> Future for CircuitBreakerFuture<Fut>
+where
+    Fut: Future<Output = Result<ConnectResponse, SendRequestError>>,
+{
+    type Output = Fut::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        let fut = match this.fut.as_pin_mut() {
+            Some(fut) => fut,
+            None => return Poll::Ready(Err(this.err.take().unwrap())),
+        };
+
+        let res = ready!(fut.poll(cx));
+
+        if let Some(outcome) = this.outcome.take() {
+            let success = match res {
+                Ok(ConnectResponse::Client(ref res)) => !res.status().is_server_error(),
+                Ok(ConnectResponse::Tunnel(..)) => true,
+                Err(_) => false,
+            };
+
+            outcome.finish(success);
+        }
+
+        Poll::Ready(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_http::{body::Body, RequestHeadType};
+    use actix_service::fn_service;
+    use futures_util::future::ready;
+
+    use super::*;
+    use crate::test::TestResponse;
+
+    fn request() -> ConnectRequest {
+        let mut head = RequestHead::default();
+        head.uri = "http://upstream.local/".parse().unwrap();
+        ConnectRequest::Client(RequestHeadType::Owned(head), Body::None, None)
+    }
+
+    #[actix_rt::test]
+    async fn test_open_fast_fail_and_close() {
+        let failing = Rc::new(Cell::new(true));
+        let calls = Rc::new(Cell::new(0));
+        let transitions = Rc::new(RefCell::new(Vec::new()));
+        let registry = CircuitBreakerRegistry::new();
+
+        let stub = {
+            let failing = Rc::clone(&failing);
+            let calls = Rc::clone(&calls);
+            fn_service(move |_: ConnectRequest| {
+                calls.set(calls.get() + 1);
+
+                ready(if failing.get() {
+                    Err(SendRequestError::Timeout)
+                } else {
+                    Ok(ConnectResponse::Client(TestResponse::default().finish()))
+                })
+            })
+        };
+
+        let t = Rc::clone(&transitions);
+        let srv = CircuitBreaker::new()
+            .config(
+                CircuitBreakerConfig::new()
+                    .min_requests(3)
+                    .failure_rate(0.5)
+                    .open_duration(Duration::from_millis(50))
+                    .half_open_probes(2),
+            )
+            .registry(registry.clone())
+            .on_transition(move |authority, from, to| {
+                assert_eq!(authority, "upstream.local");
+                t.borrow_mut().push((from, to));
+            })
+            .new_transform(stub);
+
+        for _ in 0..3 {
+            assert!(srv.call(request()).await.is_err());
+        }
+        assert_eq!(registry.state("upstream.local"), CircuitState::Open);
+        assert_eq!(calls.get(), 3);
+
+        // open circuit fails fast without calling the connector
+        match srv.call(request()).await {
+            Err(SendRequestError::CircuitOpen {
+                authority,
+                retry_after,
+            }) => {
+                assert_eq!(authority, "upstream.local");
+                assert!(retry_after <= Duration::from_millis(50));
+            }
+            _ => panic!("expected open circuit"),
+        }
+        assert_eq!(calls.get(), 3);
+
+        actix_rt::time::sleep(Duration::from_millis(60)).await;
+        failing.set(false);
+
+        assert!(srv.call(request()).await.is_ok());
+        assert_eq!(registry.state("upstream.local"), CircuitState::HalfOpen);
+        assert!(srv.call(request()).await.is_ok());
+        assert_eq!(registry.state("upstream.local"), CircuitState::Closed);
+        assert_eq!(calls.get(), 5);
+
+        assert_eq!(
+            *transitions.borrow(),
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_failed_probe_reopens() {
+        let registry = CircuitBreakerRegistry::new();
+        let srv = CircuitBreaker::new()
+            .authority(
+                "upstream.local",
+                CircuitBreakerConfig::new()
+                    .min_requests(1)
+                    .open_duration(Duration::from_millis(20)),
+            )
+            .registry(registry.clone())
+            .new_transform(fn_service(|_: ConnectRequest| {
+                ready(Err::<ConnectResponse, _>(SendRequestError::Timeout))
+            }));
+
+        assert!(srv.call(request()).await.is_err());
+        assert_eq!(registry.state("upstream.local"), CircuitState::Open);
+
+        actix_rt::time::sleep(Duration::from_millis(30)).await;
+
+        assert!(matches!(
+            srv.call(request()).await,
+            Err(SendRequestError::Timeout)
+        ));
+        assert_eq!(registry.state("upstream.local"), CircuitState::Open);
+    }
+
+    #[actix_rt::test]
+    async fn test_stale_probe_outcome_ignored() {
+        // outcomes of the calls, in call order
+        let outcomes = Rc::new(RefCell::new(VecDeque::from(vec![false, true, true])));
+        let registry = CircuitBreakerRegistry::new();
+
+        let stub = {
+            let outcomes = Rc::clone(&outcomes);
+            fn_service(move |_: ConnectRequest| {
+                ready(if outcomes.borrow_mut().pop_front().unwrap() {
+                    Ok(ConnectResponse::Client(TestResponse::default().finish()))
+                } else {
+                    Err(SendRequestError::Timeout)
+                })
+            })
+        };
+
+        let srv = CircuitBreaker::new()
+            .config(
+                CircuitBreakerConfig::new()
+                    .open_duration(Duration::from_millis(20))
+                    .half_open_probes(2),
+            )
+            .registry(registry.clone())
+            .new_transform(stub);
+
+        let half_open = |registry: &CircuitBreakerRegistry| match registry
+            .circuits
+            .borrow()
+            .get("upstream.local")
+        {
+            Some(Circuit::HalfOpen {
+                period,
+                in_flight,
+                successes,
+            }) => (*period, *in_flight, *successes),
+            _ => panic!("expected half-open circuit"),
+        };
+
+        // circuit whose open duration has just ended
+        registry.circuits.borrow_mut().insert(
+            "upstream.local".to_owned(),
+            Circuit::Open {
+                until: Instant::now(),
+            },
+        );
+
+        // probes A and B of the first half-open period
+        let a = srv.call(request());
+        let b = srv.call(request());
+        let (first, in_flight, _) = half_open(&registry);
+        assert_eq!(in_flight, 2);
+
+        // A fails and opens the circuit
+        assert!(a.await.is_err());
+        assert_eq!(registry.state("upstream.local"), CircuitState::Open);
+
+        // probe C of the next half-open period
+        actix_rt::time::sleep(Duration::from_millis(30)).await;
+        let c = srv.call(request());
+        let (second, in_flight, _) = half_open(&registry);
+        assert_ne!(first, second);
+        assert_eq!(in_flight, 1);
+
+        // B completes but belongs to the first period
+        assert!(b.await.is_ok());
+        assert_eq!(half_open(&registry), (second, 1, 0));
+
+        // a dropped probe of the first period holds no slot either
+        drop(Outcome {
+            inner: Rc::clone(&srv.inner),
+            authority: "upstream.local".to_owned(),
+            probe: Some(first),
+        });
+        assert_eq!(half_open(&registry), (second, 1, 0));
+
+        assert!(c.await.is_ok());
+        assert_eq!(half_open(&registry), (second, 0, 1));
+    }
+}
@@ -1,5 +1,14 @@
+mod cache;
+mod circuit_breaker;
+mod propagate;
 mod redirect;
 
+pub use self::cache::{ResponseCache, ResponseCacheService};
+pub use self::circuit_breaker::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerFuture, CircuitBreakerRegistry,
+    CircuitBreakerService, CircuitState,
+};
+pub use self::propagate::{Propagate, PropagateService};
 pub use self::redirect::Redirect;
 
 use std::marker::PhantomData;
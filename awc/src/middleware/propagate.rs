@@ -0,0 +1,172 @@
+use std::future::Future;
+
+use actix_http::{http::HeaderMap, RequestHeadType};
+use actix_service::Service;
+
+use super::Transform;
+
+use crate::connect::ConnectRequest;
+
+tokio::task_local! {
+    static PROPAGATED: HeaderMap;
+}
+
+/// Client middleware that copies headers of the request being served onto outgoing requests.
+///
+/// The headers to propagate are set for the duration of a future with [`Propagate::scope`],
+/// usually by a server middleware wrapping handlers. Requests sent from within that future get
+/// every header in scope that they do not set themselves. Requests sent from elsewhere are not
+/// changed.
+///
+/// The headers are stored in a task-local, so they are not available to closures run on another
+/// thread, such as with `web::block`, nor to tasks spawned from the handler. Pass the values along
+/// explicitly and use [`Propagate::scope`] again in those cases.
+///
+/// # Examples
+/// ```
+/// use awc::{http::HeaderMap, middleware::Propagate, Client};
+///
+/// # async fn run(inbound: HeaderMap) {
+/// let client = Client::builder().wrap(Propagate::new()).finish();
+///
+/// Propagate::scope(inbound, async {
+///     // carries the `inbound` headers
+///     let res = client.get("http://localhost:8080/").send().await;
+/// })
+/// .await;
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Propagate;
+
+impl Propagate {
+    /// Creates propagation middleware.
+    pub fn new() -> Self {
+        Propagate
+    }
+
+    /// Runs `fut` with `headers` propagated to requests sent by clients using this middleware.
+    ///
+    /// Scopes can be nested; the innermost one applies.
+    pub async fn scope<F: Future>(headers: HeaderMap, fut: F) -> F::Output {
+        PROPAGATED.scope(headers, fut).await
+    }
+
+    /// Returns a copy of the headers propagated from the current scope, if any.
+    pub fn current() -> Option<HeaderMap> {
+        PROPAGATED.try_with(HeaderMap::clone).ok()
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for Propagate
+where
+    S: Service<ConnectRequest>,
+{
+    type Transform = PropagateService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        PropagateService { connector: service }
+    }
+}
+
+/// Service created by the [`Propagate`] middleware.
+pub struct PropagateService<S> {
+    connector: S,
+}
+
+impl<S> Service<ConnectRequest> for PropagateService<S>
+where
+    S: Service<ConnectRequest>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, mut req: ConnectRequest) -> Self::Future {
+        let _ = PROPAGATED.try_with(|propagated| match req {
+            ConnectRequest::Client(ref mut head, ..) => inject(head, propagated),
+            ConnectRequest::Tunnel(ref mut head, ..) => {
+                for (name, value) in propagated.iter() {
+                    if !head.headers.contains_key(name) {
+                        head.headers.append(name.clone(), value.clone());
+                    }
+                }
+            }
+        });
+
+        self.connector.call(req)
+    }
+}
+
+/// Adds propagated headers that are not set on the request.
+fn inject(head: &mut RequestHeadType, propagated: &HeaderMap) {
+    match head {
+        RequestHeadType::Owned(head) => {
+            for (name, value) in propagated.iter() {
+                if !head.headers.contains_key(name) {
+                    head.headers.append(name.clone(), value.clone());
+                }
+            }
+        }
+
+        RequestHeadType::Rc(head, extra) => {
+            let extra = extra.get_or_insert_with(HeaderMap::new);
+
+            for (name, value) in propagated.iter() {
+                if !head.headers.contains_key(name) && !extra.contains_key(name) {
+                    extra.append(name.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test::start, web, App, HttpRequest};
+
+    use super::*;
+    use crate::{http::header::HeaderValue, ClientBuilder};
+
+    #[actix_rt::test]
+    async fn test_propagate() {
+        let srv = start(|| {
+            App::new().service(web::resource("/").to(|req: HttpRequest| async move {
+                let header = |name| {
+                    req.headers()
+                        .get(name)
+                        .map(|val| val.to_str().unwrap().to_owned())
+                        .unwrap_or_default()
+                };
+                format!("{} {}", header("x-request-id"), header("x-tenant"))
+            }))
+        });
+
+        let client = ClientBuilder::new().wrap(Propagate::new()).finish();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-request-id".parse().unwrap(),
+            HeaderValue::from_static("abc"),
+        );
+        headers.insert("x-tenant".parse().unwrap(), HeaderValue::from_static("t1"));
+
+        let body = Propagate::scope(headers, async {
+            let mut res = client
+                .get(srv.url("/"))
+                .insert_header(("x-tenant", "t2"))
+                .send()
+                .await
+                .unwrap();
+            res.body().await.unwrap()
+        })
+        .await;
+        assert_eq!(body, "abc t2");
+
+        // outside of a scope
+        let mut res = client.get(srv.url("/")).send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), " ");
+    }
+}
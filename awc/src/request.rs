@@ -14,11 +14,11 @@ use actix_http::http::header::{self, IntoHeaderPair};
 use actix_http::http::{
     uri, ConnectionType, Error as HttpError, HeaderMap, HeaderValue, Method, Uri, Version,
 };
-use actix_http::{Error, RequestHead};
+use actix_http::{client::ConnectionLease, Error, RequestHead};
 
 use crate::error::{FreezeRequestError, InvalidUrl};
 use crate::frozen::FrozenClientRequest;
-use crate::sender::{PrepForSendingError, RequestSender, SendClientRequest};
+use crate::sender::{ContentDigest, PrepForSendingError, RequestSender, SendClientRequest};
 use crate::ClientConfig;
 
 cfg_if::cfg_if! {
@@ -107,11 +107,43 @@ impl ClientRequest {
     ///
     /// This address is used for connection. If address is not
     /// provided url's host name get resolved.
+    ///
+    /// The `Host` header and TLS server name are still taken from the url, and pooled
+    /// connections to this address are not shared with requests that resolve the host name.
     pub fn address(mut self, addr: net::SocketAddr) -> Self {
         self.addr = Some(addr);
         self
     }
 
+    /// Send this request on a connection reserved by `lease`.
+    ///
+    /// Requests sent with the same lease one after another, each after the previous response body
+    /// has been read, reuse one connection instead of taking whichever pooled connection is idle.
+    /// This allows protocols that depend on request ordering within a connection. See
+    /// [`ConnectionLease`] for details.
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = awc::Client::new();
+    /// let lease = awc::ConnectionLease::new();
+    ///
+    /// let mut res = client.post("http://localhost:8080/begin").lease(&lease).send().await?;
+    /// res.body().await?;
+    ///
+    /// // sent on the same connection as the first request
+    /// let mut res = client.post("http://localhost:8080/commit").lease(&lease).send().await?;
+    /// res.body().await?;
+    ///
+    /// // hands the connection back to the pool
+    /// drop(lease);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lease(self, lease: &ConnectionLease) -> Self {
+        self.head.extensions_mut().insert(lease.clone());
+        self
+    }
+
     /// Set HTTP method of this request.
     #[inline]
     pub fn method(mut self, method: Method) -> Self {
@@ -312,6 +344,18 @@ impl ClientRequest {
         self
     }
 
+    /// Send SHA-256 digest of the body in the `Content-Digest` header.
+    ///
+    /// The digest has to be known before the body is sent, so only bodies held in memory can be
+    /// digested; sending a streamed body fails with [`SendRequestError::Body`] without making the
+    /// request. An explicitly set `Content-Digest` header is left unchanged.
+    ///
+    /// [`SendRequestError::Body`]: crate::error::SendRequestError::Body
+    pub fn content_digest(self) -> Self {
+        self.head.extensions_mut().insert(ContentDigest);
+        self
+    }
+
     /// Set request timeout. Overrides client wide timeout setting.
     ///
     /// Request timeout is the total time before a response must be received.
@@ -1,6 +1,6 @@
 use std::{
     future::Future,
-    net,
+    io, net,
     pin::Pin,
     rc::Rc,
     task::{Context, Poll},
@@ -20,6 +20,7 @@ use bytes::Bytes;
 use derive_more::From;
 use futures_core::Stream;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 
 #[cfg(feature = "compress")]
 use actix_http::{encoding::Decoder, http::header::ContentEncoding, Payload, PayloadStream};
@@ -165,6 +166,9 @@ impl From<PrepForSendingError> for SendClientRequest {
     }
 }
 
+/// Marks a request head whose body digest is sent in the `Content-Digest` header.
+pub(crate) struct ContentDigest;
+
 #[derive(Debug)]
 pub(crate) enum RequestSender {
     Owned(RequestHead),
@@ -173,7 +177,7 @@ pub(crate) enum RequestSender {
 
 impl RequestSender {
     pub(crate) fn send_body<B>(
-        self,
+        mut self,
         addr: Option<net::SocketAddr>,
         response_decompress: bool,
         timeout: Option<Duration>,
@@ -183,20 +187,46 @@ impl RequestSender {
     where
         B: Into<Body>,
     {
-        let req = match self {
-            RequestSender::Owned(head) => {
-                ConnectRequest::Client(RequestHeadType::Owned(head), body.into(), addr)
+        let body = body.into();
+        let timeout = timeout.or(config.timeout);
+
+        if self.head().extensions().contains::<ContentDigest>() {
+            let digest = match body {
+                Body::None => None,
+                Body::Empty => Some(Sha256::digest(b"")),
+                Body::Bytes(ref bytes) => Some(Sha256::digest(bytes)),
+                Body::Message(_) => {
+                    // digest has to be known before the head is sent
+                    let err = io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Content-Digest can not be sent with a streamed body",
+                    );
+                    return SendRequestError::Body(err.into()).into();
+                }
+            };
+
+            if let Some(digest) = digest {
+                let name = HeaderName::from_static(CONTENT_DIGEST);
+                if let Err(e) = self.set_header_if_none(name, content_digest(&digest)) {
+                    return e.into();
+                }
             }
-            RequestSender::Rc(head, extra_headers) => ConnectRequest::Client(
-                RequestHeadType::Rc(head, extra_headers),
-                body.into(),
-                addr,
-            ),
-        };
+        }
 
-        let fut = config.connector.call(req);
+        let fut = config.connector.call(self.into_connect_request(body, addr));
 
-        SendClientRequest::new(fut, response_decompress, timeout.or(config.timeout))
+        SendClientRequest::new(fut, response_decompress, timeout)
+    }
+
+    fn into_connect_request(self, body: Body, addr: Option<net::SocketAddr>) -> ConnectRequest {
+        match self {
+            RequestSender::Owned(head) => {
+                ConnectRequest::Client(RequestHeadType::Owned(head), body, addr)
+            }
+            RequestSender::Rc(head, extra_headers) => {
+                ConnectRequest::Client(RequestHeadType::Rc(head, extra_headers), body, addr)
+            }
+        }
     }
 
     pub(crate) fn send_json<T: Serialize>(
@@ -285,6 +315,13 @@ impl RequestSender {
         self.send_body(addr, response_decompress, timeout, config, Body::Empty)
     }
 
+    fn head(&self) -> &RequestHead {
+        match self {
+            RequestSender::Owned(head) => head,
+            RequestSender::Rc(head, _) => head,
+        }
+    }
+
     fn set_header_if_none<V>(&mut self, key: HeaderName, value: V) -> Result<(), HttpError>
     where
         V: IntoHeaderValue,
@@ -318,3 +355,9 @@ impl RequestSender {
         Ok(())
     }
 }
+
+const CONTENT_DIGEST: &str = "content-digest";
+
+fn content_digest(digest: &[u8]) -> String {
+    format!("sha-256=:{}:", base64::encode(digest))
+}
@@ -34,13 +34,15 @@ use std::{fmt, str};
 use actix_codec::Framed;
 #[cfg(feature = "cookies")]
 use actix_http::cookie::{Cookie, CookieJar};
+#[cfg(feature = "compress")]
+use actix_http::encoding::Decoder;
 use actix_http::{ws, Payload, RequestHead};
 use actix_rt::time::timeout;
 use actix_service::Service;
 
 pub use actix_http::ws::{CloseCode, CloseReason, Codec, Frame, Message};
 
-use crate::connect::{BoxedSocket, ConnectRequest};
+use crate::connect::{BoxedSocket, ConnectRequest, ConnectResponse};
 use crate::error::{InvalidUrl, SendRequestError, WsClientError};
 use crate::http::header::{self, HeaderName, HeaderValue, IntoHeaderValue, AUTHORIZATION};
 use crate::http::{ConnectionType, Error as HttpError, Method, StatusCode, Uri, Version};
@@ -341,23 +343,31 @@ impl WebsocketsRequest {
             fut.await?
         };
 
-        let (head, framed) = res.into_tunnel_response();
+        // a refused upgrade (e.g. `401 Unauthorized`) is handed back as a regular response so
+        // its body can be read and the request retried
+        let (head, framed) = match res {
+            ConnectResponse::Tunnel(head, framed) => (head, framed),
+            ConnectResponse::Client(res) => {
+                log::trace!("Upgrade refused with status: {}", res.status());
+                return Err(WsClientError::InvalidResponse(refused_response(res)));
+            }
+        };
 
         // verify response
         if head.status != StatusCode::SWITCHING_PROTOCOLS {
-            return Err(WsClientError::InvalidResponseStatus(head.status));
+            log::trace!("Upgrade refused with status: {}", head.status);
+            return Err(WsClientError::InvalidResponse(ClientResponse::new(
+                head,
+                Payload::None,
+            )));
         }
 
         // check for "UPGRADE" to WebSocket header
-        let has_hdr = if let Some(hdr) = head.headers.get(&header::UPGRADE) {
-            if let Ok(s) = hdr.to_str() {
-                s.to_ascii_lowercase().contains("websocket")
-            } else {
-                false
-            }
-        } else {
-            false
-        };
+        // servers may send more than one Upgrade/Connection header; any of them may match
+        let has_hdr = head
+            .headers
+            .get_all(&header::UPGRADE)
+            .any(|hdr| header_contains(hdr, "websocket"));
         if !has_hdr {
             log::trace!("Invalid upgrade header");
             return Err(WsClientError::InvalidUpgradeHeader);
@@ -365,12 +375,12 @@ impl WebsocketsRequest {
 
         // Check for "CONNECTION" header
         if let Some(conn) = head.headers.get(&header::CONNECTION) {
-            if let Ok(s) = conn.to_str() {
-                if !s.to_ascii_lowercase().contains("upgrade") {
-                    log::trace!("Invalid connection header: {}", s);
-                    return Err(WsClientError::InvalidConnectionHeader(conn.clone()));
-                }
-            } else {
+            let has_upgrade = head
+                .headers
+                .get_all(&header::CONNECTION)
+                .any(|hdr| header_contains(hdr, "upgrade"));
+
+            if !has_upgrade {
                 log::trace!("Invalid connection header: {:?}", conn);
                 return Err(WsClientError::InvalidConnectionHeader(conn.clone()));
             }
@@ -413,6 +423,26 @@ impl WebsocketsRequest {
     }
 }
 
+/// Checks if header value contains lowercase `token`, ignoring ASCII case.
+fn header_contains(hdr: &HeaderValue, token: &str) -> bool {
+    match hdr.to_str() {
+        Ok(val) => val.to_ascii_lowercase().contains(token),
+        Err(_) => false,
+    }
+}
+
+/// Prepares the response to a refused upgrade for reading its body.
+///
+/// The connection is released to the connection pool once the body has been read.
+fn refused_response(res: ClientResponse) -> ClientResponse {
+    #[cfg(feature = "compress")]
+    let res = res.map_body(|head, payload| {
+        Payload::Stream(Box::pin(Decoder::from_headers(payload, &head.headers)))
+    });
+
+    res
+}
+
 impl fmt::Debug for WebsocketsRequest {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(
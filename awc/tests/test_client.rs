@@ -110,6 +110,45 @@ async fn test_form() {
     assert!(response.status().is_success());
 }
 
+#[actix_rt::test]
+async fn test_content_digest() {
+    use sha2::{Digest, Sha256};
+
+    let srv = test::start(|| {
+        App::new().service(web::resource("/").route(web::to(
+            |req: HttpRequest, body: Bytes| {
+                let expected = format!("sha-256=:{}:", base64::encode(Sha256::digest(&body)));
+
+                match req.headers().get("content-digest") {
+                    Some(digest) if digest == expected.as_str() => HttpResponse::Ok(),
+                    _ => HttpResponse::BadRequest(),
+                }
+            },
+        )))
+    });
+
+    let response = srv.post("/").content_digest().send_body(STR).await.unwrap();
+    assert!(response.status().is_success());
+
+    let body = stream::iter(
+        STR.as_bytes()
+            .chunks(64)
+            .map(|chunk| Ok::<_, Error>(Bytes::copy_from_slice(chunk))),
+    );
+    // digest of a streamed body is not known up front
+    let err = srv
+        .post("/")
+        .content_digest()
+        .send_stream(body)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SendRequestError::Body(_)));
+
+    // without opting in, no digest is sent
+    let response = srv.post("/").send_body(STR).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[actix_rt::test]
 async fn test_timeout() {
     let srv = test::start(|| {
@@ -267,6 +306,67 @@ async fn test_connection_reuse() {
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
 
+async fn echo_host(name: &'static str, req: HttpRequest) -> String {
+    let host = req.headers().get(header::HOST).unwrap().to_str().unwrap();
+    format!("{} {}", name, host)
+}
+
+#[actix_rt::test]
+async fn test_resolve_override() {
+    let canary = test::start(|| {
+        App::new().route("/", web::to(|req: HttpRequest| echo_host("canary", req)))
+    });
+    let stable = test::start(|| {
+        App::new().route("/", web::to(|req: HttpRequest| echo_host("stable", req)))
+    });
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().resolve_to("api.example.com", canary.addr()))
+        .finish();
+
+    // static override replaces dns lookup
+    let mut res = client.get("http://api.example.com/").send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "canary api.example.com");
+
+    // per-request address wins over static override
+    let mut res = client
+        .get("http://api.example.com/")
+        .address(stable.addr())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), "stable api.example.com");
+
+    // pooled connection to the explicit address is not reused for other requests
+    let mut res = client.get("http://api.example.com/").send().await.unwrap();
+    assert_eq!(res.body().await.unwrap(), "canary api.example.com");
+}
+
+#[actix_rt::test]
+async fn test_connection_lease() {
+    let srv = test::start(|| {
+        App::new().route(
+            "/",
+            web::to(|req: HttpRequest| async move { req.peer_addr().unwrap().to_string() }),
+        )
+    });
+
+    let client = awc::Client::default();
+    let lease = awc::ConnectionLease::new();
+
+    let mut res = client.get(srv.url("/")).lease(&lease).send().await.unwrap();
+    let first = res.body().await.unwrap();
+
+    // the leased connection is not taken by requests without the lease
+    let mut res = client.get(srv.url("/")).send().await.unwrap();
+    let unleased = res.body().await.unwrap();
+    assert_ne!(first, unleased);
+
+    let mut res = client.get(srv.url("/")).lease(&lease).send().await.unwrap();
+    let second = res.body().await.unwrap();
+    assert_eq!(first, second);
+}
+
 #[actix_rt::test]
 async fn test_connection_force_close() {
     let num = Arc::new(AtomicUsize::new(0));
@@ -13,7 +13,11 @@ use std::{
 use actix_http::HttpService;
 use actix_http_test::test_server;
 use actix_service::{map_config, pipeline_factory, ServiceFactoryExt};
-use actix_web::{dev::AppConfig, http::Version, web, App, HttpResponse};
+use actix_web::{
+    dev::AppConfig,
+    http::{header, Version},
+    web, App, HttpRequest, HttpResponse,
+};
 use futures_util::future::ok;
 use rustls::internal::pemfile::{certs, pkcs8_private_keys};
 use rustls::{ClientConfig, NoClientAuth, ServerConfig};
@@ -99,3 +103,65 @@ async fn test_connection_reuse_h2() {
     // one connection
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
+
+#[actix_rt::test]
+async fn test_resolve_to_validates_hostname() {
+    // certificate for the logical host name, signed by a test CA
+    let mut ca_params = rcgen::CertificateParams::new(Vec::new());
+    ca_params.is_ca = rcgen::IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+    let ca = rcgen::Certificate::from_params(ca_params).unwrap();
+
+    let cert = rcgen::generate_simple_self_signed(vec!["api.example.com".to_owned()]).unwrap();
+    let cert_file = cert.serialize_pem_with_signer(&ca).unwrap();
+    let key_file = cert.serialize_private_key_pem();
+
+    let mut server_config = ServerConfig::new(NoClientAuth::new());
+    let cert_chain = certs(&mut BufReader::new(cert_file.as_bytes())).unwrap();
+    let mut keys = pkcs8_private_keys(&mut BufReader::new(key_file.as_bytes())).unwrap();
+    server_config
+        .set_single_cert(cert_chain, keys.remove(0))
+        .unwrap();
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .h1(map_config(
+                App::new().service(web::resource("/").route(web::to(
+                    |req: HttpRequest| async move {
+                        let host = req.headers().get(header::HOST).unwrap();
+                        HttpResponse::Ok().body(host.to_str().unwrap().to_owned())
+                    },
+                ))),
+                |_| AppConfig::default(),
+            ))
+            .rustls(server_config.clone())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let mut config = ClientConfig::new();
+    config
+        .root_store
+        .add(&rustls::Certificate(ca.serialize_der().unwrap()))
+        .unwrap();
+    config.set_protocols(&[b"http/1.1".to_vec()]);
+
+    let client = awc::Client::builder()
+        .connector(
+            awc::Connector::new()
+                .rustls(Arc::new(config))
+                .resolve_to("api.example.com", srv.addr())
+                .resolve_to("other.example.com", srv.addr()),
+        )
+        .finish();
+
+    let mut res = client.get("https://api.example.com/").send().await.unwrap();
+    assert!(res.status().is_success());
+    assert_eq!(res.body().await.unwrap(), "api.example.com");
+
+    // same server, but the certificate does not cover this host name
+    assert!(client
+        .get("https://other.example.com/")
+        .send()
+        .await
+        .is_err());
+}
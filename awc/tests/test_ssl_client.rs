@@ -2,8 +2,11 @@
 
 extern crate tls_openssl as openssl;
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use actix_http::HttpService;
 use actix_http_test::test_server;
@@ -89,3 +92,52 @@ async fn test_connection_reuse_h2() {
     // one connection
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
+
+#[actix_rt::test]
+async fn test_tls_session_resumption() {
+    let srv = test_server(move || {
+        HttpService::build()
+            .finish(map_config(
+                App::new().service(web::resource("/").route(web::to(HttpResponse::Ok))),
+                |_| AppConfig::default(),
+            ))
+            .openssl(tls_config())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+    builder.set_verify(SslVerifyMode::NONE);
+    builder.set_alpn_protos(b"\x08http/1.1").unwrap();
+
+    let handshakes = Rc::new(RefCell::new(Vec::new()));
+    let recorded = handshakes.clone();
+
+    let client = awc::Client::builder()
+        .connector(
+            awc::Connector::new()
+                .ssl(builder.build())
+                .tls_session_cache(8)
+                .on_tls_handshake(move |hs| recorded.borrow_mut().push(hs.clone())),
+        )
+        .finish();
+
+    for _ in 0..2 {
+        let response = client
+            .get(srv.surl("/"))
+            .force_close()
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        // let the closed connection hand its session to the cache
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let handshakes = handshakes.borrow();
+    assert_eq!(handshakes.len(), 2);
+    assert_eq!(handshakes[0].host(), "localhost");
+    assert_eq!(handshakes[0].resumed(), Some(false));
+    assert_eq!(handshakes[1].resumed(), Some(true));
+}
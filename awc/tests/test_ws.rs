@@ -1,9 +1,20 @@
-use std::io;
+use std::io::{self, Read, Write};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use actix_codec::Framed;
-use actix_http::{body::BodySize, h1, ws, Error, HttpService, Request, Response};
+use actix_http::{
+    body::BodySize,
+    h1,
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    ws, Error, HttpService, Request, Response,
+};
 use actix_http_test::test_server;
+use awc::error::WsClientError;
 use bytes::Bytes;
+use flate2::{write::GzEncoder, Compression};
 use futures_util::future::ok;
 use futures_util::{SinkExt, StreamExt};
 
@@ -66,3 +77,163 @@ async fn test_simple() {
     let item = framed.next().await.unwrap().unwrap();
     assert_eq!(item, ws::Frame::Close(Some(ws::CloseCode::Normal.into())));
 }
+
+#[actix_rt::test]
+async fn test_refused_upgrade() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .upgrade(|(req, mut framed): (Request, Framed<_, _>)| {
+                async move {
+                    if req.headers().contains_key(header::AUTHORIZATION) {
+                        let mut res = ws::handshake_response(req.head()).finish();
+
+                        // unexpected extra headers
+                        let headers = res.headers_mut();
+                        headers.insert(header::UPGRADE, HeaderValue::from_static("h2c"));
+                        headers.append(header::UPGRADE, HeaderValue::from_static("websocket"));
+                        headers.insert(
+                            HeaderName::from_static("x-extra"),
+                            HeaderValue::from_static("1"),
+                        );
+
+                        framed
+                            .send(h1::Message::Item((res.drop_body(), BodySize::None)))
+                            .await?;
+
+                        let framed = framed.replace_codec(ws::Codec::new());
+                        ws::Dispatcher::with(framed, ws_service).await
+                    } else {
+                        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+                        enc.write_all(br#"{"error":"unauthorized"}"#).unwrap();
+                        let body = Bytes::from(enc.finish().unwrap());
+
+                        let res = Response::Unauthorized()
+                            .insert_header((header::CONTENT_TYPE, "application/json"))
+                            .insert_header((header::CONTENT_ENCODING, "gzip"))
+                            .finish();
+
+                        let size = BodySize::Sized(body.len() as u64);
+                        framed
+                            .send(h1::Message::Item((res.drop_body(), size)))
+                            .await?;
+                        framed.send(h1::Message::Chunk(Some(body))).await?;
+                        framed.send(h1::Message::Chunk(None)).await?;
+
+                        Ok(())
+                    }
+                }
+            })
+            .finish(|_| ok::<_, Error>(Response::NotFound()))
+            .tcp()
+    })
+    .await;
+
+    let client = awc::Client::new();
+
+    let err = client.ws(srv.url("/")).connect().await.unwrap_err();
+    let mut res = match err {
+        WsClientError::InvalidResponse(res) => res,
+        err => panic!("unexpected error: {}", err),
+    };
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    let body = res.body().await.unwrap();
+    assert_eq!(body, Bytes::from_static(br#"{"error":"unauthorized"}"#));
+
+    // retry with credentials
+    let (res, mut framed) = client
+        .ws(srv.url("/"))
+        .bearer_auth("token")
+        .connect()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+    assert_eq!(res.headers().get("x-extra").unwrap(), "1");
+
+    framed.send(ws::Message::Text("text".into())).await.unwrap();
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(item, ws::Frame::Text(Bytes::from_static(b"text")));
+}
+
+#[actix_rt::test]
+async fn test_refused_upgrade_keep_alive() {
+    let addr = actix_http_test::unused_addr();
+    let lst = std::net::TcpListener::bind(addr).unwrap();
+    let connections = Arc::new(AtomicUsize::new(0));
+
+    std::thread::spawn({
+        let connections = Arc::clone(&connections);
+
+        move || {
+            for stream in lst.incoming() {
+                connections.fetch_add(1, Ordering::SeqCst);
+                let mut stream = stream.unwrap();
+
+                std::thread::spawn(move || {
+                    let mut buf = [0; 1024];
+
+                    loop {
+                        let n = match stream.read(&mut buf) {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => n,
+                        };
+
+                        let req = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                        let res: &[u8] = if req.starts_with("get /close") {
+                            b"HTTP/1.1 401 Unauthorized\r\n\
+                              connection: close\r\n\
+                              content-length: 12\r\n\
+                              \r\n\
+                              unauthorized"
+                        } else if req.contains("upgrade: websocket") {
+                            b"HTTP/1.1 401 Unauthorized\r\n\
+                              content-length: 12\r\n\
+                              \r\n\
+                              unauthorized"
+                        } else {
+                            b"HTTP/1.1 200 OK\r\n\
+                              content-length: 2\r\n\
+                              \r\n\
+                              ok"
+                        };
+
+                        if stream.write_all(res).is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        }
+    });
+
+    let client = awc::Client::new();
+    let url = format!("http://{}/", addr);
+
+    async fn refused(client: &awc::Client, url: &str) {
+        let mut res = match client.ws(url).connect().await.unwrap_err() {
+            WsClientError::InvalidResponse(res) => res,
+            err => panic!("unexpected error: {}", err),
+        };
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let body = res.body().await.unwrap();
+        assert_eq!(body, Bytes::from_static(b"unauthorized"));
+    }
+
+    refused(&client, &url).await;
+    refused(&client, &url).await;
+
+    let mut res = client.get(url.as_str()).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"ok"));
+
+    // connection was released to the pool once the body was read
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // and closed if the response asks for it
+    refused(&client, &format!("http://{}/close", addr)).await;
+
+    let res = client.get(url.as_str()).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,40 @@
+//! Feeds arbitrary bytes, split in two at an input-chosen position, to the multipart boundary
+//! scanner and checks that it finds the first delimiter.
+
+#![no_main]
+
+use actix_multipart::{BoundaryScanner, Scan};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, data) = match data.split_first() {
+        Some((split, data)) => (usize::from(*split).min(data.len()), data),
+        None => return,
+    };
+
+    let mut scanner = BoundaryScanner::new("abc").unwrap();
+    let mut buf = Vec::new();
+    let mut content = Vec::new();
+    let mut found = false;
+
+    'parts: for part in &[&data[..split], &data[split..]] {
+        buf.extend_from_slice(part);
+
+        loop {
+            match scanner.scan(&buf) {
+                Scan::Delimiter => {
+                    found = true;
+                    break 'parts;
+                }
+                Scan::NeedMore | Scan::Content(0) => break,
+                Scan::Content(n) => content.extend(buf.drain(..n)),
+            }
+        }
+    }
+
+    let delimiter = data.windows(7).position(|w| w == b"\r\n--abc");
+    assert_eq!(found, delimiter.is_some());
+    if let Some(idx) = delimiter {
+        assert_eq!(content, &data[..idx]);
+    }
+});
@@ -0,0 +1,34 @@
+//! Feeds arbitrary bytes, split in two at an input-chosen position, to the chunked decoder.
+
+#![no_main]
+
+use actix_codec::Decoder as _;
+use actix_http::h1::{ChunkedDecoder, ChunkedState, PayloadItem};
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, data) = match data.split_first() {
+        Some((split, data)) => (usize::from(*split).min(data.len()), data),
+        None => return,
+    };
+
+    let mut decoder = ChunkedDecoder::new();
+    let mut buf = BytesMut::new();
+
+    for part in &[&data[..split], &data[split..]] {
+        buf.extend_from_slice(part);
+
+        loop {
+            match decoder.decode(&mut buf) {
+                Ok(Some(PayloadItem::Chunk(_))) => {}
+                Ok(Some(PayloadItem::Eof)) => {
+                    assert_eq!(decoder.state(), ChunkedState::End);
+                    return;
+                }
+                Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+    }
+});
@@ -0,0 +1,30 @@
+//! Feeds arbitrary bytes, split in two at an input-chosen position, to the HTTP/1 request codec.
+
+#![no_main]
+
+use actix_codec::Decoder as _;
+use actix_http::h1::{Codec, Message};
+use bytes::BytesMut;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, data) = match data.split_first() {
+        Some((split, data)) => (usize::from(*split).min(data.len()), data),
+        None => return,
+    };
+
+    let mut codec = Codec::default();
+    let mut buf = BytesMut::new();
+
+    for part in &[&data[..split], &data[split..]] {
+        buf.extend_from_slice(part);
+
+        loop {
+            match codec.decode(&mut buf) {
+                Ok(Some(Message::Item(_))) | Ok(Some(Message::Chunk(Some(_)))) => {}
+                Ok(Some(Message::Chunk(None))) | Ok(None) => break,
+                Err(_) => return,
+            }
+        }
+    }
+});
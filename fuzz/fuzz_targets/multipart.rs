@@ -0,0 +1,37 @@
+//! Feeds arbitrary bytes, split in two at an input-chosen position, to the multipart parser.
+
+#![no_main]
+
+use actix_multipart::Multipart;
+use actix_web::{
+    error::PayloadError,
+    http::header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+};
+use bytes::Bytes;
+use futures_util::{stream, StreamExt as _};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let (split, data) = match data.split_first() {
+        Some((split, data)) => (usize::from(*split).min(data.len()), data),
+        None => return,
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("multipart/form-data; boundary=abc"),
+    );
+
+    let chunks = vec![
+        Ok::<_, PayloadError>(Bytes::copy_from_slice(&data[..split])),
+        Ok(Bytes::copy_from_slice(&data[split..])),
+    ];
+    let mut multipart = Multipart::new(&headers, stream::iter(chunks));
+
+    futures_executor::block_on(async {
+        while let Some(Ok(mut field)) = multipart.next().await {
+            while let Some(Ok(_)) = field.next().await {}
+        }
+    });
+});
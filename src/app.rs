@@ -16,13 +16,16 @@ use crate::app_service::{AppEntry, AppInit, AppRoutingFactory};
 use crate::config::ServiceConfig;
 use crate::data::{Data, DataFactory, FnDataFactory};
 use crate::dev::ResourceDef;
-use crate::error::Error;
+use crate::discovery::EnableDiscovery;
+use crate::error::{Error, ErrorDetail};
+use crate::info::TrustForwardedPrefix;
 use crate::resource::Resource;
 use crate::route::Route;
 use crate::service::{
     AppServiceFactory, HttpServiceFactory, ServiceFactoryWrapper, ServiceRequest,
     ServiceResponse,
 };
+use crate::sharded::ShardedHandle;
 
 type HttpNewService = BoxServiceFactory<(), ServiceRequest, ServiceResponse, Error, ()>;
 
@@ -133,6 +136,34 @@ where
         self
     }
 
+    /// Set worker-local application data.
+    ///
+    /// `data` is called for every application instance, i.e. once per worker, and the instance is
+    /// registered with `handle`. Handlers access their own worker's instance with the
+    /// [`Sharded<D>`](crate::web::Sharded) extractor, and the instances of all workers are visited
+    /// with [`Sharded::collect`](crate::web::Sharded::collect).
+    ///
+    /// The handle must be created outside of the application factory and cloned into it.
+    pub fn sharded_data<F, D>(mut self, handle: &ShardedHandle<D>, data: F) -> Self
+    where
+        F: Fn() -> D + 'static,
+        D: 'static,
+    {
+        let handle = handle.clone();
+
+        self.data_factories.push(Box::new(move || {
+            let shard = data();
+            let handle = handle.clone();
+
+            async move {
+                let data: Box<dyn DataFactory> = Box::new(handle.register(shard));
+                Ok(data)
+            }
+            .boxed_local()
+        }));
+        self
+    }
+
     /// Set application level arbitrary data item.
     ///
     /// Application data stored with `App::app_data()` method is available
@@ -145,6 +176,65 @@ where
         self
     }
 
+    /// Set how much detail of server errors is included in response bodies.
+    ///
+    /// See [`ErrorDetail`] for details and the default.
+    ///
+    /// ```
+    /// use actix_web::{error::ErrorDetail, App};
+    ///
+    /// let app = App::new().error_detail(ErrorDetail::MessageOnly);
+    /// ```
+    pub fn error_detail(self, detail: ErrorDetail) -> Self {
+        self.app_data(detail)
+    }
+
+    /// Trust the `X-Forwarded-Prefix` header set by a reverse proxy.
+    ///
+    /// When a proxy mounts the application under an external path prefix, e.g. forwarding
+    /// `/app/login` to `/login`, trusting the header makes the prefix available as
+    /// [`ConnectionInfo::prefix`](crate::dev::ConnectionInfo::prefix). It is then included in urls
+    /// generated by [`HttpRequest::url_for`](crate::HttpRequest::url_for) and in redirects to
+    /// absolute paths made with [`web::Redirect`](crate::web::Redirect).
+    ///
+    /// Only enable this when every request passes through a proxy that sets or strips the
+    /// header; otherwise clients can choose the prefix of generated urls. Not trusted by default.
+    ///
+    /// ```
+    /// use actix_web::App;
+    ///
+    /// let app = App::new().trust_forwarded_prefix(true);
+    /// ```
+    pub fn trust_forwarded_prefix(self, trust: bool) -> Self {
+        self.app_data(TrustForwardedPrefix(trust))
+    }
+
+    /// Answer `OPTIONS` requests with a JSON document describing the requested resource.
+    ///
+    /// The document lists the methods the resource's routes were registered for, the names of
+    /// the path parameters, and the metadata and accepted content types declared with
+    /// [`Route::meta`] and [`Route::accepts`] for each route. The methods are also sent in an
+    /// `Allow` header. Methods checked by custom guards are not known and are not listed.
+    ///
+    /// Resources with a route of their own for `OPTIONS` keep using it, and CORS preflight
+    /// requests are never answered with the document. Discovery can be switched on or off per
+    /// resource with [`Resource::discovery`].
+    ///
+    /// ```
+    /// use actix_web::{web, App, HttpResponse};
+    ///
+    /// let app = App::new().enable_discovery().service(
+    ///     web::resource("/users/{id}").route(
+    ///         web::get()
+    ///             .meta("summary", "Get user")
+    ///             .to(|| HttpResponse::Ok()),
+    ///     ),
+    /// );
+    /// ```
+    pub fn enable_discovery(self) -> Self {
+        self.app_data(EnableDiscovery(true))
+    }
+
     /// Run external configuration as part of the application building
     /// process
     ///
@@ -677,4 +767,65 @@ mod tests {
         let body = read_body(resp).await;
         assert_eq!(body, Bytes::from_static(b"https://youtube.com/watch/12345"));
     }
+
+    #[actix_rt::test]
+    async fn test_error_detail() {
+        async fn leaky() -> Result<HttpResponse, Error> {
+            Err(crate::error::ErrorInternalServerError(
+                "connection to db://admin:hunter2@10.0.0.1 refused",
+            ))
+        }
+
+        async fn user_facing() -> Result<HttpResponse, Error> {
+            Err(HttpResponse::ServiceUnavailable()
+                .body("maintenance until 10:00")
+                .into())
+        }
+
+        async fn bad_request() -> Result<HttpResponse, Error> {
+            Err(crate::error::ErrorBadRequest("missing field `name`"))
+        }
+
+        let app = |detail| {
+            App::new()
+                .error_detail(detail)
+                .route("/leaky", web::get().to(leaky))
+                .route("/user-facing", web::get().to(user_facing))
+                .route("/bad-request", web::get().to(bad_request))
+        };
+
+        let body = |detail, path: &'static str| async move {
+            let srv = init_service(app(detail)).await;
+            let req = TestRequest::with_uri(path)
+                .insert_header(("x-request-id", "req-42"))
+                .to_request();
+            let res = call_service(&srv, req).await;
+            (res.status(), read_body(res).await)
+        };
+
+        let (status, leaked) = body(ErrorDetail::Full, "/leaky").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(leaked, "connection to db://admin:hunter2@10.0.0.1 refused");
+
+        let (status, redacted) = body(ErrorDetail::MessageOnly, "/leaky").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(redacted, "Internal Server Error (request id: req-42)");
+
+        let (status, redacted) = body(ErrorDetail::None, "/leaky").await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(redacted.is_empty());
+
+        // user-facing and client errors are never redacted
+        for detail in &[
+            ErrorDetail::Full,
+            ErrorDetail::MessageOnly,
+            ErrorDetail::None,
+        ] {
+            let (_, body1) = body(*detail, "/user-facing").await;
+            assert_eq!(body1, "maintenance until 10:00");
+
+            let (_, body2) = body(*detail, "/bad-request").await;
+            assert_eq!(body2, "missing field `name`");
+        }
+    }
 }
@@ -1,17 +1,24 @@
 use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
 use std::rc::Rc;
-use std::task::Poll;
+use std::task::{Context, Poll};
 
+use actix_http::body::{Body, ResponseBody};
+use actix_http::http::{
+    header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    StatusCode,
+};
 use actix_http::{Extensions, Request, Response};
 use actix_router::{Path, ResourceDef, Router, Url};
 use actix_service::boxed::{self, BoxService, BoxServiceFactory};
 use actix_service::{fn_service, Service, ServiceFactory};
-use futures_core::future::LocalBoxFuture;
+use futures_core::{future::LocalBoxFuture, ready};
 use futures_util::future::join_all;
 
 use crate::config::{AppConfig, AppService};
 use crate::data::FnDataFactory;
-use crate::error::Error;
+use crate::error::{Error, ErrorDetail, InternalError};
 use crate::guard::Guard;
 use crate::request::{HttpRequest, HttpRequestPool};
 use crate::rmap::ResourceMap;
@@ -133,8 +140,11 @@ where
                 factory.create(&mut app_data);
             });
 
+            let error_detail = app_data.get::<ErrorDetail>().copied().unwrap_or_default();
+
             Ok(AppInitService {
                 service,
+                error_detail,
                 app_data: Rc::new(app_data),
                 app_state: AppInitServiceState::new(rmap, config),
             })
@@ -148,6 +158,7 @@ where
     T: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
 {
     service: T,
+    error_detail: ErrorDetail,
     app_data: Rc<Extensions>,
     app_state: Rc<AppInitServiceState>,
 }
@@ -192,13 +203,21 @@ where
 {
     type Response = ServiceResponse<B>;
     type Error = T::Error;
-    type Future = T::Future;
+    type Future = AppInitServiceFuture<T::Future>;
 
     actix_service::forward_ready!(service);
 
     fn call(&self, req: Request) -> Self::Future {
         let (head, payload) = req.into_parts();
 
+        let redact = match self.error_detail {
+            ErrorDetail::Full => None,
+            detail => Some(Redact {
+                detail,
+                request_id: head.headers.get("x-request-id").cloned(),
+            }),
+        };
+
         let req = if let Some(mut req) = self.app_state.pool().pop() {
             let inner = Rc::get_mut(&mut req.inner).unwrap();
             inner.path.get_mut().update(&head.uri);
@@ -213,7 +232,109 @@ where
                 self.app_data.clone(),
             )
         };
-        self.service.call(ServiceRequest::new(req, payload))
+
+        AppInitServiceFuture {
+            fut: self.service.call(ServiceRequest::new(req, payload)),
+            redact,
+        }
+    }
+}
+
+/// Future of [`AppInitService`] that hides server error details according to [`ErrorDetail`].
+#[pin_project::pin_project]
+pub struct AppInitServiceFuture<F> {
+    #[pin]
+    fut: F,
+    redact: Option<Redact>,
+}
+
+impl<F, B> Future for AppInitServiceFuture<F>
+where
+    F: Future<Output = Result<ServiceResponse<B>, Error>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.fut.poll(cx));
+
+        let redact = match this.redact.take() {
+            Some(redact) => redact,
+            None => return Poll::Ready(res),
+        };
+
+        Poll::Ready(match res {
+            Ok(res) => Ok(redact.response(res)),
+            Err(err) => Err(redact.error(err)),
+        })
+    }
+}
+
+/// Replaces the bodies of server error responses.
+struct Redact {
+    detail: ErrorDetail,
+    request_id: Option<HeaderValue>,
+}
+
+impl Redact {
+    /// Returns true if the details of an error responded to with `status` should be hidden.
+    fn applies(err: &Error, status: StatusCode) -> bool {
+        let redacted = status.is_server_error() && !err.as_response_error().is_user_facing();
+
+        if redacted {
+            log::error!("Hiding details of error response ({}): {}", status, err);
+        }
+
+        redacted
+    }
+
+    fn body(&self, status: StatusCode) -> Body {
+        match self.detail {
+            ErrorDetail::MessageOnly => {
+                let reason = status.canonical_reason().unwrap_or("Server Error");
+
+                match self.request_id.as_ref().and_then(|id| id.to_str().ok()) {
+                    Some(id) => Body::from(format!("{} (request id: {})", reason, id)),
+                    None => Body::from(reason),
+                }
+            }
+            ErrorDetail::Full | ErrorDetail::None => Body::Empty,
+        }
+    }
+
+    fn response<B>(self, res: ServiceResponse<B>) -> ServiceResponse<B> {
+        let status = res.status();
+
+        match res.response().error() {
+            Some(err) if Self::applies(err, status) => {}
+            _ => return res,
+        }
+
+        let body = self.body(status);
+
+        res.map_body(|head, _| {
+            head.headers.remove(CONTENT_LENGTH);
+            head.headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; charset=utf-8"),
+            );
+
+            ResponseBody::Other(body)
+        })
+    }
+
+    fn error(self, err: Error) -> Error {
+        let status = err.as_response_error().status_code();
+
+        if !Self::applies(&err, status) {
+            return err;
+        }
+
+        let res = Response::build(status)
+            .content_type("text/plain; charset=utf-8")
+            .body(self.body(status));
+
+        InternalError::from_response(err, res).into()
     }
 }
 
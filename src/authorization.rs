@@ -0,0 +1,372 @@
+//! Per-route authorization, see [`Route::require_permission`](crate::Route::require_permission).
+
+use std::collections::HashMap;
+
+use actix_http::error::{Error, ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized};
+use futures_core::future::LocalBoxFuture;
+use futures_util::future;
+
+use crate::{dev::Payload, service::ServiceRequest, web::Data, FromRequest, HttpRequest};
+
+/// Outcome of a permission check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    /// The permission is granted. Claims about the subject of the request are passed on to
+    /// handlers through [`AuthContext`].
+    Allow(HashMap<String, String>),
+
+    /// The subject of the request is known but lacks the permission. Responds with
+    /// `403 Forbidden`.
+    Deny,
+
+    /// The request does not carry valid credentials. Responds with `401 Unauthorized`.
+    Unauthenticated,
+}
+
+impl Decision {
+    /// Grants a permission without any claims.
+    pub fn allow() -> Self {
+        Decision::Allow(HashMap::new())
+    }
+}
+
+/// Permission checks run for routes with required permissions.
+///
+/// Register an implementation as `Data<dyn Permissions>` app data. Routes requiring permissions
+/// respond with `500 Internal Server Error` when none is registered.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+///
+/// use actix_web::{web, App, HttpRequest, HttpResponse};
+/// use futures_util::future::{ready, LocalBoxFuture};
+///
+/// struct ApiKeys;
+///
+/// impl web::Permissions for ApiKeys {
+///     fn check<'a>(
+///         &'a self,
+///         req: &'a HttpRequest,
+///         required: &'a str,
+///     ) -> LocalBoxFuture<'a, web::Decision> {
+///         let decision = match req.headers().get("x-api-key") {
+///             None => web::Decision::Unauthenticated,
+///             Some(key) if key == "admin" || required == "orders:read" => {
+///                 web::Decision::allow()
+///             }
+///             Some(_) => web::Decision::Deny,
+///         };
+///
+///         Box::pin(ready(decision))
+///     }
+/// }
+///
+/// let app = App::new()
+///     .app_data(web::Data::from(Arc::new(ApiKeys) as Arc<dyn web::Permissions>))
+///     .route(
+///         "/orders",
+///         web::post()
+///             .require_permission("orders:write")
+///             .to(|| HttpResponse::Created()),
+///     );
+/// ```
+pub trait Permissions {
+    /// Decides whether the request has the `required` permission.
+    fn check<'a>(
+        &'a self,
+        req: &'a HttpRequest,
+        required: &'a str,
+    ) -> LocalBoxFuture<'a, Decision>;
+}
+
+/// Permissions required by a route.
+///
+/// Every group must be satisfied; a group is satisfied when any one of its permissions is granted.
+#[derive(Debug, Default)]
+pub(crate) struct Requirements(Vec<Vec<String>>);
+
+impl Requirements {
+    pub(crate) fn push(&mut self, any: Vec<String>) {
+        self.0.push(any);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Checks the requirements, returning the error to respond with if they are not met.
+    ///
+    /// Permissions are checked one after another and checking stops at the first group that is not
+    /// satisfied. A group fails with `403 Forbidden` if any of its checks denied the permission,
+    /// and with `401 Unauthorized` otherwise.
+    pub(crate) async fn authorize(&self, req: &ServiceRequest) -> Result<AuthContext, Error> {
+        let permissions = match req.app_data::<Data<dyn Permissions>>() {
+            Some(permissions) => permissions.clone(),
+            None => {
+                log::error!(
+                    "Route requires permissions but no Permissions are registered. \
+                     Request path: {:?}",
+                    req.path()
+                );
+                return Err(ErrorInternalServerError(
+                    "Permissions are not configured, to configure use App::app_data()",
+                ));
+            }
+        };
+
+        let mut ctx = AuthContext {
+            granted: Vec::new(),
+            claims: HashMap::new(),
+        };
+
+        for any in &self.0 {
+            let mut denied = false;
+            let mut granted = false;
+
+            for permission in any {
+                match permissions.check(req.request(), permission).await {
+                    Decision::Allow(claims) => {
+                        ctx.granted.push(permission.clone());
+                        ctx.claims.extend(claims);
+                        granted = true;
+                        break;
+                    }
+                    Decision::Deny => denied = true,
+                    Decision::Unauthenticated => {}
+                }
+            }
+
+            if !granted {
+                return Err(if denied {
+                    ErrorForbidden("Permission denied")
+                } else {
+                    ErrorUnauthorized("Authentication required")
+                });
+            }
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// Extractor for the outcome of the permission checks of a route.
+///
+/// Only available in handlers of routes with
+/// [`Route::require_permission`](crate::Route::require_permission) or
+/// [`Route::require_any_permission`](crate::Route::require_any_permission); extraction fails
+/// with `500 Internal Server Error` elsewhere.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, HttpResponse};
+///
+/// async fn create_order(auth: web::AuthContext) -> HttpResponse {
+///     let user = auth.claim("sub").unwrap_or("anonymous");
+///     HttpResponse::Created().body(format!("order created by {}", user))
+/// }
+///
+/// let route = web::post()
+///     .require_permission("orders:write")
+///     .to(create_order);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthContext {
+    granted: Vec<String>,
+    claims: HashMap<String, String>,
+}
+
+impl AuthContext {
+    /// Returns the permissions granted to the request, in the order they were required.
+    ///
+    /// For requirements satisfied by any of several permissions, only the first permission that
+    /// was granted is listed.
+    pub fn granted(&self) -> &[String] {
+        &self.granted
+    }
+
+    /// Returns true if `permission` was checked and granted.
+    pub fn is_granted(&self, permission: &str) -> bool {
+        self.granted.iter().any(|granted| granted == permission)
+    }
+
+    /// Returns the claims of all granted permissions.
+    ///
+    /// Where checks returned different values for a claim, the value of the last check is kept.
+    pub fn claims(&self) -> &HashMap<String, String> {
+        &self.claims
+    }
+
+    /// Returns the value of a claim.
+    pub fn claim(&self, key: &str) -> Option<&str> {
+        self.claims.get(key).map(String::as_str)
+    }
+}
+
+impl FromRequest for AuthContext {
+    type Config = ();
+    type Error = Error;
+    type Future = future::Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(ctx) = req.extensions().get::<AuthContext>() {
+            future::ok(ctx.clone())
+        } else {
+            log::debug!(
+                "Failed to construct AuthContext extractor, the route requires no permissions. \
+                 Request path: {:?}",
+                req.path(),
+            );
+            future::err(ErrorInternalServerError(
+                "Missing AuthContext, to require permissions use Route::require_permission()",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures_util::future::ready;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    /// Grants admins everything and readers `orders:read`, based on the `x-user` header.
+    struct FakePermissions;
+
+    impl Permissions for FakePermissions {
+        fn check<'a>(
+            &'a self,
+            req: &'a HttpRequest,
+            required: &'a str,
+        ) -> LocalBoxFuture<'a, Decision> {
+            let user = req
+                .headers()
+                .get("x-user")
+                .and_then(|user| user.to_str().ok());
+
+            let decision = match (user, required) {
+                (None, _) => Decision::Unauthenticated,
+                (Some("admin"), _) | (Some("reader"), "orders:read") => {
+                    let mut claims = HashMap::new();
+                    claims.insert("sub".to_owned(), user.unwrap().to_owned());
+                    Decision::Allow(claims)
+                }
+                (Some("ops"), "ops") => Decision::allow(),
+                (Some(_), _) => Decision::Deny,
+            };
+
+            Box::pin(ready(decision))
+        }
+    }
+
+    async fn describe(auth: AuthContext) -> HttpResponse {
+        HttpResponse::Ok().body(format!(
+            "{} {}",
+            auth.claim("sub").unwrap_or("-"),
+            auth.granted().join(",")
+        ))
+    }
+
+    fn request(method: &str, user: Option<&str>) -> TestRequest {
+        let req = TestRequest::with_uri("/orders").method(method.parse().unwrap());
+
+        match user {
+            Some(user) => req.insert_header(("x-user", user)),
+            None => req,
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_require_permission() {
+        let srv = init_service(
+            App::new()
+                .app_data(Data::from(Arc::new(FakePermissions) as Arc<dyn Permissions>))
+                .service(
+                    web::resource("/orders")
+                        .route(web::get().require_permission("orders:read").to(describe))
+                        .route(
+                            web::post()
+                                .require_permission("orders:write")
+                                .require_any_permission(vec!["orders:admin", "ops"])
+                                .to(describe),
+                        ),
+                ),
+        )
+        .await;
+
+        let cases = vec![
+            ("GET", Some("reader"), StatusCode::OK, "reader orders:read"),
+            ("GET", Some("admin"), StatusCode::OK, "admin orders:read"),
+            ("GET", Some("guest"), StatusCode::FORBIDDEN, ""),
+            ("GET", None, StatusCode::UNAUTHORIZED, ""),
+            (
+                "POST",
+                Some("admin"),
+                StatusCode::OK,
+                "admin orders:write,orders:admin",
+            ),
+            ("POST", Some("reader"), StatusCode::FORBIDDEN, ""),
+            ("POST", None, StatusCode::UNAUTHORIZED, ""),
+        ];
+
+        for (method, user, status, body) in cases {
+            let res = call_service(&srv, request(method, user).to_request()).await;
+            assert_eq!(res.status(), status, "{} as {:?}", method, user);
+
+            if status == StatusCode::OK {
+                assert_eq!(read_body(res).await, body);
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_require_any_permission() {
+        let srv = init_service(
+            App::new()
+                .app_data(Data::from(Arc::new(FakePermissions) as Arc<dyn Permissions>))
+                .route(
+                    "/orders",
+                    web::delete()
+                        .require_any_permission(vec!["orders:admin", "ops"])
+                        .to(describe),
+                ),
+        )
+        .await;
+
+        // denied first permission, granted second
+        let res = call_service(&srv, request("DELETE", Some("ops")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(read_body(res).await, "- ops");
+
+        let res = call_service(&srv, request("DELETE", Some("reader")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_rt::test]
+    async fn test_not_configured() {
+        let srv = init_service(
+            App::new()
+                .route(
+                    "/orders",
+                    web::get().require_permission("orders:read").to(describe),
+                )
+                .route("/open", web::get().to(describe)),
+        )
+        .await;
+
+        let res = call_service(&srv, request("GET", Some("admin")).to_request()).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // extractor is not available on routes without requirements
+        let req = TestRequest::with_uri("/open").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
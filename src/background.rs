@@ -0,0 +1,358 @@
+//! Background tasks tied to the lifecycle of an [`HttpServer`](crate::HttpServer).
+
+use std::{fmt, future::Future, rc::Rc, time::Duration};
+
+use actix_rt::{task::JoinHandle, time};
+use futures_core::future::LocalBoxFuture;
+use futures_util::future::{pending, select};
+
+type TaskFn = dyn Fn() -> LocalBoxFuture<'static, ()>;
+
+/// What happens to a background task that panics.
+///
+/// Set with [`HttpServer::background_restart`](crate::HttpServer::background_restart). The panic
+/// is logged either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// The task is not run again.
+    Never,
+
+    /// The task is run again after the given delay. Periodic tasks resume their schedule instead.
+    After(Duration),
+}
+
+impl Default for RestartPolicy {
+    /// Restart after one second.
+    fn default() -> Self {
+        RestartPolicy::After(Duration::from_secs(1))
+    }
+}
+
+enum Schedule {
+    OnStart,
+    Every(Duration),
+}
+
+struct BackgroundTask {
+    schedule: Schedule,
+    task: Box<dyn Fn() -> LocalBoxFuture<'static, ()> + Send>,
+}
+
+/// Tasks registered with an [`HttpServer`](crate::HttpServer) before it is started.
+#[derive(Default)]
+pub(crate) struct BackgroundTasks {
+    tasks: Vec<BackgroundTask>,
+    restart: RestartPolicy,
+    ignore_signals: bool,
+}
+
+impl BackgroundTasks {
+    pub(crate) fn on_start<T, Fut>(&mut self, task: T)
+    where
+        T: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.push(Schedule::OnStart, task);
+    }
+
+    pub(crate) fn periodic<T, Fut>(&mut self, interval: Duration, task: T)
+    where
+        T: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.push(Schedule::Every(interval), task);
+    }
+
+    pub(crate) fn restart(&mut self, policy: RestartPolicy) {
+        self.restart = policy;
+    }
+
+    /// Keep tasks running on shutdown signals, for servers that do not handle them.
+    pub(crate) fn disable_signals(&mut self) {
+        self.ignore_signals = true;
+    }
+
+    fn push<T, Fut>(&mut self, schedule: Schedule, task: T)
+    where
+        T: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.tasks.push(BackgroundTask {
+            schedule,
+            task: Box::new(move || Box::pin(task())),
+        });
+    }
+
+    /// Spawns all tasks on the current runtime and cancels them once `stopped` resolves or a
+    /// shutdown signal is received, whichever comes first.
+    pub(crate) fn start<F>(self, stopped: F)
+    where
+        F: Future + 'static,
+    {
+        if self.tasks.is_empty() {
+            return;
+        }
+
+        let restart = self.restart;
+        let ignore_signals = self.ignore_signals;
+        let handles = self
+            .tasks
+            .into_iter()
+            .map(|BackgroundTask { schedule, task }| {
+                let task: Rc<TaskFn> = Rc::from(task as Box<TaskFn>);
+                actix_rt::spawn(supervise(schedule, task, restart))
+            })
+            .collect::<Vec<_>>();
+
+        actix_rt::spawn(async move {
+            // graceful shutdown waits for connections, tasks are stopped as soon as it starts
+            if ignore_signals {
+                stopped.await;
+            } else {
+                select(Box::pin(stopped), Box::pin(shutdown_signal())).await;
+            }
+
+            for handle in handles {
+                handle.abort();
+            }
+        });
+    }
+}
+
+impl fmt::Debug for BackgroundTasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackgroundTasks")
+            .field("tasks", &self.tasks.len())
+            .field("restart", &self.restart)
+            .field("ignore_signals", &self.ignore_signals)
+            .finish()
+    }
+}
+
+/// Resolves once the process receives one of the signals that stop the server.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use actix_rt::signal::unix::{signal, SignalKind};
+        use futures_util::future::poll_fn;
+        use std::task::Poll;
+
+        let mut signals = [
+            SignalKind::interrupt(),
+            SignalKind::terminate(),
+            SignalKind::quit(),
+        ]
+        .iter()
+        .filter_map(|kind| signal(*kind).ok())
+        .collect::<Vec<_>>();
+
+        if signals.is_empty() {
+            return pending().await;
+        }
+
+        poll_fn(|cx| {
+            for signal in &mut signals {
+                if signal.poll_recv(cx).is_ready() {
+                    return Poll::Ready(());
+                }
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+
+    #[cfg(not(unix))]
+    {
+        if actix_rt::signal::ctrl_c().await.is_err() {
+            pending::<()>().await
+        }
+    }
+}
+
+async fn supervise(schedule: Schedule, task: Rc<TaskFn>, restart: RestartPolicy) {
+    match schedule {
+        Schedule::OnStart => {
+            while !run_once(&task).await {
+                match restart {
+                    RestartPolicy::Never => break,
+                    RestartPolicy::After(delay) => time::sleep(delay).await,
+                }
+            }
+        }
+
+        Schedule::Every(period) => {
+            let mut ticks = time::interval(period);
+
+            loop {
+                ticks.tick().await;
+
+                if !run_once(&task).await && restart == RestartPolicy::Never {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Runs one invocation of `task` in its own task, so that a panic is caught at its join handle.
+///
+/// Returns false if the invocation panicked.
+async fn run_once(task: &Rc<TaskFn>) -> bool {
+    let mut run = AbortOnDrop(actix_rt::spawn(task()));
+
+    match (&mut run.0).await {
+        Ok(()) => true,
+        Err(err) => {
+            log::error!("Background task failed: {}", err);
+            false
+        }
+    }
+}
+
+/// Cancels the invocation when its supervisor is cancelled.
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::Cell,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    fn counter() -> (Arc<AtomicUsize>, impl Fn() -> usize) {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let count = {
+            let runs = runs.clone();
+            move || runs.load(Ordering::SeqCst)
+        };
+
+        (runs, count)
+    }
+
+    #[actix_rt::test]
+    async fn test_periodic_task_cancelled_on_stop() {
+        tokio::time::pause();
+
+        let (runs, count) = counter();
+        let mut tasks = BackgroundTasks::default();
+        tasks.periodic(Duration::from_secs(600), move || {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let (stop, stopped) = oneshot::channel::<()>();
+        tasks.start(stopped);
+
+        // first run happens right away, then once per interval
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(count(), 1);
+        time::sleep(Duration::from_secs(600)).await;
+        assert_eq!(count(), 2);
+        time::sleep(Duration::from_secs(1200)).await;
+        assert_eq!(count(), 4);
+
+        stop.send(()).unwrap();
+        time::sleep(Duration::from_secs(3600)).await;
+        assert_eq!(count(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_start_task_cancelled_on_stop() {
+        tokio::time::pause();
+
+        let (runs, count) = counter();
+        let mut tasks = BackgroundTasks::default();
+        tasks.on_start(move || {
+            let runs = runs.clone();
+            async move {
+                loop {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    time::sleep(Duration::from_secs(10)).await;
+                }
+            }
+        });
+
+        let (stop, stopped) = oneshot::channel::<()>();
+        tasks.start(stopped);
+
+        time::sleep(Duration::from_secs(25)).await;
+        assert_eq!(count(), 3);
+
+        stop.send(()).unwrap();
+        time::sleep(Duration::from_secs(100)).await;
+        assert_eq!(count(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_panicking_task_restarted() {
+        tokio::time::pause();
+
+        thread_local! {
+            static PANICKED: Cell<bool> = Cell::new(false);
+        }
+
+        let (runs, count) = counter();
+        let mut tasks = BackgroundTasks::default();
+        tasks.restart(RestartPolicy::After(Duration::from_secs(5)));
+        tasks.on_start(move || {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+
+                if !PANICKED.with(|panicked| panicked.replace(true)) {
+                    panic!("first run fails");
+                }
+            }
+        });
+
+        let (_stop, stopped) = oneshot::channel::<()>();
+        tasks.start(stopped);
+
+        time::sleep(Duration::from_secs(1)).await;
+        assert_eq!(count(), 1);
+        time::sleep(Duration::from_secs(5)).await;
+        assert_eq!(count(), 2);
+
+        // completed successfully, so not run again
+        time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(count(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_panicking_task_not_restarted() {
+        tokio::time::pause();
+
+        let (runs, count) = counter();
+        let mut tasks = BackgroundTasks::default();
+        tasks.restart(RestartPolicy::Never);
+        tasks.periodic(Duration::from_secs(10), move || {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                panic!("always fails");
+            }
+        });
+
+        let (_stop, stopped) = oneshot::channel::<()>();
+        tasks.start(stopped);
+
+        time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(count(), 1);
+    }
+}
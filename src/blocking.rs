@@ -0,0 +1,555 @@
+//! Admission control for blocking tasks, see [`BlockingConfig`].
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use futures_util::future::{ok, Ready};
+
+use crate::{
+    dev::Payload,
+    error::{BlockingError, Error},
+    FromRequest, HttpRequest,
+};
+
+/// Limits for blocking tasks run with [`BlockingConfig::block`].
+///
+/// At most `max_running` blocking tasks run at once. Further tasks wait in a queue of at most
+/// `max_queue` entries for at most `queue_timeout`. Tasks that do not fit in the queue, or wait
+/// longer than the timeout, fail with [`BlockingError::Overloaded`], which responds with
+/// `503 Service Unavailable` and a `Retry-After` header.
+///
+/// Register the config as app data of an app, scope or resource; the config closest to the
+/// handler is used. [`web::block`](crate::web::block) applies it to tasks started while a handler
+/// runs. Handlers may also extract it and call [`block`](Self::block), e.g. from tasks they
+/// spawn. Where no config is registered, blocking tasks are not limited.
+///
+/// The config is a cheap to clone handle; [`queued`](Self::queued) and
+/// [`running`](Self::running) report current usage of the worker it was registered on. Usage of
+/// all workers of a server is reported by [`WorkerStats`](crate::WorkerStats).
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use actix_web::{error::BlockingError, web, App, HttpResponse, HttpServer};
+///
+/// async fn report(blocking: web::BlockingConfig) -> Result<HttpResponse, BlockingError> {
+///     let report = blocking.block(|| "expensive report".to_owned()).await?;
+///     Ok(HttpResponse::Ok().body(report))
+/// }
+///
+/// HttpServer::new(|| {
+///     App::new()
+///         .app_data(
+///             web::BlockingConfig::new()
+///                 .max_running(16)
+///                 .max_queue(64)
+///                 .queue_timeout(Duration::from_secs(2)),
+///         )
+///         .route("/report", web::get().to(report))
+/// });
+/// ```
+#[derive(Clone)]
+pub struct BlockingConfig {
+    max_running: usize,
+    max_queue: usize,
+    queue_timeout: Option<Duration>,
+    state: Rc<State>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<BlockingConfig>> = RefCell::new(None);
+    static GAUGE: RefCell<Option<BlockingGauge>> = RefCell::new(None);
+}
+
+/// Counts of blocking tasks of all workers of a server.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BlockingGauge {
+    running: Arc<AtomicUsize>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl BlockingGauge {
+    /// Makes configs created afterwards on the current worker thread report to this gauge.
+    pub(crate) fn register(&self) {
+        GAUGE.with(|gauge| *gauge.borrow_mut() = Some(self.clone()));
+    }
+
+    pub(crate) fn running(&self) -> usize {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+}
+
+/// Config consulted by `web::block` while a handler runs, restored to the previous one on drop.
+pub(crate) struct CurrentGuard(Option<BlockingConfig>);
+
+impl Drop for CurrentGuard {
+    fn drop(&mut self) {
+        let prev = self.0.take();
+        CURRENT.with(|current| *current.borrow_mut() = prev);
+    }
+}
+
+struct State {
+    running: Cell<usize>,
+    next_id: Cell<u64>,
+    queue: RefCell<VecDeque<(u64, Waker)>>,
+    gauge: Option<BlockingGauge>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            running: Cell::new(0),
+            next_id: Cell::new(0),
+            queue: RefCell::new(VecDeque::new()),
+            gauge: GAUGE.with(|gauge| gauge.borrow().clone()),
+        }
+    }
+}
+
+impl State {
+    fn inc_running(&self) {
+        self.running.set(self.running.get() + 1);
+
+        if let Some(ref gauge) = self.gauge {
+            gauge.running.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn dec_running(&self) {
+        self.running.set(self.running.get() - 1);
+
+        if let Some(ref gauge) = self.gauge {
+            gauge.running.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Reports a task entering or leaving the queue to the gauge.
+    fn gauge_queued(&self, entered: bool) {
+        if let Some(ref gauge) = self.gauge {
+            if entered {
+                gauge.queued.fetch_add(1, Ordering::Relaxed);
+            } else {
+                gauge.queued.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn wake_front(&self) {
+        if let Some((_, waker)) = self.queue.borrow().front() {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+impl BlockingConfig {
+    /// Creates config without limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets maximum number of blocking tasks running at once.
+    pub fn max_running(mut self, max_running: usize) -> Self {
+        self.max_running = max_running;
+        self
+    }
+
+    /// Sets maximum number of blocking tasks waiting for a free slot.
+    pub fn max_queue(mut self, max_queue: usize) -> Self {
+        self.max_queue = max_queue;
+        self
+    }
+
+    /// Sets maximum duration a blocking task may wait for a free slot.
+    ///
+    /// It is also used as the `Retry-After` delay of overload responses, which defaults to one
+    /// second otherwise.
+    pub fn queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = Some(timeout);
+        self
+    }
+
+    /// Returns number of blocking tasks waiting for a free slot.
+    pub fn queued(&self) -> usize {
+        self.state.queue.borrow().len()
+    }
+
+    /// Returns number of blocking tasks currently running.
+    pub fn running(&self) -> usize {
+        self.state.running.get()
+    }
+
+    /// Makes the config registered for a request the one consulted by `web::block` until the
+    /// returned guard is dropped.
+    pub(crate) fn enter(req: &HttpRequest) -> CurrentGuard {
+        let config = req.app_data::<Self>().cloned();
+        CurrentGuard(CURRENT.with(|current| current.replace(config)))
+    }
+
+    /// Returns config consulted by `web::block`, if a handler with a registered config is running.
+    pub(crate) fn current() -> Option<BlockingConfig> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Executes blocking function on a thread pool once a slot is available, returns future that
+    /// resolves to result of the function execution.
+    ///
+    /// Like [`web::block`](crate::web::block), the task is started without waiting for the
+    /// returned future to be polled. The task keeps its place in the queue, and its slot while
+    /// running, even if the returned future is dropped.
+    pub fn block<F, R>(&self, f: F) -> impl Future<Output = Result<R, BlockingError>>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let task = actix_rt::spawn(self.clone().run(f));
+        async { task.await.unwrap_or(Err(BlockingError::Gone)) }
+    }
+
+    fn overloaded(&self) -> BlockingError {
+        BlockingError::Overloaded {
+            retry_after: self.queue_timeout.unwrap_or_else(|| Duration::from_secs(1)),
+        }
+    }
+
+    /// Runs blocking function on the thread pool once a slot is available.
+    async fn run<F, R>(self, f: F) -> Result<R, BlockingError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let acquire = Acquire {
+            config: &self,
+            id: None,
+        };
+
+        let _permit = match self.queue_timeout {
+            Some(timeout) => actix_rt::time::timeout(timeout, acquire)
+                .await
+                .map_err(|_| self.overloaded())??,
+            None => acquire.await?,
+        };
+
+        actix_rt::task::spawn_blocking(f)
+            .await
+            .map_err(|_| BlockingError::Gone)
+    }
+}
+
+impl Default for BlockingConfig {
+    fn default() -> Self {
+        Self {
+            max_running: usize::MAX,
+            max_queue: usize::MAX,
+            queue_timeout: None,
+            state: Rc::new(State::default()),
+        }
+    }
+}
+
+/// Extracts the config registered closest to the handler, or a config without limits.
+impl FromRequest for BlockingConfig {
+    type Config = ();
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(req.app_data::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// Future that waits for a free slot, keeping its place in the queue until dropped.
+struct Acquire<'a> {
+    config: &'a BlockingConfig,
+    id: Option<u64>,
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<Permit, BlockingError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let config = this.config;
+        let state = &config.state;
+        let has_slot = state.running.get() < config.max_running;
+
+        let mut queue = state.queue.borrow_mut();
+
+        let acquired = match this.id {
+            None if queue.is_empty() && has_slot => true,
+
+            None => {
+                if queue.len() >= config.max_queue {
+                    return Poll::Ready(Err(config.overloaded()));
+                }
+
+                let id = state.next_id.get();
+                state.next_id.set(id.wrapping_add(1));
+                queue.push_back((id, cx.waker().clone()));
+                state.gauge_queued(true);
+                this.id = Some(id);
+
+                false
+            }
+
+            Some(id) => match queue.front() {
+                Some((front, _)) if *front == id && has_slot => {
+                    queue.pop_front();
+                    state.gauge_queued(false);
+                    this.id = None;
+                    true
+                }
+                _ => {
+                    if let Some(entry) = queue.iter_mut().find(|(i, _)| *i == id) {
+                        entry.1 = cx.waker().clone();
+                    }
+                    false
+                }
+            },
+        };
+
+        if acquired {
+            state.inc_running();
+            drop(queue);
+
+            // slots freed while this task was queued may also be free for the next one
+            if state.running.get() < config.max_running {
+                state.wake_front();
+            }
+
+            Poll::Ready(Ok(Permit {
+                state: Rc::clone(state),
+            }))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            let state = &self.config.state;
+            state.queue.borrow_mut().retain(|(i, _)| *i != id);
+            state.gauge_queued(false);
+            state.wake_front();
+        }
+    }
+}
+
+/// Running slot, released on drop.
+struct Permit {
+    state: Rc<State>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.state.dec_running();
+        self.state.wake_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse, ResponseError,
+    };
+
+    #[actix_rt::test]
+    async fn test_queue_limit() {
+        let config = BlockingConfig::new().max_running(1).max_queue(1);
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let first = config.block(move || rx.recv().unwrap());
+        let second = config.block(|| 42);
+        actix_rt::task::yield_now().await;
+
+        assert_eq!(config.running(), 1);
+        assert_eq!(config.queued(), 1);
+
+        let err = config.block(|| 0).await.unwrap_err();
+        assert!(matches!(err, BlockingError::Overloaded { .. }));
+        assert_eq!(
+            err.error_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        tx.send(()).unwrap();
+        first.await.unwrap();
+        assert_eq!(second.await.unwrap(), 42);
+
+        assert_eq!(config.running(), 0);
+        assert_eq!(config.queued(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_queue_timeout() {
+        let config = BlockingConfig::new()
+            .max_running(1)
+            .queue_timeout(Duration::from_millis(50));
+
+        let (tx, rx) = mpsc::channel::<()>();
+        let first = config.block(move || rx.recv().unwrap());
+        actix_rt::task::yield_now().await;
+
+        match config.block(|| 0).await {
+            Err(BlockingError::Overloaded { retry_after }) => {
+                assert_eq!(retry_after, Duration::from_millis(50))
+            }
+            _ => panic!("expected overloaded error"),
+        }
+        assert_eq!(config.queued(), 0);
+
+        tx.send(()).unwrap();
+        first.await.unwrap();
+        assert_eq!(config.running(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_wake_next_when_slots_free() {
+        let config = BlockingConfig::new().max_running(2);
+
+        let (tx1, rx1) = mpsc::channel::<()>();
+        let (tx2, rx2) = mpsc::channel::<()>();
+        let first = config.block(move || rx1.recv().unwrap());
+        let second = config.block(move || rx2.recv().unwrap());
+        let third = config.block(|| 3);
+        let fourth = config.block(|| 4);
+        actix_rt::task::yield_now().await;
+        assert_eq!(config.queued(), 2);
+
+        // both slots free up before the queued tasks are polled
+        tx1.send(()).unwrap();
+        tx2.send(()).unwrap();
+        first.await.unwrap();
+        second.await.unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let res = actix_rt::time::timeout(timeout, third).await;
+        assert_eq!(res.unwrap().unwrap(), 3);
+        let res = actix_rt::time::timeout(timeout, fourth).await;
+        assert_eq!(res.unwrap().unwrap(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_gauge() {
+        let gauge = BlockingGauge::default();
+        gauge.register();
+
+        let config = BlockingConfig::new().max_running(1);
+        let (tx, rx) = mpsc::channel::<()>();
+        let first = config.block(move || rx.recv().unwrap());
+        let second = config.block(|| 42);
+        actix_rt::task::yield_now().await;
+
+        assert_eq!(gauge.running(), 1);
+        assert_eq!(gauge.queued(), 1);
+
+        tx.send(()).unwrap();
+        first.await.unwrap();
+        assert_eq!(second.await.unwrap(), 42);
+
+        assert_eq!(gauge.running(), 0);
+        assert_eq!(gauge.queued(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_web_block_in_handler() {
+        async fn limited() -> Result<HttpResponse, BlockingError> {
+            let (tx, rx) = mpsc::channel::<()>();
+            let first = web::block(move || rx.recv().unwrap());
+            actix_rt::task::yield_now().await;
+
+            let second = web::block(|| ()).await;
+            tx.send(()).unwrap();
+            first.await?;
+            second?;
+
+            Ok(HttpResponse::Ok().finish())
+        }
+
+        let srv = init_service(
+            App::new()
+                .app_data(BlockingConfig::new().max_running(1).max_queue(0))
+                .route("/", web::get().to(limited)),
+        )
+        .await;
+
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // no registered config has no limits
+        let srv = init_service(App::new().route("/", web::get().to(limited))).await;
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_eager() {
+        let config = BlockingConfig::new().max_running(1);
+
+        let (tx, rx) = mpsc::channel();
+        drop(config.block(move || tx.send(42).unwrap()));
+        assert_eq!(
+            web::block(move || rx.recv_timeout(Duration::from_secs(5)))
+                .await
+                .unwrap(),
+            Ok(42)
+        );
+
+        let (tx, rx) = mpsc::channel();
+        drop(web::block(move || tx.send(42).unwrap()));
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok(42));
+    }
+
+    #[actix_rt::test]
+    async fn test_extract_per_scope() {
+        async fn limits(config: BlockingConfig) -> HttpResponse {
+            let value = config.block(|| 42).await.unwrap();
+            HttpResponse::Ok().body(format!("{} {}", config.max_running, value))
+        }
+
+        let srv = init_service(
+            App::new()
+                .app_data(BlockingConfig::new().max_running(4))
+                .service(
+                    web::scope("/reports")
+                        .app_data(BlockingConfig::new().max_running(1))
+                        .route("", web::get().to(limits)),
+                )
+                .route("/", web::get().to(limits)),
+        )
+        .await;
+
+        let res = call_service(&srv, TestRequest::with_uri("/reports").to_request()).await;
+        assert_eq!(read_body(res).await, "1 42");
+
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(read_body(res).await, "4 42");
+
+        // no registered config has no limits
+        let srv = init_service(App::new().route("/", web::get().to(limits))).await;
+        let res = call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(read_body(res).await, format!("{} 42", usize::MAX));
+    }
+}
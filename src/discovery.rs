@@ -0,0 +1,225 @@
+//! API discovery through `OPTIONS` requests, see
+//! [`App::enable_discovery`](crate::App::enable_discovery).
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    http::{
+        header::{HeaderValue, ACCESS_CONTROL_REQUEST_METHOD, ALLOW},
+        Method,
+    },
+    service::ServiceRequest,
+    HttpResponse,
+};
+
+/// Marker stored as app data by [`App::enable_discovery`](crate::App::enable_discovery).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct EnableDiscovery(pub(crate) bool);
+
+/// What a route declares about itself, listed in discovery documents.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RouteDescription {
+    pub(crate) methods: Vec<Method>,
+    pub(crate) meta: BTreeMap<String, String>,
+    pub(crate) accepts: Vec<String>,
+}
+
+/// Discovery responder of one resource.
+pub(crate) struct Discovery {
+    enabled: Option<bool>,
+    routes: Vec<RouteDescription>,
+    methods: Vec<Method>,
+}
+
+impl Discovery {
+    pub(crate) fn new(enabled: Option<bool>, routes: Vec<RouteDescription>) -> Self {
+        let mut methods = Vec::new();
+
+        for method in routes.iter().flat_map(|route| &route.methods) {
+            if !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        }
+
+        if !methods.contains(&Method::OPTIONS) {
+            methods.push(Method::OPTIONS);
+        }
+
+        Discovery {
+            enabled,
+            routes,
+            methods,
+        }
+    }
+
+    /// Returns true for `OPTIONS` requests that should be answered with the discovery document.
+    ///
+    /// CORS preflight requests are left to the resource's default service.
+    pub(crate) fn handles(&self, req: &ServiceRequest) -> bool {
+        req.method() == Method::OPTIONS
+            && !req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD)
+            && self.enabled.unwrap_or_else(|| {
+                req.app_data::<EnableDiscovery>()
+                    .map_or(false, |enabled| enabled.0)
+            })
+    }
+
+    pub(crate) fn respond(&self, req: &ServiceRequest) -> HttpResponse {
+        let methods = self.methods.iter().map(Method::as_str).collect::<Vec<_>>();
+
+        let document = Document {
+            path: req.match_pattern(),
+            methods: methods.clone(),
+            params: req.match_info().iter().map(|(name, _)| name).collect(),
+            routes: self
+                .routes
+                .iter()
+                .map(|route| RouteDocument {
+                    methods: route.methods.iter().map(Method::as_str).collect(),
+                    meta: &route.meta,
+                    accepts: &route.accepts,
+                })
+                .collect(),
+        };
+
+        let allow = methods.join(", ");
+
+        HttpResponse::Ok()
+            .insert_header((ALLOW, HeaderValue::from_str(&allow).unwrap()))
+            .json(&document)
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    path: Option<String>,
+    methods: Vec<&'a str>,
+    params: Vec<&'a str>,
+    routes: Vec<RouteDocument<'a>>,
+}
+
+#[derive(Serialize)]
+struct RouteDocument<'a> {
+    methods: Vec<&'a str>,
+    meta: &'a BTreeMap<String, String>,
+    accepts: &'a [String],
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_discovery_document() {
+        let srv = init_service(
+            App::new()
+                .enable_discovery()
+                .service(
+                    web::resource("/users/{id}")
+                        .route(web::get().meta("summary", "Get user").to(HttpResponse::Ok))
+                        .route(web::put().accepts("application/json").to(HttpResponse::Ok)),
+                )
+                .service(web::resource("/health").route(web::get().to(HttpResponse::Ok))),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/users/42")
+            .method(Method::OPTIONS)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(ALLOW).unwrap(), "GET, PUT, OPTIONS");
+
+        let doc: Value = serde_json::from_slice(&read_body(res).await).unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "path": "/users/{id}",
+                "methods": ["GET", "PUT", "OPTIONS"],
+                "params": ["id"],
+                "routes": [
+                    { "methods": ["GET"], "meta": { "summary": "Get user" }, "accepts": [] },
+                    { "methods": ["PUT"], "meta": {}, "accepts": ["application/json"] },
+                ],
+            })
+        );
+
+        let req = TestRequest::with_uri("/health")
+            .method(Method::OPTIONS)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.headers().get(ALLOW).unwrap(), "GET, OPTIONS");
+
+        let doc: Value = serde_json::from_slice(&read_body(res).await).unwrap();
+        assert_eq!(doc["params"], json!([]));
+        assert_eq!(
+            doc["routes"],
+            json!([{ "methods": ["GET"], "meta": {}, "accepts": [] }])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_discovery_precedence() {
+        let srv = init_service(
+            App::new()
+                .enable_discovery()
+                .service(
+                    web::resource("/custom")
+                        .route(web::get().to(HttpResponse::Ok))
+                        .route(web::method(Method::OPTIONS).to(HttpResponse::NoContent)),
+                )
+                .service(
+                    web::resource("/off")
+                        .discovery(false)
+                        .route(web::get().to(HttpResponse::Ok)),
+                ),
+        )
+        .await;
+
+        // user registered OPTIONS route wins
+        let req = TestRequest::with_uri("/custom")
+            .method(Method::OPTIONS)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let req = TestRequest::with_uri("/off")
+            .method(Method::OPTIONS)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        // CORS preflight is left alone
+        let srv = init_service(
+            App::new()
+                .enable_discovery()
+                .service(web::resource("/").route(web::get().to(HttpResponse::Ok))),
+        )
+        .await;
+        let req = TestRequest::default()
+            .method(Method::OPTIONS)
+            .insert_header((ACCESS_CONTROL_REQUEST_METHOD, "GET"))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        // disabled by default
+        let srv = init_service(
+            App::new().service(web::resource("/").route(web::get().to(HttpResponse::Ok))),
+        )
+        .await;
+        let req = TestRequest::default().method(Method::OPTIONS).to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+}
@@ -5,7 +5,46 @@ use derive_more::{Display, Error, From};
 use serde_json::error::Error as JsonError;
 use url::ParseError as UrlParseError;
 
-use crate::{http::StatusCode, HttpResponse};
+use crate::{
+    extract::ExtractionErrorKind,
+    http::{
+        header::{HeaderValue, WWW_AUTHENTICATE},
+        StatusCode,
+    },
+    HttpResponse,
+};
+
+/// How much detail of server errors is included in response bodies.
+///
+/// Set for an application with [`App::error_detail`](crate::App::error_detail). The default
+/// response body of an error is its `Display` text, which may contain internals like database
+/// messages or file paths. When details are hidden, the bodies of responses created from errors
+/// with a `5xx` status are replaced, while the full error is still logged. Errors whose
+/// [`is_user_facing`](ResponseError::is_user_facing) method returns true are left unchanged.
+///
+/// The default is `Full` in debug builds and `None` in release builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorDetail {
+    /// Response bodies are left unchanged.
+    Full,
+
+    /// Response bodies are replaced with the status' canonical reason, followed by the value of
+    /// the request's `X-Request-Id` header if it has one.
+    MessageOnly,
+
+    /// Response bodies are removed.
+    None,
+}
+
+impl Default for ErrorDetail {
+    fn default() -> Self {
+        if cfg!(debug_assertions) {
+            ErrorDetail::Full
+        } else {
+            ErrorDetail::None
+        }
+    }
+}
 
 /// Errors which can occur when attempting to generate resource uri.
 #[derive(Debug, PartialEq, Display, From)]
@@ -55,17 +94,78 @@ pub enum UrlencodedError {
     #[display(fmt = "Parse error.")]
     Parse,
 
+    /// Payload could not be deserialized into the form type.
+    #[display(fmt = "URL encoded form deserialize error: {}", _0)]
+    Deserialize(serde::de::value::Error),
+
+    /// Payload bytes are not valid in the charset declared by the request.
+    #[display(fmt = "URL encoded payload does not match its declared charset.")]
+    Encoding,
+
+    /// URL decoded key of a form field is longer than allowed.
+    #[display(
+        fmt = "URL encoded form key is longer ({} bytes) than allowed (limit: {} bytes).",
+        len,
+        limit
+    )]
+    KeyOverflow { len: usize, limit: usize },
+
+    /// URL decoded value of a form field is longer than allowed.
+    #[display(
+        fmt = "URL encoded form value of `{}` is longer ({} bytes) than allowed (limit: {} bytes).",
+        key,
+        len,
+        limit
+    )]
+    ValueOverflow {
+        key: String,
+        len: usize,
+        limit: usize,
+    },
+
+    /// Request announces a body but has no payload.
+    #[display(
+        fmt = "Request has no payload. The request announces a body that is not available to the Form extractor."
+    )]
+    NoPayload,
+
     /// Payload error.
     #[display(fmt = "Error that occur during reading payload: {}.", _0)]
     Payload(PayloadError),
 }
 
+impl UrlencodedError {
+    pub(crate) fn kind(&self) -> ExtractionErrorKind {
+        match self {
+            UrlencodedError::Overflow { .. }
+            | UrlencodedError::KeyOverflow { .. }
+            | UrlencodedError::ValueOverflow { .. }
+            | UrlencodedError::Payload(PayloadError::Overflow) => ExtractionErrorKind::Overflow,
+            UrlencodedError::ContentType => ExtractionErrorKind::ContentType,
+            UrlencodedError::Parse | UrlencodedError::Deserialize(_) => {
+                ExtractionErrorKind::Deserialize
+            }
+            UrlencodedError::NoPayload => ExtractionErrorKind::NoPayload,
+            UrlencodedError::Chunked
+            | UrlencodedError::Encoding
+            | UrlencodedError::UnknownLength
+            | UrlencodedError::Payload(_) => ExtractionErrorKind::Payload,
+        }
+    }
+}
+
 /// Return `BadRequest` for `UrlencodedError`
 impl ResponseError for UrlencodedError {
     fn status_code(&self) -> StatusCode {
         match *self {
-            UrlencodedError::Overflow { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            UrlencodedError::Overflow { .. }
+            | UrlencodedError::KeyOverflow { .. }
+            | UrlencodedError::ValueOverflow { .. }
+            | UrlencodedError::Payload(PayloadError::Overflow) => StatusCode::PAYLOAD_TOO_LARGE,
             UrlencodedError::UnknownLength => StatusCode::LENGTH_REQUIRED,
+            UrlencodedError::Payload(PayloadError::BudgetExhausted) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -83,11 +183,42 @@ pub enum JsonPayloadError {
     /// Deserialize error
     #[display(fmt = "Json deserialize error: {}", _0)]
     Deserialize(JsonError),
+    /// Deserialize error at a specific field path, eg. `items[0].id`
+    #[display(fmt = "Json deserialize error at `{}`: {}", path, source)]
+    #[from(ignore)]
+    DeserializeAt { path: String, source: JsonError },
+    /// Request has no payload
+    #[display(
+        fmt = "Request has no payload. The Json extractor can only be used for requests with a body."
+    )]
+    NoPayload,
     /// Payload error
     #[display(fmt = "Error that occur during reading payload: {}", _0)]
     Payload(PayloadError),
 }
 
+impl JsonPayloadError {
+    /// Returns path of the field that failed to deserialize, if known.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            JsonPayloadError::DeserializeAt { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> ExtractionErrorKind {
+        match self {
+            JsonPayloadError::Overflow => ExtractionErrorKind::Overflow,
+            JsonPayloadError::ContentType => ExtractionErrorKind::ContentType,
+            JsonPayloadError::Deserialize(_) | JsonPayloadError::DeserializeAt { .. } => {
+                ExtractionErrorKind::Deserialize
+            }
+            JsonPayloadError::NoPayload => ExtractionErrorKind::NoPayload,
+            JsonPayloadError::Payload(_) => ExtractionErrorKind::Payload,
+        }
+    }
+}
+
 impl std::error::Error for JsonPayloadError {}
 
 /// Return `BadRequest` for `JsonPayloadError`
@@ -95,6 +226,12 @@ impl ResponseError for JsonPayloadError {
     fn error_response(&self) -> HttpResponse {
         match *self {
             JsonPayloadError::Overflow => HttpResponse::new(StatusCode::PAYLOAD_TOO_LARGE),
+            JsonPayloadError::NoPayload => HttpResponse::build(StatusCode::BAD_REQUEST)
+                .content_type(mime::TEXT_PLAIN_UTF_8)
+                .body(self.to_string()),
+            JsonPayloadError::Payload(PayloadError::BudgetExhausted) => {
+                HttpResponse::new(StatusCode::SERVICE_UNAVAILABLE)
+            }
             _ => HttpResponse::new(StatusCode::BAD_REQUEST),
         }
     }
@@ -117,6 +254,39 @@ impl ResponseError for PathError {
     }
 }
 
+/// Error returned by [`PathUuid`](crate::web::PathUuid) when a path segment is not a valid UUID.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Display)]
+#[display(fmt = "Invalid UUID in path: {:?}", value)]
+pub struct PathUuidError {
+    value: String,
+}
+
+#[cfg(feature = "uuid")]
+impl PathUuidError {
+    pub(crate) fn new(value: impl Into<String>) -> Self {
+        PathUuidError {
+            value: value.into(),
+        }
+    }
+
+    /// Returns the path segment that failed to parse.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl std::error::Error for PathUuidError {}
+
+/// Return `BadRequest` for `PathUuidError`
+#[cfg(feature = "uuid")]
+impl ResponseError for PathUuidError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
 /// A set of errors that can occur during parsing query strings.
 #[derive(Debug, Display, Error, From)]
 pub enum QueryPayloadError {
@@ -132,6 +302,81 @@ impl ResponseError for QueryPayloadError {
     }
 }
 
+/// Errors which can occur when extracting credentials from the `Authorization` header.
+///
+/// Both variants carry the `WWW-Authenticate` challenge sent with the `401 Unauthorized` response.
+#[derive(Debug, Display)]
+pub enum AuthenticationError {
+    /// Credentials of the expected scheme are missing.
+    #[display(fmt = "Authorization header is missing")]
+    Missing { challenge: String },
+
+    /// Authorization header could not be parsed.
+    #[display(fmt = "Authorization header is malformed")]
+    Malformed { challenge: String },
+}
+
+impl AuthenticationError {
+    /// Returns the `WWW-Authenticate` challenge for this error.
+    pub fn challenge(&self) -> &str {
+        match self {
+            AuthenticationError::Missing { challenge }
+            | AuthenticationError::Malformed { challenge } => challenge,
+        }
+    }
+}
+
+impl std::error::Error for AuthenticationError {}
+
+/// Return `Unauthorized` with a `WWW-Authenticate` challenge for `AuthenticationError`
+impl ResponseError for AuthenticationError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::UNAUTHORIZED
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let mut res = HttpResponse::build(self.status_code())
+            .content_type(mime::TEXT_PLAIN_UTF_8)
+            .body(self.to_string());
+
+        if let Ok(challenge) = HeaderValue::from_str(self.challenge()) {
+            res.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+        }
+
+        res
+    }
+}
+
+/// Errors which can occur when verifying a token with the
+/// [`CsrfToken`](crate::web::CsrfToken) extractor.
+#[derive(Debug, Display)]
+pub enum CsrfError {
+    /// No token was sent with the request.
+    #[display(fmt = "CSRF token is missing")]
+    Missing,
+
+    /// Token does not match the token expected for the request.
+    #[display(fmt = "CSRF token is invalid")]
+    Mismatch,
+
+    /// Form body could not be read while looking for the token.
+    #[display(fmt = "Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+impl std::error::Error for CsrfError {}
+
+/// Return `Forbidden` for `CsrfError`, unless reading the form body failed.
+impl ResponseError for CsrfError {
+    fn status_code(&self) -> StatusCode {
+        match *self {
+            CsrfError::Missing | CsrfError::Mismatch => StatusCode::FORBIDDEN,
+            CsrfError::Payload(PayloadError::Overflow) => StatusCode::PAYLOAD_TOO_LARGE,
+            CsrfError::Payload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
 /// Error type returned when reading body as lines.
 #[derive(From, Display, Debug)]
 pub enum ReadlinesError {
@@ -175,6 +420,9 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::LENGTH_REQUIRED);
         let resp: HttpResponse = UrlencodedError::ContentType.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp: HttpResponse =
+            UrlencodedError::Payload(PayloadError::BudgetExhausted).error_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
     }
 
     #[test]
@@ -183,6 +431,9 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
         let resp: HttpResponse = JsonPayloadError::ContentType.error_response();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp: HttpResponse =
+            JsonPayloadError::Payload(PayloadError::BudgetExhausted).error_response();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
     }
 
     #[test]
@@ -1,8 +1,12 @@
 //! Request extractors
 
 use std::{
+    collections::HashMap,
+    fmt,
     future::Future,
     pin::Pin,
+    rc::Rc,
+    sync::Mutex,
     task::{Context, Poll},
 };
 
@@ -10,8 +14,10 @@ use futures_util::{
     future::{ready, Ready},
     ready,
 };
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
 
-use crate::{dev::Payload, Error, HttpRequest};
+use crate::{data::Data, dev::Payload, http::header, Error, HttpRequest};
 
 /// Trait implemented by types that can be extracted from request.
 ///
@@ -31,7 +37,9 @@ pub trait FromRequest: Sized {
 
     /// Create a Self from request head asynchronously.
     ///
-    /// This method is short for `T::from_request(req, &mut Payload::None)`.
+    /// This method is short for `T::from_request(req, &mut Payload::None)`. Body extractors treat
+    /// the missing payload as an empty body: `Bytes` and `String` resolve to empty values and
+    /// `Form` to an empty form, while `Json` fails with a `NoPayload` error.
     fn extract(req: &HttpRequest) -> Self::Future {
         Self::from_request(req, &mut Payload::None)
     }
@@ -220,6 +228,393 @@ where
     }
 }
 
+/// Extract a boxed value, for storing extracted values of different types together.
+///
+/// Configuration and errors are those of the inner extractor.
+///
+/// ## Example
+///
+/// ```rust
+/// use actix_web::{web, App};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     username: String,
+/// }
+///
+/// /// extract boxed form data
+/// async fn index(form: Box<web::Form<Info>>) -> String {
+///     format!("Welcome {}!", form.username)
+/// }
+///
+/// fn main() {
+///     let app = App::new().service(
+///         web::resource("/index.html").route(web::post().to(index))
+///     );
+/// }
+/// ```
+impl<T> FromRequest for Box<T>
+where
+    T: FromRequest,
+{
+    type Error = T::Error;
+    type Future = FromRequestBoxFuture<T::Future>;
+    type Config = T::Config;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        FromRequestBoxFuture {
+            fut: T::from_request(req, payload),
+        }
+    }
+}
+
+#[pin_project::pin_project]
+pub struct FromRequestBoxFuture<Fut> {
+    #[pin]
+    fut: Fut,
+}
+
+impl<Fut, T, E> Future for FromRequestBoxFuture<Fut>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    type Output = Result<Box<T>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.fut.poll(cx));
+        Poll::Ready(res.map(Box::new))
+    }
+}
+
+/// Opt-in diagnostics for requests rejected by an extractor.
+///
+/// When registered through one of the `.app_data()` methods, every extraction failure on the
+/// covered routes is logged (target `actix_web::extract`) with the matched route pattern, the
+/// extractor type, the error, the declared content type and length and, for extractors that
+/// buffer the request body (currently `Json`), the limit in play and a truncated sample of the
+/// buffered payload. The whole payload is passed through the configured redaction patterns before
+/// it is truncated to the sample size.
+///
+/// ```
+/// use actix_web::{web, App};
+///
+/// let app = App::new().app_data(
+///     web::ExtractionDebug::default()
+///         .sample_size(128)
+///         .redact(r#""password"\s*:\s*"[^"]*""#),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ExtractionDebug {
+    sample_size: usize,
+    redactions: Vec<Regex>,
+    hook: Option<Rc<dyn Fn(&ExtractionFailure)>>,
+}
+
+impl ExtractionDebug {
+    /// Set maximum number of payload bytes included in samples. By default 256 bytes are kept.
+    pub fn sample_size(mut self, size: usize) -> Self {
+        self.sample_size = size;
+        self
+    }
+
+    /// Add a pattern whose matches are replaced with `[REDACTED]` in payload samples.
+    ///
+    /// # Panics
+    /// Panics if `pattern` is not a valid regular expression.
+    pub fn redact(mut self, pattern: &str) -> Self {
+        self.redactions
+            .push(Regex::new(pattern).expect("Invalid redaction pattern"));
+        self
+    }
+
+    /// Set a function that is called with every failure record, in addition to it being logged.
+    pub fn on_failure<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&ExtractionFailure) + 'static,
+    {
+        self.hook = Some(Rc::new(f));
+        self
+    }
+
+    /// Store a redacted sample of a buffered payload for the failure record of this request.
+    pub(crate) fn attach_sample(&self, req: &HttpRequest, body: &[u8], limit: usize) {
+        req.extensions_mut().insert(PayloadSample {
+            sample: self.sample(body),
+            limit,
+        });
+    }
+
+    pub(crate) fn record(&self, req: &HttpRequest, extractor: &'static str, err: &Error) {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(ToOwned::to_owned);
+
+        let content_length = req
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+
+        let (limit, payload_sample) = match req.extensions_mut().remove::<PayloadSample>() {
+            Some(sample) => (Some(sample.limit), Some(sample.sample)),
+            None => (None, None),
+        };
+
+        let failure = ExtractionFailure {
+            route: req.match_pattern(),
+            extractor,
+            error: err.to_string(),
+            content_type,
+            content_length,
+            limit,
+            payload_sample,
+        };
+
+        log::warn!(target: "actix_web::extract", "{}", failure);
+
+        if let Some(ref hook) = self.hook {
+            (hook)(&failure);
+        }
+    }
+
+    /// Redacts the whole payload, then truncates it to the sample size.
+    ///
+    /// Truncating first could cut a secret in half so that the patterns no longer match it.
+    fn sample(&self, body: &[u8]) -> String {
+        let mut sample = String::from_utf8_lossy(body).into_owned();
+
+        for re in &self.redactions {
+            sample = re.replace_all(&sample, "[REDACTED]").into_owned();
+        }
+
+        let mut len = sample.len().min(self.sample_size);
+        while !sample.is_char_boundary(len) {
+            len -= 1;
+        }
+        sample.truncate(len);
+
+        sample
+    }
+}
+
+impl Default for ExtractionDebug {
+    fn default() -> Self {
+        ExtractionDebug {
+            sample_size: 256,
+            redactions: Vec::new(),
+            hook: None,
+        }
+    }
+}
+
+/// Redacted payload prefix attached to request extensions by body extractors.
+struct PayloadSample {
+    sample: String,
+    limit: usize,
+}
+
+/// Details of a request rejected by an extractor, as reported by [`ExtractionDebug`].
+#[derive(Debug, Clone)]
+pub struct ExtractionFailure {
+    /// Pattern of the matched route, if any.
+    pub route: Option<String>,
+
+    /// Type name of the extractor that failed.
+    pub extractor: &'static str,
+
+    /// Rendered extraction error.
+    pub error: String,
+
+    /// Declared `Content-Type` of the request.
+    pub content_type: Option<String>,
+
+    /// Declared `Content-Length` of the request.
+    pub content_length: Option<u64>,
+
+    /// Payload size limit applied by the extractor, if it buffered the payload.
+    pub limit: Option<usize>,
+
+    /// Truncated and redacted prefix of the buffered payload, if any.
+    pub payload_sample: Option<String>,
+}
+
+impl fmt::Display for ExtractionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Extraction failed; route: {:?}, extractor: {}, error: {}, content type: {:?}, \
+             content length: {:?}, limit: {:?}, payload sample: {:?}",
+            self.route,
+            self.extractor,
+            self.error,
+            self.content_type,
+            self.content_length,
+            self.limit,
+            self.payload_sample,
+        )
+    }
+}
+
+/// Hook for observing the outcome of `Json`, `Form` and `Query` extraction.
+///
+/// Register an implementation as `Data<dyn ExtractionMetrics>` app data to be notified of every
+/// extraction on the covered routes, e.g. to detect clients that drift from the expected schema.
+/// Routes are reported by their matched pattern. All methods do nothing by default.
+///
+/// Detecting unknown fields requires deserializing the request data a second time, which only
+/// happens while a hook is registered.
+///
+/// ```
+/// use std::sync::Arc;
+/// use actix_web::{web, App};
+///
+/// let counter = Arc::new(web::ExtractionCounter::new());
+///
+/// let app = App::new().app_data(web::Data::from(
+///     Arc::clone(&counter) as Arc<dyn web::ExtractionMetrics>
+/// ));
+/// ```
+pub trait ExtractionMetrics {
+    /// Called when an extractor succeeds.
+    fn on_success(&self, route: Option<&str>, extractor: &'static str) {
+        let _ = (route, extractor);
+    }
+
+    /// Called after a successful extraction for every field of the request data that was ignored
+    /// by the target type, e.g. `address.zip`.
+    fn on_unknown_field(&self, route: Option<&str>, field: &str) {
+        let _ = (route, field);
+    }
+
+    /// Called when an extractor fails.
+    fn on_failure(
+        &self,
+        route: Option<&str>,
+        extractor: &'static str,
+        kind: ExtractionErrorKind,
+    ) {
+        let _ = (route, extractor, kind);
+    }
+}
+
+/// Kind of extraction failure reported to [`ExtractionMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExtractionErrorKind {
+    /// Content type of the request is not accepted by the extractor.
+    ContentType,
+
+    /// Payload is larger than the configured limit.
+    Overflow,
+
+    /// Payload could not be read.
+    Payload,
+
+    /// Data could not be deserialized into the target type.
+    Deserialize,
+
+    /// Request has no payload, so there is no body to extract from.
+    NoPayload,
+}
+
+/// [`ExtractionMetrics`] implementation that counts extraction outcomes per route.
+#[derive(Debug, Default)]
+pub struct ExtractionCounter {
+    routes: Mutex<HashMap<String, ExtractionCounts>>,
+}
+
+/// Extraction outcomes of a route, as counted by [`ExtractionCounter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtractionCounts {
+    /// Number of successful extractions.
+    pub successes: usize,
+
+    /// Number of unknown fields sent by clients.
+    pub unknown_fields: usize,
+
+    /// Number of failed extractions.
+    pub failures: usize,
+}
+
+impl ExtractionCounter {
+    /// Creates counter with no recorded extractions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns counts of a route pattern. Requests that did not match a route are counted under an
+    /// empty pattern.
+    pub fn route(&self, route: &str) -> ExtractionCounts {
+        let routes = self.routes.lock().unwrap();
+        routes.get(route).copied().unwrap_or_default()
+    }
+
+    fn update(&self, route: Option<&str>, f: impl FnOnce(&mut ExtractionCounts)) {
+        let mut routes = self.routes.lock().unwrap();
+        f(routes.entry(route.unwrap_or("").to_owned()).or_default());
+    }
+}
+
+impl ExtractionMetrics for ExtractionCounter {
+    fn on_success(&self, route: Option<&str>, _: &'static str) {
+        self.update(route, |counts| counts.successes += 1);
+    }
+
+    fn on_unknown_field(&self, route: Option<&str>, _: &str) {
+        self.update(route, |counts| counts.unknown_fields += 1);
+    }
+
+    fn on_failure(&self, route: Option<&str>, _: &'static str, _: ExtractionErrorKind) {
+        self.update(route, |counts| counts.failures += 1);
+    }
+}
+
+/// Reports extraction outcomes of a request to the registered [`ExtractionMetrics`].
+#[derive(Clone)]
+pub(crate) struct ExtractionReporter {
+    metrics: Data<dyn ExtractionMetrics>,
+    route: Option<String>,
+}
+
+impl ExtractionReporter {
+    /// Returns reporter if an `ExtractionMetrics` hook is registered for the request.
+    pub(crate) fn from_req(req: &HttpRequest) -> Option<Self> {
+        req.app_data::<Data<dyn ExtractionMetrics>>()
+            .map(|metrics| ExtractionReporter {
+                metrics: metrics.clone(),
+                route: req.match_pattern(),
+            })
+    }
+
+    pub(crate) fn success<T>(&self) {
+        self.metrics
+            .on_success(self.route.as_deref(), std::any::type_name::<T>());
+    }
+
+    pub(crate) fn failure<T>(&self, kind: ExtractionErrorKind) {
+        self.metrics
+            .on_failure(self.route.as_deref(), std::any::type_name::<T>(), kind);
+    }
+
+    /// Deserializes `T` again to find the fields it ignores.
+    pub(crate) fn unknown_fields<'de, T, D>(&self, de: D)
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let route = self.route.as_deref();
+
+        let _: Result<T, _> = serde_ignored::deserialize(de, |path| {
+            self.metrics.on_unknown_field(route, &path.to_string())
+        });
+    }
+}
+
 #[doc(hidden)]
 impl FromRequest for () {
     type Error = Error;
@@ -258,6 +653,10 @@ macro_rules! tuple_from_req ({$fut_type:ident, $(($n:tt, $T:ident)),+} => {
         struct FutWrapper<$($T: FromRequest),+>($(#[pin] $T::Future),+);
 
         /// FromRequest implementation for tuple
+        ///
+        /// The futures of all members are created up front and polled together, so extractors
+        /// that only read the request run concurrently with ones reading the body. The payload
+        /// is handed out in member order; the first extractor that takes it consumes it.
         #[doc(hidden)]
         #[allow(unused_parens)]
         impl<$($T: FromRequest + 'static),+> FromRequest for ($($T,)+)
@@ -336,9 +735,14 @@ mod tests {
     use bytes::Bytes;
     use serde_derive::Deserialize;
 
+    use std::cell::{Cell, RefCell};
+    use std::marker::PhantomData;
+
     use super::*;
-    use crate::test::TestRequest;
-    use crate::types::{Form, FormConfig};
+    use crate::http::StatusCode;
+    use crate::test::{call_service, init_service, TestRequest};
+    use crate::types::{Form, FormConfig, Json};
+    use crate::{web, App};
 
     #[derive(Deserialize, Debug, PartialEq)]
     struct Info {
@@ -415,4 +819,320 @@ mod tests {
             .unwrap();
         assert!(r.is_err());
     }
+
+    thread_local! {
+        static IN_FLIGHT: Cell<usize> = Cell::new(0);
+        static MAX_IN_FLIGHT: Cell<usize> = Cell::new(0);
+    }
+
+    /// Extractor whose future stays pending for one poll, counting overlapping extractions.
+    struct Probe<T>(PhantomData<T>);
+
+    struct ProbeFuture<T> {
+        started: bool,
+        _t: PhantomData<T>,
+    }
+
+    impl<T: Unpin> Future for ProbeFuture<T> {
+        type Output = Result<Probe<T>, Error>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.started {
+                IN_FLIGHT.with(|n| n.set(n.get() - 1));
+                return Poll::Ready(Ok(Probe(PhantomData)));
+            }
+
+            self.started = true;
+            let n = IN_FLIGHT.with(|n| {
+                n.set(n.get() + 1);
+                n.get()
+            });
+            MAX_IN_FLIGHT.with(|max| max.set(max.get().max(n)));
+
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    impl<T: Unpin + 'static> FromRequest for Probe<T> {
+        type Error = Error;
+        type Future = ProbeFuture<T>;
+        type Config = ();
+
+        fn from_request(_: &HttpRequest, _: &mut Payload) -> Self::Future {
+            ProbeFuture {
+                started: false,
+                _t: PhantomData,
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_tuple_extractors_overlap() {
+        struct First;
+        struct Second;
+
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        <(Probe<First>, Probe<Second>)>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+
+        assert_eq!(IN_FLIGHT.with(|n| n.get()), 0);
+        assert_eq!(MAX_IN_FLIGHT.with(|max| max.get()), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_box() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((header::CONTENT_LENGTH, 11))
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+
+        let r = Box::<Form<Info>>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(r.hello, "world");
+        assert_eq!(
+            *r,
+            Form(Info {
+                hello: "world".into()
+            })
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((header::CONTENT_LENGTH, 9))
+            .set_payload(Bytes::from_static(b"bye=world"))
+            .to_http_parts();
+
+        assert!(Box::<Form<Info>>::from_request(&req, &mut pl)
+            .await
+            .is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_extraction_debug_json() {
+        let failures = Rc::new(RefCell::new(Vec::new()));
+        let failures2 = Rc::clone(&failures);
+
+        let srv = init_service(
+            App::new()
+                .app_data(
+                    ExtractionDebug::default()
+                        .sample_size(32)
+                        .redact(r#""password":"[^"]*""#)
+                        .on_failure(move |failure| {
+                            failures2.borrow_mut().push(failure.clone())
+                        }),
+                )
+                .route(
+                    "/users/{id}",
+                    web::post().to(|_: Json<Info>| async { "never" }),
+                ),
+        )
+        .await;
+
+        let payload = r#"{"password":"hunter2","hello":42,"padding":"xxxxxxxxxxxxxxxx"}"#;
+        let req = TestRequest::post()
+            .uri("/users/1")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .insert_header((header::CONTENT_LENGTH, payload.len()))
+            .set_payload(payload)
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let failures = failures.borrow();
+        assert_eq!(failures.len(), 1);
+
+        let failure = &failures[0];
+        assert_eq!(failure.route.as_deref(), Some("/users/{id}"));
+        assert!(failure.extractor.contains("Json"));
+        assert!(failure.error.contains("Json deserialize error"));
+        assert_eq!(failure.content_type.as_deref(), Some("application/json"));
+        assert_eq!(failure.content_length, Some(payload.len() as u64));
+        assert_eq!(failure.limit, Some(32_768));
+        assert_eq!(
+            failure.payload_sample.as_deref(),
+            Some(r#"{[REDACTED],"hello":42,"padding""#)
+        );
+    }
+
+    #[test]
+    fn test_extraction_debug_sample() {
+        let debug = ExtractionDebug::default()
+            .sample_size(16)
+            .redact(r#""password":"[^"]*""#);
+
+        // secret crossing the sample boundary is redacted
+        assert_eq!(debug.sample(br#"{"password":"hunter2"}"#), "{[REDACTED]}");
+        assert_eq!(
+            debug.sample(br#"{"id":1,"password":"hunter2"}"#),
+            r#"{"id":1,[REDACTE"#
+        );
+
+        // truncated on char boundary
+        let debug = ExtractionDebug::default().sample_size(2);
+        assert_eq!(debug.sample("aé".as_bytes()), "a");
+    }
+
+    #[actix_rt::test]
+    async fn test_extraction_debug_not_configured() {
+        let srv = init_service(
+            App::new().route("/", web::post().to(|_: Json<Info>| async { "never" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload("{}")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_rt::test]
+    async fn test_extraction_metrics() {
+        use std::sync::Arc;
+
+        let counter = Arc::new(ExtractionCounter::new());
+
+        let srv = init_service(
+            App::new()
+                .app_data(Data::from(
+                    Arc::clone(&counter) as Arc<dyn ExtractionMetrics>
+                ))
+                .route(
+                    "/users/{id}",
+                    web::post().to(|_: Json<Info>| async { "ok" }),
+                )
+                .route(
+                    "/search",
+                    web::get().to(|_: crate::types::Query<Info>| async { "ok" }),
+                )
+                .route(
+                    "/forward",
+                    web::get().to(|_: crate::types::QueryWithRest<Info>| async { "ok" }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/users/1")
+            .set_json(&serde_json::json!({ "hello": "world", "extra": 1, "more": 2 }))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::post()
+            .uri("/users/1")
+            .set_json(&serde_json::json!({ "hello": 42 }))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        assert_eq!(
+            counter.route("/users/{id}"),
+            ExtractionCounts {
+                successes: 1,
+                unknown_fields: 2,
+                failures: 1,
+            }
+        );
+
+        let req = TestRequest::get()
+            .uri("/search?hello=world&page=2")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::get().uri("/search").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        assert_eq!(
+            counter.route("/search"),
+            ExtractionCounts {
+                successes: 1,
+                unknown_fields: 1,
+                failures: 1,
+            }
+        );
+
+        let req = TestRequest::get()
+            .uri("/forward?hello=world&page=2")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::get().uri("/forward").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // parameters kept by the extractor are not unknown
+        assert_eq!(
+            counter.route("/forward"),
+            ExtractionCounts {
+                successes: 1,
+                unknown_fields: 0,
+                failures: 1,
+            }
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_extraction_metrics_form() {
+        #[derive(Default)]
+        struct Recorder(std::sync::Mutex<Vec<String>>);
+
+        impl ExtractionMetrics for Recorder {
+            fn on_unknown_field(&self, route: Option<&str>, field: &str) {
+                let event = format!("unknown {:?} {}", route, field);
+                self.0.lock().unwrap().push(event);
+            }
+
+            fn on_failure(
+                &self,
+                route: Option<&str>,
+                _: &'static str,
+                kind: ExtractionErrorKind,
+            ) {
+                let event = format!("failure {:?} {:?}", route, kind);
+                self.0.lock().unwrap().push(event);
+            }
+        }
+
+        let recorder = std::sync::Arc::new(Recorder::default());
+
+        let srv = init_service(
+            App::new()
+                .app_data(Data::from(
+                    std::sync::Arc::clone(&recorder) as std::sync::Arc<dyn ExtractionMetrics>
+                ))
+                .route("/form", web::post().to(|_: Form<Info>| async { "ok" })),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/form")
+            .insert_header((header::CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload("hello=world&color=red")
+            .to_request();
+        call_service(&srv, req).await;
+
+        let req = TestRequest::post()
+            .uri("/form")
+            .set_payload("hello=world")
+            .to_request();
+        call_service(&srv, req).await;
+
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            vec![
+                r#"unknown Some("/form") color"#.to_owned(),
+                r#"failure Some("/form") ContentType"#.to_owned(),
+            ]
+        );
+    }
 }
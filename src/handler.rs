@@ -3,15 +3,16 @@ use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-use actix_http::{Error, Response};
+use actix_http::{Error, KeepAlivePolicy, Response};
 use actix_service::{Service, ServiceFactory};
 use futures_util::future::{ready, Ready};
 use futures_util::ready;
 use pin_project::pin_project;
 
-use crate::extract::FromRequest;
+use crate::blocking::BlockingConfig;
+use crate::extract::{ExtractionDebug, FromRequest};
 use crate::request::HttpRequest;
-use crate::responder::Responder;
+use crate::responder::{Responder, ResponsePostProcessors};
 use crate::service::{ServiceRequest, ServiceResponse};
 
 ///  A request handler is an async function that accepts zero or more parameters that can be
@@ -154,21 +155,35 @@ where
                 HandlerProj::Extract(fut, req, handle) => {
                     match ready!(fut.poll(cx)) {
                         Ok(item) => {
-                            let fut = handle.call(item);
+                            let fut = {
+                                let _blocking = BlockingConfig::enter(req.as_ref().unwrap());
+                                handle.call(item)
+                            };
                             let state = HandlerServiceFuture::Handle(fut, req.take());
                             self.as_mut().set(state);
                         }
                         Err(e) => {
-                            let res: Response = e.into().into();
                             let req = req.take().unwrap();
+                            let err: Error = e.into();
+
+                            if let Some(debug) = req.app_data::<ExtractionDebug>() {
+                                debug.record(&req, std::any::type_name::<T>(), &err);
+                            }
+
+                            let mut res: Response = err.into();
+                            post_process(&req, &mut res);
                             return Poll::Ready(Ok(ServiceResponse::new(req, res)));
                         }
                     };
                 }
                 HandlerProj::Handle(fut, req) => {
-                    let res = ready!(fut.poll(cx));
+                    let res = {
+                        let _blocking = BlockingConfig::enter(req.as_ref().unwrap());
+                        ready!(fut.poll(cx))
+                    };
                     let req = req.take().unwrap();
-                    let res = res.respond_to(&req);
+                    let mut res = res.respond_to_owned(req.clone());
+                    post_process(&req, &mut res);
                     return Poll::Ready(Ok(ServiceResponse::new(req, res)));
                 }
             }
@@ -176,6 +191,21 @@ where
     }
 }
 
+/// Applies the keep-alive policy and runs the post-processors registered for the request's route
+/// on the response.
+fn post_process(req: &HttpRequest, res: &mut Response) {
+    // a policy set on the response takes precedence over the route's
+    if let Some(policy) = req.app_data::<KeepAlivePolicy>() {
+        if !res.extensions().contains::<KeepAlivePolicy>() {
+            res.extensions_mut().insert(*policy);
+        }
+    }
+
+    if let Some(processors) = req.app_data::<ResponsePostProcessors>() {
+        processors.apply(res);
+    }
+}
+
 /// FromRequest trait impl for tuples
 macro_rules! factory_tuple ({ $(($n:tt, $T:ident)),+} => {
     impl<Func, $($T,)+ Res> Handler<($($T,)+), Res> for Func
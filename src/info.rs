@@ -6,6 +6,11 @@ use crate::http::header::{self, HeaderName};
 const X_FORWARDED_FOR: &[u8] = b"x-forwarded-for";
 const X_FORWARDED_HOST: &[u8] = b"x-forwarded-host";
 const X_FORWARDED_PROTO: &[u8] = b"x-forwarded-proto";
+const X_FORWARDED_PREFIX: &[u8] = b"x-forwarded-prefix";
+
+/// Marker stored as app data by [`App::trust_forwarded_prefix`](crate::App::trust_forwarded_prefix).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TrustForwardedPrefix(pub(crate) bool);
 
 /// `HttpRequest` connection information
 #[derive(Debug, Clone, Default)]
@@ -14,19 +19,31 @@ pub struct ConnectionInfo {
     host: String,
     realip_remote_addr: Option<String>,
     remote_addr: Option<String>,
+    prefix: String,
 }
 
 impl ConnectionInfo {
     /// Create *ConnectionInfo* instance for a request.
+    ///
+    /// The `X-Forwarded-Prefix` header is not trusted by connection info created this way.
     pub fn get<'a>(req: &'a RequestHead, cfg: &AppConfig) -> Ref<'a, Self> {
+        Self::get_with_prefix(req, cfg, false)
+    }
+
+    pub(crate) fn get_with_prefix<'a>(
+        req: &'a RequestHead,
+        cfg: &AppConfig,
+        trust_prefix: bool,
+    ) -> Ref<'a, Self> {
         if !req.extensions().contains::<ConnectionInfo>() {
-            req.extensions_mut().insert(ConnectionInfo::new(req, cfg));
+            req.extensions_mut()
+                .insert(ConnectionInfo::new(req, cfg, trust_prefix));
         }
         Ref::map(req.extensions(), |e| e.get().unwrap())
     }
 
     #[allow(clippy::cognitive_complexity, clippy::borrow_interior_mutable_const)]
-    fn new(req: &RequestHead, cfg: &AppConfig) -> ConnectionInfo {
+    fn new(req: &RequestHead, cfg: &AppConfig, trust_prefix: bool) -> ConnectionInfo {
         let mut host = None;
         let mut scheme = None;
         let mut realip_remote_addr = None;
@@ -119,8 +136,25 @@ impl ConnectionInfo {
             }
         }
 
+        // external path prefix, only honored when the proxy is trusted to set it
+        let mut prefix = String::new();
+        if trust_prefix {
+            if let Some(h) = req
+                .headers
+                .get(&HeaderName::from_lowercase(X_FORWARDED_PREFIX).unwrap())
+            {
+                if let Some(val) = h.to_str().ok().and_then(|h| h.split(',').next()) {
+                    let val = val.trim().trim_end_matches('/');
+                    if val.starts_with('/') && !val.starts_with("//") {
+                        prefix = val.to_owned();
+                    }
+                }
+            }
+        }
+
         ConnectionInfo {
             remote_addr,
+            prefix,
             scheme: scheme.unwrap_or("http").to_owned(),
             host: host.unwrap_or("localhost").to_owned(),
             realip_remote_addr: realip_remote_addr.map(|s| s.to_owned()),
@@ -152,6 +186,20 @@ impl ConnectionInfo {
         &self.host
     }
 
+    /// External path prefix the application is mounted under by a reverse proxy.
+    ///
+    /// Read from the `X-Forwarded-Prefix` header, but only when the application trusts it; see
+    /// [`App::trust_forwarded_prefix`](crate::App::trust_forwarded_prefix). The prefix starts with
+    /// a `/` and has no trailing slash. It is empty when there is no trusted prefix.
+    ///
+    /// The prefix is included in urls generated by
+    /// [`HttpRequest::url_for`](crate::HttpRequest::url_for) and in the location of a
+    /// [`Redirect`](crate::web::Redirect) to an absolute path.
+    #[inline]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
     /// remote_addr address of the request.
     ///
     /// Get remote_addr address from socket address
@@ -239,4 +287,29 @@ mod tests {
         let info = req.connection_info();
         assert_eq!(info.scheme(), "https");
     }
+
+    #[test]
+    fn test_forwarded_prefix() {
+        let req = TestRequest::default()
+            .insert_header((X_FORWARDED_PREFIX, "/app/"))
+            .to_http_request();
+        assert_eq!(req.connection_info().prefix(), "");
+
+        let req = TestRequest::default()
+            .app_data(TrustForwardedPrefix(true))
+            .insert_header((X_FORWARDED_PREFIX, "/app/"))
+            .to_http_request();
+        assert_eq!(req.connection_info().prefix(), "/app");
+
+        let req = TestRequest::default()
+            .app_data(TrustForwardedPrefix(true))
+            .insert_header((X_FORWARDED_PREFIX, "//evil.example"))
+            .to_http_request();
+        assert_eq!(req.connection_info().prefix(), "");
+
+        let req = TestRequest::default()
+            .app_data(TrustForwardedPrefix(true))
+            .to_http_request();
+        assert_eq!(req.connection_info().prefix(), "");
+    }
 }
@@ -65,6 +65,10 @@
 //! * `openssl` - HTTPS support via `openssl` crate, supports `HTTP/2`
 //! * `rustls` - HTTPS support via `rustls` crate, supports `HTTP/2`
 //! * `secure-cookies` - secure cookies support
+//! * `uuid` - `web::PathUuid` extractor for UUID path segments
+//! * `files` - `Responder` implementation for `PathBuf` serving the file at the path
+//! * `askama` - `web::AskamaStream` adapter for streaming askama templates with `web::Template`
+//! * `sailfish` - `web::SailfishStream` adapter for streaming sailfish templates with `web::Template`
 
 #![deny(rust_2018_idioms, nonstandard_style)]
 #![allow(clippy::needless_doctest_main, clippy::type_complexity)]
@@ -78,8 +82,12 @@ extern crate tls_rustls as rustls;
 
 mod app;
 mod app_service;
+mod authorization;
+mod background;
+mod blocking;
 mod config;
 mod data;
+mod discovery;
 pub mod error;
 mod extract;
 pub mod guard;
@@ -95,6 +103,7 @@ mod route;
 mod scope;
 mod server;
 mod service;
+mod sharded;
 pub mod test;
 pub(crate) mod types;
 pub mod web;
@@ -107,15 +116,30 @@ pub use actix_rt as rt;
 pub use actix_web_codegen::*;
 
 pub use crate::app::App;
+pub use crate::background::RestartPolicy;
 pub use crate::extract::FromRequest;
 pub use crate::request::HttpRequest;
 pub use crate::resource::Resource;
-pub use crate::responder::Responder;
+pub use crate::responder::{
+    BoxedResponder, EitherResult, JsonErrorResponder, Lazy, OrStatus, Responder,
+};
 pub use crate::route::Route;
 pub use crate::scope::Scope;
-pub use crate::server::HttpServer;
+pub use crate::server::{BindError, BindErrorKind, BindErrors, HttpServer, WorkerStats};
 // TODO: is exposing the error directly really needed
-pub use crate::types::{Either, EitherExtractError};
+pub use crate::types::{Either, Either3, Either3ExtractError, EitherExtractError};
+
+pub mod client {
+    //! HTTP client for making requests to other services.
+    //!
+    //! Re-exports the most used items of the [`awc`] crate.
+
+    pub use awc::error::{
+        ConnectError, InvalidUrl, PayloadError, SendRequestError, WsClientError,
+    };
+    pub use awc::middleware::Propagate;
+    pub use awc::{Client, ClientBuilder, ClientRequest, ClientResponse, Connector};
+}
 
 pub mod dev {
     //! The `actix-web` prelude for library developers
@@ -135,15 +159,23 @@ pub mod dev {
     pub use crate::rmap::ResourceMap;
     pub use crate::service::{HttpServiceFactory, ServiceRequest, ServiceResponse, WebService};
 
+    #[doc(hidden)]
+    pub use crate::types::file::ChunkedReadFile;
     pub use crate::types::form::UrlEncoded;
     pub use crate::types::json::JsonBody;
     pub use crate::types::readlines::Readlines;
 
-    pub use actix_http::body::{Body, BodySize, MessageBody, ResponseBody, SizedStream};
+    pub use actix_http::body::{
+        Body, BodySize, BodyStream, EitherBody, MessageBody, ResponseBody, SharedBody,
+        SizedStream,
+    };
     #[cfg(feature = "compress")]
     pub use actix_http::encoding::Decoder as Decompress;
     pub use actix_http::ResponseBuilder as HttpResponseBuilder;
-    pub use actix_http::{Extensions, Payload, PayloadStream, RequestHead, ResponseHead};
+    pub use actix_http::{
+        EarlyHints, Extensions, KeepAlivePolicy, Payload, PayloadStream, RequestHead,
+        RequestWireStats, ResponseHead,
+    };
     pub use actix_router::{Path, ResourceDef, ResourcePath, Url};
     pub use actix_server::Server;
     pub use actix_service::{Service, Transform};
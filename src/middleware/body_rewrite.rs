@@ -0,0 +1,428 @@
+//! For middleware documentation, see [`BodyRewrite`].
+
+use std::{
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::body::{BodySize, EitherBody, MessageBody, ResponseBody};
+use actix_service::{Service, Transform};
+use bytes::{Buf as _, Bytes, BytesMut};
+use futures_core::{future::LocalBoxFuture, ready};
+use futures_util::future::{ready, Ready};
+use mime::Mime;
+
+use super::{body_transform::remove_stale_headers, content_type::matches};
+use crate::{
+    dev::{ServiceRequest, ServiceResponse},
+    error::Error,
+    http::header::{self, ContentEncoding},
+};
+
+/// Middleware for replacing substrings in response bodies as they are streamed.
+///
+/// Only responses whose *Content-Type* matches one of the given types are rewritten, so binary
+/// bodies are never touched. Types may use wildcards (`text/*`); parameters such as `charset` are
+/// ignored when matching. Responses with a *Content-Encoding* other than `identity` are passed
+/// through untouched as well, since their bytes are not text.
+///
+/// Bodies are not buffered. A match that spans two chunks is found by holding back the end of a
+/// chunk that could be the start of a pattern, which keeps at most one byte less than the longest
+/// pattern in memory. Where several patterns match at the same position, the longest one is
+/// replaced; the text inserted by a replacement is not searched again.
+///
+/// Rewritten bodies change length, so they are sent as a stream: the *Content-Length* header is
+/// removed, together with the *Digest*, *Content-Digest* and *Content-MD5* headers describing the
+/// original body.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::BodyRewrite, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(
+///         BodyRewrite::new(vec![mime::TEXT_HTML, mime::TEXT_CSS])
+///             .replace("http://internal:8080", "https://example.com")
+///             .replace("http://", "https://"),
+///     )
+///     .default_service(web::to(|| HttpResponse::Ok().body("proxied page")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BodyRewrite {
+    content_types: Rc<[Mime]>,
+    replacements: Vec<(Bytes, Bytes)>,
+}
+
+impl BodyRewrite {
+    /// Constructs middleware that rewrites responses with the given content types.
+    pub fn new<I>(content_types: I) -> Self
+    where
+        I: IntoIterator<Item = Mime>,
+    {
+        BodyRewrite {
+            content_types: content_types.into_iter().collect(),
+            replacements: Vec::new(),
+        }
+    }
+
+    /// Adds a replacement of every occurrence of `find` with `replace`.
+    ///
+    /// # Panics
+    /// Panics if `find` is empty.
+    pub fn replace(mut self, find: impl Into<Bytes>, replace: impl Into<Bytes>) -> Self {
+        let find = find.into();
+        assert!(!find.is_empty(), "BodyRewrite pattern must not be empty");
+
+        self.replacements.push((find, replace.into()));
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for BodyRewrite
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, RewriteBody<ResponseBody<B>>>>;
+    type Error = Error;
+    type Transform = BodyRewriteMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BodyRewriteMiddleware {
+            service,
+            content_types: Rc::clone(&self.content_types),
+            rewriter: Rc::new(Rewriter {
+                replacements: self.replacements.clone(),
+            }),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct BodyRewriteMiddleware<S> {
+    service: S,
+    content_types: Rc<[Mime]>,
+    rewriter: Rc<Rewriter>,
+}
+
+impl<S, B> Service<ServiceRequest> for BodyRewriteMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, RewriteBody<ResponseBody<B>>>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+        let content_types = Rc::clone(&self.content_types);
+        let rewriter = Rc::clone(&self.rewriter);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            if rewriter.replacements.is_empty() || !is_rewritable(&res, &content_types) {
+                return Ok(res.map_into_left_body());
+            }
+
+            Ok(res.map_body(|head, body| {
+                remove_stale_headers(&mut head.headers);
+                head.headers.remove(header::CONTENT_LENGTH);
+
+                ResponseBody::Body(EitherBody::right(RewriteBody {
+                    body,
+                    rewriter,
+                    carry: BytesMut::new(),
+                    done: false,
+                }))
+            }))
+        })
+    }
+}
+
+/// Returns true if the response has a body of one of `content_types` that is not content encoded.
+fn is_rewritable<B: MessageBody>(res: &ServiceResponse<B>, content_types: &[Mime]) -> bool {
+    if matches!(
+        res.response().body().size(),
+        BodySize::None | BodySize::Empty
+    ) {
+        return false;
+    }
+
+    let headers = res.headers();
+
+    let encoded = headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|enc| enc.to_str().ok())
+        .map_or(false, |enc| {
+            ContentEncoding::from(enc) != ContentEncoding::Identity
+        });
+
+    let mime = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .and_then(|ct| ct.parse::<Mime>().ok());
+
+    match mime {
+        Some(mime) if !encoded => content_types.iter().any(|pattern| matches(pattern, &mime)),
+        _ => false,
+    }
+}
+
+/// Replaces substrings in a body that arrives in chunks.
+#[derive(Debug)]
+struct Rewriter {
+    replacements: Vec<(Bytes, Bytes)>,
+}
+
+impl Rewriter {
+    /// Writes the rewritten start of `data` to `out` and returns the number of bytes consumed.
+    ///
+    /// Unless `eof` is set, data from the first position where a pattern could still match once
+    /// more data arrives is not consumed, to be passed again with the next chunk.
+    fn rewrite(&self, data: &[u8], eof: bool, out: &mut BytesMut) -> usize {
+        let mut pos = 0;
+        let mut copied = 0;
+
+        while pos < data.len() {
+            let rest = &data[pos..];
+
+            let incomplete = !eof
+                && self
+                    .replacements
+                    .iter()
+                    .any(|(find, _)| rest.len() < find.len() && find.starts_with(rest));
+
+            if incomplete {
+                break;
+            }
+
+            let found = self
+                .replacements
+                .iter()
+                .filter(|(find, _)| rest.starts_with(find))
+                .max_by_key(|(find, _)| find.len());
+
+            match found {
+                Some((find, replace)) => {
+                    out.extend_from_slice(&data[copied..pos]);
+                    out.extend_from_slice(replace);
+                    pos += find.len();
+                    copied = pos;
+                }
+                None => pos += 1,
+            }
+        }
+
+        out.extend_from_slice(&data[copied..pos]);
+        pos
+    }
+}
+
+/// Body with its substrings replaced by [`BodyRewrite`].
+#[doc(hidden)]
+#[pin_project::pin_project]
+pub struct RewriteBody<B> {
+    #[pin]
+    body: B,
+    rewriter: Rc<Rewriter>,
+    carry: BytesMut,
+    done: bool,
+}
+
+impl<B: MessageBody> MessageBody for RewriteBody<B> {
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Error>>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            let mut out = BytesMut::new();
+
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(chunk)) => {
+                    this.carry.extend_from_slice(&chunk);
+                    let consumed = this.rewriter.rewrite(&this.carry[..], false, &mut out);
+                    this.carry.advance(consumed);
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => {
+                    *this.done = true;
+                    this.rewriter.rewrite(&this.carry[..], true, &mut out);
+                    this.carry.clear();
+                }
+            }
+
+            if !out.is_empty() {
+                return Poll::Ready(Some(Ok(out.freeze())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    fn rewriter() -> Rewriter {
+        Rewriter {
+            replacements: vec![
+                (
+                    Bytes::from_static(b"http://"),
+                    Bytes::from_static(b"https://"),
+                ),
+                (
+                    Bytes::from_static(b"http://internal:8080"),
+                    Bytes::from_static(b"https://example.com"),
+                ),
+                (Bytes::from_static(b"aa"), Bytes::from_static(b"b")),
+            ],
+        }
+    }
+
+    /// Rewrites `chunks` as a stream, returning the output and the largest amount held back.
+    fn rewrite_chunks(rewriter: &Rewriter, chunks: &[&[u8]]) -> (Vec<u8>, usize) {
+        let mut carry = BytesMut::new();
+        let mut out = BytesMut::new();
+        let mut held = 0;
+
+        for chunk in chunks {
+            carry.extend_from_slice(chunk);
+            let consumed = rewriter.rewrite(&carry, false, &mut out);
+            carry.advance(consumed);
+            held = held.max(carry.len());
+        }
+
+        rewriter.rewrite(&carry, true, &mut out);
+        (out.to_vec(), held)
+    }
+
+    #[test]
+    fn test_matches_split_across_chunks() {
+        let rewriter = rewriter();
+        let page = b"<a href=\"http://internal:8080/a\">http://other/</a> aaa";
+        let expected = b"<a href=\"https://example.com/a\">https://other/</a> ba";
+
+        let (whole, _) = rewrite_chunks(&rewriter, &[&page[..]]);
+        assert_eq!(whole, &expected[..]);
+
+        for split in 0..page.len() {
+            let (out, held) = rewrite_chunks(&rewriter, &[&page[..split], &page[split..]]);
+            assert_eq!(out, &expected[..], "split at {}", split);
+            assert!(held < "http://internal:8080".len());
+        }
+
+        let bytes = page.iter().map(std::slice::from_ref).collect::<Vec<_>>();
+        let (out, _) = rewrite_chunks(&rewriter, &bytes);
+        assert_eq!(out, &expected[..]);
+    }
+
+    #[test]
+    fn test_overlapping_patterns() {
+        let rewriter = rewriter();
+
+        // longest pattern wins where several match, falling back to shorter ones otherwise
+        let page = b"http://internal:8080 http://internal:80";
+        let (out, _) = rewrite_chunks(&rewriter, &[&page[..]]);
+        assert_eq!(out, &b"https://example.com https://internal:80"[..]);
+
+        // replaced text is not searched again
+        let (out, _) = rewrite_chunks(&rewriter, &[&b"aaaa"[..], &b"a"[..]]);
+        assert_eq!(out, &b"bba"[..]);
+    }
+
+    #[actix_rt::test]
+    async fn test_rewrite_response() {
+        let srv = init_service(
+            App::new()
+                .wrap(
+                    BodyRewrite::new(vec![mime::TEXT_HTML])
+                        .replace("http://internal:8080", "https://example.com"),
+                )
+                .route(
+                    "/stream",
+                    web::get().to(|| {
+                        let chunks = vec![
+                            Ok::<_, Error>(Bytes::from_static(b"<a href=\"http://inter")),
+                            Ok(Bytes::from_static(b"nal:8080/\">home</a>")),
+                        ];
+
+                        HttpResponse::Ok()
+                            .content_type("text/html; charset=utf-8")
+                            .streaming(stream::iter(chunks))
+                    }),
+                )
+                .route(
+                    "/sized",
+                    web::get().to(|| {
+                        HttpResponse::Ok()
+                            .content_type(mime::TEXT_HTML)
+                            .insert_header((header::CONTENT_LENGTH, 24))
+                            .insert_header(("digest", "sha-256=abc"))
+                            .body("see http://internal:8080")
+                    }),
+                )
+                .route(
+                    "/binary",
+                    web::get().to(|| {
+                        HttpResponse::Ok()
+                            .content_type(mime::APPLICATION_OCTET_STREAM)
+                            .insert_header((header::CONTENT_LENGTH, 20))
+                            .body("http://internal:8080")
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/stream").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            read_body(res).await,
+            Bytes::from_static(b"<a href=\"https://example.com/\">home</a>")
+        );
+
+        let req = TestRequest::with_uri("/sized").to_request();
+        let res = call_service(&srv, req).await;
+        assert!(!res.headers().contains_key(header::CONTENT_LENGTH));
+        assert!(!res.headers().contains_key("digest"));
+        assert_eq!(res.response().body().size(), BodySize::Stream);
+        assert_eq!(
+            read_body(res).await,
+            Bytes::from_static(b"see https://example.com")
+        );
+
+        let req = TestRequest::with_uri("/binary").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.headers().get(header::CONTENT_LENGTH).unwrap(), "20");
+        assert_eq!(res.response().body().size(), BodySize::Sized(20));
+        assert_eq!(
+            read_body(res).await,
+            Bytes::from_static(b"http://internal:8080")
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! For middleware documentation, see [`BodyTransform`].
+
+use std::{future::Future, rc::Rc};
+
+use actix_http::{
+    body::{Body, EitherBody, MessageBody, ResponseBody},
+    error::PayloadError,
+};
+use actix_service::{Service, Transform};
+use bytes::{Bytes, BytesMut};
+use futures_core::future::LocalBoxFuture;
+use futures_util::{
+    future::{ready, Ready},
+    stream::{self, StreamExt as _},
+};
+
+use super::err_handlers::buffer_body;
+use crate::{
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::{Error, ErrorInternalServerError},
+    http::{
+        header::{self, HeaderName, HeaderValue},
+        HeaderMap,
+    },
+    HttpMessage,
+};
+
+/// Default limit on the size of transformed bodies; 256KiB.
+const DEFAULT_LIMIT: usize = 262_144;
+
+type Transformer = dyn Fn(Bytes) -> LocalBoxFuture<'static, Result<Bytes, Error>>;
+
+/// Size limit and handling of oversized bodies for [`BodyTransform`].
+///
+/// Register with [`App::app_data`](crate::App::app_data) or
+/// [`Scope::app_data`](crate::Scope::app_data). The middleware uses the config visible where it is
+/// wrapped, so a config registered on a scope applies to the middleware wrapped around the scope's
+/// nested scopes and resources. When not registered, bodies up to 256KiB are transformed and
+/// larger ones are passed through untouched.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::{BodyTransform, BodyTransformConfig, Compat}, web, App};
+///
+/// let transform = BodyTransform::new().request(|body| async move { Ok(body) });
+///
+/// let app = App::new().service(
+///     web::scope("/sealed")
+///         .app_data(BodyTransformConfig::default().limit(4096).reject_oversized(true))
+///         .service(
+///             web::resource("/upload")
+///                 .wrap(Compat::new(transform))
+///                 .to(|body: String| async move { body }),
+///         ),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct BodyTransformConfig {
+    limit: usize,
+    reject_oversized: bool,
+}
+
+impl BodyTransformConfig {
+    /// Set the max size in bytes of bodies that are buffered and transformed.
+    ///
+    /// By default the limit is 256KiB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Reject bodies larger than the limit instead of passing them through untransformed.
+    ///
+    /// Oversized requests get a `413 Payload Too Large` response and oversized responses are
+    /// replaced with a `500 Internal Server Error` response. Disabled by default.
+    pub fn reject_oversized(mut self, reject: bool) -> Self {
+        self.reject_oversized = reject;
+        self
+    }
+
+    fn from_req(req: &ServiceRequest) -> Self {
+        req.app_data::<Self>().cloned().unwrap_or_default()
+    }
+}
+
+impl Default for BodyTransformConfig {
+    fn default() -> Self {
+        BodyTransformConfig {
+            limit: DEFAULT_LIMIT,
+            reject_oversized: false,
+        }
+    }
+}
+
+/// Middleware for transforming request and response bodies, e.g. to decrypt and encrypt them.
+///
+/// The request transformer is given the buffered request body before the wrapped service runs, so
+/// extractors see its output. The response transformer is given the buffered body of every
+/// response. Empty bodies are not transformed, and bodies larger than the limit of the
+/// [`BodyTransformConfig`] are passed through as a stream or rejected.
+///
+/// A transformed body replaces the original one, so its `Content-Length` is updated and the
+/// `Content-Encoding`, `Digest`, `Content-Digest` and `Content-MD5` headers describing the original
+/// are removed. Transformers see the body as it was sent, before any content decoding.
+///
+/// To transform the bodies of some routes only, wrap the middleware around their scope or resource
+/// with [`Compat`](super::Compat).
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::BodyTransform, web, App};
+///
+/// fn flip(body: web::Bytes) -> web::Bytes {
+///     body.iter().map(|byte| !byte).collect::<Vec<u8>>().into()
+/// }
+///
+/// let app = App::new()
+///     .wrap(
+///         BodyTransform::new()
+///             .request(|body| async move { Ok(flip(body)) })
+///             .response(|body| async move { Ok(flip(body)) }),
+///     )
+///     .route("/", web::post().to(|body: String| async move { body }));
+/// ```
+#[derive(Clone, Default)]
+pub struct BodyTransform {
+    request: Option<Rc<Transformer>>,
+    response: Option<Rc<Transformer>>,
+}
+
+impl BodyTransform {
+    /// Constructs middleware that transforms neither requests nor responses.
+    pub fn new() -> Self {
+        BodyTransform::default()
+    }
+
+    /// Set the transformer applied to request bodies.
+    pub fn request<F, Fut>(mut self, transform: F) -> Self
+    where
+        F: Fn(Bytes) -> Fut + 'static,
+        Fut: Future<Output = Result<Bytes, Error>> + 'static,
+    {
+        let transform: Rc<Transformer> =
+            Rc::new(move |body| Box::pin(transform(body)) as LocalBoxFuture<'static, _>);
+        self.request = Some(transform);
+        self
+    }
+
+    /// Set the transformer applied to response bodies.
+    pub fn response<F, Fut>(mut self, transform: F) -> Self
+    where
+        F: Fn(Bytes) -> Fut + 'static,
+        Fut: Future<Output = Result<Bytes, Error>> + 'static,
+    {
+        let transform: Rc<Transformer> =
+            Rc::new(move |body| Box::pin(transform(body)) as LocalBoxFuture<'static, _>);
+        self.response = Some(transform);
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for BodyTransform
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = BodyTransformMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BodyTransformMiddleware {
+            service: Rc::new(service),
+            request: self.request.clone(),
+            response: self.response.clone(),
+        }))
+    }
+}
+
+#[doc(hidden)]
+pub struct BodyTransformMiddleware<S> {
+    service: Rc<S>,
+    request: Option<Rc<Transformer>>,
+    response: Option<Rc<Transformer>>,
+}
+
+impl<S, B> Service<ServiceRequest> for BodyTransformMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let request = self.request.clone();
+        let response = self.response.clone();
+        let config = BodyTransformConfig::from_req(&req);
+
+        Box::pin(async move {
+            if let Some(transform) = request {
+                if let Err(err) = transform_request(&mut req, &*transform, &config).await {
+                    return Ok(req.error_response(err));
+                }
+            }
+
+            let res = service.call(req).await?;
+
+            match response {
+                Some(transform) => transform_response(res, &*transform, &config)
+                    .await
+                    .map(ServiceResponse::map_into_right_body),
+                None => Ok(res.map_into_left_body()),
+            }
+        })
+    }
+}
+
+/// Replaces the payload of the request with its transformed body.
+async fn transform_request(
+    req: &mut ServiceRequest,
+    transform: &Transformer,
+    config: &BodyTransformConfig,
+) -> Result<(), Error> {
+    let declared_len = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|len| len.to_str().ok())
+        .and_then(|len| len.parse::<usize>().ok());
+
+    if matches!(declared_len, Some(len) if len > config.limit) {
+        if config.reject_oversized {
+            return Err(PayloadError::Overflow.into());
+        }
+
+        return Ok(());
+    }
+
+    let mut payload = req.take_payload();
+    let mut buf = BytesMut::new();
+
+    while let Some(chunk) = payload.next().await {
+        buf.extend_from_slice(&chunk?);
+
+        if buf.len() > config.limit {
+            if config.reject_oversized {
+                return Err(PayloadError::Overflow.into());
+            }
+
+            // pass the body through, putting back the bytes read so far
+            let rest = stream::once(ready(Ok(buf.freeze()))).chain(payload);
+            req.set_payload(Payload::Stream(Box::pin(rest)));
+            return Ok(());
+        }
+    }
+
+    let body = if buf.is_empty() {
+        buf.freeze()
+    } else {
+        let body = transform(buf.freeze()).await?;
+
+        let headers = req.headers_mut();
+        remove_stale_headers(headers);
+        headers.remove(header::TRANSFER_ENCODING);
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+
+        body
+    };
+
+    let (_, mut h1_payload) = actix_http::h1::Payload::create(true);
+    h1_payload.unread_data(body);
+    req.set_payload(Payload::from(h1_payload));
+
+    Ok(())
+}
+
+/// Replaces the body of the response with its transformed body.
+async fn transform_response<B>(
+    res: ServiceResponse<B>,
+    transform: &Transformer,
+    config: &BodyTransformConfig,
+) -> Result<ServiceResponse<Body>, Error>
+where
+    B: MessageBody + 'static,
+{
+    let res = buffer_body(res, config.limit).await?;
+
+    let body = match res.response().body() {
+        ResponseBody::Other(Body::Bytes(bytes)) if !bytes.is_empty() => bytes.clone(),
+        ResponseBody::Other(Body::Message(_)) if config.reject_oversized => {
+            let err = ErrorInternalServerError("Response body is too large to transform");
+            return Ok(res.error_response(err));
+        }
+        _ => return Ok(res),
+    };
+
+    let body = match transform(body).await {
+        Ok(body) => body,
+        Err(err) => return Ok(res.error_response(err)),
+    };
+
+    Ok(res.map_body(|head, _| {
+        remove_stale_headers(&mut head.headers);
+        head.headers.remove(header::CONTENT_LENGTH);
+        ResponseBody::Other(Body::Bytes(body))
+    }))
+}
+
+/// Removes headers describing the encoding or digest of a body that has been replaced.
+pub(super) fn remove_stale_headers(headers: &mut HeaderMap) {
+    headers.remove(header::CONTENT_ENCODING);
+
+    for name in &["digest", "content-digest", "content-md5"] {
+        headers.remove(HeaderName::from_static(*name));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        middleware::Compat,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpRequest,
+    };
+
+    fn xor(body: Bytes) -> Bytes {
+        body.iter()
+            .map(|byte| byte ^ 0x2a)
+            .collect::<Vec<u8>>()
+            .into()
+    }
+
+    fn xor_transform() -> BodyTransform {
+        BodyTransform::new()
+            .request(|body| ready(Ok(xor(body))))
+            .response(|body| ready(Ok(xor(body))))
+    }
+
+    async fn echo(req: HttpRequest, body: String) -> String {
+        format!("{} {}", body, req.headers().contains_key("digest"))
+    }
+
+    #[actix_rt::test]
+    async fn test_transform_bodies() {
+        let srv = init_service(
+            App::new()
+                .wrap(xor_transform())
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_LENGTH, 5))
+            .insert_header(("digest", "sha-256=abc"))
+            .set_payload(xor(Bytes::from_static(b"hello")))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers().get(header::CONTENT_LENGTH).is_none());
+
+        let body = read_body(res).await;
+        assert_eq!(xor(body), "hello false");
+    }
+
+    #[actix_rt::test]
+    async fn test_scoped_transform() {
+        let srv = init_service(
+            App::new()
+                .service(
+                    web::scope("/sealed")
+                        .wrap(Compat::new(xor_transform()))
+                        .route("/", web::post().to(echo)),
+                )
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/sealed/")
+            .insert_header((header::CONTENT_LENGTH, 5))
+            .set_payload(xor(Bytes::from_static(b"hello")))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(xor(read_body(res).await), "hello false");
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_LENGTH, 5))
+            .set_payload("hello")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(read_body(res).await, "hello false");
+    }
+
+    #[actix_rt::test]
+    async fn test_oversized_bodies() {
+        let srv = init_service(
+            App::new()
+                .app_data(BodyTransformConfig::default().limit(4))
+                .wrap(xor_transform())
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_LENGTH, 5))
+            .set_payload("hello")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(read_body(res).await, "hello false");
+
+        let srv = init_service(
+            App::new()
+                .app_data(
+                    BodyTransformConfig::default()
+                        .limit(4)
+                        .reject_oversized(true),
+                )
+                .wrap(xor_transform())
+                .route("/", web::post().to(echo)),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((header::CONTENT_LENGTH, 5))
+            .set_payload("hello")
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
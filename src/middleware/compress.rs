@@ -10,7 +10,7 @@ use std::{
 };
 
 use actix_http::{
-    body::MessageBody,
+    body::{BodySize, MessageBody},
     encoding::Encoder,
     http::header::{ContentEncoding, ACCEPT_ENCODING},
     Error,
@@ -30,6 +30,15 @@ use crate::{
 /// Use `BodyEncoding` trait for overriding response compression. To disable compression set
 /// encoding to `ContentEncoding::Identity`.
 ///
+/// With [`honor_save_data`](Self::honor_save_data) enabled, requests carrying the `Save-Data: on`
+/// client hint are compressed with the client's preferred encoding whenever it accepts one, even
+/// if negotiation with the configured encoding would have left the response uncompressed.
+///
+/// Compressed bodies are streamed, so their size is not known up front. Any `Content-Length` set
+/// for the uncompressed body is removed and the response is sent with chunked transfer encoding
+/// (on HTTP/1.1). Bodies whose size was already unknown, such as those of streaming responses, are
+/// compressed the same way unless disabled with [`compress_unsized`](Self::compress_unsized).
+///
 /// # Examples
 /// ```rust
 /// use actix_web::{web, middleware, App, HttpResponse};
@@ -39,12 +48,38 @@ use crate::{
 ///     .default_service(web::to(|| HttpResponse::NotFound()));
 /// ```
 #[derive(Debug, Clone)]
-pub struct Compress(ContentEncoding);
+pub struct Compress {
+    encoding: ContentEncoding,
+    save_data: bool,
+    compress_unsized: bool,
+}
 
 impl Compress {
     /// Create new `Compress` middleware with the specified encoding.
     pub fn new(encoding: ContentEncoding) -> Self {
-        Compress(encoding)
+        Compress {
+            encoding,
+            save_data: false,
+            compress_unsized: true,
+        }
+    }
+
+    /// Compress responses to clients that send the `Save-Data: on` hint whenever they accept a
+    /// compressed encoding.
+    ///
+    /// Disabled by default.
+    pub fn honor_save_data(mut self, enabled: bool) -> Self {
+        self.save_data = enabled;
+        self
+    }
+
+    /// Compress bodies of unknown size, such as streaming responses.
+    ///
+    /// When disabled, such bodies are sent uncompressed with their original framing. Enabled by
+    /// default.
+    pub fn compress_unsized(mut self, enabled: bool) -> Self {
+        self.compress_unsized = enabled;
+        self
     }
 }
 
@@ -68,7 +103,9 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ok(CompressMiddleware {
             service,
-            encoding: self.0,
+            encoding: self.encoding,
+            save_data: self.save_data,
+            compress_unsized: self.compress_unsized,
         })
     }
 }
@@ -76,6 +113,8 @@ where
 pub struct CompressMiddleware<S> {
     service: S,
     encoding: ContentEncoding,
+    save_data: bool,
+    compress_unsized: bool,
 }
 
 impl<S, B> Service<ServiceRequest> for CompressMiddleware<S>
@@ -94,7 +133,16 @@ where
         // negotiate content-encoding
         let encoding = if let Some(val) = req.headers().get(&ACCEPT_ENCODING) {
             if let Ok(enc) = val.to_str() {
-                AcceptEncoding::parse(enc, self.encoding)
+                let encoding = AcceptEncoding::parse(enc, self.encoding);
+
+                if encoding == ContentEncoding::Identity
+                    && self.save_data
+                    && wants_save_data(&req)
+                {
+                    AcceptEncoding::preferred_compression(enc)
+                } else {
+                    encoding
+                }
             } else {
                 ContentEncoding::Identity
             }
@@ -104,12 +152,24 @@ where
 
         CompressResponse {
             encoding,
+            compress_unsized: self.compress_unsized,
             fut: self.service.call(req),
             _phantom: PhantomData,
         }
     }
 }
 
+/// Returns true if the request carries the `Save-Data: on` client hint.
+fn wants_save_data(req: &ServiceRequest) -> bool {
+    req.headers()
+        .get("save-data")
+        .and_then(|val| val.to_str().ok())
+        .map_or(false, |val| {
+            let token = val.split(';').next().unwrap_or("");
+            token.trim().eq_ignore_ascii_case("on")
+        })
+}
+
 #[pin_project]
 pub struct CompressResponse<S, B>
 where
@@ -119,6 +179,7 @@ where
     #[pin]
     fut: S::Future,
     encoding: ContentEncoding,
+    compress_unsized: bool,
     _phantom: PhantomData<B>,
 }
 
@@ -136,6 +197,10 @@ where
             Ok(resp) => {
                 let enc = if let Some(enc) = resp.response().get_encoding() {
                     enc
+                } else if !*this.compress_unsized
+                    && resp.response().body().size() == BodySize::Stream
+                {
+                    ContentEncoding::Identity
                 } else {
                     *this.encoding
                 };
@@ -190,7 +255,14 @@ impl AcceptEncoding {
         };
         let quality = match parts.len() {
             1 => encoding.quality(),
-            _ => f64::from_str(parts[1]).unwrap_or(0.0),
+            _ => {
+                let q = parts[1];
+                let q = q
+                    .strip_prefix("q=")
+                    .or_else(|| q.strip_prefix("Q="))
+                    .unwrap_or(q);
+                f64::from_str(q).unwrap_or(0.0)
+            }
         };
         Some(AcceptEncoding { encoding, quality })
     }
@@ -215,4 +287,55 @@ impl AcceptEncoding {
         }
         ContentEncoding::Identity
     }
+
+    /// Returns the compressed encoding the client prefers, if it accepts any.
+    ///
+    /// Encodings with a quality of zero are not acceptable to the client and are never chosen.
+    fn preferred_compression(raw: &str) -> ContentEncoding {
+        let mut encodings: Vec<_> = raw
+            .replace(' ', "")
+            .split(',')
+            .filter_map(AcceptEncoding::new)
+            .filter(|enc| enc.quality > 0.0)
+            .collect();
+        encodings.sort();
+
+        encodings
+            .into_iter()
+            .map(|enc| enc.encoding)
+            .find(|enc| *enc != ContentEncoding::Identity && *enc != ContentEncoding::Auto)
+            .unwrap_or(ContentEncoding::Identity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_compression() {
+        let preferred = AcceptEncoding::preferred_compression;
+
+        assert_eq!(preferred("gzip"), ContentEncoding::Gzip);
+        assert_eq!(preferred("identity"), ContentEncoding::Identity);
+        assert_eq!(
+            preferred("gzip;q=0.5, deflate;q=0.8"),
+            ContentEncoding::Deflate
+        );
+        assert_eq!(
+            preferred("gzip;q=1.0, deflate;q=0.8"),
+            ContentEncoding::Gzip
+        );
+
+        // q=0 means not acceptable
+        assert_eq!(preferred("gzip;q=0"), ContentEncoding::Identity);
+        assert_eq!(
+            preferred("gzip;q=0, deflate;q=0.1"),
+            ContentEncoding::Deflate
+        );
+        assert_eq!(
+            preferred("gzip; q=0.0, identity"),
+            ContentEncoding::Identity
+        );
+    }
 }
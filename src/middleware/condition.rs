@@ -1,15 +1,30 @@
 //! For middleware documentation, see [`Condition`].
 
-use std::task::{Context, Poll};
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
+use actix_http::body::EitherBody;
 use actix_service::{Service, Transform};
-use futures_util::future::{Either, FutureExt, LocalBoxFuture};
+use futures_core::ready;
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use pin_project::pin_project;
+
+use crate::{dev::ServiceRequest, service::ServiceResponse};
 
 /// Middleware for conditionally enabling other middleware.
 ///
-/// The controlled middleware must not change the `Service` interfaces. This means you cannot
-/// control such middlewares like `Logger` or `Compress` directly. See the [`Compat`](super::Compat)
-/// middleware for a workaround.
+/// The controlled middleware may change the response body type, such as [`ErrorHandlers`] does.
+/// Responses are wrapped in an [`EitherBody`]: the left side holds bodies of responses from the
+/// enabled middleware, the right side those of the wrapped service when it is disabled.
+///
+/// The controlled middleware must not change the error type. This means you cannot control
+/// middlewares like `Logger` or `Compress` directly. See the [`Compat`](super::Compat) middleware
+/// for a workaround.
+///
+/// [`ErrorHandlers`]: super::ErrorHandlers
 ///
 /// # Examples
 /// ```rust
@@ -34,16 +49,16 @@ impl<T> Condition<T> {
     }
 }
 
-impl<S, T, Req> Transform<S, Req> for Condition<T>
+impl<S, T, BE, BD, Err> Transform<S, ServiceRequest> for Condition<T>
 where
-    S: Service<Req> + 'static,
-    T: Transform<S, Req, Response = S::Response, Error = S::Error>,
+    S: Service<ServiceRequest, Response = ServiceResponse<BD>, Error = Err> + 'static,
+    T: Transform<S, ServiceRequest, Response = ServiceResponse<BE>, Error = Err>,
     T::Future: 'static,
     T::InitError: 'static,
     T::Transform: 'static,
 {
-    type Response = S::Response;
-    type Error = S::Error;
+    type Response = ServiceResponse<EitherBody<BE, BD>>;
+    type Error = Err;
     type Transform = ConditionMiddleware<T::Transform, S>;
     type InitError = T::InitError;
     type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
@@ -67,14 +82,14 @@ pub enum ConditionMiddleware<E, D> {
     Disable(D),
 }
 
-impl<E, D, Req> Service<Req> for ConditionMiddleware<E, D>
+impl<E, D, BE, BD, Err> Service<ServiceRequest> for ConditionMiddleware<E, D>
 where
-    E: Service<Req>,
-    D: Service<Req, Response = E::Response, Error = E::Error>,
+    E: Service<ServiceRequest, Response = ServiceResponse<BE>, Error = Err>,
+    D: Service<ServiceRequest, Response = ServiceResponse<BD>, Error = Err>,
 {
-    type Response = E::Response;
-    type Error = E::Error;
-    type Future = Either<E::Future, D::Future>;
+    type Response = ServiceResponse<EitherBody<BE, BD>>;
+    type Error = Err;
+    type Future = ConditionMiddlewareFuture<E::Future, D::Future>;
 
     fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         match self {
@@ -83,14 +98,48 @@ where
         }
     }
 
-    fn call(&self, req: Req) -> Self::Future {
+    fn call(&self, req: ServiceRequest) -> Self::Future {
         match self {
-            ConditionMiddleware::Enable(service) => Either::Left(service.call(req)),
-            ConditionMiddleware::Disable(service) => Either::Right(service.call(req)),
+            ConditionMiddleware::Enable(service) => ConditionMiddlewareFuture::Enabled {
+                fut: service.call(req),
+            },
+            ConditionMiddleware::Disable(service) => ConditionMiddlewareFuture::Disabled {
+                fut: service.call(req),
+            },
         }
     }
 }
 
+#[doc(hidden)]
+#[pin_project(project = ConditionProj)]
+pub enum ConditionMiddlewareFuture<E, D> {
+    Enabled {
+        #[pin]
+        fut: E,
+    },
+    Disabled {
+        #[pin]
+        fut: D,
+    },
+}
+
+impl<E, D, BE, BD, Err> Future for ConditionMiddlewareFuture<E, D>
+where
+    E: Future<Output = Result<ServiceResponse<BE>, Err>>,
+    D: Future<Output = Result<ServiceResponse<BD>, Err>>,
+{
+    type Output = Result<ServiceResponse<EitherBody<BE, BD>>, Err>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = match self.project() {
+            ConditionProj::Enabled { fut } => ready!(fut.poll(cx))?.map_into_left_body(),
+            ConditionProj::Disabled { fut } => ready!(fut.poll(cx))?.map_into_right_body(),
+        };
+
+        Poll::Ready(Ok(res))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_service::IntoService;
@@ -0,0 +1,158 @@
+//! For middleware documentation, see [`RequireContentType`].
+
+use std::{
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures_util::future::{ready, Either, Ready};
+use mime::Mime;
+
+use crate::{
+    dev::{Service, Transform},
+    error::ErrorUnsupportedMediaType,
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage,
+};
+
+/// Middleware for rejecting requests with an unexpected *Content-Type*.
+///
+/// Requests whose content type is missing, malformed or not in the allowlist are rejected with
+/// `415 Unsupported Media Type` before reaching the wrapped service. Allowed types may use
+/// wildcards (`application/*` or `*/*`); parameters such as `charset` are ignored when matching.
+///
+/// # Examples
+/// ```rust
+/// use actix_web::{middleware::RequireContentType, web, App, HttpResponse};
+///
+/// let app = App::new().service(
+///     web::resource("/items")
+///         .wrap(RequireContentType::new(vec![mime::APPLICATION_JSON]))
+///         .route(web::post().to(HttpResponse::Created)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequireContentType {
+    allowed: Rc<[Mime]>,
+}
+
+impl RequireContentType {
+    /// Constructs middleware that only accepts the given content types.
+    pub fn new<I>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = Mime>,
+    {
+        RequireContentType {
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+}
+
+/// Returns true if `mime` matches `pattern`, taking wildcards in `pattern` into account.
+pub(super) fn matches(pattern: &Mime, mime: &Mime) -> bool {
+    (pattern.type_() == mime::STAR || pattern.type_() == mime.type_())
+        && (pattern.subtype() == mime::STAR || pattern.subtype() == mime.subtype())
+        && (pattern.subtype() == mime::STAR || pattern.suffix() == mime.suffix())
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireContentType
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequireContentTypeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireContentTypeMiddleware {
+            service,
+            allowed: Rc::clone(&self.allowed),
+        }))
+    }
+}
+
+pub struct RequireContentTypeMiddleware<S> {
+    service: S,
+    allowed: Rc<[Mime]>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireContentTypeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let allowed = match req.mime_type() {
+            Ok(Some(mime)) => self.allowed.iter().any(|pattern| matches(pattern, &mime)),
+            _ => false,
+        };
+
+        if allowed {
+            Either::Left(self.service.call(req))
+        } else {
+            Either::Right(ready(Err(ErrorUnsupportedMediaType(
+                "Unsupported content type",
+            ))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::{header::CONTENT_TYPE, StatusCode},
+        test::{ok_service, TestRequest},
+    };
+
+    #[actix_rt::test]
+    async fn test_reject_content_type() {
+        let mw = RequireContentType::new(vec![mime::APPLICATION_JSON])
+            .new_transform(ok_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .to_srv_request();
+        let err = mw.call(req).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        assert!(mw.call(req).await.is_err());
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/json; charset=utf-8"))
+            .to_srv_request();
+        let res = mw.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_wildcard() {
+        let mw = RequireContentType::new(vec!["application/*".parse().unwrap()])
+            .new_transform(ok_service())
+            .await
+            .unwrap();
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/problem+json"))
+            .to_srv_request();
+        assert!(mw.call(req).await.is_ok());
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .to_srv_request();
+        assert!(mw.call(req).await.is_err());
+    }
+}
@@ -0,0 +1,562 @@
+//! For middleware documentation, see [`Csp`].
+
+use std::{
+    borrow::Cow,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    mem,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{
+    future::{ready, Ready},
+    ready,
+};
+use mime::Mime;
+use rand::Rng as _;
+
+use crate::{
+    dev::{Body, MessageBody, ResponseBody, Service, Transform},
+    http::{
+        header::{HeaderValue, CONTENT_LENGTH, CONTENT_SECURITY_POLICY, CONTENT_TYPE},
+        HeaderMap,
+    },
+    service::{ServiceRequest, ServiceResponse},
+    Error, HttpMessage as _,
+};
+
+/// Source expression of a Content-Security-Policy directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CspSource {
+    /// `'none'`; matches nothing.
+    None,
+    /// `'self'`; matches the origin of the document.
+    SelfOrigin,
+    /// `'unsafe-inline'`.
+    UnsafeInline,
+    /// `'unsafe-eval'`.
+    UnsafeEval,
+    /// `'strict-dynamic'`.
+    StrictDynamic,
+    /// `'nonce-<value>'` with the nonce generated for the current request.
+    Nonce,
+    /// Host or scheme source, such as `https://cdn.example.com` or `data:`, written as is.
+    Host(Cow<'static, str>),
+}
+
+impl CspSource {
+    /// Constructs a host or scheme source.
+    pub fn host(host: impl Into<Cow<'static, str>>) -> Self {
+        CspSource::Host(host.into())
+    }
+
+    fn write(&self, nonce: &str, buf: &mut String) {
+        match self {
+            CspSource::None => buf.push_str("'none'"),
+            CspSource::SelfOrigin => buf.push_str("'self'"),
+            CspSource::UnsafeInline => buf.push_str("'unsafe-inline'"),
+            CspSource::UnsafeEval => buf.push_str("'unsafe-eval'"),
+            CspSource::StrictDynamic => buf.push_str("'strict-dynamic'"),
+            CspSource::Nonce => {
+                buf.push_str("'nonce-");
+                buf.push_str(nonce);
+                buf.push('\'');
+            }
+            CspSource::Host(host) => buf.push_str(host),
+        }
+    }
+}
+
+/// Content-Security-Policy built from typed directives.
+///
+/// Directives are rendered in the order they were added.
+///
+/// # Examples
+/// ```
+/// use actix_web::middleware::{CspPolicy, CspSource};
+///
+/// let policy = CspPolicy::new()
+///     .default_src(vec![CspSource::SelfOrigin])
+///     .script_src(vec![CspSource::SelfOrigin, CspSource::Nonce])
+///     .frame_ancestors(vec![CspSource::None])
+///     .report_uri("/csp-report");
+///
+/// assert_eq!(
+///     policy.render("abc"),
+///     "default-src 'self'; script-src 'self' 'nonce-abc'; \
+///      frame-ancestors 'none'; report-uri /csp-report",
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CspPolicy {
+    directives: Vec<(&'static str, Vec<CspSource>)>,
+    report_uri: Option<Cow<'static, str>>,
+}
+
+impl CspPolicy {
+    /// Constructs an empty policy.
+    pub fn new() -> Self {
+        CspPolicy::default()
+    }
+
+    /// Sets the `default-src` directive.
+    pub fn default_src(self, sources: impl IntoIterator<Item = CspSource>) -> Self {
+        self.directive("default-src", sources)
+    }
+
+    /// Sets the `script-src` directive.
+    pub fn script_src(self, sources: impl IntoIterator<Item = CspSource>) -> Self {
+        self.directive("script-src", sources)
+    }
+
+    /// Sets the `style-src` directive.
+    pub fn style_src(self, sources: impl IntoIterator<Item = CspSource>) -> Self {
+        self.directive("style-src", sources)
+    }
+
+    /// Sets the `frame-ancestors` directive.
+    pub fn frame_ancestors(self, sources: impl IntoIterator<Item = CspSource>) -> Self {
+        self.directive("frame-ancestors", sources)
+    }
+
+    /// Sets the `report-uri` directive, which is always rendered last.
+    pub fn report_uri(mut self, uri: impl Into<Cow<'static, str>>) -> Self {
+        self.report_uri = Some(uri.into());
+        self
+    }
+
+    fn directive(
+        mut self,
+        name: &'static str,
+        sources: impl IntoIterator<Item = CspSource>,
+    ) -> Self {
+        let sources = sources.into_iter().collect();
+
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = sources,
+            None => self.directives.push((name, sources)),
+        }
+
+        self
+    }
+
+    /// Renders the policy as a header value, using `nonce` for [`CspSource::Nonce`] sources.
+    pub fn render(&self, nonce: &str) -> String {
+        let mut buf = String::new();
+
+        for (name, sources) in &self.directives {
+            if !buf.is_empty() {
+                buf.push_str("; ");
+            }
+
+            buf.push_str(name);
+
+            for source in sources {
+                buf.push(' ');
+                source.write(nonce, &mut buf);
+            }
+        }
+
+        if let Some(ref uri) = self.report_uri {
+            if !buf.is_empty() {
+                buf.push_str("; ");
+            }
+
+            buf.push_str("report-uri ");
+            buf.push_str(uri);
+        }
+
+        buf
+    }
+}
+
+/// Nonce generated by the [`Csp`] middleware for the current request.
+///
+/// Available from the request extensions so that template engines can add it to inline
+/// `<script>` and `<style>` tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    fn generate() -> Self {
+        let bytes: [u8; 16] = rand::thread_rng().gen();
+        CspNonce(base64::encode(&bytes))
+    }
+
+    /// Returns the nonce as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Middleware for setting a Content-Security-Policy with a per-request nonce.
+///
+/// A fresh nonce is generated for every request and stored in the request extensions as a
+/// [`CspNonce`]. The `Content-Security-Policy` header is rendered from the [`CspPolicy`] with that
+/// nonce and replaces any policy set by the handler.
+///
+/// When a placeholder is configured with [`nonce_placeholder`](Self::nonce_placeholder), buffered
+/// `text/html` bodies up to the [`body_limit`](Self::body_limit) have every occurrence of the
+/// placeholder replaced with the nonce. Streaming and larger bodies are passed through untouched.
+///
+/// # Examples
+/// ```
+/// use actix_web::{
+///     middleware::{Csp, CspPolicy, CspSource},
+///     web, App, HttpResponse,
+/// };
+///
+/// let policy = CspPolicy::new().script_src(vec![CspSource::Nonce]);
+///
+/// let app = App::new()
+///     .wrap(Csp::new(policy).nonce_placeholder("{{csp-nonce}}"))
+///     .route(
+///         "/",
+///         web::get().to(|| {
+///             HttpResponse::Ok()
+///                 .content_type("text/html")
+///                 .body(r#"<script nonce="{{csp-nonce}}">init()</script>"#)
+///         }),
+///     );
+/// ```
+#[derive(Clone)]
+pub struct Csp {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    policy: CspPolicy,
+    placeholder: Option<Bytes>,
+    body_limit: usize,
+}
+
+impl Csp {
+    /// Constructs middleware that sets the given policy on every response.
+    pub fn new(policy: CspPolicy) -> Self {
+        Csp {
+            inner: Rc::new(Inner {
+                policy,
+                placeholder: None,
+                body_limit: 262_144,
+            }),
+        }
+    }
+
+    /// Sets token in HTML bodies to replace with the nonce.
+    ///
+    /// Body rewriting is disabled unless a placeholder is set.
+    ///
+    /// # Panics
+    /// Panics if `placeholder` is empty.
+    pub fn nonce_placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        let placeholder = placeholder.into();
+        assert!(
+            !placeholder.is_empty(),
+            "Nonce placeholder must not be empty"
+        );
+
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple `Inner` copies exist.")
+            .placeholder = Some(Bytes::from(placeholder));
+
+        self
+    }
+
+    /// Sets maximum size of bodies that are rewritten.
+    ///
+    /// Default is 256 KiB.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple `Inner` copies exist.")
+            .body_limit = limit;
+
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Csp
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CspMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CspMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+pub struct CspMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for CspMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = CspFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let nonce = CspNonce::generate();
+        req.extensions_mut().insert(nonce.clone());
+
+        CspFuture {
+            fut: self.service.call(req),
+            inner: Rc::clone(&self.inner),
+            nonce,
+            _body: PhantomData,
+        }
+    }
+}
+
+#[pin_project::pin_project]
+pub struct CspFuture<S: Service<ServiceRequest>, B> {
+    #[pin]
+    fut: S::Future,
+    inner: Rc<Inner>,
+    nonce: CspNonce,
+    _body: PhantomData<B>,
+}
+
+impl<S, B> Future for CspFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody + 'static,
+{
+    type Output = Result<ServiceResponse<B>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.fut.poll(cx))?;
+
+        let nonce = this.nonce.as_str();
+
+        let policy = this.inner.policy.render(nonce);
+        match HeaderValue::from_str(&policy) {
+            Ok(value) => {
+                res.headers_mut().insert(CONTENT_SECURITY_POLICY, value);
+            }
+            Err(_) => log::error!("Invalid Content-Security-Policy: {}", policy),
+        }
+
+        let placeholder = match this.inner.placeholder {
+            Some(ref placeholder) if is_html(res.headers()) => placeholder,
+            _ => return Poll::Ready(Ok(res)),
+        };
+
+        let limit = this.inner.body_limit;
+        let res = res.map_body(|head, mut body| {
+            let bytes = match buffered_body(&mut body, limit) {
+                Some(bytes) => bytes,
+                None => return body,
+            };
+
+            match replace_all(&bytes, placeholder, nonce.as_bytes()) {
+                Some(rewritten) => {
+                    head.headers.remove(CONTENT_LENGTH);
+                    ResponseBody::Other(Body::Bytes(rewritten))
+                }
+                None => ResponseBody::Other(Body::Bytes(bytes)),
+            }
+        });
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+/// Returns true if the response has a `text/html` content type.
+fn is_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<Mime>().ok())
+        .map_or(false, |mime| {
+            mime.type_() == mime::TEXT && mime.subtype() == mime::HTML
+        })
+}
+
+/// Takes the bytes of a buffered body that is no larger than `limit`.
+fn buffered_body<B>(body: &mut ResponseBody<B>, limit: usize) -> Option<Bytes>
+where
+    B: MessageBody + 'static,
+{
+    let body = match body {
+        ResponseBody::Body(body) => {
+            let body: &mut dyn MessageBody = body;
+            body.downcast_mut::<Body>()?
+        }
+        ResponseBody::Other(body) => body,
+    };
+
+    match body {
+        Body::Bytes(bytes) if bytes.len() <= limit => Some(mem::replace(bytes, Bytes::new())),
+        _ => None,
+    }
+}
+
+/// Replaces every occurrence of `from` with `to`, returning `None` if there are none.
+fn replace_all(haystack: &[u8], from: &[u8], to: &[u8]) -> Option<Bytes> {
+    let mut buf: Option<BytesMut> = None;
+    let mut start = 0;
+    let mut pos = 0;
+
+    while pos + from.len() <= haystack.len() {
+        if &haystack[pos..pos + from.len()] == from {
+            let buf = buf.get_or_insert_with(|| BytesMut::with_capacity(haystack.len()));
+            buf.extend_from_slice(&haystack[start..pos]);
+            buf.extend_from_slice(to);
+
+            pos += from.len();
+            start = pos;
+        } else {
+            pos += 1;
+        }
+    }
+
+    buf.map(|mut buf| {
+        buf.extend_from_slice(&haystack[start..]);
+        buf.freeze()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test::{self, TestRequest},
+        web, App, HttpRequest, HttpResponse,
+    };
+
+    #[test]
+    fn test_policy_render() {
+        let policy = CspPolicy::new()
+            .script_src(vec![CspSource::SelfOrigin, CspSource::Nonce])
+            .style_src(vec![
+                CspSource::SelfOrigin,
+                CspSource::host("https://fonts.example.com"),
+            ])
+            .frame_ancestors(vec![CspSource::None])
+            .report_uri("https://example.com/csp")
+            .script_src(vec![CspSource::Nonce, CspSource::StrictDynamic]);
+
+        assert_eq!(
+            policy.render("n0nce"),
+            "script-src 'nonce-n0nce' 'strict-dynamic'; \
+             style-src 'self' https://fonts.example.com; \
+             frame-ancestors 'none'; report-uri https://example.com/csp"
+        );
+
+        assert_eq!(CspPolicy::new().render("n0nce"), "");
+    }
+
+    #[actix_rt::test]
+    async fn test_nonce_header_and_body() {
+        let policy = CspPolicy::new().script_src(vec![CspSource::Nonce]);
+
+        let srv = test::init_service(
+            App::new()
+                .wrap(Csp::new(policy).nonce_placeholder("{{nonce}}"))
+                .route(
+                    "/html",
+                    web::get().to(|req: HttpRequest| {
+                        let nonce = req.extensions().get::<CspNonce>().unwrap().clone();
+                        HttpResponse::Ok()
+                            .content_type("text/html; charset=utf-8")
+                            .insert_header(("x-nonce", nonce.as_str()))
+                            .body("<script nonce=\"{{nonce}}\"></script>{{nonce}}")
+                    }),
+                )
+                .route(
+                    "/text",
+                    web::get().to(|| HttpResponse::Ok().body("{{nonce}}")),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&srv, TestRequest::with_uri("/html").to_request()).await;
+        let nonce = res
+            .headers()
+            .get("x-nonce")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_owned();
+        assert_eq!(nonce.len(), 24);
+        assert_eq!(
+            res.headers().get(CONTENT_SECURITY_POLICY).unwrap(),
+            &format!("script-src 'nonce-{}'", nonce)
+        );
+        assert_eq!(
+            test::read_body(res).await,
+            format!("<script nonce=\"{0}\"></script>{0}", nonce)
+        );
+
+        // only html bodies are rewritten
+        let res = test::call_service(&srv, TestRequest::with_uri("/text").to_request()).await;
+        assert!(res.headers().contains_key(CONTENT_SECURITY_POLICY));
+        assert_eq!(test::read_body(res).await, Bytes::from_static(b"{{nonce}}"));
+
+        // nonce differs between requests
+        let res = test::call_service(&srv, TestRequest::with_uri("/html").to_request()).await;
+        assert_ne!(res.headers().get("x-nonce").unwrap(), nonce.as_str());
+    }
+
+    #[actix_rt::test]
+    async fn test_body_limit() {
+        let srv = test::init_service(
+            App::new()
+                .wrap(
+                    Csp::new(CspPolicy::new())
+                        .nonce_placeholder("{{nonce}}")
+                        .body_limit(8),
+                )
+                .route(
+                    "/",
+                    web::get().to(|| {
+                        HttpResponse::Ok()
+                            .content_type("text/html")
+                            .body("too long {{nonce}}")
+                    }),
+                ),
+        )
+        .await;
+
+        let res = test::call_service(&srv, TestRequest::default().to_request()).await;
+        assert_eq!(
+            test::read_body(res).await,
+            Bytes::from_static(b"too long {{nonce}}")
+        );
+    }
+
+    #[test]
+    fn test_replace_all() {
+        assert_eq!(replace_all(b"abc", b"x", b"y"), None);
+        assert_eq!(
+            replace_all(b"xaxbx", b"x", b"yy").unwrap(),
+            Bytes::from_static(b"yyayybyy")
+        );
+    }
+}
@@ -7,9 +7,12 @@ use std::{
     task::{Context, Poll},
 };
 
+use actix_http::body::{Body, BodySize, EitherBody, MessageBody, ResponseBody};
 use actix_service::{Service, Transform};
 use ahash::AHashMap;
+use bytes::{Bytes, BytesMut};
 use futures_core::{future::LocalBoxFuture, ready};
+use futures_util::future::poll_fn;
 
 use crate::{
     dev::{ServiceRequest, ServiceResponse},
@@ -17,6 +20,9 @@ use crate::{
     http::StatusCode,
 };
 
+/// Default limit on the size of response bodies buffered for handlers; 64KiB.
+const DEFAULT_BUFFER_LIMIT: usize = 65_536;
+
 /// Return type for [`ErrorHandlers`] custom handlers.
 pub enum ErrorHandlerResponse<B> {
     /// Immediate HTTP response.
@@ -26,13 +32,19 @@ pub enum ErrorHandlerResponse<B> {
     Future(LocalBoxFuture<'static, Result<ServiceResponse<B>, Error>>),
 }
 
-type ErrorHandler<B> = dyn Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>>;
+type ErrorHandler = dyn Fn(ServiceResponse<Body>) -> Result<ErrorHandlerResponse<Body>>;
 
 /// Middleware for registering custom status code based error handlers.
 ///
 /// Register handlers with the `ErrorHandlers::handler()` method to register a custom error handler
 /// for a given status code. Handlers can modify existing responses or create completely new ones.
 ///
+/// Responses whose status has no registered handler pass through untouched, keeping their
+/// original body type as the left side of an [`EitherBody`]. Only for matched statuses the body is
+/// buffered and handed to the handler as `Body::Bytes`, so it can be read and rewritten. Bodies
+/// larger than the [`buffer_limit`](Self::buffer_limit) are not buffered; the handler receives them
+/// as a streaming `Body::Message` instead.
+///
 /// # Examples
 /// ```rust
 /// use actix_web::middleware::{ErrorHandlers, ErrorHandlerResponse};
@@ -55,21 +67,23 @@ type ErrorHandler<B> = dyn Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse
 ///         .route(web::head().to(|| HttpResponse::MethodNotAllowed())
 ///     ));
 /// ```
-pub struct ErrorHandlers<B> {
-    handlers: Handlers<B>,
+pub struct ErrorHandlers {
+    handlers: Handlers,
+    buffer_limit: usize,
 }
 
-type Handlers<B> = Rc<AHashMap<StatusCode, Box<ErrorHandler<B>>>>;
+type Handlers = Rc<AHashMap<StatusCode, Box<ErrorHandler>>>;
 
-impl<B> Default for ErrorHandlers<B> {
+impl Default for ErrorHandlers {
     fn default() -> Self {
         ErrorHandlers {
             handlers: Rc::new(AHashMap::default()),
+            buffer_limit: DEFAULT_BUFFER_LIMIT,
         }
     }
 }
 
-impl<B> ErrorHandlers<B> {
+impl ErrorHandlers {
     /// Construct new `ErrorHandlers` instance.
     pub fn new() -> Self {
         ErrorHandlers::default()
@@ -78,46 +92,62 @@ impl<B> ErrorHandlers<B> {
     /// Register error handler for specified status code.
     pub fn handler<F>(mut self, status: StatusCode, handler: F) -> Self
     where
-        F: Fn(ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> + 'static,
+        F: Fn(ServiceResponse<Body>) -> Result<ErrorHandlerResponse<Body>> + 'static,
     {
         Rc::get_mut(&mut self.handlers)
             .unwrap()
             .insert(status, Box::new(handler));
         self
     }
+
+    /// Set the max size in bytes of response bodies buffered for handlers.
+    ///
+    /// By default the limit is 64KiB.
+    pub fn buffer_limit(mut self, limit: usize) -> Self {
+        self.buffer_limit = limit;
+        self
+    }
 }
 
-impl<S, B> Transform<S, ServiceRequest> for ErrorHandlers<B>
+impl<S, B> Transform<S, ServiceRequest> for ErrorHandlers
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
-    type Transform = ErrorHandlersMiddleware<S, B>;
+    type Transform = ErrorHandlersMiddleware<S>;
     type InitError = ();
     type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
         let handlers = self.handlers.clone();
-        Box::pin(async move { Ok(ErrorHandlersMiddleware { service, handlers }) })
+        let buffer_limit = self.buffer_limit;
+        Box::pin(async move {
+            Ok(ErrorHandlersMiddleware {
+                service,
+                handlers,
+                buffer_limit,
+            })
+        })
     }
 }
 
 #[doc(hidden)]
-pub struct ErrorHandlersMiddleware<S, B> {
+pub struct ErrorHandlersMiddleware<S> {
     service: S,
-    handlers: Handlers<B>,
+    handlers: Handlers,
+    buffer_limit: usize,
 }
 
-impl<S, B> Service<ServiceRequest> for ErrorHandlersMiddleware<S, B>
+impl<S, B> Service<ServiceRequest> for ErrorHandlersMiddleware<S>
 where
     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
     S::Future: 'static,
-    B: 'static,
+    B: MessageBody + 'static,
 {
-    type Response = ServiceResponse<B>;
+    type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
     type Future = ErrorHandlersFuture<S::Future, B>;
 
@@ -126,7 +156,11 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let handlers = self.handlers.clone();
         let fut = self.service.call(req);
-        ErrorHandlersFuture::ServiceFuture { fut, handlers }
+        ErrorHandlersFuture::ServiceFuture {
+            fut,
+            handlers,
+            buffer_limit: self.buffer_limit,
+        }
     }
 }
 
@@ -138,40 +172,128 @@ where
     ServiceFuture {
         #[pin]
         fut: Fut,
-        handlers: Handlers<B>,
+        handlers: Handlers,
+        buffer_limit: usize,
     },
     HandlerFuture {
-        fut: LocalBoxFuture<'static, Fut::Output>,
+        fut: LocalBoxFuture<'static, Result<ServiceResponse<EitherBody<B>>, Error>>,
     },
 }
 
 impl<Fut, B> Future for ErrorHandlersFuture<Fut, B>
 where
     Fut: Future<Output = Result<ServiceResponse<B>, Error>>,
+    B: MessageBody + 'static,
 {
-    type Output = Fut::Output;
+    type Output = Result<ServiceResponse<EitherBody<B>>, Error>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.as_mut().project() {
-            ErrorHandlersProj::ServiceFuture { fut, handlers } => {
+            ErrorHandlersProj::ServiceFuture {
+                fut,
+                handlers,
+                buffer_limit,
+            } => {
                 let res = ready!(fut.poll(cx))?;
-                match handlers.get(&res.status()) {
-                    Some(handler) => match handler(res)? {
-                        ErrorHandlerResponse::Response(res) => Poll::Ready(Ok(res)),
-                        ErrorHandlerResponse::Future(fut) => {
-                            self.as_mut()
-                                .set(ErrorHandlersFuture::HandlerFuture { fut });
-                            self.poll(cx)
-                        }
-                    },
-                    None => Poll::Ready(Ok(res)),
+                let status = res.status();
+
+                if !handlers.contains_key(&status) {
+                    return Poll::Ready(Ok(res.map_into_left_body()));
                 }
+
+                let handlers = Rc::clone(handlers);
+                let buffer_limit = *buffer_limit;
+
+                let fut = async move {
+                    let res = buffer_body(res, buffer_limit).await?;
+
+                    let res = match (handlers[&status])(res)? {
+                        ErrorHandlerResponse::Response(res) => res,
+                        ErrorHandlerResponse::Future(fut) => fut.await?,
+                    };
+
+                    Ok(res.map_into_right_body())
+                };
+
+                self.as_mut()
+                    .set(ErrorHandlersFuture::HandlerFuture { fut: Box::pin(fut) });
+                self.poll(cx)
             }
             ErrorHandlersProj::HandlerFuture { fut } => fut.as_mut().poll(cx),
         }
     }
 }
 
+/// Buffers the body of the response if it is no larger than `limit`.
+///
+/// Larger bodies are boxed as they are, with any bytes read before the limit was hit put back in
+/// front of the rest of the stream.
+pub(super) async fn buffer_body<B>(
+    mut res: ServiceResponse<B>,
+    limit: usize,
+) -> Result<ServiceResponse<Body>>
+where
+    B: MessageBody + 'static,
+{
+    let mut body = Box::pin(res.take_body());
+
+    let body = match body.size() {
+        BodySize::None => Body::None,
+        BodySize::Empty => Body::Empty,
+        BodySize::Sized(len) if len > limit as u64 => {
+            Body::from_message(Remainder { prefix: None, body })
+        }
+        BodySize::Sized(_) | BodySize::Stream => {
+            let mut buf = BytesMut::new();
+
+            loop {
+                match poll_fn(|cx| body.as_mut().poll_next(cx)).await {
+                    Some(chunk) => {
+                        buf.extend_from_slice(&chunk?);
+
+                        if buf.len() > limit {
+                            break Body::from_message(Remainder {
+                                prefix: Some(buf.freeze()),
+                                body,
+                            });
+                        }
+                    }
+                    None => break Body::Bytes(buf.freeze()),
+                }
+            }
+        }
+    };
+
+    Ok(res.map_body(|_, _| ResponseBody::Other(body)))
+}
+
+/// Body that was partially read while buffering, followed by the rest of its stream.
+struct Remainder<B> {
+    prefix: Option<Bytes>,
+    body: Pin<Box<ResponseBody<B>>>,
+}
+
+impl<B: MessageBody> MessageBody for Remainder<B> {
+    fn size(&self) -> BodySize {
+        match self.prefix {
+            Some(_) => BodySize::Stream,
+            None => self.body.size(),
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Error>>> {
+        let this = self.get_mut();
+
+        match this.prefix.take() {
+            Some(prefix) => Poll::Ready(Some(Ok(prefix))),
+            None => this.body.as_mut().poll_next(cx),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use actix_service::IntoService;
@@ -182,6 +304,18 @@ mod tests {
     use crate::test::{self, TestRequest};
     use crate::HttpResponse;
 
+    fn rewrite_500(res: ServiceResponse<Body>) -> Result<ErrorHandlerResponse<Body>> {
+        let msg = match res.response().body() {
+            ResponseBody::Other(Body::Bytes(bytes)) => {
+                format!("rewritten: {}", String::from_utf8_lossy(bytes))
+            }
+            _ => "not buffered".to_owned(),
+        };
+
+        let res = res.into_response(HttpResponse::InternalServerError().body(msg));
+        Ok(ErrorHandlerResponse::Response(res))
+    }
+
     fn render_500<B>(mut res: ServiceResponse<B>) -> Result<ErrorHandlerResponse<B>> {
         res.response_mut()
             .headers_mut()
@@ -229,4 +363,86 @@ mod tests {
         let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
         assert_eq!(resp.headers().get(CONTENT_TYPE).unwrap(), "0001");
     }
+
+    #[actix_rt::test]
+    async fn test_pass_through() {
+        let srv =
+            |req: ServiceRequest| ok(req.into_response(HttpResponse::Ok().body("untouched")));
+
+        let mw = ErrorHandlers::new()
+            .handler(StatusCode::INTERNAL_SERVER_ERROR, rewrite_500)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert!(matches!(
+            resp.response().body(),
+            ResponseBody::Body(EitherBody::Left { .. })
+        ));
+        assert_eq!(test::read_body(resp).await, "untouched");
+    }
+
+    #[actix_rt::test]
+    async fn test_rewrite_body() {
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(HttpResponse::InternalServerError().body("oops")))
+        };
+
+        let mw = ErrorHandlers::new()
+            .handler(StatusCode::INTERNAL_SERVER_ERROR, rewrite_500)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(test::read_body(resp).await, "rewritten: oops");
+    }
+
+    #[actix_rt::test]
+    async fn test_buffer_limit() {
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(
+                HttpResponse::InternalServerError().body("larger than the limit"),
+            ))
+        };
+
+        let mw = ErrorHandlers::new()
+            .handler(StatusCode::INTERNAL_SERVER_ERROR, rewrite_500)
+            .buffer_limit(8)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert_eq!(test::read_body(resp).await, "not buffered");
+
+        let srv = |req: ServiceRequest| {
+            let chunks = futures_util::stream::iter(vec![
+                Ok::<_, Error>(Bytes::from_static(b"larger ")),
+                Ok(Bytes::from_static(b"than the limit")),
+            ]);
+            ok(req.into_response(HttpResponse::InternalServerError().streaming(chunks)))
+        };
+
+        let passthrough = |res: ServiceResponse<Body>| -> Result<ErrorHandlerResponse<Body>> {
+            assert!(matches!(
+                res.response().body(),
+                ResponseBody::Other(Body::Message(_))
+            ));
+            Ok(ErrorHandlerResponse::Response(res))
+        };
+
+        let mw = ErrorHandlers::new()
+            .handler(StatusCode::INTERNAL_SERVER_ERROR, passthrough)
+            .buffer_limit(8)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        // bytes read before the limit was hit are not lost
+        let resp = test::call_service(&mw, TestRequest::default().to_srv_request()).await;
+        assert_eq!(test::read_body(resp).await, "larger than the limit");
+    }
 }
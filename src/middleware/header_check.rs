@@ -0,0 +1,257 @@
+//! For middleware documentation, see [`ResponseHeaderCheck`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures_util::{
+    future::{ready, Ready},
+    ready,
+};
+
+use crate::{
+    body::{BodySize, MessageBody},
+    dev::{Service, Transform},
+    http::header::{HeaderName, CONTENT_TYPE},
+    service::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+/// Debugging middleware that warns about responses missing required headers.
+///
+/// Checks every response for the required headers, `Content-Type` by default, and logs a warning
+/// naming the request path and the missing headers. This catches handlers that forget to set a
+/// content type early on. Responses without a body are not required to have a `Content-Type`.
+///
+/// Responses that fail the check also get a [`MissingHeaders`] extension listing the missing
+/// headers, which outer middleware and tests can inspect.
+///
+/// The check only runs in debug builds; in release builds responses pass through unchanged.
+///
+/// # Examples
+/// ```
+/// use actix_web::{http::header, middleware::ResponseHeaderCheck, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(ResponseHeaderCheck::new().require(header::CACHE_CONTROL))
+///     .route("/", web::get().to(HttpResponse::Ok));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseHeaderCheck {
+    required: Rc<Vec<HeaderName>>,
+}
+
+impl ResponseHeaderCheck {
+    /// Constructs middleware requiring the `Content-Type` header.
+    pub fn new() -> Self {
+        ResponseHeaderCheck {
+            required: Rc::new(vec![CONTENT_TYPE]),
+        }
+    }
+
+    /// Adds a header every response must carry.
+    pub fn require(mut self, name: HeaderName) -> Self {
+        let required = Rc::get_mut(&mut self.required).expect("Multiple copies exist.");
+
+        if !required.contains(&name) {
+            required.push(name);
+        }
+
+        self
+    }
+}
+
+impl Default for ResponseHeaderCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Response extension listing required headers the response was missing.
+///
+/// Inserted by the [`ResponseHeaderCheck`] middleware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingHeaders(pub Vec<HeaderName>);
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseHeaderCheck
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ResponseHeaderCheckMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ResponseHeaderCheckMiddleware {
+            service,
+            required: Rc::clone(&self.required),
+        }))
+    }
+}
+
+pub struct ResponseHeaderCheckMiddleware<S> {
+    service: S,
+    required: Rc<Vec<HeaderName>>,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseHeaderCheckMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = ResponseHeaderCheckFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        ResponseHeaderCheckFuture {
+            fut: self.service.call(req),
+            required: Rc::clone(&self.required),
+            _body: PhantomData,
+        }
+    }
+}
+
+#[pin_project::pin_project]
+pub struct ResponseHeaderCheckFuture<S: Service<ServiceRequest>, B> {
+    #[pin]
+    fut: S::Future,
+    required: Rc<Vec<HeaderName>>,
+    _body: PhantomData<B>,
+}
+
+impl<S, B> Future for ResponseHeaderCheckFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    B: MessageBody,
+{
+    type Output = <S::Future as Future>::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.fut.poll(cx))?;
+
+        if !cfg!(debug_assertions) {
+            return Poll::Ready(Ok(res));
+        }
+
+        let has_body = !matches!(
+            res.response().body().size(),
+            BodySize::None | BodySize::Empty | BodySize::Sized(0)
+        );
+
+        let missing = this
+            .required
+            .iter()
+            .filter(|name| **name != CONTENT_TYPE || has_body)
+            .filter(|name| !res.headers().contains_key(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            log::warn!(
+                "Response to {} {} is missing required headers: {:?}",
+                res.request().method(),
+                res.request().path(),
+                missing
+            );
+
+            res.response_mut()
+                .extensions_mut()
+                .insert(MissingHeaders(missing));
+        }
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_service::IntoService;
+    use futures_util::future::ok;
+
+    use super::*;
+    use crate::{
+        http::header::CACHE_CONTROL,
+        test::{ok_service, TestRequest},
+        HttpResponse,
+    };
+
+    #[actix_rt::test]
+    async fn test_missing_content_type() {
+        let srv = |req: ServiceRequest| ok(req.into_response(HttpResponse::Ok().body("text")));
+        let mw = ResponseHeaderCheck::new()
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+
+        if cfg!(debug_assertions) {
+            let ext = res.response().extensions();
+            let missing = ext.get::<MissingHeaders>().unwrap();
+            assert_eq!(missing.0, vec![CONTENT_TYPE]);
+        } else {
+            assert!(res
+                .response()
+                .extensions()
+                .get::<MissingHeaders>()
+                .is_none());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_required_headers_present() {
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(
+                HttpResponse::Ok()
+                    .content_type("text/plain")
+                    .insert_header((CACHE_CONTROL, "no-store"))
+                    .body("text"),
+            ))
+        };
+        let mw = ResponseHeaderCheck::new()
+            .require(CACHE_CONTROL)
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert!(res
+            .response()
+            .extensions()
+            .get::<MissingHeaders>()
+            .is_none());
+
+        // bodyless responses need no content type
+        let mw = ResponseHeaderCheck::new()
+            .new_transform(ok_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert!(res
+            .response()
+            .extensions()
+            .get::<MissingHeaders>()
+            .is_none());
+    }
+}
@@ -71,6 +71,9 @@ use crate::{
 /// `%{FOO}o` | `response.headers["FOO"]`
 /// `%{FOO}e` | `env_var["FOO"]`
 /// `%{FOO}xi` | [Custom request replacement](Logger::custom_request_replace) labelled "FOO"
+/// `%{header_bytes}w` | Size of the request head **\*\***
+/// `%{parse_micros}w` | Time taken to receive and parse the request head, in microseconds
+/// `%{preface_wait_micros}w` | Time waited for the client's first bytes, in microseconds
 ///
 /// # Security
 /// **\*** "Real IP" remote address is calculated using
@@ -78,6 +81,9 @@ use crate::{
 ///
 /// If you use this value, ensure that all requests come from trusted hosts. Otherwise, it is
 /// trivial for the remote client to falsify their source IP address.
+///
+/// **\*\*** Measured differently for HTTP/1 and HTTP/2, see
+/// [`RequestWireStats`](crate::dev::RequestWireStats).
 #[derive(Debug)]
 pub struct Logger(Rc<Inner>);
 
@@ -363,7 +369,7 @@ impl Format {
     /// Returns `None` if the format string syntax is incorrect.
     pub fn new(s: &str) -> Format {
         log::trace!("Access log format: {}", s);
-        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([aioe]|xi)|[atPrUsbTD]?)").unwrap();
+        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([aioew]|xi)|[atPrUsbTD]?)").unwrap();
 
         let mut idx = 0;
         let mut results = Vec::new();
@@ -392,6 +398,14 @@ impl Format {
                     }
                     "e" => FormatText::EnvironHeader(key.as_str().to_owned()),
                     "xi" => FormatText::CustomRequest(key.as_str().to_owned(), None),
+                    "w" => match key.as_str() {
+                        "header_bytes" => FormatText::WireStat(WireStat::HeaderBytes),
+                        "parse_micros" => FormatText::WireStat(WireStat::ParseMicros),
+                        "preface_wait_micros" => {
+                            FormatText::WireStat(WireStat::PrefaceWaitMicros)
+                        }
+                        _ => FormatText::Str(m.as_str().to_owned()),
+                    },
                     _ => unreachable!(),
                 })
             } else {
@@ -439,6 +453,15 @@ enum FormatText {
     ResponseHeader(HeaderName),
     EnvironHeader(String),
     CustomRequest(String, Option<CustomRequestFn>),
+    WireStat(WireStat),
+}
+
+/// Field of the request's [`RequestWireStats`](crate::dev::RequestWireStats).
+#[derive(Debug, Clone, Copy)]
+enum WireStat {
+    HeaderBytes,
+    ParseMicros,
+    PrefaceWaitMicros,
 }
 
 #[derive(Clone)]
@@ -569,6 +592,15 @@ impl FormatText {
 
                 *self = s;
             }
+            FormatText::WireStat(stat) => {
+                let stats = req.head().wire_stats;
+                let val = match stat {
+                    WireStat::HeaderBytes => stats.header_bytes as u64,
+                    WireStat::ParseMicros => stats.parse_micros,
+                    WireStat::PrefaceWaitMicros => stats.preface_wait_micros,
+                };
+                *self = FormatText::Str(val.to_string());
+            }
             _ => {}
         }
     }
@@ -591,6 +623,7 @@ mod tests {
     use futures_util::future::ok;
 
     use super::*;
+    use crate::dev::RequestWireStats;
     use crate::http::{header, StatusCode};
     use crate::test::{self, TestRequest};
 
@@ -765,6 +798,33 @@ mod tests {
         assert!(s.contains("192.0.2.60"));
     }
 
+    #[actix_rt::test]
+    async fn test_wire_stats_format() {
+        let mut format =
+            Format::new("%{header_bytes}w %{parse_micros}w %{preface_wait_micros}w %{x}w");
+
+        let mut req = TestRequest::default().to_srv_request();
+        req.head_mut().wire_stats = RequestWireStats {
+            header_bytes: 4096,
+            parse_micros: 250,
+            preface_wait_micros: 1200,
+        };
+
+        let now = OffsetDateTime::now_utc();
+        for unit in &mut format.0 {
+            unit.render_request(now, &req);
+        }
+
+        let render = |fmt: &mut fmt::Formatter<'_>| {
+            for unit in &format.0 {
+                unit.render(fmt, 1024, now)?;
+            }
+            Ok(())
+        };
+        let s = format!("{}", FormatDisplay(&render));
+        assert_eq!(s, "4096 250 1200 %{x}w");
+    }
+
     #[actix_rt::test]
     async fn test_custom_closure_log() {
         let mut logger = Logger::new("test %{CUSTOM}xi")
@@ -1,20 +1,251 @@
 //! Commonly used middleware.
+//!
+//! # Authoring middleware
+//! Middleware that passes some requests on and answers others itself has to return one response
+//! type for both paths. Rather than boxing the inner service's body, declare the response as
+//! `ServiceResponse<EitherBody<B, R>>`, where `B` is the body of the wrapped service and `R` the
+//! body of the middleware's own responses. Wrap passed through responses with
+//! [`ServiceResponse::map_into_left_body`] and the middleware's own with
+//! [`ServiceResponse::map_into_right_body`]. `R` defaults to [`Body`](crate::dev::Body); naming a
+//! concrete type, such as a [`BodyStream`](crate::dev::BodyStream), keeps the middleware's own
+//! responses unboxed as well.
+//!
+//! Since [`EitherBody`](crate::dev::EitherBody) is itself a
+//! [`MessageBody`](crate::dev::MessageBody), such middleware compose with each other and with
+//! body wrapping middleware like `Compress` in any order.
+//!
+//! ```
+//! use std::vec;
+//!
+//! use actix_web::{
+//!     dev::{BodyStream, EitherBody, Service, ServiceRequest, ServiceResponse, Transform},
+//!     web::Bytes,
+//!     Error, HttpResponse,
+//! };
+//! use futures_util::{
+//!     future::{ready, Either, LocalBoxFuture, Ready},
+//!     stream::{self, Iter},
+//!     FutureExt as _,
+//! };
+//!
+//! type Notice = BodyStream<Iter<vec::IntoIter<Result<Bytes, Error>>>>;
+//!
+//! /// Answers every request with a streamed maintenance notice while enabled.
+//! pub struct Maintenance(pub bool);
+//!
+//! impl<S, B> Transform<S, ServiceRequest> for Maintenance
+//! where
+//!     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+//!     S::Future: 'static,
+//! {
+//!     type Response = ServiceResponse<EitherBody<B, Notice>>;
+//!     type Error = Error;
+//!     type Transform = MaintenanceMiddleware<S>;
+//!     type InitError = ();
+//!     type Future = Ready<Result<Self::Transform, Self::InitError>>;
+//!
+//!     fn new_transform(&self, service: S) -> Self::Future {
+//!         ready(Ok(MaintenanceMiddleware { service, enabled: self.0 }))
+//!     }
+//! }
+//!
+//! pub struct MaintenanceMiddleware<S> {
+//!     service: S,
+//!     enabled: bool,
+//! }
+//!
+//! impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+//! where
+//!     S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+//!     S::Future: 'static,
+//! {
+//!     type Response = ServiceResponse<EitherBody<B, Notice>>;
+//!     type Error = Error;
+//!     type Future = Either<
+//!         Ready<Result<Self::Response, Error>>,
+//!         LocalBoxFuture<'static, Result<Self::Response, Error>>,
+//!     >;
+//!
+//!     actix_service::forward_ready!(service);
+//!
+//!     fn call(&self, req: ServiceRequest) -> Self::Future {
+//!         if self.enabled {
+//!             let notice = stream::iter(vec![
+//!                 Ok(Bytes::from_static(b"down for maintenance, ")),
+//!                 Ok(Bytes::from_static(b"back soon")),
+//!             ]);
+//!
+//!             let res = HttpResponse::ServiceUnavailable().message_body(BodyStream::new(notice));
+//!             return Either::Left(ready(Ok(req.into_response(res).map_into_right_body())));
+//!         }
+//!
+//!         let fut = self.service.call(req);
+//!         Either::Right(async move { Ok(fut.await?.map_into_left_body()) }.boxed_local())
+//!     }
+//! }
+//! ```
 
+mod body_rewrite;
+mod body_transform;
 mod compat;
 mod condition;
+mod content_type;
+mod csp;
 mod default_headers;
 mod err_handlers;
+mod header_check;
 mod logger;
 mod normalize;
+mod propagation;
+mod version_header;
 
+pub use self::body_rewrite::BodyRewrite;
+pub use self::body_transform::{BodyTransform, BodyTransformConfig};
 pub use self::compat::Compat;
 pub use self::condition::Condition;
+pub use self::content_type::RequireContentType;
+pub use self::csp::{Csp, CspNonce, CspPolicy, CspSource};
 pub use self::default_headers::DefaultHeaders;
 pub use self::err_handlers::{ErrorHandlerResponse, ErrorHandlers};
+pub use self::header_check::{MissingHeaders, ResponseHeaderCheck};
 pub use self::logger::Logger;
-pub use self::normalize::{NormalizePath, TrailingSlash};
+pub use self::normalize::{NormalizePath, OriginalPath, TrailingSlash};
+pub use self::propagation::{PropagationCapture, PropagationConfig};
+pub use self::version_header::VersionHeader;
 
 #[cfg(feature = "compress")]
 mod compress;
 #[cfg(feature = "compress")]
 pub use self::compress::Compress;
+
+#[cfg(all(test, feature = "compress"))]
+mod tests {
+    use std::io::Read as _;
+
+    use actix_service::{Service, Transform};
+    use bytes::Bytes;
+    use flate2::read::GzDecoder;
+    use futures_util::{
+        future::{ok, Either, LocalBoxFuture, Ready},
+        stream::{self, Iter},
+        FutureExt as _,
+    };
+
+    use super::*;
+    use crate::{
+        dev::{BodyStream, EitherBody, ServiceRequest, ServiceResponse},
+        http::{header, StatusCode},
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, Error, HttpResponse,
+    };
+
+    type Chunks = BodyStream<Iter<std::vec::IntoIter<Result<Bytes, Error>>>>;
+
+    /// Answers requests to `path` with a streamed `body` instead of calling the inner service.
+    struct ShortCircuit {
+        path: &'static str,
+        body: &'static [&'static str],
+    }
+
+    impl<S, B> Transform<S, ServiceRequest> for ShortCircuit
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+    {
+        type Response = ServiceResponse<EitherBody<B, Chunks>>;
+        type Error = Error;
+        type Transform = ShortCircuitMiddleware<S>;
+        type InitError = ();
+        type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+        fn new_transform(&self, service: S) -> Self::Future {
+            ok(ShortCircuitMiddleware {
+                service,
+                path: self.path,
+                body: self.body,
+            })
+        }
+    }
+
+    struct ShortCircuitMiddleware<S> {
+        service: S,
+        path: &'static str,
+        body: &'static [&'static str],
+    }
+
+    impl<S, B> Service<ServiceRequest> for ShortCircuitMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+    {
+        type Response = ServiceResponse<EitherBody<B, Chunks>>;
+        type Error = Error;
+        type Future = Either<
+            Ready<Result<Self::Response, Error>>,
+            LocalBoxFuture<'static, Result<Self::Response, Error>>,
+        >;
+
+        actix_service::forward_ready!(service);
+
+        fn call(&self, req: ServiceRequest) -> Self::Future {
+            if req.path() == self.path {
+                let chunks = self
+                    .body
+                    .iter()
+                    .map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())))
+                    .collect::<Vec<_>>();
+
+                let res =
+                    HttpResponse::Ok().message_body(BodyStream::new(stream::iter(chunks)));
+                return Either::Left(ok(req.into_response(res).map_into_right_body()));
+            }
+
+            let fut = self.service.call(req);
+            Either::Right(async move { Ok(fut.await?.map_into_left_body()) }.boxed_local())
+        }
+    }
+
+    fn gunzip(body: &[u8]) -> String {
+        let mut out = String::new();
+        GzDecoder::new(body).read_to_string(&mut out).unwrap();
+        out
+    }
+
+    #[actix_rt::test]
+    async fn test_compose_short_circuiting_middleware() {
+        let srv = init_service(
+            App::new()
+                .wrap(ShortCircuit {
+                    path: "/inner",
+                    body: &["inner ", "short ", "circuit"],
+                })
+                .wrap(ShortCircuit {
+                    path: "/outer",
+                    body: &["outer ", "short ", "circuit"],
+                })
+                .wrap(Compress::default())
+                .default_service(web::to(|| HttpResponse::Ok().body("from handler"))),
+        )
+        .await;
+
+        for (path, expected) in &[
+            ("/", "from handler"),
+            ("/inner", "inner short circuit"),
+            ("/outer", "outer short circuit"),
+        ] {
+            let req = TestRequest::with_uri(path).to_request();
+            let res = call_service(&srv, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert!(!res.headers().contains_key(header::CONTENT_ENCODING));
+            assert_eq!(read_body(res).await, expected.as_bytes());
+
+            let req = TestRequest::with_uri(path)
+                .insert_header((header::ACCEPT_ENCODING, "gzip"))
+                .to_request();
+            let res = call_service(&srv, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+            assert_eq!(gunzip(&read_body(res).await), *expected);
+        }
+    }
+}
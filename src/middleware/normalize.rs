@@ -1,5 +1,7 @@
 //! For middleware documentation, see [`NormalizePath`].
 
+use std::fmt;
+
 use actix_http::http::{PathAndQuery, Uri};
 use actix_service::{Service, Transform};
 use bytes::Bytes;
@@ -8,7 +10,7 @@ use regex::Regex;
 
 use crate::{
     service::{ServiceRequest, ServiceResponse},
-    Error,
+    Error, HttpMessage as _,
 };
 
 /// Determines the behavior of the [`NormalizePath`] middleware.
@@ -39,6 +41,26 @@ impl Default for TrailingSlash {
     }
 }
 
+/// Path of a request before it was changed by [`NormalizePath`].
+///
+/// Inserted into the request extensions by the middleware whenever it rewrites the path. Requests
+/// whose path was already normal do not have it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginalPath(String);
+
+impl OriginalPath {
+    /// Returns the original path as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for OriginalPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Middleware for normalizing a request's path so that routes can be matched more flexibly.
 ///
 /// # Normalization Steps
@@ -47,6 +69,15 @@ impl Default for TrailingSlash {
 ///   slashes as-is, depending on which [`TrailingSlash`] variant is supplied
 ///   to [`new`](NormalizePath::new()).
 ///
+/// The path is rewritten in place before routing, without redirecting the client, so all variants
+/// of a path are served by the same resource and [`HttpRequest::match_pattern`] and
+/// [`HttpRequest::url_for`] see the normalized path. Only literal slashes are merged;
+/// percent-encoded slashes (`%2F`) are left untouched. The path as sent by the client is kept in
+/// the request extensions as an [`OriginalPath`].
+///
+/// [`HttpRequest::match_pattern`]: crate::HttpRequest::match_pattern
+/// [`HttpRequest::url_for`]: crate::HttpRequest::url_for
+///
 /// # Default Behavior
 /// The default constructor chooses to strip trailing slashes from the end of paths with them
 /// ([`TrailingSlash::Trim`]). The implication is that route definitions should be defined without
@@ -174,8 +205,11 @@ where
             parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
 
             let uri = Uri::from_parts(parts).unwrap();
+            let original = OriginalPath(original_path.to_owned());
+
             req.match_info_mut().get_mut().update(&uri);
             req.head_mut().uri = uri;
+            req.extensions_mut().insert(original);
         }
 
         self.service.call(req)
@@ -189,8 +223,8 @@ mod tests {
     use super::*;
     use crate::{
         dev::ServiceRequest,
-        test::{call_service, init_service, TestRequest},
-        web, App, HttpResponse,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpRequest, HttpResponse,
     };
 
     #[actix_rt::test]
@@ -304,6 +338,48 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn test_original_path() {
+        let app = init_service(
+            App::new().wrap(NormalizePath::default()).service(
+                web::resource("/a/{b}")
+                    .name("item")
+                    .to(|req: HttpRequest| async move {
+                        let original = req
+                            .extensions()
+                            .get::<OriginalPath>()
+                            .map(|path| path.to_string())
+                            .unwrap_or_else(|| "-".to_owned());
+
+                        format!(
+                            "{} {} {} {}",
+                            req.path(),
+                            original,
+                            req.match_pattern().unwrap(),
+                            req.url_for("item", &["c"]).unwrap().path()
+                        )
+                    }),
+            ),
+        )
+        .await;
+
+        let tests = vec![
+            ("/a/b", "/a/b - /a/{b} /a/c"),
+            ("/a/b/", "/a/b /a/b/ /a/{b} /a/c"),
+            ("/a//b", "/a/b /a//b /a/{b} /a/c"),
+            ("//a//b//?x=1", "/a/b //a//b// /a/{b} /a/c"),
+            // percent-encoded slashes are not merged
+            ("/a/%2F%2Fb", "/a/%2F%2Fb - /a/{b} /a/c"),
+        ];
+
+        for (path, expected) in tests {
+            let req = TestRequest::with_uri(path).to_request();
+            let res = call_service(&app, req).await;
+            assert!(res.status().is_success(), "{}", path);
+            assert_eq!(read_body(res).await, expected);
+        }
+    }
+
     #[actix_rt::test]
     async fn test_in_place_normalization() {
         let srv = |req: ServiceRequest| {
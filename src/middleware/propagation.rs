@@ -0,0 +1,204 @@
+//! For middleware documentation, see [`PropagationCapture`].
+
+use std::rc::Rc;
+
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+
+use crate::{
+    client::Propagate,
+    dev::{Service, Transform},
+    http::{header::HeaderName, HeaderMap},
+    service::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+/// Names of the request headers propagated to outgoing client requests.
+///
+/// Register with [`App::app_data`](crate::App::app_data) to configure [`PropagationCapture`].
+/// When not registered, `X-Request-Id`, `traceparent` and `baggage` are propagated.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::{PropagationCapture, PropagationConfig}, App};
+///
+/// let app = App::new()
+///     .app_data(PropagationConfig::new(&["x-request-id", "x-tenant"]))
+///     .wrap(PropagationCapture);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PropagationConfig {
+    headers: Rc<[HeaderName]>,
+}
+
+impl PropagationConfig {
+    /// Constructs config propagating the given headers.
+    ///
+    /// # Panics
+    /// Panics if any of `headers` is not a valid header name.
+    pub fn new<I>(headers: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let headers = headers
+            .into_iter()
+            .map(|name| HeaderName::from_bytes(name.as_ref().as_bytes()).unwrap())
+            .collect::<Vec<_>>();
+
+        PropagationConfig {
+            headers: headers.into(),
+        }
+    }
+
+    /// Returns names of the propagated headers.
+    pub fn headers(&self) -> &[HeaderName] {
+        &self.headers
+    }
+}
+
+impl Default for PropagationConfig {
+    fn default() -> Self {
+        PropagationConfig::new(&["x-request-id", "traceparent", "baggage"])
+    }
+}
+
+/// Middleware that makes headers of the current request available to outgoing client requests.
+///
+/// While the wrapped service handles a request, the request's values of the headers listed in
+/// [`PropagationConfig`] are propagated to requests sent by clients using the
+/// [`client::Propagate`](crate::client::Propagate) middleware. This correlates the requests a
+/// handler makes to upstream services with the request it is serving, without threading headers
+/// through handler code.
+///
+/// The values are held in a task-local for the duration of the handler call. Closures run with
+/// [`web::block`](crate::web::block) and tasks spawned by the handler do not see them; send those
+/// requests before handing off, or pass the values along and use
+/// [`Propagate::scope`](crate::client::Propagate::scope) yourself.
+///
+/// # Examples
+/// ```
+/// use actix_web::{client::{Client, Propagate}, middleware::PropagationCapture, web, App};
+///
+/// async fn index() -> String {
+///     let client = Client::builder().wrap(Propagate::new()).finish();
+///
+///     // carries the `X-Request-Id` of the request being served
+///     let _res = client.get("http://localhost:8081/").send().await;
+///     "done".to_owned()
+/// }
+///
+/// let app = App::new()
+///     .wrap(PropagationCapture)
+///     .route("/", web::get().to(index));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PropagationCapture;
+
+impl<S, B> Transform<S, ServiceRequest> for PropagationCapture
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = PropagationCaptureMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(PropagationCaptureMiddleware { service }))
+    }
+}
+
+pub struct PropagationCaptureMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for PropagationCaptureMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let mut headers = HeaderMap::new();
+
+        {
+            let default_config;
+            let config = match req.app_data::<PropagationConfig>() {
+                Some(config) => config,
+                None => {
+                    default_config = PropagationConfig::default();
+                    &default_config
+                }
+            };
+
+            for name in config.headers() {
+                for value in req.headers().get_all(name) {
+                    headers.append(name.clone(), value.clone());
+                }
+            }
+        }
+
+        Box::pin(Propagate::scope(headers, self.service.call(req)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Client,
+        test::{self, TestRequest},
+        web, App, HttpRequest,
+    };
+
+    #[actix_rt::test]
+    async fn test_propagation() {
+        let upstream = test::start(|| {
+            App::new().default_service(web::to(|req: HttpRequest| async move {
+                let header = |name| {
+                    req.headers()
+                        .get(name)
+                        .map(|val| val.to_str().unwrap().to_owned())
+                        .unwrap_or_else(|| "-".to_owned())
+                };
+                format!("{} {}", header("x-request-id"), header("x-tenant"))
+            }))
+        });
+        let upstream_url = upstream.url("/");
+
+        let srv = test::init_service(
+            App::new()
+                .app_data(PropagationConfig::new(&["x-request-id", "x-tenant"]))
+                .wrap(PropagationCapture)
+                .default_service(web::to(move || {
+                    let url = upstream_url.clone();
+
+                    async move {
+                        let client = Client::builder().wrap(Propagate::new()).finish();
+                        let mut res = client.get(url).send().await.unwrap();
+                        res.body().await.unwrap()
+                    }
+                })),
+        )
+        .await;
+
+        let req = TestRequest::default()
+            .insert_header(("x-request-id", "abc-123"))
+            .insert_header(("x-tenant", "acme"))
+            .insert_header(("x-other", "ignored"))
+            .to_request();
+        let body = test::read_response(&srv, req).await;
+        assert_eq!(body, "abc-123 acme");
+
+        let req = TestRequest::default().to_request();
+        let body = test::read_response(&srv, req).await;
+        assert_eq!(body, "- -");
+    }
+}
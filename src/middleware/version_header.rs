@@ -0,0 +1,194 @@
+//! For middleware documentation, see [`VersionHeader`].
+
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use futures_util::{
+    future::{ready, Ready},
+    ready,
+};
+
+use crate::{
+    dev::{Service, Transform},
+    http::header::{HeaderName, HeaderValue},
+    service::{ServiceRequest, ServiceResponse},
+    Error,
+};
+
+/// Middleware for tagging responses with the application's version.
+///
+/// Adds an `X-App-Version` header, or the header set with [`header_name`](Self::header_name),
+/// to every response. Responses that already carry the header keep their value.
+///
+/// # Examples
+/// ```
+/// use actix_web::{middleware::VersionHeader, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .wrap(VersionHeader::new(env!("CARGO_PKG_VERSION")))
+///     .route("/", web::get().to(HttpResponse::Ok));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VersionHeader {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    name: HeaderName,
+    value: HeaderValue,
+}
+
+impl VersionHeader {
+    /// Constructs middleware that adds `version` to every response.
+    ///
+    /// # Panics
+    /// Panics if `version` is not a valid header value.
+    pub fn new(version: &'static str) -> Self {
+        VersionHeader {
+            inner: Rc::new(Inner {
+                name: HeaderName::from_static("x-app-version"),
+                value: HeaderValue::from_static(version),
+            }),
+        }
+    }
+
+    /// Sets name of the version header.
+    ///
+    /// Default is `X-App-Version`.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a valid lowercase header name.
+    pub fn header_name(mut self, name: &'static str) -> Self {
+        Rc::get_mut(&mut self.inner)
+            .expect("Multiple `Inner` copies exist.")
+            .name = HeaderName::from_static(name);
+
+        self
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for VersionHeader
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = VersionHeaderMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(VersionHeaderMiddleware {
+            service,
+            inner: Rc::clone(&self.inner),
+        }))
+    }
+}
+
+pub struct VersionHeaderMiddleware<S> {
+    service: S,
+    inner: Rc<Inner>,
+}
+
+impl<S, B> Service<ServiceRequest> for VersionHeaderMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = VersionHeaderFuture<S, B>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        VersionHeaderFuture {
+            fut: self.service.call(req),
+            inner: Rc::clone(&self.inner),
+            _body: PhantomData,
+        }
+    }
+}
+
+#[pin_project::pin_project]
+pub struct VersionHeaderFuture<S: Service<ServiceRequest>, B> {
+    #[pin]
+    fut: S::Future,
+    inner: Rc<Inner>,
+    _body: PhantomData<B>,
+}
+
+impl<S, B> Future for VersionHeaderFuture<S, B>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+{
+    type Output = <S::Future as Future>::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.fut.poll(cx))?;
+
+        if !res.headers().contains_key(&this.inner.name) {
+            res.headers_mut()
+                .insert(this.inner.name.clone(), this.inner.value.clone());
+        }
+
+        Poll::Ready(Ok(res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_service::IntoService;
+    use futures_util::future::ok;
+
+    use super::*;
+    use crate::{
+        test::{ok_service, TestRequest},
+        HttpResponse,
+    };
+
+    #[actix_rt::test]
+    async fn test_version_header() {
+        let mw = VersionHeader::new("1.2.3")
+            .new_transform(ok_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get_all("x-app-version").count(), 1);
+        assert_eq!(res.headers().get("x-app-version").unwrap(), "1.2.3");
+    }
+
+    #[actix_rt::test]
+    async fn test_existing_value_kept() {
+        let srv = |req: ServiceRequest| {
+            ok(req.into_response(
+                HttpResponse::Ok()
+                    .insert_header(("x-build", "handler"))
+                    .finish(),
+            ))
+        };
+        let mw = VersionHeader::new("1.2.3")
+            .header_name("x-build")
+            .new_transform(srv.into_service())
+            .await
+            .unwrap();
+
+        let res = mw
+            .call(TestRequest::default().to_srv_request())
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get_all("x-build").count(), 1);
+        assert_eq!(res.headers().get("x-build").unwrap(), "handler");
+        assert!(!res.headers().contains_key("x-app-version"));
+    }
+}
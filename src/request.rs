@@ -3,7 +3,9 @@ use std::rc::Rc;
 use std::{fmt, net};
 
 use actix_http::http::{HeaderMap, Method, Uri, Version};
-use actix_http::{Error, Extensions, HttpMessage, Message, Payload, RequestHead};
+use actix_http::{
+    EarlyHints, Error, Extensions, HttpMessage, Message, Payload, RequestHead, RequestWireStats,
+};
 use actix_router::{Path, Url};
 use futures_util::future::{ok, Ready};
 use smallvec::SmallVec;
@@ -12,7 +14,7 @@ use crate::app_service::AppInitServiceState;
 use crate::config::AppConfig;
 use crate::error::UrlGenerationError;
 use crate::extract::FromRequest;
-use crate::info::ConnectionInfo;
+use crate::info::{ConnectionInfo, TrustForwardedPrefix};
 use crate::rmap::ResourceMap;
 
 #[derive(Clone)]
@@ -157,6 +159,33 @@ impl HttpRequest {
         self.head().extensions_mut()
     }
 
+    /// Handle for sending `103 Early Hints` responses before the final response.
+    ///
+    /// Returns a detached handle, whose [`send`](EarlyHints::send) always returns false, if the
+    /// connection does not support early hints.
+    ///
+    /// ```rust
+    /// use actix_web::{http::header, HttpRequest, HttpResponse};
+    ///
+    /// async fn index(req: HttpRequest) -> HttpResponse {
+    ///     let mut hints = header::HeaderMap::new();
+    ///     hints.insert(
+    ///         header::LINK,
+    ///         header::HeaderValue::from_static("</style.css>; rel=preload; as=style"),
+    ///     );
+    ///     req.early_hints().send(hints);
+    ///
+    ///     // ... produce the final response
+    ///     HttpResponse::Ok().finish()
+    /// }
+    /// ```
+    pub fn early_hints(&self) -> EarlyHints {
+        self.extensions()
+            .get::<EarlyHints>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// Generate url for named resource
     ///
     /// ```rust
@@ -183,6 +212,40 @@ impl HttpRequest {
         self.resource_map().url_for(&self, name, elements)
     }
 
+    /// Generate path to named resource, relative to the path of the current request.
+    ///
+    /// Relative paths resolve correctly in the browser no matter which external prefix a reverse
+    /// proxy mounts the application under, which makes them suited to links in HTML responses.
+    /// For a request to `/user/1/posts`, the `"post"` resource `/user/{id}/post/{post_id}`
+    /// with `["1", "7"]` generates `post/7`. External resources are returned as is.
+    ///
+    /// ```
+    /// # use actix_web::{web, App, HttpRequest, HttpResponse};
+    /// async fn index(req: HttpRequest) -> HttpResponse {
+    ///     let link = req.relative_url_for("post", &["1", "7"]).unwrap();
+    ///     HttpResponse::Ok().body(format!("<a href=\"{}\">next</a>", link))
+    /// }
+    ///
+    /// let app = App::new()
+    ///     .service(web::resource("/user/{id}/posts").to(index))
+    ///     .service(
+    ///         web::resource("/user/{id}/post/{post_id}")
+    ///             .name("post")
+    ///             .to(|| HttpResponse::Ok()),
+    ///     );
+    /// ```
+    pub fn relative_url_for<U, I>(
+        &self,
+        name: &str,
+        elements: U,
+    ) -> Result<String, UrlGenerationError>
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        self.resource_map().relative_url_for(&self, name, elements)
+    }
+
     /// Generate url for named resource
     ///
     /// This method is similar to `HttpRequest::url_for()` but it can be used
@@ -211,13 +274,24 @@ impl HttpRequest {
         self.head().peer_addr
     }
 
+    /// Measurements of how the request head was received.
+    ///
+    /// Useful for spotting clients that send oversized heads or send them slowly.
+    #[inline]
+    pub fn wire_stats(&self) -> RequestWireStats {
+        self.head().wire_stats
+    }
+
     /// Get *ConnectionInfo* for the current request.
     ///
     /// This method panics if request's extensions container is already
     /// borrowed.
     #[inline]
     pub fn connection_info(&self) -> Ref<'_, ConnectionInfo> {
-        ConnectionInfo::get(self.head(), self.app_config())
+        let trust_prefix = self
+            .app_data::<TrustForwardedPrefix>()
+            .map_or(false, |trust| trust.0);
+        ConnectionInfo::get_with_prefix(self.head(), self.app_config(), trust_prefix)
     }
 
     /// App config
@@ -497,6 +571,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_url_for_forwarded_prefix() {
+        let rmap = || {
+            let mut res = ResourceDef::new("/user/{name}.{ext}");
+            *res.name_mut() = "index".to_string();
+
+            let mut rmap = ResourceMap::new(ResourceDef::new(""));
+            rmap.add(&mut res, None);
+            rmap
+        };
+
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "www.rust-lang.org"))
+            .insert_header(("x-forwarded-prefix", "/app"))
+            .rmap(rmap())
+            .to_http_request();
+        let url = req.url_for("index", &["test", "html"]).unwrap();
+        assert_eq!(url.as_str(), "http://www.rust-lang.org/user/test.html");
+
+        let req = TestRequest::default()
+            .insert_header((header::HOST, "www.rust-lang.org"))
+            .insert_header(("x-forwarded-prefix", "/app"))
+            .app_data(TrustForwardedPrefix(true))
+            .rmap(rmap())
+            .to_http_request();
+        let url = req.url_for("index", &["test", "html"]).unwrap();
+        assert_eq!(url.as_str(), "http://www.rust-lang.org/app/user/test.html");
+    }
+
+    #[test]
+    fn test_relative_url_for() {
+        let mut rmap = ResourceMap::new(ResourceDef::new(""));
+        for (pattern, name) in &[
+            ("/user/{id}/post/{post_id}", "post"),
+            ("/user/{id}/", "user"),
+            ("/static/{file}", "static"),
+            ("https://youtube.com/watch/{video_id}", "youtube"),
+        ] {
+            let mut rdef = ResourceDef::new(*pattern);
+            *rdef.name_mut() = (*name).to_string();
+            rmap.add(&mut rdef, None);
+        }
+
+        let req = TestRequest::with_uri("/user/1/posts")
+            .insert_header(("x-forwarded-prefix", "/app"))
+            .app_data(TrustForwardedPrefix(true))
+            .rmap(rmap)
+            .to_http_request();
+
+        assert_eq!(req.relative_url_for("post", &["1", "7"]).unwrap(), "post/7");
+        assert_eq!(
+            req.relative_url_for("post", &["2", "7"]).unwrap(),
+            "../2/post/7"
+        );
+        assert_eq!(req.relative_url_for("user", &["1"]).unwrap(), "./");
+        assert_eq!(
+            req.relative_url_for("static", &["app.css"]).unwrap(),
+            "../../static/app.css"
+        );
+        assert_eq!(
+            req.relative_url_for("youtube", &["oHg5SJYRHA0"]).unwrap(),
+            "https://youtube.com/watch/oHg5SJYRHA0"
+        );
+        assert_eq!(
+            req.relative_url_for("unknown", &["1"]),
+            Err(UrlGenerationError::ResourceNotFound)
+        );
+    }
+
     #[test]
     fn test_url_for_static() {
         let mut rdef = ResourceDef::new("/index.html");
@@ -12,10 +12,11 @@ use actix_service::{
     ServiceFactoryExt, Transform,
 };
 use futures_core::future::LocalBoxFuture;
-use futures_util::future::join_all;
+use futures_util::future::{join_all, ok};
 
 use crate::data::Data;
 use crate::dev::{insert_slash, AppService, HttpServiceFactory, ResourceDef};
+use crate::discovery::Discovery;
 use crate::extract::FromRequest;
 use crate::guard::Guard;
 use crate::handler::Handler;
@@ -56,6 +57,7 @@ pub struct Resource<T = ResourceEndpoint> {
     app_data: Option<Extensions>,
     guards: Vec<Box<dyn Guard>>,
     default: HttpNewService,
+    discovery: Option<bool>,
     factory_ref: Rc<RefCell<Option<ResourceFactory>>>,
 }
 
@@ -74,6 +76,7 @@ impl Resource {
             default: boxed::factory(fn_service(|req: ServiceRequest| async {
                 Ok(req.into_response(Response::MethodNotAllowed().finish()))
             })),
+            discovery: None,
         }
     }
 }
@@ -210,6 +213,15 @@ where
         self
     }
 
+    /// Answer `OPTIONS` requests not matched by any route with an API discovery document.
+    ///
+    /// Overrides the application setting made with
+    /// [`App::enable_discovery`](crate::App::enable_discovery) for this resource.
+    pub fn discovery(mut self, enabled: bool) -> Self {
+        self.discovery = Some(enabled);
+        self
+    }
+
     /// Register a new route and add handler. This route matches all requests.
     ///
     /// ```rust
@@ -276,6 +288,7 @@ where
             guards: self.guards,
             routes: self.routes,
             default: self.default,
+            discovery: self.discovery,
             app_data: self.app_data,
             factory_ref: self.factory_ref,
         }
@@ -338,6 +351,7 @@ where
             guards: self.guards,
             routes: self.routes,
             default: self.default,
+            discovery: self.discovery,
             app_data: self.app_data,
             factory_ref: self.factory_ref,
         }
@@ -409,10 +423,19 @@ where
     >,
 {
     fn into_factory(self) -> T {
+        let discovery = Discovery::new(
+            self.discovery,
+            self.routes
+                .iter()
+                .map(|r| r.description().clone())
+                .collect(),
+        );
+
         *self.factory_ref.borrow_mut() = Some(ResourceFactory {
             routes: self.routes,
             app_data: self.app_data.map(Rc::new),
             default: self.default,
+            discovery: Rc::new(discovery),
         });
 
         self.endpoint
@@ -423,6 +446,7 @@ pub struct ResourceFactory {
     routes: Vec<Route>,
     app_data: Option<Rc<Extensions>>,
     default: HttpNewService,
+    discovery: Rc<Discovery>,
 }
 
 impl ServiceFactory<ServiceRequest> for ResourceFactory {
@@ -441,6 +465,7 @@ impl ServiceFactory<ServiceRequest> for ResourceFactory {
         let factory_fut = join_all(self.routes.iter().map(|route| route.new_service(())));
 
         let app_data = self.app_data.clone();
+        let discovery = self.discovery.clone();
 
         Box::pin(async move {
             let default = default_fut.await?;
@@ -453,6 +478,7 @@ impl ServiceFactory<ServiceRequest> for ResourceFactory {
                 app_data,
                 default,
                 routes,
+                discovery,
             })
         })
     }
@@ -462,6 +488,7 @@ pub struct ResourceService {
     routes: Vec<RouteService>,
     app_data: Option<Rc<Extensions>>,
     default: HttpService,
+    discovery: Rc<Discovery>,
 }
 
 impl Service<ServiceRequest> for ResourceService {
@@ -486,6 +513,11 @@ impl Service<ServiceRequest> for ResourceService {
             req.add_data_container(app_data.clone());
         }
 
+        if self.discovery.handles(&req) {
+            let res = self.discovery.respond(&req);
+            return Box::pin(ok(req.into_response(res)));
+        }
+
         self.default.call(req)
     }
 }
@@ -1,12 +1,26 @@
-use std::fmt;
+use std::{
+    borrow::Cow,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use actix_http::{
+    body::{Body, BodySize, MessageBody as _, ResponseBody},
     error::InternalError,
-    http::{header::IntoHeaderPair, Error as HttpError, HeaderMap, StatusCode},
-    ResponseBuilder,
+    http::{
+        header::{
+            self, HttpDate, IntoHeaderPair, IntoHeaderValue, CACHE_CONTROL, CONTENT_LENGTH,
+            CONTENT_TYPE, EXPIRES,
+        },
+        Error as HttpError, HeaderMap, HeaderName, HeaderValue, StatusCode,
+    },
+    Extensions, ResponseBuilder,
 };
 use bytes::{Bytes, BytesMut};
 
+#[cfg(feature = "cookies")]
+use crate::cookie::Cookie;
 use crate::{Error, HttpRequest, HttpResponse};
 
 /// Trait implemented by types that can be converted to an HTTP response.
@@ -16,8 +30,45 @@ pub trait Responder {
     /// Convert self to `HttpResponse`.
     fn respond_to(self, req: &HttpRequest) -> HttpResponse;
 
+    /// Convert self to `HttpResponse`, taking ownership of the request.
+    ///
+    /// Handlers call this method instead of [`respond_to`](Self::respond_to), which it forwards to
+    /// by default. Responders can override it to move values out of the request, e.g. out of its
+    /// extensions, instead of cloning them. The `Option`, `Result` and `Either` responders, and
+    /// the wrapper returned by the `with_*` methods, pass the request on to their inner responder.
+    ///
+    /// ```
+    /// use actix_web::{HttpRequest, HttpResponse, Responder};
+    ///
+    /// struct Report(String);
+    ///
+    /// struct ReportResponder;
+    ///
+    /// impl Responder for ReportResponder {
+    ///     fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+    ///         self.respond_to_owned(req.clone())
+    ///     }
+    ///
+    ///     fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+    ///         match req.extensions_mut().remove::<Report>() {
+    ///             Some(report) => HttpResponse::Ok().body(report.0),
+    ///             None => HttpResponse::NoContent().finish(),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse
+    where
+        Self: Sized,
+    {
+        self.respond_to(&req)
+    }
+
     /// Override a status code for a Responder.
     ///
+    /// Status overrides are applied from the inside out, so the outermost one wins: a status set
+    /// by the wrapped responder, including the `(T, StatusCode)` tuple responder, is replaced.
+    ///
     /// ```rust
     /// use actix_web::{http::StatusCode, HttpRequest, Responder};
     ///
@@ -57,6 +108,127 @@ pub trait Responder {
     {
         CustomResponder::new(self).with_header(header)
     }
+
+    /// Append headers from an iterator to the final response.
+    ///
+    /// See [`CustomResponder::with_headers`].
+    fn with_headers<I, H>(self, headers: I) -> CustomResponder<Self>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = H>,
+        H: IntoHeaderPair,
+    {
+        CustomResponder::new(self).with_headers(headers)
+    }
+
+    /// Set the content type of the final response.
+    ///
+    /// Replaces the content type set by the responder.
+    ///
+    /// ```rust
+    /// use actix_web::{web, HttpRequest, Responder};
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Problem {
+    ///     title: String,
+    /// }
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     web::Json(Problem { title: "Out of credit".to_owned() })
+    ///         .with_content_type("application/problem+json")
+    /// }
+    /// ```
+    fn with_content_type<V>(self, content_type: V) -> CustomResponder<Self>
+    where
+        Self: Sized,
+        V: IntoHeaderValue,
+    {
+        CustomResponder::new(self).with_content_type(content_type)
+    }
+
+    /// Set an explicit `Content-Length` header on the final response.
+    ///
+    /// See [`CustomResponder::with_content_length`] for caveats.
+    ///
+    /// ```rust
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "abc".with_content_length(3)
+    /// }
+    /// ```
+    fn with_content_length(self, len: u64) -> CustomResponder<Self>
+    where
+        Self: Sized,
+    {
+        CustomResponder::new(self).with_content_length(len)
+    }
+
+    /// Add a cookie to the final response.
+    ///
+    /// ```rust
+    /// use actix_web::{cookie::Cookie, HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "Welcome!".with_cookie(Cookie::new("name", "value"))
+    /// }
+    /// ```
+    #[cfg(feature = "cookies")]
+    fn with_cookie(self, cookie: Cookie<'static>) -> CustomResponder<Self>
+    where
+        Self: Sized,
+    {
+        CustomResponder::new(self).with_cookie(cookie)
+    }
+
+    /// Insert a value into the extensions of the final response.
+    ///
+    /// See [`CustomResponder::with_extension`].
+    fn with_extension<V: 'static>(self, val: V) -> CustomResponder<Self>
+    where
+        Self: Sized,
+    {
+        CustomResponder::new(self).with_extension(val)
+    }
+
+    /// Allow shared caches to store the final response for `max_age`.
+    ///
+    /// See [`CustomResponder::cacheable`].
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use actix_web::{web, Responder};
+    ///
+    /// fn index() -> impl Responder {
+    ///     web::Json(vec!["red", "green", "blue"]).cacheable(Duration::from_secs(3600))
+    /// }
+    /// ```
+    fn cacheable(self, max_age: Duration) -> CustomResponder<Self>
+    where
+        Self: Sized,
+    {
+        CustomResponder::new(self).cacheable(max_age)
+    }
+
+    /// Respond with a JSON body describing the error when the responder responds with an error.
+    ///
+    /// See [`JsonErrorResponder`].
+    ///
+    /// ```rust
+    /// use actix_web::{error, Responder};
+    ///
+    /// fn index() -> impl Responder {
+    ///     let res: Result<String, _> = Err(error::ErrorForbidden("quota exceeded"));
+    ///     res.with_json_error()
+    /// }
+    /// ```
+    fn with_json_error(self) -> JsonErrorResponder<Self>
+    where
+        Self: Sized,
+    {
+        JsonErrorResponder(self)
+    }
 }
 
 impl Responder for HttpResponse {
@@ -66,6 +238,13 @@ impl Responder for HttpResponse {
     }
 }
 
+/// Creates an empty response with `204 No Content` status code.
+impl Responder for () {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::NoContent().finish()
+    }
+}
+
 impl<T: Responder> Responder for Option<T> {
     fn respond_to(self, req: &HttpRequest) -> HttpResponse {
         match self {
@@ -73,6 +252,13 @@ impl<T: Responder> Responder for Option<T> {
             None => HttpResponse::build(StatusCode::NOT_FOUND).finish(),
         }
     }
+
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+        match self {
+            Some(t) => t.respond_to_owned(req),
+            None => HttpResponse::build(StatusCode::NOT_FOUND).finish(),
+        }
+    }
 }
 
 impl<T, E> Responder for Result<T, E>
@@ -86,8 +272,33 @@ where
             Err(e) => HttpResponse::from_error(e.into()),
         }
     }
+
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+        match self {
+            Ok(val) => val.respond_to_owned(req),
+            Err(e) => HttpResponse::from_error(e.into()),
+        }
+    }
 }
 
+/// Finishes the builder with an empty body.
+///
+/// Through the `Result` implementation, handlers can also return `Result<ResponseBuilder, E>` and
+/// bail out of the fluent builder API early with an error.
+///
+/// ```
+/// use actix_web::{dev::HttpResponseBuilder, error, web, Error, HttpResponse};
+///
+/// async fn index(id: web::Path<u32>) -> Result<HttpResponseBuilder, Error> {
+///     if *id == 0 {
+///         return Err(error::ErrorBadRequest("id must not be zero"));
+///     }
+///
+///     let mut res = HttpResponse::NoContent();
+///     res.insert_header(("x-id", id.to_string()));
+///     Ok(res)
+/// }
+/// ```
 impl Responder for ResponseBuilder {
     #[inline]
     fn respond_to(mut self, _: &HttpRequest) -> HttpResponse {
@@ -95,6 +306,10 @@ impl Responder for ResponseBuilder {
     }
 }
 
+/// Overrides the status of the inner responder's response.
+///
+/// Like [`Responder::with_status`], the status is applied after the inner responder runs, so when
+/// the two are nested the outermost status wins.
 impl<T: Responder> Responder for (T, StatusCode) {
     fn respond_to(self, req: &HttpRequest) -> HttpResponse {
         let mut res = self.0.respond_to(req);
@@ -103,6 +318,47 @@ impl<T: Responder> Responder for (T, StatusCode) {
     }
 }
 
+/// Sets the status and headers of the inner responder's response.
+///
+/// Each header in the map replaces any header of the same name set by the inner responder; all
+/// values of headers that occur several times in the map are kept.
+///
+/// ```
+/// use actix_web::{http::{header, HeaderMap, HeaderValue, StatusCode}, Responder};
+///
+/// async fn index() -> impl Responder {
+///     let mut headers = HeaderMap::new();
+///     headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+///
+///     (StatusCode::ACCEPTED, headers, "queued")
+/// }
+/// ```
+impl<T: Responder> Responder for (StatusCode, HeaderMap, T) {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let (status, mut headers, responder) = self;
+
+        let mut res = responder.respond_to(req);
+        *res.status_mut() = status;
+
+        let mut last_name = None;
+        for (name, value) in headers.drain() {
+            match name {
+                Some(name) => {
+                    res.headers_mut().insert(name.clone(), value);
+                    last_name = Some(name);
+                }
+                None => {
+                    if let Some(ref name) = last_name {
+                        res.headers_mut().append(name.clone(), value);
+                    }
+                }
+            }
+        }
+
+        res
+    }
+}
+
 impl Responder for &'static str {
     fn respond_to(self, _: &HttpRequest) -> HttpResponse {
         HttpResponse::Ok()
@@ -111,6 +367,17 @@ impl Responder for &'static str {
     }
 }
 
+impl Responder for char {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        let mut buf = [0; 4];
+        HttpResponse::Ok()
+            .content_type(mime::TEXT_PLAIN_UTF_8)
+            .body(Bytes::copy_from_slice(
+                self.encode_utf8(&mut buf).as_bytes(),
+            ))
+    }
+}
+
 impl Responder for &'static [u8] {
     fn respond_to(self, _: &HttpRequest) -> HttpResponse {
         HttpResponse::Ok()
@@ -151,11 +418,66 @@ impl Responder for BytesMut {
     }
 }
 
+impl Responder for Arc<str> {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type(mime::TEXT_PLAIN_UTF_8)
+            .body(self)
+    }
+}
+
+impl Responder for Arc<[u8]> {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type(mime::APPLICATION_OCTET_STREAM)
+            .body(self)
+    }
+}
+
+impl Responder for Cow<'static, [u8]> {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        // neither arm copies the buffer
+        let body = match self {
+            Cow::Borrowed(bytes) => Bytes::from_static(bytes),
+            Cow::Owned(bytes) => Bytes::from(bytes),
+        };
+
+        HttpResponse::Ok()
+            .content_type(mime::APPLICATION_OCTET_STREAM)
+            .body(body)
+    }
+}
+
+macro_rules! impl_number_responder {
+    ($buf:path => $($ty:ty),+) => {$(
+        impl Responder for $ty {
+            fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+                let mut buf = <$buf>::new();
+                HttpResponse::Ok()
+                    .content_type(mime::TEXT_PLAIN_UTF_8)
+                    .body(Bytes::copy_from_slice(buf.format(self).as_bytes()))
+            }
+        }
+    )+};
+}
+
+impl_number_responder!(itoa::Buffer => i8, i16, i32, i64, i128, isize);
+impl_number_responder!(itoa::Buffer => u8, u16, u32, u64, u128, usize);
+impl_number_responder!(ryu::Buffer => f32, f64);
+
 /// Allows overriding status code and headers for a responder.
 pub struct CustomResponder<T> {
     responder: T,
     status: Option<StatusCode>,
     headers: Option<HeaderMap>,
+    appended_headers: Vec<(HeaderName, HeaderValue)>,
+    content_type: Option<HeaderValue>,
+    content_length: Option<u64>,
+    #[cfg(feature = "cookies")]
+    cookies: Vec<Cookie<'static>>,
+    extensions: Option<Extensions>,
+    max_age: Option<Duration>,
+    fallback: Option<BoxedResponder>,
     error: Option<HttpError>,
 }
 
@@ -165,12 +487,24 @@ impl<T: Responder> CustomResponder<T> {
             responder,
             status: None,
             headers: None,
+            appended_headers: Vec::new(),
+            content_type: None,
+            content_length: None,
+            #[cfg(feature = "cookies")]
+            cookies: Vec::new(),
+            extensions: None,
+            max_age: None,
+            fallback: None,
             error: None,
         }
     }
 
     /// Override a status code for the Responder's response.
     ///
+    /// Calling this again replaces the previously set status. The status is applied after the
+    /// inner responder has produced its response, so it takes precedence over any status the inner
+    /// responder sets.
+    ///
     /// ```rust
     /// use actix_web::{HttpRequest, Responder, http::StatusCode};
     ///
@@ -217,16 +551,223 @@ impl<T: Responder> CustomResponder<T> {
 
         self
     }
+
+    /// Append headers from an iterator to the final response.
+    ///
+    /// Unlike [`with_header`](Self::with_header), headers are appended, so they are added
+    /// alongside headers of the same name set by the inner responder or by earlier calls.
+    /// Headers that may occur only once in a response, such as *Content-Type* and *Location*, are
+    /// replaced instead; see [`header::is_singleton`].
+    ///
+    /// ```rust
+    /// use actix_web::{http::header, HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     let links = vec![
+    ///         (header::LINK, "</style.css>; rel=preload; as=style"),
+    ///         (header::LINK, "</app.js>; rel=preload; as=script"),
+    ///     ];
+    ///
+    ///     "Welcome!".with_headers(links)
+    /// }
+    /// ```
+    pub fn with_headers<I, H>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: IntoHeaderPair,
+    {
+        for header in headers {
+            match header.try_into_header_pair() {
+                Ok(pair) => self.appended_headers.push(pair),
+                Err(e) => {
+                    self.error = Some(e.into());
+                    break;
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Set the content type of the final response.
+    ///
+    /// Unlike [`with_header`](Self::with_header), the value always replaces the content type set
+    /// by the inner responder so that the response carries exactly one `Content-Type` header.
+    ///
+    /// ```rust
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "<svg/>".with_content_type("image/svg+xml")
+    /// }
+    /// ```
+    pub fn with_content_type<V>(mut self, content_type: V) -> Self
+    where
+        V: IntoHeaderValue,
+    {
+        match content_type.try_into_value() {
+            Ok(value) => self.content_type = Some(value),
+            Err(e) => self.error = Some(e.into()),
+        };
+
+        self
+    }
+
+    /// Set an explicit `Content-Length` header on the final response.
+    ///
+    /// Useful for responses to `HEAD` requests and for proxies that need the length up front. The
+    /// header replaces any `Content-Length` set by the inner responder. Streaming bodies are sent
+    /// without chunked transfer encoding so that the header is not combined with
+    /// `Transfer-Encoding: chunked`.
+    ///
+    /// The length is not checked against the body; callers are responsible for its correctness.
+    /// For buffered bodies the HTTP/1 encoder writes the actual body size instead.
+    ///
+    /// ```rust
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "abc".with_content_length(3)
+    /// }
+    /// ```
+    pub fn with_content_length(mut self, len: u64) -> Self {
+        self.content_length = Some(len);
+        self
+    }
+
+    /// Add a cookie to the final response.
+    ///
+    /// Cookies are appended as `Set-Cookie` headers after any headers set with
+    /// [`with_header`](Self::with_header).
+    ///
+    /// ```rust
+    /// use actix_web::{cookie::Cookie, http::StatusCode, HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "Created!"
+    ///         .with_status(StatusCode::CREATED)
+    ///         .with_cookie(Cookie::new("name", "value"))
+    /// }
+    /// ```
+    #[cfg(feature = "cookies")]
+    pub fn with_cookie(mut self, cookie: Cookie<'static>) -> Self {
+        self.cookies.push(cookie);
+        self
+    }
+
+    /// Insert a value into the extensions of the final response.
+    ///
+    /// Lets handlers pass typed metadata to outer middleware, which can read it from
+    /// [`HttpResponse::extensions`]. Replaces any value of the same type inserted by the
+    /// responder.
+    ///
+    /// ```rust
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// struct CacheTag(&'static str);
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "Hello!".with_extension(CacheTag("greeting"))
+    /// }
+    /// ```
+    pub fn with_extension<V: 'static>(mut self, val: V) -> Self {
+        self.extensions
+            .get_or_insert_with(Extensions::new)
+            .insert(val);
+        self
+    }
+
+    /// Allow shared caches to store the final response for `max_age`.
+    ///
+    /// Sets `Cache-Control: public, max-age=<seconds>` and an `Expires` header `max_age` from the
+    /// time of the response, for caches that only understand HTTP/1.0. Headers set with
+    /// [`with_header`](Self::with_header) take precedence. Error responses are left uncached.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use actix_web::{HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     "Hello!".cacheable(Duration::from_secs(60))
+    /// }
+    /// ```
+    pub fn cacheable(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Respond with `fallback` when the inner responder produces an error response.
+    ///
+    /// The error response is discarded and the overrides of this responder, like its status and
+    /// headers, are applied to the fallback response instead.
+    ///
+    /// ```rust
+    /// use actix_web::{error, http::StatusCode, HttpRequest, Responder};
+    ///
+    /// fn index(req: HttpRequest) -> impl Responder {
+    ///     let page: Result<String, error::Error> = render_page();
+    ///
+    ///     page.with_status(StatusCode::OK)
+    ///         .or_else_respond("page temporarily unavailable")
+    /// }
+    /// # fn render_page() -> Result<String, error::Error> { Ok(String::new()) }
+    /// ```
+    pub fn or_else_respond<R>(mut self, fallback: R) -> Self
+    where
+        R: Responder + 'static,
+    {
+        self.fallback = Some(BoxedResponder::new(fallback));
+        self
+    }
 }
 
 impl<T: Responder> Responder for CustomResponder<T> {
     fn respond_to(self, req: &HttpRequest) -> HttpResponse {
-        let mut res = self.responder.respond_to(req);
+        self.respond_to_owned(req.clone())
+    }
+
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+        // an invalid header was given to the builder methods; don't run inner responder
+        if let Some(err) = self.error {
+            return HttpResponse::from_error(err.into());
+        }
 
+        // the request is only kept back when a fallback may need it
+        let mut res = match self.fallback {
+            Some(fallback) => {
+                let res = self.responder.respond_to_owned(req.clone());
+
+                if let Some(err) = res.error() {
+                    log::debug!("Responding with fallback after responder error: {}", err);
+                    fallback.respond_to(&req)
+                } else {
+                    res
+                }
+            }
+            None => self.responder.respond_to_owned(req),
+        };
+
+        // applied last so that this status wins over anything the inner responder set
         if let Some(status) = self.status {
             *res.status_mut() = status;
         }
 
+        if let Some(max_age) = self.max_age {
+            if res.error().is_none() {
+                let cache_control = format!("public, max-age={}", max_age.as_secs());
+                res.headers_mut().insert(
+                    CACHE_CONTROL,
+                    HeaderValue::from_str(&cache_control).unwrap(),
+                );
+
+                if let Some(expires) = SystemTime::now().checked_add(max_age) {
+                    if let Ok(expires) = HttpDate::from(expires).try_into_value() {
+                        res.headers_mut().insert(EXPIRES, expires);
+                    }
+                }
+            }
+        }
+
         if let Some(ref headers) = self.headers {
             for (k, v) in headers {
                 // TODO: before v4, decide if this should be append instead
@@ -234,10 +775,344 @@ impl<T: Responder> Responder for CustomResponder<T> {
             }
         }
 
+        for (k, v) in self.appended_headers {
+            // singleton headers are replaced instead of duplicated
+            if header::is_singleton(&k) {
+                res.headers_mut().insert(k, v);
+            } else {
+                res.headers_mut().append(k, v);
+            }
+        }
+
+        if let Some(content_type) = self.content_type {
+            res.headers_mut().insert(CONTENT_TYPE, content_type);
+        }
+
+        if let Some(len) = self.content_length {
+            res.headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(len));
+
+            if let BodySize::Stream = res.body().size() {
+                res.head_mut().no_chunking(true);
+            }
+        }
+
+        #[cfg(feature = "cookies")]
+        for cookie in &self.cookies {
+            if let Err(err) = res.add_cookie(cookie) {
+                return Error::from(err).into();
+            }
+        }
+
+        if let Some(extensions) = self.extensions {
+            res.extensions_mut().extend(extensions);
+        }
+
         res
     }
 }
 
+/// Type-erased responder.
+///
+/// Allows handlers to return different responder types from different branches without nesting
+/// [`Either`](crate::Either) types.
+///
+/// ```
+/// use actix_web::{BoxedResponder, HttpResponse};
+///
+/// async fn index() -> BoxedResponder {
+///     if cache_hit() {
+///         BoxedResponder::new("cached")
+///     } else {
+///         BoxedResponder::new(HttpResponse::Accepted().finish())
+///     }
+/// }
+/// # fn cache_hit() -> bool { true }
+/// ```
+pub struct BoxedResponder(Box<dyn ErasedResponder>);
+
+impl BoxedResponder {
+    /// Boxes a responder.
+    pub fn new<R: Responder + 'static>(responder: R) -> Self {
+        BoxedResponder(Box::new(responder))
+    }
+}
+
+impl fmt::Debug for BoxedResponder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BoxedResponder")
+    }
+}
+
+impl Responder for BoxedResponder {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        self.0.respond_boxed(req)
+    }
+}
+
+/// Object safe counterpart of `Responder`, which consumes `self` by value.
+trait ErasedResponder {
+    fn respond_boxed(self: Box<Self>, req: &HttpRequest) -> HttpResponse;
+}
+
+impl<R: Responder> ErasedResponder for R {
+    fn respond_boxed(self: Box<Self>, req: &HttpRequest) -> HttpResponse {
+        (*self).respond_to(req)
+    }
+}
+
+/// Responder for a `Result` whose success and error arms are both responders.
+///
+/// The `Result` responder requires the error to convert into an [`Error`]. This wrapper instead
+/// responds with the error arm's own response, so handlers can return typed error bodies. If the
+/// error arm responds with a status that is not a client or server error, it is replaced with
+/// `500 Internal Server Error`.
+///
+/// ```
+/// use actix_web::{http::StatusCode, web, EitherResult, Responder};
+///
+/// async fn index(name: web::Path<String>) -> EitherResult<String, impl Responder> {
+///     EitherResult(if name.is_empty() {
+///         Err("name is required".with_status(StatusCode::UNPROCESSABLE_ENTITY))
+///     } else {
+///         Ok(format!("Hello {}!", name))
+///     })
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EitherResult<T, E>(pub Result<T, E>);
+
+impl<T, E> EitherResult<T, E> {
+    /// Unwraps into the inner result.
+    pub fn into_inner(self) -> Result<T, E> {
+        self.0
+    }
+}
+
+impl<T, E> From<Result<T, E>> for EitherResult<T, E> {
+    fn from(res: Result<T, E>) -> Self {
+        EitherResult(res)
+    }
+}
+
+impl<T, E> Responder for EitherResult<T, E>
+where
+    T: Responder,
+    E: Responder,
+{
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        match self.0 {
+            Ok(val) => val.respond_to(req),
+            Err(err) => {
+                let mut res = err.respond_to(req);
+
+                let status = res.status();
+                if !status.is_client_error() && !status.is_server_error() {
+                    *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                }
+
+                res
+            }
+        }
+    }
+}
+
+/// Functions run on every response produced by a handler's responder.
+///
+/// When registered through one of the `.app_data()` methods, the post-processors run in order of
+/// registration on the response of every handler on the covered routes, including responses to
+/// requests rejected by an extractor. This suits uniform additions such as a caching policy.
+/// Unlike middleware, post-processors do not see responses from the default service or from
+/// middleware that short-circuits a request.
+///
+/// ```
+/// use actix_web::{http::header, web, App, HttpResponse};
+///
+/// let app = App::new()
+///     .app_data(web::ResponsePostProcessors::new().add(|res: &mut HttpResponse| {
+///         res.headers_mut().insert(
+///             header::CACHE_CONTROL,
+///             header::HeaderValue::from_static("no-store"),
+///         );
+///     }))
+///     .route("/", web::get().to(|| HttpResponse::Ok()));
+/// ```
+#[derive(Default)]
+pub struct ResponsePostProcessors {
+    processors: Vec<Box<dyn Fn(&mut HttpResponse)>>,
+}
+
+impl ResponsePostProcessors {
+    /// Constructs an empty registry.
+    pub fn new() -> Self {
+        ResponsePostProcessors::default()
+    }
+
+    /// Add a post-processor, run after the ones added before it.
+    pub fn add<F>(mut self, processor: F) -> Self
+    where
+        F: Fn(&mut HttpResponse) + 'static,
+    {
+        self.processors.push(Box::new(processor));
+        self
+    }
+
+    /// Runs every post-processor on the response.
+    pub(crate) fn apply(&self, res: &mut HttpResponse) {
+        for processor in &self.processors {
+            processor(res);
+        }
+    }
+}
+
+impl fmt::Debug for ResponsePostProcessors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponsePostProcessors")
+            .field("len", &self.processors.len())
+            .finish()
+    }
+}
+
+/// Responder that replaces the body of error responses with a JSON object.
+///
+/// When the wrapped responder responds with an error, e.g. the `Err` arm of a `Result`, the body is
+/// replaced with `{ "error": "<message>" }`, where the message is the error's `Display` output, and
+/// the content type is set to `application/json`. The status and other headers are kept, as is the
+/// error itself so that middleware like [`Logger`](crate::middleware::Logger) still sees it.
+/// Responses that do not carry an error are left untouched.
+///
+/// Created with [`Responder::with_json_error`].
+#[derive(Debug, Clone)]
+pub struct JsonErrorResponder<T>(T);
+
+impl<T> JsonErrorResponder<T> {
+    /// Unwraps into the inner responder.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Responder> Responder for JsonErrorResponder<T> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let res = self.0.respond_to(req);
+
+        let body = match res.error() {
+            Some(err) => serde_json::json!({ "error": err.to_string() }).to_string(),
+            None => return res,
+        };
+
+        res.map_body(|head, _| {
+            head.headers
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            ResponseBody::Body(Body::from(body))
+        })
+    }
+}
+
+/// Responder for an `Option` that responds with a chosen status when it is `None`.
+///
+/// The `Option` responder uses `404 Not Found` for `None`; this wrapper allows statuses such as
+/// `410 Gone` or `204 No Content` to be used instead. `Some` values respond as usual. The `None`
+/// response has an empty body.
+///
+/// ```
+/// use actix_web::{http::StatusCode, web, OrStatus};
+///
+/// async fn index(id: web::Path<u32>) -> OrStatus<String> {
+///     let archived = *id < 100;
+///     let item = if archived { None } else { Some(format!("item {}", id)) };
+///
+///     OrStatus::new(item, StatusCode::GONE)
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrStatus<T> {
+    value: Option<T>,
+    status: StatusCode,
+}
+
+impl<T> OrStatus<T> {
+    /// Constructs responder that responds with `status` if `value` is `None`.
+    pub fn new(value: Option<T>, status: StatusCode) -> Self {
+        OrStatus { value, status }
+    }
+
+    /// Returns status used when the value is `None`.
+    pub fn none_status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Unwraps into the inner option.
+    pub fn into_inner(self) -> Option<T> {
+        self.value
+    }
+}
+
+impl<T> From<Option<T>> for OrStatus<T> {
+    fn from(value: Option<T>) -> Self {
+        OrStatus::new(value, StatusCode::NOT_FOUND)
+    }
+}
+
+impl<T: Responder> Responder for OrStatus<T> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        match self.value {
+            Some(val) => val.respond_to(req),
+            None => HttpResponse::build(self.status).finish(),
+        }
+    }
+}
+
+/// Responder that builds its response with a closure when it is responded to.
+///
+/// The closure is given the request, which makes it a convenient place for work that depends on
+/// it, such as content negotiation, without defining a responder type.
+///
+/// ```
+/// use actix_web::{http::header, HttpResponse, Lazy, Responder};
+///
+/// async fn index() -> impl Responder {
+///     Lazy::new(|req| {
+///         let wants_json = req
+///             .headers()
+///             .get(header::ACCEPT)
+///             .map_or(false, |accept| accept == "application/json");
+///
+///         if wants_json {
+///             HttpResponse::Ok().json(serde_json::json!({ "greeting": "hello" }))
+///         } else {
+///             HttpResponse::Ok().body("hello")
+///         }
+///     })
+/// }
+/// ```
+pub struct Lazy<F>(F);
+
+impl<F> Lazy<F>
+where
+    F: FnOnce(&HttpRequest) -> HttpResponse,
+{
+    /// Constructs responder from a closure that builds the response.
+    pub fn new(f: F) -> Self {
+        Lazy(f)
+    }
+}
+
+impl<F> fmt::Debug for Lazy<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Lazy")
+    }
+}
+
+impl<F> Responder for Lazy<F>
+where
+    F: FnOnce(&HttpRequest) -> HttpResponse,
+{
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        (self.0)(req)
+    }
+}
+
 impl<T> Responder for InternalError<T>
 where
     T: fmt::Debug + fmt::Display + 'static,
@@ -253,8 +1128,11 @@ pub(crate) mod tests {
     use bytes::{Bytes, BytesMut};
 
     use super::*;
-    use crate::dev::{Body, ResponseBody};
-    use crate::http::{header::CONTENT_TYPE, HeaderValue, StatusCode};
+    use crate::dev::{Body, BodySize, MessageBody, ResponseBody};
+    use crate::http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderValue, StatusCode,
+    };
     use crate::test::{init_service, TestRequest};
     use crate::{error, web, App};
 
@@ -283,6 +1161,18 @@ pub(crate) mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn test_unit_responder() {
+        async fn handler() {}
+
+        let srv = init_service(App::new().service(web::resource("/").to(handler))).await;
+
+        let req = TestRequest::default().to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert!(matches!(resp.response().body().body(), Body::Empty));
+    }
+
     pub(crate) trait BodyTest {
         fn bin_ref(&self) -> &[u8];
         fn body(&self) -> &Body;
@@ -345,6 +1235,29 @@ pub(crate) mod tests {
             HeaderValue::from_static("text/plain; charset=utf-8")
         );
 
+        let resp = '✓'.respond_to(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.body().bin_ref(), "✓".as_bytes());
+        assert_eq!(resp.body().bin_ref().len(), 3);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+
+        let resp = 42u32.respond_to(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.body().bin_ref(), b"42");
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+
+        let resp = (-7i64).respond_to(&req);
+        assert_eq!(resp.body().bin_ref(), b"-7");
+
+        let resp = 1.5f64.respond_to(&req);
+        assert_eq!(resp.body().bin_ref(), b"1.5");
+
         let resp = Bytes::from_static(b"test").respond_to(&req);
         assert_eq!(resp.status(), StatusCode::OK);
         assert_eq!(resp.body().bin_ref(), b"test");
@@ -366,6 +1279,70 @@ pub(crate) mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[actix_rt::test]
+    async fn test_shared_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        fn shared_body(res: &HttpResponse) -> &Bytes {
+            match res.body().body() {
+                Body::Bytes(bytes) => bytes,
+                _ => panic!("expected shared body"),
+            }
+        }
+
+        let text: Arc<str> = Arc::from("test");
+        let res1 = Arc::clone(&text).respond_to(&req);
+        let res2 = Arc::clone(&text).respond_to(&req);
+        assert_eq!(
+            res1.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+        assert_eq!(res1.body().size(), BodySize::Sized(4));
+
+        // both responses point at the original buffer
+        let body1 = shared_body(&res1);
+        let body2 = shared_body(&res2);
+        assert_eq!(body1.as_ptr(), text.as_ptr());
+        assert_eq!(body2.as_ptr(), text.as_ptr());
+        assert_eq!(body1.len(), text.len());
+        assert_eq!(Arc::strong_count(&text), 3);
+
+        let bin: Arc<[u8]> = Arc::from(&b"test"[..]);
+        let res = Arc::clone(&bin).respond_to(&req);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/octet-stream")
+        );
+        assert_eq!(shared_body(&res).as_ptr(), bin.as_ptr());
+    }
+
+    #[actix_rt::test]
+    async fn test_cow_bytes_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        static DATA: &[u8] = b"static";
+        let res = Cow::Borrowed(DATA).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/octet-stream")
+        );
+        match res.body() {
+            ResponseBody::Body(Body::Bytes(bytes)) => {
+                assert_eq!(bytes, DATA);
+                assert_eq!(bytes.as_ptr(), DATA.as_ptr());
+            }
+            _ => panic!("expected bytes body"),
+        }
+
+        let res = Cow::<'static, [u8]>::Owned(b"built".to_vec()).respond_to(&req);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/octet-stream")
+        );
+        assert_eq!(res.body().bin_ref(), b"built");
+    }
+
     #[actix_rt::test]
     async fn test_result_responder() {
         let req = TestRequest::default().to_http_request();
@@ -385,6 +1362,22 @@ pub(crate) mod tests {
         assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[actix_rt::test]
+    async fn test_result_response_builder_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let mut builder = HttpResponse::Accepted();
+        builder.insert_header(("x-test", "1"));
+        let res = Ok::<_, Error>(builder).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.headers().get("x-test").unwrap(), "1");
+        assert!(res.error().is_none());
+
+        let res = Err::<ResponseBuilder, _>(error::ErrorConflict("taken")).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+        assert!(res.error().is_some());
+    }
+
     #[actix_rt::test]
     async fn test_custom_responder() {
         let req = TestRequest::default().to_http_request();
@@ -409,6 +1402,239 @@ pub(crate) mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_custom_responder_with_extension() {
+        #[derive(Debug, PartialEq)]
+        struct Marker(u32);
+
+        let req = TestRequest::default().to_http_request();
+
+        let res = "test"
+            .with_status(StatusCode::ACCEPTED)
+            .with_extension(Marker(7))
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.extensions().get::<Marker>(), Some(&Marker(7)));
+        assert_eq!(res.body().bin_ref(), b"test");
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_with_headers() {
+        let req = TestRequest::default().to_http_request();
+
+        let headers = vec![
+            (
+                HeaderName::from_static("x-one"),
+                HeaderValue::from_static("1"),
+            ),
+            (
+                HeaderName::from_static("x-two"),
+                HeaderValue::from_static("2"),
+            ),
+            (
+                HeaderName::from_static("x-one"),
+                HeaderValue::from_static("3"),
+            ),
+        ];
+
+        let res = "test"
+            .with_header(("x-two", "0"))
+            .with_headers(headers)
+            .respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let one: Vec<_> = res.headers().get_all("x-one").collect();
+        assert_eq!(one, vec!["1", "3"]);
+        let two: Vec<_> = res.headers().get_all("x-two").collect();
+        assert_eq!(two, vec!["0", "2"]);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+
+        // singleton headers are not duplicated
+        let res = "test"
+            .with_headers(vec![
+                (CONTENT_TYPE, "text/html"),
+                (CONTENT_TYPE, "text/csv"),
+            ])
+            .respond_to(&req);
+        let ctype: Vec<_> = res.headers().get_all(CONTENT_TYPE).collect();
+        assert_eq!(ctype, vec!["text/csv"]);
+    }
+
+    #[actix_rt::test]
+    async fn test_status_precedence() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = ("test", StatusCode::CREATED)
+            .with_status(StatusCode::ACCEPTED)
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.body().bin_ref(), b"test");
+
+        let res = (
+            "test".with_status(StatusCode::CREATED),
+            StatusCode::ACCEPTED,
+        )
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+
+        let res = (
+            "test".with_status(StatusCode::CREATED),
+            StatusCode::ACCEPTED,
+        )
+            .with_status(StatusCode::NO_CONTENT)
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+        let res = "test"
+            .with_status(StatusCode::CREATED)
+            .with_status(StatusCode::ACCEPTED)
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_error() {
+        struct Unreachable;
+
+        impl Responder for Unreachable {
+            fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+                panic!("inner responder should not be called");
+            }
+        }
+
+        let req = TestRequest::default().to_http_request();
+        let res = Unreachable
+            .with_header(("invalid header name", "value"))
+            .with_status(StatusCode::CREATED)
+            .respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(res.error().is_some());
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_fallback() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Err::<&'static str, _>(crate::error::ErrorBadGateway("upstream down"))
+            .with_status(StatusCode::SERVICE_UNAVAILABLE)
+            .with_header(("x-fallback", "1"))
+            .or_else_respond("try again later")
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get("x-fallback").unwrap(), "1");
+        assert_eq!(res.body().bin_ref(), b"try again later");
+        assert!(res.error().is_none());
+
+        let res = Ok::<_, Error>("fine")
+            .with_status(StatusCode::ACCEPTED)
+            .or_else_respond("try again later")
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.body().bin_ref(), b"fine");
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_cacheable() {
+        use crate::http::header::{CACHE_CONTROL, EXPIRES};
+
+        let req = TestRequest::default().to_http_request();
+
+        let before = SystemTime::now();
+        let res = "data".cacheable(Duration::from_secs(3600)).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CACHE_CONTROL).unwrap(),
+            "public, max-age=3600"
+        );
+
+        let expires = res.headers().get(EXPIRES).unwrap().to_str().unwrap();
+        let expires = SystemTime::from(expires.parse::<HttpDate>().unwrap());
+        // HTTP dates have second precision
+        let earliest = before + Duration::from_secs(3599);
+        let latest = SystemTime::now() + Duration::from_secs(3601);
+        assert!(expires >= earliest && expires <= latest);
+
+        // explicit headers win
+        let res = "data"
+            .cacheable(Duration::from_secs(60))
+            .with_header((CACHE_CONTROL, "no-store"))
+            .respond_to(&req);
+        assert_eq!(res.headers().get(CACHE_CONTROL).unwrap(), "no-store");
+
+        let res = Err::<&'static str, _>(error::ErrorNotFound("gone"))
+            .cacheable(Duration::from_secs(60))
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert!(res.headers().get(CACHE_CONTROL).is_none());
+        assert!(res.headers().get(EXPIRES).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_with_content_length() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = "abc".with_content_length(3).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CONTENT_LENGTH).unwrap(), "3");
+        assert_eq!(res.body().bin_ref(), b"abc");
+        assert!(res.head().chunked());
+
+        let stream = futures_util::stream::iter(vec![Ok::<_, Error>(Bytes::from("abc"))]);
+        let res = HttpResponse::Ok()
+            .streaming(stream)
+            .with_content_length(3)
+            .respond_to(&req);
+        assert_eq!(res.headers().get(CONTENT_LENGTH).unwrap(), "3");
+        assert!(!res.head().chunked());
+    }
+
+    #[actix_rt::test]
+    async fn test_custom_responder_with_content_type() {
+        let req = TestRequest::default().to_http_request();
+        let res = web::Json(serde_json::json!({ "title": "Out of credit" }))
+            .with_content_type("application/problem+json")
+            .respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get_all(CONTENT_TYPE).count(), 1);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/problem+json")
+        );
+    }
+
+    #[cfg(feature = "cookies")]
+    #[actix_rt::test]
+    async fn test_custom_responder_with_cookie() {
+        use crate::cookie::Cookie;
+        use crate::http::header::SET_COOKIE;
+
+        let req = TestRequest::default().to_http_request();
+        let res = "ok".with_cookie(Cookie::new("a", "b")).respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().bin_ref(), b"ok");
+        assert_eq!(
+            res.headers().get(SET_COOKIE).unwrap(),
+            HeaderValue::from_static("a=b")
+        );
+
+        let res = "ok"
+            .with_status(StatusCode::CREATED)
+            .with_cookie(Cookie::new("a", "b"))
+            .with_cookie(Cookie::new("c", "d"))
+            .respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let cookies = res.headers().get_all(SET_COOKIE).collect::<Vec<_>>();
+        assert_eq!(cookies, vec!["a=b", "c=d"]);
+    }
+
     #[actix_rt::test]
     async fn test_tuple_responder_with_status_code() {
         let req = TestRequest::default().to_http_request();
@@ -427,4 +1653,214 @@ pub(crate) mod tests {
             HeaderValue::from_static("application/json")
         );
     }
+
+    #[actix_rt::test]
+    async fn test_boxed_responder() {
+        async fn handler(flag: web::Path<bool>) -> BoxedResponder {
+            if flag.into_inner() {
+                BoxedResponder::new("text")
+            } else {
+                BoxedResponder::new(HttpResponse::Accepted().finish())
+            }
+        }
+
+        let srv = init_service(App::new().route("/{flag}", web::get().to(handler))).await;
+
+        let req = TestRequest::with_uri("/true").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+
+        let req = TestRequest::with_uri("/false").to_request();
+        let resp = srv.call(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    }
+
+    #[actix_rt::test]
+    async fn test_either_result_responder() {
+        #[derive(Debug)]
+        struct Invalid(&'static str);
+
+        impl Responder for Invalid {
+            fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+                HttpResponse::build(StatusCode::UNPROCESSABLE_ENTITY)
+                    .body(format!("invalid field: {}", self.0))
+            }
+        }
+
+        let req = TestRequest::default().to_http_request();
+
+        let res = EitherResult::<_, Invalid>(Ok("ok")).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = EitherResult::<&'static str, _>(Err(Invalid("name"))).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(res.body().bin_ref(), b"invalid field: name");
+
+        // error arm responding with a success status
+        let res = EitherResult::<&'static str, _>(Err("oops")).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(res.body().bin_ref(), b"oops");
+    }
+
+    #[actix_rt::test]
+    async fn test_or_status_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = OrStatus::new(None::<&'static str>, StatusCode::GONE).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::GONE);
+        assert_eq!(res.body().bin_ref(), b"");
+
+        let res = OrStatus::new(Some("found"), StatusCode::GONE).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().bin_ref(), b"found");
+
+        let res = OrStatus::from(None::<&'static str>).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_rt::test]
+    async fn test_lazy_responder() {
+        let req = TestRequest::with_uri("/greet?name=lazy").to_http_request();
+
+        let res =
+            Lazy::new(|req| HttpResponse::Ok().body(format!("query: {}", req.query_string())))
+                .respond_to(&req);
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().bin_ref(), b"query: name=lazy");
+    }
+
+    #[actix_rt::test]
+    async fn test_status_headers_tuple_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-request-id"),
+            HeaderValue::from_static("42"),
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+
+        let res = (StatusCode::CREATED, headers, "a,b").respond_to(&req);
+        assert_eq!(res.status(), StatusCode::CREATED);
+        assert_eq!(res.headers().get("x-request-id").unwrap(), "42");
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/csv");
+        assert_eq!(res.headers().get_all(CONTENT_TYPE).count(), 1);
+        assert_eq!(res.body().bin_ref(), b"a,b");
+    }
+
+    #[actix_rt::test]
+    async fn test_json_error_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let res: Result<&'static str, Error> = Err(error::ErrorForbidden("quota exceeded"));
+        let res = res.with_json_error().respond_to(&req);
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/json")
+        );
+        assert_eq!(res.body().bin_ref(), br#"{"error":"quota exceeded"}"#);
+        assert!(res.error().is_some());
+
+        let res = Ok::<_, Error>("fine").with_json_error().respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("text/plain; charset=utf-8")
+        );
+        assert_eq!(res.body().bin_ref(), b"fine");
+    }
+
+    #[actix_rt::test]
+    async fn test_response_post_processors() {
+        let srv = init_service(
+            App::new()
+                .app_data(
+                    ResponsePostProcessors::new()
+                        .add(|res: &mut HttpResponse| {
+                            res.headers_mut().insert(
+                                HeaderName::from_static("x-correlation-id"),
+                                HeaderValue::from_static("abc"),
+                            );
+                        })
+                        .add(|res: &mut HttpResponse| {
+                            let id = res.headers().get("x-correlation-id").cloned();
+                            res.headers_mut()
+                                .insert(HeaderName::from_static("x-copied-id"), id.unwrap());
+                        }),
+                )
+                .route("/", web::get().to(|| async { "hello" }))
+                .route(
+                    "/path/{id}",
+                    web::get().to(|_: web::Path<u32>| async { "" }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("x-correlation-id").unwrap(), "abc");
+        assert_eq!(res.headers().get("x-copied-id").unwrap(), "abc");
+
+        // responses to rejected requests are post-processed too
+        let req = TestRequest::with_uri("/path/abc").to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers().get("x-correlation-id").unwrap(), "abc");
+    }
+
+    #[actix_rt::test]
+    async fn test_respond_to_owned() {
+        struct Report(String);
+
+        struct ReportResponder;
+
+        impl Responder for ReportResponder {
+            fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+                HttpResponse::InternalServerError().finish()
+            }
+
+            fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+                let report = req.extensions_mut().remove::<Report>().unwrap();
+                HttpResponse::Ok().body(report.0)
+            }
+        }
+
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|req: HttpRequest| async move {
+                req.extensions_mut().insert(Report("moved".to_owned()));
+                Ok::<_, Error>(Some(ReportResponder))
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(crate::test::read_body(res).await, "moved");
+
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|req: HttpRequest| async move {
+                req.extensions_mut().insert(Report("wrapped".to_owned()));
+                ReportResponder
+                    .with_status(StatusCode::ACCEPTED)
+                    .with_header(("x-report", "1"))
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::ACCEPTED);
+        assert_eq!(res.headers().get("x-report").unwrap(), "1");
+        assert_eq!(crate::test::read_body(res).await, "wrapped");
+    }
 }
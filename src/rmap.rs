@@ -65,9 +65,10 @@ impl ResourceMap {
             if path.starts_with('/') {
                 let conn = req.connection_info();
                 Ok(Url::parse(&format!(
-                    "{}://{}{}",
+                    "{}://{}{}{}",
                     conn.scheme(),
                     conn.host(),
+                    conn.prefix(),
                     path
                 ))?)
             } else {
@@ -78,6 +79,34 @@ impl ResourceMap {
         }
     }
 
+    /// Generate path to named resource relative to the path of the current request.
+    ///
+    /// Check [`HttpRequest::relative_url_for()`](crate::HttpRequest::relative_url_for) for
+    /// detailed information.
+    pub fn relative_url_for<U, I>(
+        &self,
+        req: &HttpRequest,
+        name: &str,
+        elements: U,
+    ) -> Result<String, UrlGenerationError>
+    where
+        U: IntoIterator<Item = I>,
+        I: AsRef<str>,
+    {
+        let mut path = String::new();
+        let mut elements = elements.into_iter();
+
+        if self.patterns_for(name, &mut path, &mut elements)?.is_some() {
+            if path.starts_with('/') {
+                Ok(relative_path(req.path(), &path))
+            } else {
+                Ok(path)
+            }
+        } else {
+            Err(UrlGenerationError::ResourceNotFound)
+        }
+    }
+
     pub fn has_resource(&self, path: &str) -> bool {
         let path = if path.is_empty() { "/" } else { path };
 
@@ -247,6 +276,36 @@ impl ResourceMap {
     }
 }
 
+/// Makes absolute path `target` relative to the directory of absolute path `base`.
+fn relative_path(base: &str, target: &str) -> String {
+    let base = base.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    let target = target
+        .trim_start_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+
+    // the last segment of each path is a file name, the others are directories
+    let base_dirs = &base[..base.len() - 1];
+    let target_dirs = &target[..target.len() - 1];
+
+    let common = base_dirs
+        .iter()
+        .zip(target_dirs)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut rel = "../".repeat(base_dirs.len() - common);
+    rel.push_str(&target[common..].join("/"));
+
+    // keep relative paths from being empty or read as a scheme
+    let first = rel.split('/').next().unwrap_or_default();
+    if rel.is_empty() || first.contains(':') {
+        rel.insert_str(0, "./");
+    }
+
+    rel
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -9,12 +9,14 @@ use actix_service::{
 };
 use futures_core::future::LocalBoxFuture;
 
+use crate::authorization::Requirements;
+use crate::discovery::RouteDescription;
 use crate::extract::FromRequest;
 use crate::guard::{self, Guard};
 use crate::handler::{Handler, HandlerService};
 use crate::responder::Responder;
 use crate::service::{ServiceRequest, ServiceResponse};
-use crate::HttpResponse;
+use crate::{HttpMessage as _, HttpResponse};
 
 /// Resource route definition
 ///
@@ -23,6 +25,8 @@ use crate::HttpResponse;
 pub struct Route {
     service: BoxServiceFactory<(), ServiceRequest, ServiceResponse, Error, ()>,
     guards: Rc<Vec<Box<dyn Guard>>>,
+    requirements: Rc<Requirements>,
+    description: RouteDescription,
 }
 
 impl Route {
@@ -32,12 +36,18 @@ impl Route {
         Route {
             service: boxed::factory(HandlerService::new(HttpResponse::NotFound)),
             guards: Rc::new(Vec::new()),
+            requirements: Rc::new(Requirements::default()),
+            description: RouteDescription::default(),
         }
     }
 
     pub(crate) fn take_guards(&mut self) -> Vec<Box<dyn Guard>> {
         std::mem::take(Rc::get_mut(&mut self.guards).unwrap())
     }
+
+    pub(crate) fn description(&self) -> &RouteDescription {
+        &self.description
+    }
 }
 
 impl ServiceFactory<ServiceRequest> for Route {
@@ -51,17 +61,23 @@ impl ServiceFactory<ServiceRequest> for Route {
     fn new_service(&self, _: ()) -> Self::Future {
         let fut = self.service.new_service(());
         let guards = self.guards.clone();
+        let requirements = self.requirements.clone();
 
         Box::pin(async move {
             let service = fut.await?;
-            Ok(RouteService { service, guards })
+            Ok(RouteService {
+                service: Rc::new(service),
+                guards,
+                requirements,
+            })
         })
     }
 }
 
 pub struct RouteService {
-    service: BoxService<ServiceRequest, ServiceResponse, Error>,
+    service: Rc<BoxService<ServiceRequest, ServiceResponse, Error>>,
     guards: Rc<Vec<Box<dyn Guard>>>,
+    requirements: Rc<Requirements>,
 }
 
 impl RouteService {
@@ -83,7 +99,22 @@ impl Service<ServiceRequest> for RouteService {
     actix_service::forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        self.service.call(req)
+        if self.requirements.is_empty() {
+            return self.service.call(req);
+        }
+
+        let service = Rc::clone(&self.service);
+        let requirements = Rc::clone(&self.requirements);
+
+        Box::pin(async move {
+            match requirements.authorize(&req).await {
+                Ok(ctx) => {
+                    req.extensions_mut().insert(ctx);
+                    service.call(req).await
+                }
+                Err(err) => Ok(req.error_response(err)),
+            }
+        })
     }
 }
 
@@ -102,12 +133,49 @@ impl Route {
     /// # }
     /// ```
     pub fn method(mut self, method: Method) -> Self {
+        self.description.methods.push(method.clone());
         Rc::get_mut(&mut self.guards)
             .unwrap()
             .push(Box::new(guard::Method(method)));
         self
     }
 
+    /// Add metadata listed for the route in API discovery documents.
+    ///
+    /// Setting a key again replaces its value. See
+    /// [`App::enable_discovery`](crate::App::enable_discovery).
+    ///
+    /// ```rust
+    /// # use actix_web::*;
+    /// App::new().service(web::resource("/users").route(
+    ///     web::get()
+    ///         .meta("summary", "List users")
+    ///         .to(|| HttpResponse::Ok()))
+    /// );
+    /// ```
+    pub fn meta(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.description.meta.insert(key.into(), value.into());
+        self
+    }
+
+    /// Declare a content type accepted by the route, listed in API discovery documents.
+    ///
+    /// This is documentation only; it does not restrict the requests the route matches. Use a
+    /// guard for that.
+    ///
+    /// ```rust
+    /// # use actix_web::*;
+    /// App::new().service(web::resource("/users").route(
+    ///     web::post()
+    ///         .accepts("application/json")
+    ///         .to(|| HttpResponse::Created()))
+    /// );
+    /// ```
+    pub fn accepts(mut self, content_type: impl Into<String>) -> Self {
+        self.description.accepts.push(content_type.into());
+        self
+    }
+
     /// Add guard to the route.
     ///
     /// ```rust
@@ -126,6 +194,62 @@ impl Route {
         self
     }
 
+    /// Require a permission for requests to the route.
+    ///
+    /// Before the handler is called, the permission is checked with the [`Permissions`]
+    /// registered as `Data<dyn Permissions>` app data. Requests that are not granted the permission
+    /// get a `401 Unauthorized` or `403 Forbidden` response, depending on the [`Decision`]. The
+    /// outcome of the checks is available to the handler through the [`AuthContext`] extractor.
+    ///
+    /// Unlike guards, permissions do not take part in route matching. Calling this method several
+    /// times requires all of the permissions.
+    ///
+    /// ```rust
+    /// # use actix_web::*;
+    /// App::new().service(web::resource("/orders").route(
+    ///     web::post()
+    ///         .require_permission("orders:write")
+    ///         .to(|_auth: web::AuthContext| HttpResponse::Created()))
+    /// );
+    /// ```
+    ///
+    /// [`Permissions`]: crate::web::Permissions
+    /// [`Decision`]: crate::web::Decision
+    /// [`AuthContext`]: crate::web::AuthContext
+    pub fn require_permission(self, permission: impl Into<String>) -> Self {
+        self.require_any_permission(Some(permission))
+    }
+
+    /// Require any one of several permissions for requests to the route.
+    ///
+    /// Permissions are checked in order until one is granted. Combined with other requirements
+    /// of the route, all of which must be met. See
+    /// [`require_permission`](Self::require_permission).
+    ///
+    /// ```rust
+    /// # use actix_web::*;
+    /// App::new().service(web::resource("/orders/{id}").route(
+    ///     web::delete()
+    ///         .require_permission("orders:write")
+    ///         .require_any_permission(vec!["orders:admin", "support"])
+    ///         .to(|| HttpResponse::NoContent()))
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `permissions` is empty.
+    pub fn require_any_permission<I, P>(mut self, permissions: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+    {
+        let any = permissions.into_iter().map(Into::into).collect::<Vec<_>>();
+        assert!(!any.is_empty(), "Route requires at least one permission");
+
+        Rc::get_mut(&mut self.requirements).unwrap().push(any);
+        self
+    }
+
     /// Set handler function, use request extractors for parameters.
     ///
     /// ```rust
@@ -1,16 +1,29 @@
 use std::{
     any::Any,
-    cmp, fmt, io,
+    cell::{Cell, RefCell},
+    cmp, fmt,
+    future::Future,
+    io,
     marker::PhantomData,
-    net,
-    sync::{Arc, Mutex},
+    mem, net,
+    pin::Pin,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
 
 use actix_http::{
-    body::MessageBody, Error, Extensions, HttpService, KeepAlive, Request, Response,
+    body::MessageBody, http::ConnectionType, Error, Extensions, HttpService, KeepAlive,
+    Request, Response,
 };
 use actix_server::{Server, ServerBuilder};
 use actix_service::{map_config, IntoServiceFactory, Service, ServiceFactory};
+use futures_util::{future::LocalBoxFuture, ready};
+use pin_project::pin_project;
 
 #[cfg(unix)]
 use actix_http::Protocol;
@@ -24,7 +37,11 @@ use actix_tls::accept::openssl::{AlpnError, SslAcceptor, SslAcceptorBuilder};
 #[cfg(feature = "rustls")]
 use actix_tls::accept::rustls::ServerConfig as RustlsServerConfig;
 
-use crate::config::AppConfig;
+use crate::{
+    background::{BackgroundTasks, RestartPolicy},
+    blocking::BlockingGauge,
+    config::AppConfig,
+};
 
 struct Socket {
     scheme: &'static str,
@@ -36,6 +53,196 @@ struct Config {
     keep_alive: KeepAlive,
     client_timeout: u64,
     client_shutdown: u64,
+    recycle: RecycleConfig,
+    blocking: BlockingGauge,
+}
+
+#[derive(Clone)]
+struct RecycleConfig {
+    max_requests: Option<usize>,
+    max_lifetime: Option<Duration>,
+    shutdown_timeout: Duration,
+    recycles: Arc<AtomicUsize>,
+
+    /// Set while a worker drains or waits for its replacement, so only one restarts at a time.
+    restarting: Arc<AtomicBool>,
+
+    /// Set once a recycled worker exited, until its replacement starts.
+    replaced: Arc<AtomicBool>,
+
+    /// Number of running workers, since a worker only exits if another one can take over.
+    workers: Arc<AtomicUsize>,
+
+    /// Used to pause accepting connections while a worker exits.
+    server: Arc<Mutex<Option<Server>>>,
+}
+
+impl Default for RecycleConfig {
+    fn default() -> Self {
+        RecycleConfig {
+            max_requests: None,
+            max_lifetime: None,
+            shutdown_timeout: Duration::from_secs(30),
+            recycles: Arc::new(AtomicUsize::new(0)),
+            restarting: Arc::new(AtomicBool::new(false)),
+            replaced: Arc::new(AtomicBool::new(false)),
+            workers: Arc::new(AtomicUsize::new(0)),
+            server: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+/// Failure to set up one of the addresses requested from an [`HttpServer`].
+#[derive(Debug)]
+pub struct BindError {
+    /// Address that failed, if the failure is specific to one.
+    pub addr: Option<net::SocketAddr>,
+
+    /// Cause of the failure.
+    pub kind: BindErrorKind,
+}
+
+/// Cause of a [`BindError`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BindErrorKind {
+    /// The address is already in use.
+    AddrInUse,
+
+    /// Binding the address is not permitted, e.g. a privileged port.
+    PermissionDenied,
+
+    /// The TLS configuration is invalid, e.g. the private key does not match the certificate.
+    Tls(String),
+
+    /// Any other I/O error, including failure to resolve the address.
+    Io(io::Error),
+}
+
+impl BindError {
+    fn io(addr: Option<net::SocketAddr>, err: io::Error) -> Self {
+        let kind = match err.kind() {
+            io::ErrorKind::AddrInUse => BindErrorKind::AddrInUse,
+            io::ErrorKind::PermissionDenied => BindErrorKind::PermissionDenied,
+            _ => BindErrorKind::Io(err),
+        };
+
+        BindError { addr, kind }
+    }
+
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    fn tls(msg: impl Into<String>) -> Self {
+        BindError {
+            addr: None,
+            kind: BindErrorKind::Tls(msg.into()),
+        }
+    }
+}
+
+impl fmt::Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(addr) = self.addr {
+            write!(f, "{}: ", addr)?;
+        }
+
+        match self.kind {
+            BindErrorKind::AddrInUse => f.write_str("address already in use"),
+            BindErrorKind::PermissionDenied => f.write_str("permission denied"),
+            BindErrorKind::Tls(ref msg) => write!(f, "invalid TLS configuration: {}", msg),
+            BindErrorKind::Io(ref err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for BindError {}
+
+/// Error returned by the `bind` methods of [`HttpServer`], listing every failure.
+///
+/// A `bind` call returns the failures of its own addresses and TLS configuration. To attempt all
+/// requested binds first, call [`HttpServer::collect_bind_errors`]; [`HttpServer::check_binds`]
+/// then returns the failures of all of them. Converts into an [`io::Error`], so `?` keeps working
+/// in functions returning `io::Result`.
+#[derive(Debug)]
+pub struct BindErrors(Vec<BindError>);
+
+impl BindErrors {
+    /// Returns the failures, in the order they were encountered.
+    pub fn errors(&self) -> &[BindError] {
+        &self.0
+    }
+
+    /// Unwraps into the list of failures.
+    pub fn into_inner(self) -> Vec<BindError> {
+        self.0
+    }
+}
+
+impl From<BindError> for BindErrors {
+    fn from(err: BindError) -> Self {
+        BindErrors(vec![err])
+    }
+}
+
+impl fmt::Display for BindErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Can not bind server: ")?;
+
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", err)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for BindErrors {}
+
+impl From<BindErrors> for io::Error {
+    fn from(errs: BindErrors) -> Self {
+        let kind = match errs.0.first().map(|err| &err.kind) {
+            Some(BindErrorKind::AddrInUse) => io::ErrorKind::AddrInUse,
+            Some(BindErrorKind::PermissionDenied) => io::ErrorKind::PermissionDenied,
+            Some(BindErrorKind::Tls(_)) => io::ErrorKind::InvalidInput,
+            Some(BindErrorKind::Io(err)) => err.kind(),
+            None => io::ErrorKind::Other,
+        };
+
+        io::Error::new(kind, errs)
+    }
+}
+
+/// Statistics of the workers of an [`HttpServer`].
+///
+/// Obtained with [`HttpServer::worker_stats`]; it keeps being updated after the server is started.
+#[derive(Debug, Clone)]
+pub struct WorkerStats {
+    recycles: Arc<AtomicUsize>,
+    blocking: BlockingGauge,
+}
+
+impl WorkerStats {
+    /// Returns number of times a worker has been recycled.
+    pub fn recycles(&self) -> usize {
+        self.recycles.load(Ordering::Relaxed)
+    }
+
+    /// Returns number of blocking tasks running under a [`BlockingConfig`] on all workers.
+    ///
+    /// [`BlockingConfig`]: crate::web::BlockingConfig
+    pub fn blocking_running(&self) -> usize {
+        self.blocking.running()
+    }
+
+    /// Returns number of blocking tasks waiting for a slot of a [`BlockingConfig`] on all
+    /// workers.
+    ///
+    /// [`BlockingConfig`]: crate::web::BlockingConfig
+    pub fn blocking_queued(&self) -> usize {
+        self.blocking.queued()
+    }
 }
 
 /// An HTTP Server.
@@ -71,6 +278,8 @@ where
     sockets: Vec<Socket>,
     builder: ServerBuilder,
     on_connect_fn: Option<Arc<dyn Fn(&dyn Any, &mut Extensions) + Send + Sync>>,
+    tasks: BackgroundTasks,
+    bind_errors: Option<Vec<BindError>>,
     _phantom: PhantomData<(S, B)>,
 }
 
@@ -95,11 +304,15 @@ where
                 keep_alive: KeepAlive::Timeout(5),
                 client_timeout: 5000,
                 client_shutdown: 5000,
+                recycle: RecycleConfig::default(),
+                blocking: BlockingGauge::default(),
             })),
             backlog: 1024,
             sockets: Vec::new(),
             builder: ServerBuilder::default(),
             on_connect_fn: None,
+            tasks: BackgroundTasks::default(),
+            bind_errors: None,
             _phantom: PhantomData,
         }
     }
@@ -125,6 +338,8 @@ where
             sockets: self.sockets,
             builder: self.builder,
             on_connect_fn: Some(Arc::new(f)),
+            tasks: self.tasks,
+            bind_errors: self.bind_errors,
             _phantom: PhantomData,
         }
     }
@@ -180,6 +395,11 @@ where
     /// Set server keep-alive setting.
     ///
     /// By default keep alive is set to a 5 seconds.
+    ///
+    /// The setting can be overridden for HTTP/1 responses with a
+    /// [`KeepAlivePolicy`](crate::dev::KeepAlivePolicy). A policy in the extensions of a response
+    /// takes precedence over one registered as app data of the scope or resource that handled the
+    /// request, which in turn takes precedence over this setting.
     pub fn keep_alive<T: Into<KeepAlive>>(self, val: T) -> Self {
         self.config.lock().unwrap().keep_alive = val.into();
         self
@@ -212,6 +432,135 @@ where
         self
     }
 
+    /// Set the number of requests a worker serves before it is recycled.
+    ///
+    /// Once the limit is reached, the worker drains: it stops taking new connections, which are
+    /// handed to the other workers instead, and responses ask clients to close their connection.
+    /// The worker exits when its last connection is closed or the
+    /// [`shutdown_timeout`](Self::shutdown_timeout) has passed, whichever comes first. The server
+    /// then starts a replacement worker with a new application, so the number of workers stays
+    /// the same. This keeps resources leaked by a worker from accumulating indefinitely.
+    ///
+    /// While a worker exits, the server briefly pauses accepting connections; they wait in the
+    /// listen backlog and are handed to the remaining workers afterwards. Only one worker
+    /// restarts at a time, until its replacement has started. Since connections can only be
+    /// handed to another worker, a server with a single worker does not recycle it and logs a
+    /// warning instead.
+    ///
+    /// By default workers are never recycled.
+    pub fn worker_max_requests(self, num: usize) -> Self {
+        self.config.lock().unwrap().recycle.max_requests = Some(num);
+        self
+    }
+
+    /// Set the time after which a worker is recycled.
+    ///
+    /// The lifetime counts from when the worker creates its application. See
+    /// [`worker_max_requests`](Self::worker_max_requests) for how workers are recycled.
+    ///
+    /// By default workers are never recycled.
+    pub fn worker_max_lifetime(self, dur: Duration) -> Self {
+        self.config.lock().unwrap().recycle.max_lifetime = Some(dur);
+        self
+    }
+
+    /// Get a handle to the statistics of this server's workers.
+    pub fn worker_stats(&self) -> WorkerStats {
+        let c = self.config.lock().unwrap();
+
+        WorkerStats {
+            recycles: c.recycle.recycles.clone(),
+            blocking: c.blocking.clone(),
+        }
+    }
+
+    /// Run `task` once when the server is started.
+    ///
+    /// Background tasks run on the thread that calls [`run`](Self::run), not on the workers. They
+    /// are cancelled as soon as the server receives a shutdown signal, before graceful shutdown
+    /// waits for open connections, or once the server has stopped if it is stopped through
+    /// [`Server::stop`](actix_server::Server::stop) or signals are disabled. A task that panics is
+    /// restarted according to the [`background_restart`](Self::background_restart) policy; one
+    /// that completes is not run again.
+    ///
+    /// To make data produced by a task available to handlers, create it outside of the
+    /// application factory and move a clone into both:
+    ///
+    /// ```rust,no_run
+    /// use std::sync::RwLock;
+    /// use actix_web::{web, App, HttpServer};
+    ///
+    /// #[actix_rt::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let greeting = web::Data::new(RwLock::new(String::new()));
+    ///     let task_greeting = greeting.clone();
+    ///
+    ///     HttpServer::new(move || App::new().app_data(greeting.clone()))
+    ///         .spawn_on_start(move || {
+    ///             let greeting = task_greeting.clone();
+    ///             async move { *greeting.write().unwrap() = "Hello!".to_owned() }
+    ///         })
+    ///         .bind("127.0.0.1:0")?
+    ///         .run()
+    ///         .await
+    /// }
+    /// ```
+    pub fn spawn_on_start<T, Fut>(mut self, task: T) -> Self
+    where
+        T: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.tasks.on_start(task);
+        self
+    }
+
+    /// Run `task` every `interval`, starting when the server is started.
+    ///
+    /// The first run happens right away. Runs never overlap; if one takes longer than `interval`,
+    /// the next starts as soon as it completes. See [`spawn_on_start`](Self::spawn_on_start) for
+    /// where tasks run and how they are stopped.
+    ///
+    /// ```rust,no_run
+    /// use std::{sync::RwLock, time::Duration};
+    /// use actix_web::{web, App, HttpServer};
+    ///
+    /// async fn fetch_jwks() -> Vec<String> {
+    ///     // ...
+    /// #   Vec::new()
+    /// }
+    ///
+    /// #[actix_rt::main]
+    /// async fn main() -> std::io::Result<()> {
+    ///     let jwks = web::Data::new(RwLock::new(Vec::new()));
+    ///     let task_jwks = jwks.clone();
+    ///
+    ///     HttpServer::new(move || App::new().app_data(jwks.clone()))
+    ///         .spawn_periodic(Duration::from_secs(600), move || {
+    ///             let jwks = task_jwks.clone();
+    ///             async move { *jwks.write().unwrap() = fetch_jwks().await }
+    ///         })
+    ///         .bind("127.0.0.1:0")?
+    ///         .run()
+    ///         .await
+    /// }
+    /// ```
+    pub fn spawn_periodic<T, Fut>(mut self, interval: Duration, task: T) -> Self
+    where
+        T: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.tasks.periodic(interval, task);
+        self
+    }
+
+    /// Set what happens to background tasks that panic.
+    ///
+    /// By default tasks are restarted after one second.
+    pub fn background_restart(mut self, policy: RestartPolicy) -> Self {
+        self.tasks.restart(policy);
+        self
+    }
+
     /// Set server host name.
     ///
     /// Host name is used by application router as a hostname for url generation.
@@ -233,6 +582,7 @@ where
     /// Disable signal handling
     pub fn disable_signals(mut self) -> Self {
         self.builder = self.builder.disable_signals();
+        self.tasks.disable_signals();
         self
     }
 
@@ -244,6 +594,7 @@ where
     ///
     /// By default shutdown timeout sets to 30 seconds.
     pub fn shutdown_timeout(mut self, sec: u64) -> Self {
+        self.config.lock().unwrap().recycle.shutdown_timeout = Duration::from_secs(sec);
         self.builder = self.builder.shutdown_timeout(sec);
         self
     }
@@ -255,6 +606,10 @@ where
 
     /// Get addresses of bound sockets and the scheme for it.
     ///
+    /// Only addresses that were actually bound are reported; addresses that failed to bind are
+    /// reported by the [`BindErrors`] returned from the `bind` methods or
+    /// [`check_binds`](Self::check_binds).
+    ///
     /// This is useful when the server is bound from different sources
     /// with some sockets listening on HTTP and some listening on HTTPS
     /// and the user should be presented with an enumeration of which
@@ -263,6 +618,52 @@ where
         self.sockets.iter().map(|s| (s.addr, s.scheme)).collect()
     }
 
+    /// Makes the `bind` methods collect their failures instead of returning them.
+    ///
+    /// Every requested bind is attempted and [`check_binds`](Self::check_binds) reports the
+    /// failures of all of them together.
+    ///
+    /// ```rust,no_run
+    /// use actix_web::{web, App, HttpResponse, HttpServer};
+    ///
+    /// # fn main() -> Result<(), actix_web::BindErrors> {
+    /// let srv = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+    ///     .collect_bind_errors()
+    ///     .bind("127.0.0.1:8080")?
+    ///     .bind("127.0.0.1:8081")?
+    ///     .check_binds()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_bind_errors(mut self) -> Self {
+        self.bind_errors.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Returns failures collected by the `bind` methods since
+    /// [`collect_bind_errors`](Self::collect_bind_errors) was called.
+    pub fn check_binds(mut self) -> Result<Self, BindErrors> {
+        let errors = self.bind_errors.as_mut().map(mem::take).unwrap_or_default();
+
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(BindErrors(errors))
+        }
+    }
+
+    /// Returns failures of a bind call, unless they are collected for
+    /// [`check_binds`](Self::check_binds).
+    fn bind_failed(mut self, errors: BindErrors) -> Result<Self, BindErrors> {
+        match self.bind_errors {
+            Some(ref mut collected) => {
+                collected.extend(errors.0);
+                Ok(self)
+            }
+            None => Err(errors),
+        }
+    }
+
     /// Use listener for accepting incoming connection requests
     ///
     /// HttpServer does not change any configuration for TcpListener,
@@ -283,6 +684,9 @@ where
                     let c = cfg.lock().unwrap();
                     let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
 
+                    c.blocking.register();
+                    let recycle = WorkerRecycle::current(&c.recycle);
+
                     let svc = HttpService::build()
                         .keep_alive(c.keep_alive)
                         .client_timeout(c.client_timeout)
@@ -294,10 +698,14 @@ where
                         svc
                     };
 
-                    svc.finish(map_config(factory(), move |_| {
-                        AppConfig::new(false, addr, host.clone())
-                    }))
-                    .tcp()
+                    let svc = svc
+                        .finish(map_config(
+                            Recycle::new(factory(), recycle.clone()),
+                            move |_| AppConfig::new(false, addr, host.clone()),
+                        ))
+                        .tcp();
+
+                    track_connections(svc, recycle)
                 })?;
         Ok(self)
     }
@@ -311,7 +719,8 @@ where
         lst: net::TcpListener,
         builder: SslAcceptorBuilder,
     ) -> io::Result<Self> {
-        self.listen_ssl_inner(lst, openssl_acceptor(builder)?)
+        let acceptor = openssl_acceptor(builder).map_err(BindErrors::from)?;
+        self.listen_ssl_inner(lst, acceptor)
     }
 
     #[cfg(feature = "openssl")]
@@ -336,6 +745,9 @@ where
                     let c = cfg.lock().unwrap();
                     let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
 
+                    c.blocking.register();
+                    let recycle = WorkerRecycle::current(&c.recycle);
+
                     let svc = HttpService::build()
                         .keep_alive(c.keep_alive)
                         .client_timeout(c.client_timeout)
@@ -349,10 +761,14 @@ where
                         svc
                     };
 
-                    svc.finish(map_config(factory(), move |_| {
-                        AppConfig::new(true, addr, host.clone())
-                    }))
-                    .openssl(acceptor.clone())
+                    let svc = svc
+                        .finish(map_config(
+                            Recycle::new(factory(), recycle.clone()),
+                            move |_| AppConfig::new(true, addr, host.clone()),
+                        ))
+                        .openssl(acceptor.clone());
+
+                    track_connections(svc, recycle)
                 })?;
         Ok(self)
     }
@@ -366,6 +782,7 @@ where
         lst: net::TcpListener,
         config: RustlsServerConfig,
     ) -> io::Result<Self> {
+        check_rustls_config(&config).map_err(BindErrors::from)?;
         self.listen_rustls_inner(lst, config)
     }
 
@@ -391,6 +808,9 @@ where
                     let c = cfg.lock().unwrap();
                     let host = c.host.clone().unwrap_or_else(|| format!("{}", addr));
 
+                    c.blocking.register();
+                    let recycle = WorkerRecycle::current(&c.recycle);
+
                     let svc = HttpService::build()
                         .keep_alive(c.keep_alive)
                         .client_timeout(c.client_timeout)
@@ -402,69 +822,114 @@ where
                         svc
                     };
 
-                    svc.finish(map_config(factory(), move |_| {
-                        AppConfig::new(true, addr, host.clone())
-                    }))
-                    .rustls(config.clone())
+                    let svc = svc
+                        .finish(map_config(
+                            Recycle::new(factory(), recycle.clone()),
+                            move |_| AppConfig::new(true, addr, host.clone()),
+                        ))
+                        .rustls(config.clone());
+
+                    track_connections(svc, recycle)
                 })?;
         Ok(self)
     }
 
     /// The socket address to bind
     ///
-    /// To bind multiple addresses this method can be called multiple times.
-    pub fn bind<A: net::ToSocketAddrs>(mut self, addr: A) -> io::Result<Self> {
-        let sockets = self.bind2(addr)?;
+    /// To bind multiple addresses this method can be called multiple times. If `addr` resolves to
+    /// several addresses, binding succeeds if any of them can be bound. Otherwise the returned
+    /// [`BindErrors`] lists the failure of each address of this call, see
+    /// [`collect_bind_errors`](Self::collect_bind_errors) to report failures of several calls
+    /// together.
+    pub fn bind<A: net::ToSocketAddrs>(mut self, addr: A) -> Result<Self, BindErrors> {
+        let sockets = match self.bind2(addr) {
+            Ok(sockets) => sockets,
+            Err(errors) => return self.bind_failed(errors),
+        };
 
         for lst in sockets {
-            self = self.listen(lst)?;
+            self = self.listen_bound(lst, Self::listen)?;
         }
 
         Ok(self)
     }
 
-    fn bind2<A: net::ToSocketAddrs>(&self, addr: A) -> io::Result<Vec<net::TcpListener>> {
-        let mut err = None;
-        let mut success = false;
+    fn bind2<A: net::ToSocketAddrs>(
+        &self,
+        addr: A,
+    ) -> Result<Vec<net::TcpListener>, BindErrors> {
+        let addrs = addr
+            .to_socket_addrs()
+            .map_err(|err| BindError::io(None, err))?;
+
+        let mut errors = Vec::new();
         let mut sockets = Vec::new();
 
-        for addr in addr.to_socket_addrs()? {
+        for addr in addrs {
             match create_tcp_listener(addr, self.backlog) {
-                Ok(lst) => {
-                    success = true;
-                    sockets.push(lst);
-                }
-                Err(e) => err = Some(e),
+                Ok(lst) => sockets.push(lst),
+                Err(err) => errors.push(BindError::io(Some(addr), err)),
             }
         }
 
-        if !success {
-            if let Some(e) = err.take() {
-                Err(e)
-            } else {
-                Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    "Can not bind to address.",
-                ))
+        if !sockets.is_empty() {
+            for err in errors {
+                log::debug!("Skipping address that failed to bind: {}", err);
             }
-        } else {
+
             Ok(sockets)
+        } else if errors.is_empty() {
+            Err(BindError::io(
+                None,
+                io::Error::new(io::ErrorKind::Other, "Can not bind to address."),
+            )
+            .into())
+        } else {
+            Err(BindErrors(errors))
         }
     }
 
+    /// Registers a bound listener, attributing errors to its address.
+    fn listen_bound<L>(self, lst: net::TcpListener, listen: L) -> Result<Self, BindErrors>
+    where
+        L: FnOnce(Self, net::TcpListener) -> io::Result<Self>,
+    {
+        let addr = lst.local_addr().ok();
+        listen(self, lst).map_err(|err| BindError::io(addr, err).into())
+    }
+
     #[cfg(feature = "openssl")]
     /// Start listening for incoming tls connections.
     ///
     /// This method sets alpn protocols to "h2" and "http/1.1"
-    pub fn bind_openssl<A>(mut self, addr: A, builder: SslAcceptorBuilder) -> io::Result<Self>
+    ///
+    /// The TLS configuration is checked before the server starts: the private key must match the
+    /// certificate and the chain certificates must be in order, each issuing the previous one.
+    /// Expired certificates are logged as warnings. Both binding and TLS configuration failures
+    /// are reported together in the returned [`BindErrors`].
+    pub fn bind_openssl<A>(
+        mut self,
+        addr: A,
+        builder: SslAcceptorBuilder,
+    ) -> Result<Self, BindErrors>
     where
         A: net::ToSocketAddrs,
     {
-        let sockets = self.bind2(addr)?;
-        let acceptor = openssl_acceptor(builder)?;
+        let sockets = self.bind2(addr);
+        let acceptor = openssl_acceptor(builder);
+
+        let (sockets, acceptor) = match (sockets, acceptor) {
+            (Ok(sockets), Ok(acceptor)) => (sockets, acceptor),
+            (sockets, acceptor) => {
+                let mut errors = sockets.err().map_or_else(Vec::new, BindErrors::into_inner);
+                errors.extend(acceptor.err());
+                return self.bind_failed(BindErrors(errors));
+            }
+        };
 
         for lst in sockets {
-            self = self.listen_ssl_inner(lst, acceptor.clone())?;
+            let acceptor = acceptor.clone();
+            self = self.listen_bound(lst, |srv, lst| srv.listen_ssl_inner(lst, acceptor))?;
         }
 
         Ok(self)
@@ -474,14 +939,30 @@ where
     /// Start listening for incoming tls connections.
     ///
     /// This method sets alpn protocols to "h2" and "http/1.1"
+    ///
+    /// The TLS configuration is checked before the server starts by a handshake with it: the
+    /// private key must match the certificate. Both binding and TLS configuration failures are
+    /// reported together in the returned [`BindErrors`].
     pub fn bind_rustls<A: net::ToSocketAddrs>(
         mut self,
         addr: A,
         config: RustlsServerConfig,
-    ) -> io::Result<Self> {
-        let sockets = self.bind2(addr)?;
+    ) -> Result<Self, BindErrors> {
+        let sockets = self.bind2(addr);
+        let checked = check_rustls_config(&config);
+
+        let sockets = match (sockets, checked) {
+            (Ok(sockets), Ok(())) => sockets,
+            (sockets, checked) => {
+                let mut errors = sockets.err().map_or_else(Vec::new, BindErrors::into_inner);
+                errors.extend(checked.err());
+                return self.bind_failed(BindErrors(errors));
+            }
+        };
+
         for lst in sockets {
-            self = self.listen_rustls_inner(lst, config.clone())?;
+            let config = config.clone();
+            self = self.listen_bound(lst, |srv, lst| srv.listen_rustls_inner(lst, config))?;
         }
         Ok(self)
     }
@@ -511,19 +992,30 @@ where
                 c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
             );
 
-            pipeline_factory(|io: UnixStream| ok((io, Protocol::Http1, None))).and_then({
-                let svc = HttpService::build()
-                    .keep_alive(c.keep_alive)
-                    .client_timeout(c.client_timeout);
+            c.blocking.register();
+            let recycle = WorkerRecycle::current(&c.recycle);
 
-                let svc = if let Some(handler) = on_connect_fn.clone() {
-                    svc.on_connect_ext(move |io: &_, ext: _| (&*handler)(io as &dyn Any, ext))
-                } else {
-                    svc
-                };
+            let svc = pipeline_factory(|io: UnixStream| ok((io, Protocol::Http1, None)))
+                .and_then({
+                    let svc = HttpService::build()
+                        .keep_alive(c.keep_alive)
+                        .client_timeout(c.client_timeout);
 
-                svc.finish(map_config(factory(), move |_| config.clone()))
-            })
+                    let svc = if let Some(handler) = on_connect_fn.clone() {
+                        svc.on_connect_ext(move |io: &_, ext: _| {
+                            (&*handler)(io as &dyn Any, ext)
+                        })
+                    } else {
+                        svc
+                    };
+
+                    svc.finish(map_config(
+                        Recycle::new(factory(), recycle.clone()),
+                        move |_| config.clone(),
+                    ))
+                });
+
+            track_connections(svc, recycle)
         })?;
         Ok(self)
     }
@@ -555,12 +1047,21 @@ where
                     socket_addr,
                     c.host.clone().unwrap_or_else(|| format!("{}", socket_addr)),
                 );
-                pipeline_factory(|io: UnixStream| ok((io, Protocol::Http1, None))).and_then(
-                    HttpService::build()
-                        .keep_alive(c.keep_alive)
-                        .client_timeout(c.client_timeout)
-                        .finish(map_config(factory(), move |_| config.clone())),
-                )
+                c.blocking.register();
+                let recycle = WorkerRecycle::current(&c.recycle);
+
+                let svc = pipeline_factory(|io: UnixStream| ok((io, Protocol::Http1, None)))
+                    .and_then(
+                        HttpService::build()
+                            .keep_alive(c.keep_alive)
+                            .client_timeout(c.client_timeout)
+                            .finish(map_config(
+                                Recycle::new(factory(), recycle.clone()),
+                                move |_| config.clone(),
+                            )),
+                    );
+
+                track_connections(svc, recycle)
             },
         )?;
         Ok(self)
@@ -600,7 +1101,490 @@ where
     /// }
     /// ```
     pub fn run(self) -> Server {
-        self.builder.run()
+        for err in self.bind_errors.iter().flatten() {
+            log::error!("Bind failure was not checked: {}", err);
+        }
+
+        let server = self.builder.run();
+
+        let server_slot = self.config.lock().unwrap().recycle.server.clone();
+        *server_slot.lock().unwrap() = Some(server.clone());
+
+        self.tasks.start(server.clone());
+        server
+    }
+}
+
+thread_local! {
+    /// Recycling state of the worker running on this thread.
+    ///
+    /// Every worker runs on its own thread, so the services of all listeners of a worker share it
+    /// and a replacement worker starts afresh.
+    static WORKER_RECYCLE: RefCell<Option<Rc<WorkerRecycle>>> = RefCell::new(None);
+}
+
+/// Tracks the requests and connections of a worker and makes it exit once it reaches its recycle
+/// limits, letting the server start a replacement.
+struct WorkerRecycle {
+    config: RecycleConfig,
+    requests: Cell<usize>,
+    connections: Cell<usize>,
+
+    /// Number of connections this worker has received, to notice ones arriving while it exits.
+    received: Cell<usize>,
+    draining: Cell<bool>,
+    exiting: Cell<bool>,
+    stopped: Cell<bool>,
+
+    /// Cleared while the worker drains, so it reports its listeners as not ready and the server
+    /// hands new connections to other workers.
+    listening: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+    warned: Cell<bool>,
+}
+
+impl WorkerRecycle {
+    /// Returns the recycling state of the current worker, or `None` if workers are not recycled.
+    ///
+    /// Must be called on the worker's thread, when its services are created.
+    fn current(config: &RecycleConfig) -> Option<Rc<Self>> {
+        if config.max_requests.is_none() && config.max_lifetime.is_none() {
+            return None;
+        }
+
+        WORKER_RECYCLE.with(|current| {
+            let mut current = current.borrow_mut();
+
+            if let Some(ref recycle) = *current {
+                return Some(Rc::clone(recycle));
+            }
+
+            config.workers.fetch_add(1, Ordering::AcqRel);
+
+            // this worker replaces one that exited, so another worker may restart now
+            if config.replaced.swap(false, Ordering::AcqRel) {
+                config.restarting.store(false, Ordering::Release);
+            }
+
+            let recycle = Rc::new(WorkerRecycle {
+                config: config.clone(),
+                requests: Cell::new(0),
+                connections: Cell::new(0),
+                received: Cell::new(0),
+                draining: Cell::new(false),
+                exiting: Cell::new(false),
+                stopped: Cell::new(false),
+                listening: Cell::new(true),
+                waker: RefCell::new(None),
+                warned: Cell::new(false),
+            });
+
+            if let Some(lifetime) = config.max_lifetime {
+                let recycle = Rc::clone(&recycle);
+                actix_rt::spawn(async move {
+                    actix_rt::time::sleep(lifetime).await;
+
+                    // wait for another restarting worker to be replaced
+                    while !WorkerRecycle::drain(&recycle) {
+                        actix_rt::time::sleep(Duration::from_secs(1)).await;
+                    }
+                });
+            }
+
+            *current = Some(Rc::clone(&recycle));
+            Some(recycle)
+        })
+    }
+
+    /// Returns true if the worker takes new connections, registering to be woken otherwise.
+    fn poll_listening(&self, cx: &mut Context<'_>) -> bool {
+        if self.listening.get() {
+            true
+        } else {
+            *self.waker.borrow_mut() = Some(cx.waker().clone());
+            false
+        }
+    }
+
+    fn set_listening(&self, listening: bool) {
+        self.listening.set(listening);
+
+        if listening {
+            if let Some(waker) = self.waker.borrow_mut().take() {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Counts a new connection until the returned guard is dropped.
+    fn connected(this: &Rc<Self>) -> ConnectionGuard {
+        this.received.set(this.received.get() + 1);
+        this.connections.set(this.connections.get() + 1);
+        ConnectionGuard(Rc::clone(this))
+    }
+
+    /// Counts a request, starting to drain the worker once it reached the request limit.
+    fn requested(this: &Rc<Self>) {
+        let requests = this.requests.get() + 1;
+        this.requests.set(requests);
+
+        if let Some(max) = this.config.max_requests {
+            if requests >= max {
+                WorkerRecycle::drain(this);
+            }
+        }
+    }
+
+    /// Starts draining the worker, unless another worker is restarting.
+    ///
+    /// Returns false if draining has to be retried later.
+    fn drain(this: &Rc<Self>) -> bool {
+        if this.draining.get() {
+            return true;
+        }
+
+        let restarting = &this.config.restarting;
+        if restarting
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return false;
+        }
+
+        // connections can only be handed to another worker
+        if this.config.workers.load(Ordering::Acquire) < 2 {
+            restarting.store(false, Ordering::Release);
+
+            if !this.warned.replace(true) {
+                log::warn!("Workers are not recycled since there is no other worker");
+            }
+
+            return true;
+        }
+
+        this.draining.set(true);
+        this.set_listening(false);
+        this.config.recycles.fetch_add(1, Ordering::Relaxed);
+        log::info!(
+            "Recycling worker after {} requests; finishing {} connections",
+            this.requests.get(),
+            this.connections.get()
+        );
+
+        if this.connections.get() == 0 {
+            actix_rt::spawn(WorkerRecycle::exit(Rc::clone(this), false));
+        } else {
+            WorkerRecycle::exit_after_timeout(this);
+        }
+
+        true
+    }
+
+    fn exit_after_timeout(this: &Rc<Self>) {
+        let this = Rc::clone(this);
+        actix_rt::spawn(async move {
+            actix_rt::time::sleep(this.config.shutdown_timeout).await;
+            WorkerRecycle::exit(this, true).await;
+        });
+    }
+
+    /// Stops the worker's thread once no connection can be handed to it anymore. The server
+    /// starts a replacement when it finds the worker gone.
+    ///
+    /// Unless `timeout` is set, exiting is left to the last open connection if there is one.
+    async fn exit(this: Rc<Self>, timeout: bool) {
+        if this.exiting.get() || (!timeout && this.connections.get() > 0) {
+            return;
+        }
+
+        this.exiting.set(true);
+
+        // keep the server from handing out connections while this worker exits
+        let server = this.config.server.lock().unwrap().clone();
+        if let Some(ref server) = server {
+            server.pause().await;
+        }
+
+        // take connections that were handed to this worker before it stopped listening; the
+        // worker is woken ahead of this task, so it receives them before this task continues
+        let received = this.received.get();
+        this.set_listening(true);
+        actix_rt::task::yield_now().await;
+        this.set_listening(false);
+
+        if this.received.get() != received {
+            // serve them first, exiting once they are closed or the timeout passed again
+            if let Some(ref server) = server {
+                server.resume().await;
+            }
+
+            this.exiting.set(false);
+            WorkerRecycle::exit_after_timeout(&this);
+            return;
+        }
+
+        if timeout && this.connections.get() > 0 {
+            log::info!("Recycled worker did not finish its connections in time, closing them");
+        }
+
+        // the server resumes once the worker's listeners are gone, see `TrackConnectionsService`
+        this.stopped.set(true);
+        this.config.workers.fetch_sub(1, Ordering::AcqRel);
+        this.config.replaced.store(true, Ordering::Release);
+
+        match actix_rt::Arbiter::try_current() {
+            Some(arbiter) => {
+                arbiter.stop();
+            }
+            None => this.listener_closed(),
+        }
+    }
+
+    /// Called when a listener of the worker is dropped. Its connection queue is closed by then,
+    /// so the server finds the worker gone and can resume accepting connections.
+    fn listener_closed(&self) {
+        if self.stopped.replace(false) {
+            if let Some(ref server) = *self.config.server.lock().unwrap() {
+                // the command is sent right away; the worker's runtime is being torn down, so
+                // there is nothing left to await it with
+                drop(server.resume());
+            }
+        }
+    }
+}
+
+/// Keeps a connection counted for worker recycling while the connection is open.
+struct ConnectionGuard(Rc<WorkerRecycle>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let recycle = &self.0;
+        let connections = recycle.connections.get() - 1;
+        recycle.connections.set(connections);
+
+        if recycle.draining.get() && connections == 0 {
+            actix_rt::spawn(WorkerRecycle::exit(Rc::clone(recycle), false));
+        }
+    }
+}
+
+/// Listener service factory wrapper that counts connections for worker recycling.
+struct TrackConnections<SF> {
+    factory: SF,
+    recycle: Option<Rc<WorkerRecycle>>,
+}
+
+fn track_connections<SF>(
+    factory: SF,
+    recycle: Option<Rc<WorkerRecycle>>,
+) -> TrackConnections<SF> {
+    TrackConnections { factory, recycle }
+}
+
+impl<SF, Req> ServiceFactory<Req> for TrackConnections<SF>
+where
+    SF: ServiceFactory<Req>,
+    SF::Future: 'static,
+    SF::Service: 'static,
+{
+    type Response = SF::Response;
+    type Error = SF::Error;
+    type Config = SF::Config;
+    type Service = TrackConnectionsService<SF::Service>;
+    type InitError = SF::InitError;
+    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, cfg: SF::Config) -> Self::Future {
+        let fut = self.factory.new_service(cfg);
+        let recycle = self.recycle.clone();
+
+        Box::pin(async move {
+            Ok(TrackConnectionsService {
+                service: fut.await?,
+                recycle,
+            })
+        })
+    }
+}
+
+/// Listener service that reports not being ready while its worker drains.
+struct TrackConnectionsService<S> {
+    service: S,
+    recycle: Option<Rc<WorkerRecycle>>,
+}
+
+impl<S, Req> Service<Req> for TrackConnectionsService<S>
+where
+    S: Service<Req>,
+    S::Future: 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if let Some(ref recycle) = self.recycle {
+            if !recycle.poll_listening(cx) {
+                return Poll::Pending;
+            }
+        }
+
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: Req) -> Self::Future {
+        let guard = self.recycle.as_ref().map(WorkerRecycle::connected);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await;
+            drop(guard);
+            res
+        })
+    }
+}
+
+impl<S> Drop for TrackConnectionsService<S> {
+    fn drop(&mut self) {
+        if let Some(ref recycle) = self.recycle {
+            recycle.listener_closed();
+        }
+    }
+}
+
+/// App factory wrapper that counts requests for worker recycling and asks clients to close their
+/// connections once the worker drains.
+struct Recycle<S, B> {
+    factory: S,
+    recycle: Option<Rc<WorkerRecycle>>,
+    _body: PhantomData<B>,
+}
+
+impl<S, B> Recycle<S, B> {
+    fn new<I>(factory: I, recycle: Option<Rc<WorkerRecycle>>) -> Self
+    where
+        I: IntoServiceFactory<S, Request>,
+        S: ServiceFactory<Request>,
+    {
+        Recycle {
+            factory: factory.into_factory(),
+            recycle,
+            _body: PhantomData,
+        }
+    }
+}
+
+impl<S, B> ServiceFactory<Request> for Recycle<S, B>
+where
+    S: ServiceFactory<Request, Config = AppConfig>,
+    S::Error: Into<Error>,
+    S::Response: Into<Response<B>>,
+{
+    type Response = Response<B>;
+    type Error = Error;
+    type Config = AppConfig;
+    type Service = RecycleService<S::Service, B>;
+    type InitError = S::InitError;
+    type Future = RecycleFuture<S::Future, B>;
+
+    fn new_service(&self, cfg: AppConfig) -> Self::Future {
+        RecycleFuture {
+            fut: self.factory.new_service(cfg),
+            recycle: self.recycle.clone(),
+            _body: PhantomData,
+        }
+    }
+}
+
+#[pin_project]
+struct RecycleFuture<F, B> {
+    #[pin]
+    fut: F,
+    recycle: Option<Rc<WorkerRecycle>>,
+    _body: PhantomData<B>,
+}
+
+impl<F, S, E, B> Future for RecycleFuture<F, B>
+where
+    F: Future<Output = Result<S, E>>,
+{
+    type Output = Result<RecycleService<S, B>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let service = ready!(this.fut.poll(cx))?;
+
+        Poll::Ready(Ok(RecycleService {
+            service,
+            recycle: this.recycle.take(),
+            _body: PhantomData,
+        }))
+    }
+}
+
+struct RecycleService<S, B> {
+    service: S,
+    recycle: Option<Rc<WorkerRecycle>>,
+    _body: PhantomData<B>,
+}
+
+impl<S, B> Service<Request> for RecycleService<S, B>
+where
+    S: Service<Request>,
+    S::Error: Into<Error>,
+    S::Response: Into<Response<B>>,
+{
+    type Response = Response<B>;
+    type Error = Error;
+    type Future = RecycleServiceFuture<S::Future, B>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&self, req: Request) -> Self::Future {
+        if let Some(ref recycle) = self.recycle {
+            WorkerRecycle::requested(recycle);
+        }
+
+        RecycleServiceFuture {
+            fut: self.service.call(req),
+            recycle: self.recycle.clone(),
+            _body: PhantomData,
+        }
+    }
+}
+
+#[pin_project]
+struct RecycleServiceFuture<F, B> {
+    #[pin]
+    fut: F,
+    recycle: Option<Rc<WorkerRecycle>>,
+    _body: PhantomData<B>,
+}
+
+impl<F, R, E, B> Future for RecycleServiceFuture<F, B>
+where
+    F: Future<Output = Result<R, E>>,
+    R: Into<Response<B>>,
+    E: Into<Error>,
+{
+    type Output = Result<Response<B>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res: Response<B> = match ready!(this.fut.poll(cx)) {
+            Ok(res) => res.into(),
+            Err(err) => return Poll::Ready(Err(err.into())),
+        };
+
+        if let Some(ref recycle) = this.recycle {
+            if recycle.draining.get() {
+                res.head_mut().set_connection_type(ConnectionType::Close);
+            }
+        }
+
+        Poll::Ready(Ok(res))
     }
 }
 
@@ -620,8 +1604,12 @@ fn create_tcp_listener(addr: net::SocketAddr, backlog: u32) -> io::Result<net::T
 }
 
 #[cfg(feature = "openssl")]
-/// Configure `SslAcceptorBuilder` with custom server flags.
-fn openssl_acceptor(mut builder: SslAcceptorBuilder) -> io::Result<SslAcceptor> {
+/// Configure `SslAcceptorBuilder` with custom server flags and check its certificates.
+fn openssl_acceptor(mut builder: SslAcceptorBuilder) -> Result<SslAcceptor, BindError> {
+    builder.check_private_key().map_err(|err| {
+        BindError::tls(format!("private key does not match certificate: {}", err))
+    })?;
+
     builder.set_alpn_select_callback(|_, protocols| {
         const H2: &[u8] = b"\x02h2";
         const H11: &[u8] = b"\x08http/1.1";
@@ -635,7 +1623,144 @@ fn openssl_acceptor(mut builder: SslAcceptorBuilder) -> io::Result<SslAcceptor>
         }
     });
 
-    builder.set_alpn_protos(b"\x08http/1.1\x02h2")?;
+    builder
+        .set_alpn_protos(b"\x08http/1.1\x02h2")
+        .map_err(|err| BindError::tls(err.to_string()))?;
+
+    let acceptor = builder.build();
+    check_openssl_certs(&acceptor)?;
+
+    Ok(acceptor)
+}
+
+#[cfg(feature = "openssl")]
+/// Checks the order of the certificate chain and warns about expired certificates.
+fn check_openssl_certs(acceptor: &SslAcceptor) -> Result<(), BindError> {
+    use openssl::{asn1::Asn1Time, x509::X509VerifyResult};
+
+    let ctx = acceptor.context();
+
+    let leaf = match ctx.certificate() {
+        Some(leaf) => leaf,
+        None => return Ok(()),
+    };
+
+    let now = Asn1Time::days_from_now(0).map_err(|err| BindError::tls(err.to_string()))?;
+
+    let mut subject = leaf;
+    for (i, cert) in std::iter::once(leaf)
+        .chain(ctx.extra_chain_certs())
+        .enumerate()
+    {
+        if cert.not_after() < now {
+            log::warn!(
+                "TLS certificate {} of the chain expired at {}",
+                i,
+                cert.not_after()
+            );
+        }
+
+        if i > 0 {
+            if cert.issued(subject) != X509VerifyResult::OK {
+                return Err(BindError::tls(format!(
+                    "certificate chain is out of order: certificate {} did not issue certificate {}",
+                    i,
+                    i - 1
+                )));
+            }
+
+            subject = cert;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rustls")]
+/// Checks that the private key of a rustls configuration matches its certificate.
+///
+/// rustls does not give access to the configured key, so a handshake with the configuration is
+/// run in memory. The client accepts any certificate but verifies the signature made with the
+/// key. Configurations that do not complete the handshake up to that point, e.g. ones that have
+/// no certificate for the `localhost` server name, can not be checked.
+fn check_rustls_config(config: &RustlsServerConfig) -> Result<(), BindError> {
+    use tls_rustls::{
+        Certificate, ClientConfig, ClientSession, RootCertStore, ServerCertVerified,
+        ServerCertVerifier, ServerSession, Session, TLSError,
+    };
+
+    struct AnyCertificate;
+
+    impl ServerCertVerifier for AnyCertificate {
+        fn verify_server_cert(
+            &self,
+            _roots: &RootCertStore,
+            _presented_certs: &[Certificate],
+            _dns_name: webpki::DNSNameRef<'_>,
+            _ocsp: &[u8],
+        ) -> Result<ServerCertVerified, TLSError> {
+            Ok(ServerCertVerified::assertion())
+        }
+    }
+
+    let mut client_config = ClientConfig::new();
+    client_config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(AnyCertificate));
+
+    let name = webpki::DNSNameRef::try_from_ascii_str("localhost").unwrap();
+    let mut client = ClientSession::new(&Arc::new(client_config), name);
+    let mut server = ServerSession::new(&Arc::new(config.clone()));
+
+    while client.is_handshaking() {
+        let sent = transfer_tls(&mut client, &mut server)?;
+
+        if let Err(err) = server.process_new_packets() {
+            log::debug!("Can not check rustls configuration: {}", err);
+            return Ok(());
+        }
+
+        let received = transfer_tls(&mut server, &mut client)?;
+
+        client.process_new_packets().map_err(|err| match err {
+            TLSError::WebPKIError(webpki::Error::InvalidSignatureForPublicKey)
+            | TLSError::WebPKIError(webpki::Error::UnsupportedSignatureAlgorithmForPublicKey) => {
+                BindError::tls(format!("private key does not match certificate: {}", err))
+            }
+            err => BindError::tls(err.to_string()),
+        })?;
+
+        if !sent && !received {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rustls")]
+/// Moves pending TLS records from one in-memory session to the other.
+///
+/// Returns whether any records were moved.
+fn transfer_tls(
+    from: &mut dyn tls_rustls::Session,
+    to: &mut dyn tls_rustls::Session,
+) -> Result<bool, BindError> {
+    let mut buf = Vec::new();
+
+    while from.wants_write() {
+        from.write_tls(&mut buf)
+            .map_err(|err| BindError::tls(err.to_string()))?;
+    }
+
+    let mut rd = &buf[..];
+    while !rd.is_empty() {
+        match to.read_tls(&mut rd) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => return Err(BindError::tls(err.to_string())),
+        }
+    }
 
-    Ok(builder.build())
+    Ok(!buf.is_empty())
 }
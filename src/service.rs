@@ -2,7 +2,7 @@ use std::cell::{Ref, RefMut};
 use std::rc::Rc;
 use std::{fmt, net};
 
-use actix_http::body::{Body, MessageBody, ResponseBody};
+use actix_http::body::{Body, EitherBody, MessageBody, ResponseBody};
 use actix_http::http::{HeaderMap, Method, StatusCode, Uri, Version};
 use actix_http::{
     Error, Extensions, HttpMessage, Payload, PayloadStream, RequestHead, Response, ResponseHead,
@@ -13,7 +13,7 @@ use actix_service::{IntoServiceFactory, ServiceFactory};
 use crate::config::{AppConfig, AppService};
 use crate::dev::insert_slash;
 use crate::guard::Guard;
-use crate::info::ConnectionInfo;
+use crate::info::{ConnectionInfo, TrustForwardedPrefix};
 use crate::request::HttpRequest;
 use crate::rmap::ResourceMap;
 
@@ -179,7 +179,10 @@ impl ServiceRequest {
     /// Get *ConnectionInfo* for the current request.
     #[inline]
     pub fn connection_info(&self) -> Ref<'_, ConnectionInfo> {
-        ConnectionInfo::get(self.head(), &*self.app_config())
+        let trust_prefix = self
+            .app_data::<TrustForwardedPrefix>()
+            .map_or(false, |trust| trust.0);
+        ConnectionInfo::get_with_prefix(self.head(), &*self.app_config(), trust_prefix)
     }
 
     /// Get a reference to the Path parameters.
@@ -410,6 +413,22 @@ impl<B> ServiceResponse<B> {
             request: self.request,
         }
     }
+
+    /// Wraps the body in the left side of an [`EitherBody`].
+    pub fn map_into_left_body<R>(self) -> ServiceResponse<EitherBody<B, R>> {
+        self.map_body(|_, body| match body {
+            ResponseBody::Body(body) => ResponseBody::Body(EitherBody::left(body)),
+            ResponseBody::Other(body) => ResponseBody::Other(body),
+        })
+    }
+
+    /// Wraps the body in the right side of an [`EitherBody`].
+    pub fn map_into_right_body<L>(self) -> ServiceResponse<EitherBody<L, B>> {
+        self.map_body(|_, body| match body {
+            ResponseBody::Body(body) => ResponseBody::Body(EitherBody::right(body)),
+            ResponseBody::Other(body) => ResponseBody::Other(body),
+        })
+    }
 }
 
 impl<B> From<ServiceResponse<B>> for Response<B> {
@@ -0,0 +1,443 @@
+use std::{
+    any::{type_name, Any},
+    cell::RefCell,
+    collections::HashMap,
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    rc::{self, Rc},
+    sync::{
+        self,
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Poll, Waker},
+};
+
+use actix_http::{
+    error::{Error, ErrorInternalServerError},
+    Extensions,
+};
+use actix_rt::{Arbiter, ArbiterHandle};
+use futures_util::future::{err, ok, poll_fn, Ready};
+
+use crate::{data::DataFactory, dev::Payload, FromRequest, HttpRequest};
+
+static NEXT_SHARD_ID: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Shards owned by the current worker, keyed by id. Values are `rc::Weak<Shard<T>>`.
+    static SHARDS: RefCell<HashMap<usize, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Handle to the shards of a [`Sharded<T>`] registered in every worker.
+///
+/// Created once, outside of the application factory, and passed to
+/// [`App::sharded_data`](crate::App::sharded_data) so that the shards of all workers can be
+/// reached by [`Sharded::collect`].
+pub struct ShardedHandle<T> {
+    shards: Arc<Mutex<Vec<ShardRef>>>,
+    _shard: PhantomData<fn(&T)>,
+}
+
+/// Registered shard, as seen from other workers.
+#[derive(Clone)]
+struct ShardRef {
+    id: usize,
+    arbiter: ArbiterHandle,
+    alive: sync::Weak<()>,
+}
+
+impl ShardRef {
+    /// Returns false once the shard is dropped or its worker has stopped.
+    fn is_live(&self) -> bool {
+        self.alive.strong_count() > 0 && self.arbiter.alive()
+    }
+}
+
+impl<T: 'static> ShardedHandle<T> {
+    /// Creates handle with no registered shards.
+    pub fn new() -> Self {
+        ShardedHandle {
+            shards: Arc::new(Mutex::new(Vec::new())),
+            _shard: PhantomData,
+        }
+    }
+
+    /// Returns number of shards currently registered.
+    ///
+    /// Shards of recycled applications and stopped workers are not counted.
+    pub fn len(&self) -> usize {
+        self.live_shards().len()
+    }
+
+    /// Returns true if no shards are registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Prunes shards that are gone and returns the remaining ones.
+    fn live_shards(&self) -> Vec<ShardRef> {
+        let mut shards = self.shards.lock().unwrap();
+        shards.retain(ShardRef::is_live);
+        shards.clone()
+    }
+
+    /// Creates a shard and registers it with this handle.
+    ///
+    /// Must be called on the worker that will own the shard, since [`Sharded::collect`] visits
+    /// are sent to the current arbiter.
+    pub(crate) fn register(&self, value: T) -> Sharded<T> {
+        let id = NEXT_SHARD_ID.fetch_add(1, Ordering::Relaxed);
+        let alive = Arc::new(());
+
+        let shard_ref = ShardRef {
+            id,
+            arbiter: Arbiter::current(),
+            alive: Arc::downgrade(&alive),
+        };
+
+        let shard = Rc::new(Shard {
+            id,
+            value,
+            _alive: alive,
+        });
+
+        SHARDS.with(|shards| {
+            let weak: Box<dyn Any> = Box::new(Rc::downgrade(&shard));
+            shards.borrow_mut().insert(id, weak);
+        });
+
+        self.shards.lock().unwrap().push(shard_ref);
+
+        Sharded {
+            shard,
+            handle: self.clone(),
+        }
+    }
+}
+
+impl<T: 'static> Default for ShardedHandle<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for ShardedHandle<T> {
+    fn clone(&self) -> Self {
+        ShardedHandle {
+            shards: Arc::clone(&self.shards),
+            _shard: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for ShardedHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedHandle")
+            .field("type", &type_name::<T>())
+            .finish()
+    }
+}
+
+/// Worker-local application data.
+///
+/// Registered with [`App::sharded_data`](crate::App::sharded_data), which constructs one instance
+/// per worker. Handlers get shared access to their own worker's instance without any locking, so
+/// hot mutable state such as counters can use `Cell` or `RefCell` instead of a `Mutex` contended
+/// by all workers. [`Sharded::collect`] visits the instances of every worker to build a combined
+/// view, e.g. for a metrics endpoint.
+///
+/// When a worker's application is recycled, its shard is replaced with a new instance and the
+/// state of the old one is no longer visited.
+///
+/// If sharded data of type `T` is not registered, using the `Sharded<T>` extractor would cause
+/// an *Internal Server Error* response.
+///
+/// # Examples
+/// ```
+/// use std::cell::Cell;
+/// use actix_web::{web, App, HttpServer};
+///
+/// #[derive(Default)]
+/// struct Hits(Cell<u64>);
+///
+/// async fn index(hits: web::Sharded<Hits>) -> &'static str {
+///     hits.0.set(hits.0.get() + 1);
+///     "Hello!"
+/// }
+///
+/// async fn stats(hits: web::Sharded<Hits>) -> String {
+///     let total: u64 = web::Sharded::collect(hits.handle(), |total, hits: &Hits| {
+///         total + hits.0.get()
+///     })
+///     .await;
+///     format!("{} hits", total)
+/// }
+///
+/// # fn run() -> std::io::Result<()> {
+/// let hits = web::ShardedHandle::<Hits>::new();
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .sharded_data(&hits, Hits::default)
+///         .route("/", web::get().to(index))
+///         .route("/stats", web::get().to(stats))
+/// })
+/// .bind("127.0.0.1:8080")?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Sharded<T> {
+    shard: Rc<Shard<T>>,
+    handle: ShardedHandle<T>,
+}
+
+/// Worker-local instance, unregistered from the worker when the last `Sharded` is dropped.
+struct Shard<T> {
+    id: usize,
+    value: T,
+    _alive: Arc<()>,
+}
+
+impl<T> Shard<T> {
+    /// Returns the shard of the current worker with the given id, if it still exists.
+    fn get(id: usize) -> Option<Rc<Self>>
+    where
+        T: 'static,
+    {
+        SHARDS.with(|shards| {
+            shards
+                .borrow()
+                .get(&id)
+                .and_then(|weak| weak.downcast_ref::<rc::Weak<Self>>())
+                .and_then(rc::Weak::upgrade)
+        })
+    }
+}
+
+impl<T> Drop for Shard<T> {
+    fn drop(&mut self) {
+        // the thread local is already gone when dropped during thread shutdown
+        let _ = SHARDS.try_with(|shards| shards.borrow_mut().remove(&self.id));
+    }
+}
+
+/// Accumulated value of [`Sharded::collect`] handed to a worker, and back once visited.
+struct Visit<R> {
+    acc: Option<R>,
+    done: bool,
+    waker: Option<Waker>,
+}
+
+/// Marks its visit done when dropped, whether the shard was visited or not.
+struct VisitGuard<R>(Arc<Mutex<Visit<R>>>);
+
+impl<R> Drop for VisitGuard<R> {
+    fn drop(&mut self) {
+        let mut visit = self.0.lock().unwrap();
+        visit.done = true;
+
+        if let Some(waker) = visit.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T: 'static> Sharded<T> {
+    /// Returns the handle to the shards of all workers.
+    pub fn handle(&self) -> &ShardedHandle<T> {
+        &self.handle
+    }
+
+    /// Folds the shards of all workers into a single value.
+    ///
+    /// Starting with `R::default()`, `fold` is called with the value so far and each worker's
+    /// shard in turn. It runs on the thread of the worker owning the shard. Shards of workers that
+    /// have stopped are skipped.
+    pub async fn collect<R, F>(handle: &ShardedHandle<T>, fold: F) -> R
+    where
+        R: Default + Send + 'static,
+        F: Fn(R, &T) -> R + Send + Sync + 'static,
+    {
+        let fold = Arc::new(fold);
+        let mut acc = R::default();
+
+        for shard in handle.live_shards() {
+            let visit = Arc::new(Mutex::new(Visit {
+                acc: Some(acc),
+                done: false,
+                waker: None,
+            }));
+
+            let id = shard.id;
+            let fold = Arc::clone(&fold);
+            let guard = VisitGuard(Arc::clone(&visit));
+
+            // visits that are dropped unanswered leave the accumulated value untouched
+            shard.arbiter.spawn_fn(move || {
+                if let Some(shard) = Shard::<T>::get(id) {
+                    let mut visit = guard.0.lock().unwrap();
+                    let val = visit.acc.take().unwrap();
+                    visit.acc = Some(fold(val, &shard.value));
+                }
+
+                drop(guard);
+            });
+
+            acc = poll_fn(|cx| {
+                let mut visit = visit.lock().unwrap();
+
+                if visit.done {
+                    Poll::Ready(visit.acc.take().unwrap())
+                } else {
+                    visit.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+
+        acc
+    }
+}
+
+impl<T> Deref for Sharded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.shard.value
+    }
+}
+
+impl<T> Clone for Sharded<T> {
+    fn clone(&self) -> Self {
+        Sharded {
+            shard: Rc::clone(&self.shard),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sharded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Sharded").field(&self.shard.value).finish()
+    }
+}
+
+impl<T: 'static> FromRequest for Sharded<T> {
+    type Config = ();
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        if let Some(st) = req.app_data::<Sharded<T>>() {
+            ok(st.clone())
+        } else {
+            log::debug!(
+                "Failed to construct App-level Sharded extractor. \
+                 Request path: {:?} (type: {})",
+                req.path(),
+                type_name::<T>(),
+            );
+            err(ErrorInternalServerError(
+                "Sharded data is not configured, to configure use App::sharded_data()",
+            ))
+        }
+    }
+}
+
+impl<T: 'static> DataFactory for Sharded<T> {
+    fn create(&self, extensions: &mut Extensions) -> bool {
+        extensions.insert(self.clone());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{
+        http::StatusCode,
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    #[derive(Default)]
+    struct Counter(Cell<usize>);
+
+    #[actix_rt::test]
+    async fn test_sharded_extractor() {
+        let handle = ShardedHandle::<Counter>::new();
+
+        let srv = init_service(
+            App::new()
+                .sharded_data(&handle, Counter::default)
+                .route(
+                    "/",
+                    web::get().to(|counter: web::Sharded<Counter>| {
+                        counter.0.set(counter.0.get() + 1);
+                        HttpResponse::Ok()
+                    }),
+                )
+                .route(
+                    "/total",
+                    web::get().to(|counter: web::Sharded<Counter>| async move {
+                        let total: usize =
+                            Sharded::collect(counter.handle(), |total, c: &Counter| {
+                                total + c.0.get()
+                            })
+                            .await;
+                        total.to_string()
+                    }),
+                ),
+        )
+        .await;
+
+        for _ in 0..3 {
+            let req = TestRequest::default().to_request();
+            let res = call_service(&srv, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let req = TestRequest::with_uri("/total").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(read_body(res).await, "3");
+        assert_eq!(handle.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_sharded_dropped_app() {
+        let handle = ShardedHandle::<Counter>::new();
+
+        let srv1 = init_service(App::new().sharded_data(&handle, Counter::default)).await;
+        let srv2 = init_service(App::new().sharded_data(&handle, Counter::default)).await;
+        assert_eq!(handle.len(), 2);
+
+        // shards of dropped applications are neither counted nor visited
+        drop(srv1);
+        assert_eq!(handle.len(), 1);
+
+        let shards: usize = Sharded::collect(&handle, |shards, _: &Counter| shards + 1).await;
+        assert_eq!(shards, 1);
+
+        drop(srv2);
+        assert!(handle.is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_sharded_not_configured() {
+        let srv = init_service(App::new().route(
+            "/",
+            web::get().to(|_: web::Sharded<Counter>| HttpResponse::Ok()),
+        ))
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,91 @@
+//! For binary response helper, see [`Binary`].
+
+use bytes::Bytes;
+use mime::Mime;
+
+use crate::{HttpRequest, HttpResponse, Responder};
+
+/// Binary responder with a configurable content type.
+///
+/// `Bytes` and `Vec<u8>` always respond with `application/octet-stream`; wrap them in `Binary`
+/// to label the payload with a more specific type.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, Responder};
+///
+/// async fn logo() -> impl Responder {
+///     let png: Vec<u8> = load_png();
+///     web::Binary::new(png).content_type(mime::IMAGE_PNG)
+/// }
+/// # fn load_png() -> Vec<u8> { Vec::new() }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binary {
+    body: Bytes,
+    content_type: Mime,
+}
+
+impl Binary {
+    /// Creates binary responder with `application/octet-stream` content type.
+    pub fn new(body: impl Into<Bytes>) -> Self {
+        Binary {
+            body: body.into(),
+            content_type: mime::APPLICATION_OCTET_STREAM,
+        }
+    }
+
+    /// Sets content type of the response.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Unwraps into the response body.
+    pub fn into_inner(self) -> Bytes {
+        self.body
+    }
+}
+
+impl From<Bytes> for Binary {
+    fn from(body: Bytes) -> Self {
+        Binary::new(body)
+    }
+}
+
+impl From<Vec<u8>> for Binary {
+    fn from(body: Vec<u8>) -> Self {
+        Binary::new(body)
+    }
+}
+
+impl Responder for Binary {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::Ok()
+            .content_type(self.content_type)
+            .body(self.body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::header::CONTENT_TYPE, test::TestRequest};
+
+    #[actix_rt::test]
+    async fn test_binary_content_type() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Binary::new(vec![0x89, b'P', b'N', b'G'])
+            .content_type(mime::IMAGE_PNG)
+            .respond_to(&req);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "image/png");
+        assert_eq!(res.body().bin_ref(), b"\x89PNG");
+
+        let res = Binary::from(Bytes::from_static(b"raw")).respond_to(&req);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Channel-backed response body with explicit flush control.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+use futures_util::future::poll_fn;
+
+use crate::{error::Error, HttpRequest, HttpResponse, Responder};
+
+/// Creates a response body together with the writer that feeds it.
+///
+/// Bytes written to the [`BodyWriter`] are buffered until [`BodyWriter::flush`] is called, which
+/// hands them to the response as a single chunk. The flush only resolves once that chunk has been
+/// taken by the response, so data written before a flush is delivered before anything written
+/// after it. Remaining bytes are sent and the body is finished when the writer is dropped.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use actix_web::{web, Responder};
+///
+/// async fn index() -> impl Responder {
+///     let (mut writer, body) = web::body_channel();
+///
+///     actix_web::rt::spawn(async move {
+///         for _ in 0..3 {
+///             writer.write("tick\n");
+///
+///             if writer.flush().await.is_err() {
+///                 // client has gone away
+///                 return;
+///             }
+///
+///             actix_web::rt::time::sleep(Duration::from_secs(1)).await;
+///         }
+///     });
+///
+///     body
+/// }
+/// ```
+pub fn body_channel() -> (BodyWriter, ChannelBody) {
+    let inner = Rc::new(RefCell::new(Inner::default()));
+
+    let writer = BodyWriter {
+        inner: Rc::clone(&inner),
+        buf: BytesMut::new(),
+    };
+
+    (writer, ChannelBody { inner })
+}
+
+#[derive(Default)]
+struct Inner {
+    chunks: VecDeque<Bytes>,
+    writer_closed: bool,
+    body_dropped: bool,
+    body_waker: Option<Waker>,
+    flush_waker: Option<Waker>,
+}
+
+impl Inner {
+    fn wake_body(&mut self) {
+        if let Some(waker) = self.body_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Writing half of a [`body_channel`].
+pub struct BodyWriter {
+    inner: Rc<RefCell<Inner>>,
+    buf: BytesMut,
+}
+
+impl BodyWriter {
+    /// Appends data to the write buffer.
+    ///
+    /// Nothing is sent to the client until the next call to [`flush`](Self::flush) or until the
+    /// writer is dropped.
+    pub fn write(&mut self, data: impl AsRef<[u8]>) {
+        self.buf.extend_from_slice(data.as_ref());
+    }
+
+    /// Returns number of buffered bytes that have not been flushed yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Sends buffered data to the response body and waits for it to be taken by the response.
+    ///
+    /// Returns an error of kind [`BrokenPipe`](io::ErrorKind::BrokenPipe) if the response body
+    /// has been dropped, e.g. because the client disconnected.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        {
+            let mut inner = self.inner.borrow_mut();
+
+            if !self.buf.is_empty() && !inner.body_dropped {
+                inner.chunks.push_back(self.buf.split().freeze());
+                inner.wake_body();
+            }
+        }
+
+        poll_fn(|cx| {
+            let mut inner = self.inner.borrow_mut();
+
+            if inner.body_dropped {
+                Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "response body has been dropped",
+                )))
+            } else if inner.chunks.is_empty() {
+                Poll::Ready(Ok(()))
+            } else {
+                inner.flush_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Returns true if the response body has been dropped and further writes will be discarded.
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().body_dropped
+    }
+}
+
+impl Drop for BodyWriter {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+
+        if !self.buf.is_empty() {
+            inner.chunks.push_back(self.buf.split().freeze());
+        }
+
+        inner.writer_closed = true;
+        inner.wake_body();
+    }
+}
+
+/// Response body fed by a [`BodyWriter`].
+///
+/// Responds with `200 OK` and a chunked body.
+pub struct ChannelBody {
+    inner: Rc<RefCell<Inner>>,
+}
+
+impl Stream for ChannelBody {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut inner = self.inner.borrow_mut();
+
+        if let Some(chunk) = inner.chunks.pop_front() {
+            if inner.chunks.is_empty() {
+                if let Some(waker) = inner.flush_waker.take() {
+                    waker.wake();
+                }
+            }
+
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+
+        if inner.writer_closed {
+            Poll::Ready(None)
+        } else {
+            inner.body_waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for ChannelBody {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+
+        inner.body_dropped = true;
+        inner.chunks.clear();
+
+        if let Some(waker) = inner.flush_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Responder for ChannelBody {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        HttpResponse::Ok().streaming(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::{http::StatusCode, test::TestRequest};
+
+    #[actix_rt::test]
+    async fn test_flush_delivers_before_next_write() {
+        let (mut writer, mut body) = body_channel();
+        let progress = Rc::new(Cell::new(0));
+
+        let p = Rc::clone(&progress);
+        actix_rt::spawn(async move {
+            writer.write("first");
+            p.set(1);
+            writer.flush().await.unwrap();
+            p.set(2);
+            writer.write("second");
+        });
+
+        let chunk = body.next().await.unwrap().unwrap();
+        assert_eq!(chunk, Bytes::from_static(b"first"));
+        // handler is still waiting on the flush and has not written more yet
+        assert_eq!(progress.get(), 1);
+
+        let chunk = body.next().await.unwrap().unwrap();
+        assert_eq!(chunk, Bytes::from_static(b"second"));
+        assert_eq!(progress.get(), 2);
+
+        assert!(body.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_flush_after_body_dropped() {
+        let (mut writer, body) = body_channel();
+        drop(body);
+
+        writer.write("data");
+        assert!(writer.is_closed());
+
+        let err = writer.flush().await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    #[actix_rt::test]
+    async fn test_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let (mut writer, body) = body_channel();
+        writer.write("data");
+        drop(writer);
+
+        let resp = body.respond_to(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
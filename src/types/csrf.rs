@@ -0,0 +1,367 @@
+//! For CSRF token extractor documentation, see [`CsrfToken`].
+
+use std::{borrow::Cow, fmt, ops, rc::Rc};
+
+use actix_http::error::PayloadError;
+use bytes::BytesMut;
+use futures_util::{
+    future::{ready, LocalBoxFuture},
+    FutureExt as _, StreamExt as _,
+};
+
+use super::remaining_payload::RemainingPayload;
+use crate::{
+    dev::Payload, error::CsrfError, extract::FromRequest, http::header::CONTENT_ENCODING, web,
+    HttpMessage, HttpRequest,
+};
+
+/// Extract a CSRF token and verify it against the token expected for the request's session.
+///
+/// The token is read from the `X-CSRF-Token` header or, for URL encoded form submissions without
+/// the header, from the `csrf_token` form field. Reading the form field does not consume the
+/// body, so the form can still be extracted with [`Form`](super::Form) by a later argument.
+///
+/// The expected token is taken from an [`ExpectedCsrfToken`] in the request extensions, usually
+/// inserted by session middleware, or from the function set with
+/// [`CsrfConfig::expected_token`]. Tokens are compared in constant time. Requests with a missing
+/// or mismatched token, or for which no token is expected, are rejected with a `403 Forbidden`
+/// response.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, HttpResponse};
+/// use serde_derive::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Transfer {
+///     amount: u64,
+/// }
+///
+/// #[post("/transfer")]
+/// async fn transfer(_: web::CsrfToken, form: web::Form<Transfer>) -> HttpResponse {
+///     HttpResponse::Ok().body(format!("Transferred {}", form.amount))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    /// Unwrap into inner token.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl ops::Deref for CsrfToken {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromRequest for CsrfToken {
+    type Error = CsrfError;
+    type Future = LocalBoxFuture<'static, Result<Self, CsrfError>>;
+    type Config = CsrfConfig;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let config = CsrfConfig::from_req(req);
+        let expected = config.expected(req);
+
+        if let Some(token) = req.headers().get(&*config.header) {
+            let res = match token.to_str() {
+                Ok(token) => verify(token, expected),
+                Err(_) => Err(CsrfError::Mismatch),
+            };
+
+            return ready(res).boxed_local();
+        }
+
+        // compressed bodies would have to be handed on decoded, so only plain forms are read
+        if !req
+            .content_type()
+            .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+            || req.headers().contains_key(CONTENT_ENCODING)
+        {
+            return ready(Err(CsrfError::Missing)).boxed_local();
+        }
+
+        let field = config.field.clone();
+        let limit = config.limit;
+        let mut body = RemainingPayload::split(payload);
+
+        async move {
+            let mut buf = BytesMut::new();
+
+            while let Some(chunk) = body.next().await {
+                let chunk = chunk.map_err(CsrfError::Payload)?;
+
+                if buf.len() + chunk.len() > limit {
+                    buf.extend_from_slice(&chunk);
+                    body.unread(buf.freeze());
+                    return Err(CsrfError::Payload(PayloadError::Overflow));
+                }
+
+                buf.extend_from_slice(&chunk);
+            }
+
+            let buf = buf.freeze();
+            body.unread(buf.clone());
+            drop(body);
+
+            let token = url::form_urlencoded::parse(&buf)
+                .find(|(key, _)| *key == field)
+                .map(|(_, token)| token)
+                .ok_or(CsrfError::Missing)?;
+
+            verify(&token, expected)
+        }
+        .boxed_local()
+    }
+}
+
+/// Token expected for the current session, inserted into the request extensions.
+///
+/// ```
+/// use actix_web::{web, HttpRequest};
+///
+/// fn start_session(req: &HttpRequest, token: String) {
+///     req.extensions_mut().insert(web::ExpectedCsrfToken(token));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedCsrfToken(pub String);
+
+fn verify(token: &str, expected: Option<String>) -> Result<CsrfToken, CsrfError> {
+    match expected {
+        Some(expected) if !expected.is_empty() && constant_time_eq(token, &expected) => {
+            Ok(CsrfToken(token.to_owned()))
+        }
+        _ => Err(CsrfError::Mismatch),
+    }
+}
+
+/// Compares tokens in time independent of where they differ, leaking only their lengths.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Configuration for the [`CsrfToken`] extractor.
+///
+/// ```
+/// use actix_web::{web, App};
+///
+/// #[derive(Clone)]
+/// struct Session(String);
+///
+/// App::new().app_data(
+///     web::CsrfConfig::default()
+///         .header("X-XSRF-Token")
+///         .field("_token")
+///         .expected_token(|req| req.extensions().get::<Session>().map(|s| s.0.clone())),
+/// );
+/// ```
+#[derive(Clone)]
+pub struct CsrfConfig {
+    header: Cow<'static, str>,
+    field: Cow<'static, str>,
+    limit: usize,
+    expected: Option<Rc<dyn Fn(&HttpRequest) -> Option<String>>>,
+}
+
+impl CsrfConfig {
+    /// Set name of the header the token is read from. The default is `X-CSRF-Token`.
+    pub fn header(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.header = name.into();
+        self
+    }
+
+    /// Set name of the form field the token is read from. The default is `csrf_token`.
+    pub fn field(mut self, name: impl Into<Cow<'static, str>>) -> Self {
+        self.field = name.into();
+        self
+    }
+
+    /// Set maximum size of form bodies searched for the token. The default limit is 32kB.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Set function returning the token expected for a request, instead of reading it from an
+    /// [`ExpectedCsrfToken`] in the request extensions.
+    pub fn expected_token<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HttpRequest) -> Option<String> + 'static,
+    {
+        self.expected = Some(Rc::new(f));
+        self
+    }
+
+    fn expected(&self, req: &HttpRequest) -> Option<String> {
+        match self.expected {
+            Some(ref expected) => expected(req),
+            None => req
+                .extensions()
+                .get::<ExpectedCsrfToken>()
+                .map(|token| token.0.clone()),
+        }
+    }
+
+    /// Extract CSRF config from app data. Check both `T` and `Data<T>`, in that order, and fall
+    /// back to the default CSRF config.
+    fn from_req(req: &HttpRequest) -> &Self {
+        req.app_data::<Self>()
+            .or_else(|| req.app_data::<web::Data<Self>>().map(|d| d.as_ref()))
+            .unwrap_or(&DEFAULT_CONFIG)
+    }
+}
+
+impl fmt::Debug for CsrfConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CsrfConfig")
+            .field("header", &self.header)
+            .field("field", &self.field)
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+/// Allow shared refs used as default.
+const DEFAULT_CONFIG: CsrfConfig = CsrfConfig {
+    header: Cow::Borrowed("x-csrf-token"),
+    field: Cow::Borrowed("csrf_token"),
+    limit: 32_768,
+    expected: None,
+};
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        DEFAULT_CONFIG.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use serde_derive::Deserialize;
+
+    use super::*;
+    use crate::{
+        http::{header::CONTENT_TYPE, StatusCode},
+        test::TestRequest,
+        HttpResponse, ResponseError,
+    };
+
+    async fn extract(req: TestRequest) -> Result<CsrfToken, CsrfError> {
+        let (req, mut pl) = req.to_http_parts();
+        req.extensions_mut()
+            .insert(ExpectedCsrfToken("s3cr3t".to_owned()));
+        CsrfToken::from_request(&req, &mut pl).await
+    }
+
+    #[actix_rt::test]
+    async fn test_valid_token() {
+        let token = extract(TestRequest::default().insert_header(("x-csrf-token", "s3cr3t")))
+            .await
+            .unwrap();
+        assert_eq!(token.into_inner(), "s3cr3t");
+
+        let token = extract(
+            TestRequest::default()
+                .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+                .set_payload(Bytes::from_static(b"amount=10&csrf_token=s3cr3t")),
+        )
+        .await
+        .unwrap();
+        assert_eq!(&*token, "s3cr3t");
+    }
+
+    #[actix_rt::test]
+    async fn test_mismatched_token() {
+        let err = extract(TestRequest::default().insert_header(("x-csrf-token", "s3cr3x")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CsrfError::Mismatch));
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+        let err = extract(TestRequest::default().insert_header(("x-csrf-token", "s3cr3t!")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CsrfError::Mismatch));
+
+        // no token expected for the request
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("x-csrf-token", ""))
+            .to_http_parts();
+        let err = CsrfToken::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, CsrfError::Mismatch));
+    }
+
+    #[actix_rt::test]
+    async fn test_missing_token() {
+        let err = extract(TestRequest::default()).await.unwrap_err();
+        assert!(matches!(err, CsrfError::Missing));
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+
+        let err = extract(
+            TestRequest::default()
+                .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+                .set_payload(Bytes::from_static(b"amount=10")),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, CsrfError::Missing));
+
+        let err = extract(
+            TestRequest::default()
+                .app_data(CsrfConfig::default().limit(8))
+                .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+                .set_payload(Bytes::from_static(b"amount=10&csrf_token=s3cr3t")),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, CsrfError::Payload(PayloadError::Overflow)));
+        assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[actix_rt::test]
+    async fn test_form_after_token() {
+        #[derive(Deserialize)]
+        struct Transfer {
+            amount: u64,
+        }
+
+        let srv = crate::test::init_service(
+            crate::App::new()
+                .app_data(CsrfConfig::default().expected_token(|_| Some("s3cr3t".to_owned())))
+                .route(
+                    "/",
+                    web::post().to(|_: CsrfToken, form: web::Form<Transfer>| async move {
+                        HttpResponse::Ok().body(form.amount.to_string())
+                    }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"amount=10&csrf_token=s3cr3t"))
+            .to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(crate::test::read_body(res).await, Bytes::from_static(b"10"));
+
+        let req = TestRequest::post()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(Bytes::from_static(b"amount=10&csrf_token=guess"))
+            .to_request();
+        let res = crate::test::call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -144,6 +144,13 @@ where
             Either::Right(b) => b.respond_to(req),
         }
     }
+
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+        match self {
+            Either::Left(a) => a.respond_to_owned(req),
+            Either::Right(b) => b.respond_to_owned(req),
+        }
+    }
 }
 
 /// A composite error resulting from failure to extract an `Either<L, R>`.
@@ -217,7 +224,153 @@ where
     }
 }
 
-fn payload_from_bytes(bytes: Bytes) -> dev::Payload {
+/// Combines three extractor or responder types into a single type.
+///
+/// Works like [`Either`] with a third branch, avoiding nested `Either` types for handlers that
+/// accept or return three representations.
+///
+/// # Extractor
+/// Tries the extractors in order: `A`, then `B`, then `C`. Like [`Either`], the entire request
+/// payload is buffered, respecting any `PayloadConfig` maximum size limits.
+///
+/// ```
+/// use actix_web::{post, web, Either3};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// // handler that accepts a name as JSON, form-urlencoded or plain bytes.
+/// #[post("/")]
+/// async fn index(payload: Either3<web::Json<Info>, web::Form<Info>, web::Bytes>) -> String {
+///     let name = match payload {
+///         Either3::A(json) => json.into_inner().name,
+///         Either3::B(form) => form.into_inner().name,
+///         Either3::C(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+///     };
+///
+///     format!("Welcome {}!", name)
+/// }
+/// ```
+///
+/// # Responder
+/// As long as all three types implement `Responder`, so will the `Either3` type. All properties of
+/// a response are determined by the Responder branch returned.
+#[derive(Debug, PartialEq)]
+pub enum Either3<A, B, C> {
+    /// A value of type `A`.
+    A(A),
+
+    /// A value of type `B`.
+    B(B),
+
+    /// A value of type `C`.
+    C(C),
+}
+
+/// See [here](#responder) for usage as a handler return type.
+impl<A, B, C> Responder for Either3<A, B, C>
+where
+    A: Responder,
+    B: Responder,
+    C: Responder,
+{
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        match self {
+            Either3::A(a) => a.respond_to(req),
+            Either3::B(b) => b.respond_to(req),
+            Either3::C(c) => c.respond_to(req),
+        }
+    }
+
+    fn respond_to_owned(self, req: HttpRequest) -> HttpResponse {
+        match self {
+            Either3::A(a) => a.respond_to_owned(req),
+            Either3::B(b) => b.respond_to_owned(req),
+            Either3::C(c) => c.respond_to_owned(req),
+        }
+    }
+}
+
+/// A composite error resulting from failure to extract an `Either3<A, B, C>`.
+///
+/// The implementation of `Into<actix_web::Error>` will return the payload buffering error or the
+/// error from the first extractor. To access the other errors, use a match clause.
+#[derive(Debug)]
+pub enum Either3ExtractError<A, B, C> {
+    /// Error from payload buffering, such as exceeding payload max size limit.
+    Bytes(Error),
+
+    /// Errors from all three extractors, in the order they were tried.
+    Extract(A, B, C),
+}
+
+impl<A, B, C> From<Either3ExtractError<A, B, C>> for Error
+where
+    A: Into<Error>,
+    B: Into<Error>,
+    C: Into<Error>,
+{
+    fn from(err: Either3ExtractError<A, B, C>) -> Error {
+        match err {
+            Either3ExtractError::Bytes(err) => err,
+            Either3ExtractError::Extract(a_err, _b_err, _c_err) => a_err.into(),
+        }
+    }
+}
+
+/// See [here](#extractor) for usage as an extractor.
+impl<A, B, C> FromRequest for Either3<A, B, C>
+where
+    A: FromRequest + 'static,
+    B: FromRequest + 'static,
+    C: FromRequest + 'static,
+{
+    type Error = Either3ExtractError<A::Error, B::Error, C::Error>;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let req2 = req.clone();
+
+        Bytes::from_request(req, payload)
+            .map_err(Either3ExtractError::Bytes)
+            .and_then(|bytes| bytes_to_a_b_or_c(req2, bytes))
+            .boxed_local()
+    }
+}
+
+async fn bytes_to_a_b_or_c<A, B, C>(
+    req: HttpRequest,
+    bytes: Bytes,
+) -> Result<Either3<A, B, C>, Either3ExtractError<A::Error, B::Error, C::Error>>
+where
+    A: FromRequest + 'static,
+    B: FromRequest + 'static,
+    C: FromRequest + 'static,
+{
+    let mut pl = payload_from_bytes(bytes.clone());
+    let a_err = match A::from_request(&req, &mut pl).await {
+        Ok(a_data) => return Ok(Either3::A(a_data)),
+        Err(err) => err,
+    };
+
+    let mut pl = payload_from_bytes(bytes.clone());
+    let b_err = match B::from_request(&req, &mut pl).await {
+        Ok(b_data) => return Ok(Either3::B(b_data)),
+        Err(err) => err,
+    };
+
+    let mut pl = payload_from_bytes(bytes);
+    match C::from_request(&req, &mut pl).await {
+        Ok(c_data) => Ok(Either3::C(c_data)),
+        Err(c_err) => Err(Either3ExtractError::Extract(a_err, b_err, c_err)),
+    }
+}
+
+pub(super) fn payload_from_bytes(bytes: Bytes) -> dev::Payload {
     let (_, mut h1_payload) = actix_http::h1::Payload::create(true);
     h1_payload.unread_data(bytes);
     dev::Payload::from(h1_payload)
@@ -303,4 +456,51 @@ mod tests {
         .into_inner();
         assert_eq!(&form.hello, "world");
     }
+
+    #[actix_rt::test]
+    async fn test_either3_extract() {
+        type Payload = Either3<Form<TestForm>, Json<TestForm>, Bytes>;
+
+        let (req, mut pl) = TestRequest::default()
+            .set_form(&TestForm {
+                hello: "world".to_owned(),
+            })
+            .to_http_parts();
+        match Payload::from_request(&req, &mut pl).await.unwrap() {
+            Either3::A(form) => assert_eq!(&form.hello, "world"),
+            _ => panic!("expected form variant"),
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .set_json(&TestForm {
+                hello: "world".to_owned(),
+            })
+            .to_http_parts();
+        match Payload::from_request(&req, &mut pl).await.unwrap() {
+            Either3::B(json) => assert_eq!(&json.hello, "world"),
+            _ => panic!("expected json variant"),
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(b"!@$%^&*()"))
+            .to_http_parts();
+        match Payload::from_request(&req, &mut pl).await.unwrap() {
+            Either3::C(bytes) => assert_eq!(&bytes[..], b"!@$%^&*()"),
+            _ => panic!("expected bytes variant"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_either3_extract_errors() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(b"!@$%^&*()"))
+            .to_http_parts();
+
+        let err = Either3::<Form<TestForm>, Json<TestForm>, Json<TestForm>>::from_request(
+            &req, &mut pl,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Either3ExtractError::Extract(_, _, _)));
+    }
 }
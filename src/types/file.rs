@@ -0,0 +1,251 @@
+//! For file serving responder, see the [`Responder`] implementation for [`PathBuf`].
+
+#[cfg(feature = "files")]
+use std::path::PathBuf;
+use std::{
+    cmp, fmt,
+    fs::File,
+    future::Future,
+    io::{self, Read, Seek},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+
+#[cfg(feature = "files")]
+use crate::{error::ErrorNotFound, HttpRequest, HttpResponse, Responder};
+use crate::{
+    error::{BlockingError, Error},
+    rt::task::{spawn_blocking, JoinHandle},
+};
+
+/// Serves the file at this path.
+///
+/// The content type is guessed from the file extension. The body is streamed without a
+/// `Content-Length` header: the file is opened on the blocking thread pool when the body is first
+/// polled, and its contents are read in chunks there.
+///
+/// Since the response head is sent before the file is opened, a missing file cannot change the
+/// status. The body then fails with a `404 Not Found` error and the connection is closed; check
+/// that the file exists in the handler to respond with `404 Not Found` instead.
+///
+/// Exactly the given path is served; it is not normalized or checked against any root
+/// directory, so it must not be built from untrusted input without validation.
+///
+/// # Examples
+/// ```
+/// use std::path::PathBuf;
+///
+/// async fn favicon() -> PathBuf {
+///     PathBuf::from("static/favicon.ico")
+/// }
+/// ```
+#[cfg(feature = "files")]
+impl Responder for PathBuf {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        let mime = mime_guess::from_path(&self).first_or_octet_stream();
+
+        HttpResponse::Ok()
+            .content_type(mime)
+            .streaming(FileStream::Path(Some(self)))
+    }
+}
+
+/// Body of the response to a [`PathBuf`], opening the file when first polled.
+#[cfg(feature = "files")]
+enum FileStream {
+    Path(Option<PathBuf>),
+    Opening(JoinHandle<Result<(File, u64), io::Error>>),
+    Reading(ChunkedReadFile),
+}
+
+#[cfg(feature = "files")]
+impl Stream for FileStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+        match this {
+            FileStream::Path(path) => {
+                let path = path.take().expect("FileStream polled after completion");
+
+                let fut = spawn_blocking(move || {
+                    let file = File::open(&path)?;
+                    let md = file.metadata()?;
+
+                    if !md.is_file() {
+                        return Err(io::ErrorKind::NotFound.into());
+                    }
+
+                    Ok((file, md.len()))
+                });
+                *this = FileStream::Opening(fut);
+                self.poll_next(cx)
+            }
+            FileStream::Opening(fut) => {
+                match ready!(Pin::new(fut).poll(cx)).map_err(|_| BlockingError::Gone)? {
+                    Ok((file, size)) => {
+                        *this = FileStream::Reading(ChunkedReadFile::new(size, 0, file));
+                        self.poll_next(cx)
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                        Poll::Ready(Some(Err(ErrorNotFound(err))))
+                    }
+                    Err(err) => Poll::Ready(Some(Err(err.into()))),
+                }
+            }
+            FileStream::Reading(file) => Pin::new(file).poll_next(cx),
+        }
+    }
+}
+
+#[doc(hidden)]
+/// A helper created from a `std::fs::File` which reads the file
+/// chunk-by-chunk on a `ThreadPool`.
+pub struct ChunkedReadFile {
+    size: u64,
+    offset: u64,
+    state: ChunkedReadFileState,
+    counter: u64,
+}
+
+enum ChunkedReadFileState {
+    File(Option<File>),
+    Future(JoinHandle<Result<(File, Bytes), io::Error>>),
+}
+
+impl ChunkedReadFile {
+    /// Reads `size` bytes of `file`, starting at `offset`.
+    pub fn new(size: u64, offset: u64, file: File) -> Self {
+        Self {
+            size,
+            offset,
+            state: ChunkedReadFileState::File(Some(file)),
+            counter: 0,
+        }
+    }
+}
+
+impl fmt::Debug for ChunkedReadFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ChunkedReadFile")
+    }
+}
+
+impl Stream for ChunkedReadFile {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.as_mut().get_mut();
+        match this.state {
+            ChunkedReadFileState::File(ref mut file) => {
+                let size = this.size;
+                let offset = this.offset;
+                let counter = this.counter;
+
+                if size == counter {
+                    Poll::Ready(None)
+                } else {
+                    let mut file = file
+                        .take()
+                        .expect("ChunkedReadFile polled after completion");
+
+                    let fut = spawn_blocking(move || {
+                        let max_bytes = cmp::min(size.saturating_sub(counter), 65_536) as usize;
+
+                        let mut buf = Vec::with_capacity(max_bytes);
+                        file.seek(io::SeekFrom::Start(offset))?;
+
+                        let n_bytes =
+                            file.by_ref().take(max_bytes as u64).read_to_end(&mut buf)?;
+
+                        if n_bytes == 0 {
+                            return Err(io::ErrorKind::UnexpectedEof.into());
+                        }
+
+                        Ok((file, Bytes::from(buf)))
+                    });
+                    this.state = ChunkedReadFileState::Future(fut);
+                    self.poll_next(cx)
+                }
+            }
+            ChunkedReadFileState::Future(ref mut fut) => {
+                let (file, bytes) =
+                    ready!(Pin::new(fut).poll(cx)).map_err(|_| BlockingError::Gone)??;
+                this.state = ChunkedReadFileState::File(Some(file));
+
+                this.offset += bytes.len() as u64;
+                this.counter += bytes.len() as u64;
+
+                Poll::Ready(Some(Ok(bytes)))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "files"))]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::{
+        http::{header, StatusCode},
+        test::{call_service, init_service, load_stream, read_body, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn test_path_buf_responder() {
+        let path = env::temp_dir().join(format!("actix-web-{}.txt", process::id()));
+        fs::write(&path, "file contents").unwrap();
+
+        let served = path.clone();
+        let custom = path.clone();
+        let srv = init_service(
+            App::new()
+                .route(
+                    "/",
+                    web::get().to(move || futures_util::future::ready(served.clone())),
+                )
+                .route(
+                    "/custom",
+                    web::get().to(move || {
+                        futures_util::future::ready(
+                            custom.clone().with_header(("x-served", "file")),
+                        )
+                    }),
+                )
+                .route(
+                    "/missing",
+                    web::get().to(|| async { PathBuf::from("/definitely/missing.txt") }),
+                ),
+        )
+        .await;
+
+        let req = TestRequest::default().to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/plain"
+        );
+        assert_eq!(read_body(res).await, "file contents");
+
+        let req = TestRequest::with_uri("/custom").to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get("x-served").unwrap(), "file");
+        assert_eq!(read_body(res).await, "file contents");
+
+        // the head is built before the file is opened, so only the body reports it missing
+        let req = TestRequest::with_uri("/missing").to_request();
+        let mut res = call_service(&srv, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let err = load_stream(res.take_body()).await.unwrap_err();
+        assert_eq!(err.as_response_error().status_code(), StatusCode::NOT_FOUND);
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+//! For first-of extractor documentation, see [`FirstOf`].
+
+use bytes::Bytes;
+use futures_util::{future::LocalBoxFuture, FutureExt, TryFutureExt};
+
+use super::either::payload_from_bytes;
+use crate::{dev, Error, FromRequest, HttpRequest};
+
+/// Extractor that tries a list of extractors in order and keeps the first one that succeeds.
+///
+/// Generalizes nested [`Either`](crate::Either) extractors to any number of candidates. `T` is a
+/// tuple of options of up to eight extractors, usually spelled with the [`web::FirstOf!`] macro.
+/// Only the option of the successful candidate is `Some`.
+///
+/// The request payload is buffered once, respecting any `PayloadConfig` maximum size limits, and
+/// each candidate is given a fresh copy of it. If every candidate fails, the error contains the
+/// errors of all of them; see [`FirstOfExtractError`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Info {
+///     name: String,
+/// }
+///
+/// // handler that accepts a name as JSON, form-urlencoded, plain text or raw bytes
+/// #[post("/")]
+/// async fn index(
+///     payload: web::FirstOf!(web::Json<Info>, web::Form<Info>, String, web::Bytes),
+/// ) -> String {
+///     let name = match payload.into_inner() {
+///         (Some(json), ..) => json.into_inner().name,
+///         (_, Some(form), ..) => form.into_inner().name,
+///         (_, _, Some(text), _) => text,
+///         (_, _, _, Some(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+///         _ => unreachable!(),
+///     };
+///
+///     format!("Welcome {}!", name)
+/// }
+/// ```
+///
+/// [`web::FirstOf!`]: crate::web::FirstOf!
+#[derive(Debug, PartialEq)]
+pub struct FirstOf<T>(pub T);
+
+impl<T> FirstOf<T> {
+    /// Unwrap into inner tuple of options.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// A composite error resulting from failure to extract a [`FirstOf`].
+///
+/// The implementation of `Into<actix_web::Error>` will return the payload buffering error or the
+/// error from the first extractor. To access the other errors, use a match clause.
+#[derive(Debug)]
+pub enum FirstOfExtractError<E> {
+    /// Error from payload buffering, such as exceeding payload max size limit.
+    Bytes(Error),
+
+    /// Tuple of errors from all extractors, in the order they were tried.
+    Extract(E),
+}
+
+/// Spells the [`FirstOf`] extractor type for a list of candidate extractors.
+///
+/// `FirstOf!(A, B, C)` is shorthand for `FirstOf<(Option<A>, Option<B>, Option<C>)>`. The max
+/// number of extractors is 8.
+///
+/// # Examples
+/// ```
+/// use actix_web::web;
+///
+/// async fn index(body: web::FirstOf!(web::Json<String>, String, web::Bytes)) -> &'static str {
+///     match body.into_inner() {
+///         (Some(_), ..) => "json",
+///         (_, Some(_), _) => "text",
+///         _ => "bytes",
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! FirstOf {
+    ($($T:ty),+ $(,)?) => {
+        $crate::web::FirstOf<($(::std::option::Option<$T>,)+)>
+    };
+}
+
+/// FromRequest implementation for tuples of candidate extractors.
+macro_rules! first_of_tuple ({ $(($n:tt, $T:ident)),+ } => {
+    impl<$($T),+> FromRequest for FirstOf<($(Option<$T>,)+)>
+    where
+        $($T: FromRequest + 'static),+
+    {
+        type Error = FirstOfExtractError<($($T::Error,)+)>;
+        type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+        type Config = ();
+
+        fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+            let req2 = req.clone();
+
+            Bytes::from_request(req, payload)
+                .map_err(FirstOfExtractError::Bytes)
+                .and_then(|bytes| async move {
+                    let mut found: ($(Option<$T>,)+) = Default::default();
+                    let mut errors: ($(Option<$T::Error>,)+) = Default::default();
+
+                    $(
+                        let mut pl = payload_from_bytes(bytes.clone());
+                        match $T::from_request(&req2, &mut pl).await {
+                            Ok(item) => {
+                                found.$n = Some(item);
+                                return Ok(FirstOf(found));
+                            }
+                            Err(err) => errors.$n = Some(err),
+                        }
+                    )+
+
+                    Err(FirstOfExtractError::Extract(($(errors.$n.unwrap(),)+)))
+                })
+                .boxed_local()
+        }
+    }
+
+    impl<$($T),+> From<FirstOfExtractError<($($T,)+)>> for Error
+    where
+        $($T: Into<Error>),+
+    {
+        fn from(err: FirstOfExtractError<($($T,)+)>) -> Error {
+            match err {
+                FirstOfExtractError::Bytes(err) => err,
+                FirstOfExtractError::Extract(errors) => errors.0.into(),
+            }
+        }
+    }
+});
+
+#[rustfmt::skip]
+mod m {
+    use super::*;
+
+    first_of_tuple!((0, A), (1, B));
+    first_of_tuple!((0, A), (1, B), (2, C));
+    first_of_tuple!((0, A), (1, B), (2, C), (3, D));
+    first_of_tuple!((0, A), (1, B), (2, C), (3, D), (4, E));
+    first_of_tuple!((0, A), (1, B), (2, C), (3, D), (4, E), (5, F));
+    first_of_tuple!((0, A), (1, B), (2, C), (3, D), (4, E), (5, F), (6, G));
+    first_of_tuple!((0, A), (1, B), (2, C), (3, D), (4, E), (5, F), (6, G), (7, H));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::{
+        http::header::CONTENT_TYPE,
+        test::TestRequest,
+        web::{self, Form, Json},
+    };
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct TestForm {
+        hello: String,
+    }
+
+    #[actix_rt::test]
+    async fn test_first_of_third_succeeds() {
+        type Payload = web::FirstOf!(Form<TestForm>, Json<TestForm>, String, Bytes);
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        match Payload::from_request(&req, &mut pl)
+            .await
+            .unwrap()
+            .into_inner()
+        {
+            (None, None, Some(text), None) => assert_eq!(text, "hello world"),
+            res => panic!("expected text candidate, got {:?}", res),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_first_of_errors() {
+        type Payload = web::FirstOf!(
+            Form<TestForm>,
+            Json<TestForm>,
+            Json<TestForm>,
+            Form<TestForm>
+        );
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "text/plain"))
+            .set_payload(Bytes::from_static(b"hello world"))
+            .to_http_parts();
+
+        let err = Payload::from_request(&req, &mut pl).await.unwrap_err();
+        assert!(matches!(err, FirstOfExtractError::Extract((_, _, _, _))));
+    }
+}
@@ -1,6 +1,8 @@
 //! For URL encoded form helper documentation, see [`Form`].
 
 use std::{
+    borrow::Cow,
+    collections::HashMap,
     fmt,
     future::Future,
     ops,
@@ -10,19 +12,27 @@ use std::{
 };
 
 use actix_http::Payload;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use encoding_rs::{Encoding, UTF_8};
 use futures_util::{
     future::{FutureExt, LocalBoxFuture},
     StreamExt,
 };
+use percent_encoding::percent_decode;
 use serde::{de::DeserializeOwned, Serialize};
+use url::form_urlencoded;
 
 #[cfg(feature = "compress")]
 use crate::dev::Decompress;
 use crate::{
-    error::UrlencodedError, extract::FromRequest, http::header::CONTENT_LENGTH, web, Error,
-    HttpMessage, HttpRequest, HttpResponse, Responder,
+    error::UrlencodedError,
+    extract::{ExtractionReporter, FromRequest},
+    http::header::{CONTENT_ENCODING, CONTENT_LENGTH},
+    types::{
+        lenient::{from_urlencoded, LenientEnums},
+        payload::{payload_missing, BudgetReservation},
+    },
+    web, Error, HttpMessage, HttpRequest, HttpResponse, Responder,
 };
 
 /// URL encoded payload extractor and responder.
@@ -35,6 +45,11 @@ use crate::{
 ///
 /// Use [`FormConfig`] to configure extraction process.
 ///
+/// Requests without a body, including those with `Content-Length: 0`, are parsed as an empty form.
+/// Requests announcing a body that is not available, such as those built with
+/// `TestRequest::to_http_request` or extracted with [`FromRequest::extract`], fail with
+/// [`UrlencodedError::NoPayload`].
+///
 /// ```
 /// use actix_web::{post, web};
 /// use serde::Deserialize;
@@ -118,23 +133,43 @@ where
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
         let req2 = req.clone();
-        let (limit, err_handler) = req
+        let config = req
             .app_data::<Self::Config>()
             .or_else(|| {
                 req.app_data::<web::Data<Self::Config>>()
                     .map(|d| d.as_ref())
             })
-            .map(|c| (c.limit, c.err_handler.clone()))
-            .unwrap_or((16384, None));
+            .cloned()
+            .unwrap_or_default();
+        let err_handler = config.err_handler;
+
+        let reporter = ExtractionReporter::from_req(req);
 
         UrlEncoded::new(req, payload)
-            .limit(limit)
+            .limit(config.limit)
+            .strict_length(config.strict_length)
+            .lenient_enums(config.lenient_enums)
+            .max_key_len(config.max_key_len)
+            .max_value_len(config.max_value_len)
+            .report_unknown_fields(reporter.clone())
             .map(move |res| match res {
-                Err(err) => match err_handler {
-                    Some(err_handler) => Err((err_handler)(err, &req2)),
-                    None => Err(err.into()),
-                },
-                Ok(item) => Ok(Form(item)),
+                Err(err) => {
+                    if let Some(reporter) = reporter {
+                        reporter.failure::<Self>(err.kind());
+                    }
+
+                    match err_handler {
+                        Some(err_handler) => Err((err_handler)(err, &req2)),
+                        None => Err(err.into()),
+                    }
+                }
+                Ok(item) => {
+                    if let Some(reporter) = reporter {
+                        reporter.success::<Self>();
+                    }
+
+                    Ok(Form(item))
+                }
             })
             .boxed_local()
     }
@@ -164,6 +199,16 @@ impl<T: Serialize> Responder for Form<T> {
     }
 }
 
+/// Creates response with OK status code, URL encoded form content type header, and serialized
+/// map as payload.
+///
+/// Shorthand for returning `web::Form(map)`; serialization errors are handled the same way.
+impl<K: Serialize, V: Serialize> Responder for HashMap<K, V> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        Form(self).respond_to(req)
+    }
+}
+
 /// [`Form`] extractor configuration.
 ///
 /// ```
@@ -189,16 +234,65 @@ impl<T: Serialize> Responder for Form<T> {
 #[derive(Clone)]
 pub struct FormConfig {
     limit: usize,
+    strict_length: bool,
+    lenient_enums: bool,
+    max_key_len: Option<usize>,
+    max_value_len: Option<usize>,
     err_handler: Option<Rc<dyn Fn(UrlencodedError, &HttpRequest) -> Error>>,
 }
 
 impl FormConfig {
     /// Set maximum accepted payload size. By default this limit is 16kB.
+    ///
+    /// For compressed payloads the limit applies to the inflated size and is enforced while
+    /// inflating. The inflated form is still buffered in full before it is deserialized.
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
 
+    /// Reject payloads that contain more bytes than declared in the `Content-Length` header.
+    ///
+    /// A form followed by stray bytes may otherwise parse successfully, hiding a framing bug in
+    /// the client. In strict mode such payloads fail with [`UrlencodedError::Parse`]. Payloads
+    /// with a `Content-Encoding` are not checked, since their declared length is not the length of
+    /// the decoded form. Disabled by default.
+    pub fn strict_length(mut self, strict: bool) -> Self {
+        self.strict_length = strict;
+        self
+    }
+
+    /// Match enum variants case-insensitively.
+    ///
+    /// Unit variants and their `#[serde(alias)]` names are matched ignoring ASCII case and
+    /// deserialized as the variant they name, so `?color=RED` is accepted for a `Red` variant.
+    /// Input that matches several variants, such as `up` for an enum with both `Up` and `UP`, fails
+    /// with [`UrlencodedError::Deserialize`] naming the variants. Fields that are not enums are
+    /// unaffected. Disabled by default.
+    pub fn lenient_enums(mut self, lenient: bool) -> Self {
+        self.lenient_enums = lenient;
+        self
+    }
+
+    /// Set maximum length of each field's key, in bytes after URL decoding.
+    ///
+    /// Forms with a longer key fail with [`UrlencodedError::KeyOverflow`]. Unlimited by default,
+    /// in which case keys are only bounded by the payload [limit](Self::limit).
+    pub fn max_key_len(mut self, max_len: usize) -> Self {
+        self.max_key_len = Some(max_len);
+        self
+    }
+
+    /// Set maximum length of each field's value, in bytes after URL decoding.
+    ///
+    /// Forms with a longer value fail with [`UrlencodedError::ValueOverflow`], which names the
+    /// field. Useful with a large payload [limit](Self::limit) to keep any single value from
+    /// taking it all up. Unlimited by default.
+    pub fn max_value_len(mut self, max_len: usize) -> Self {
+        self.max_value_len = Some(max_len);
+        self
+    }
+
     /// Set custom error handler
     pub fn error_handler<F>(mut self, f: F) -> Self
     where
@@ -213,6 +307,10 @@ impl Default for FormConfig {
     fn default() -> Self {
         FormConfig {
             limit: 16_384, // 2^14 bytes (~16kB)
+            strict_length: false,
+            lenient_enums: false,
+            max_key_len: None,
+            max_value_len: None,
             err_handler: None,
         }
     }
@@ -233,9 +331,15 @@ pub struct UrlEncoded<T> {
 
     limit: usize,
     length: Option<usize>,
+    strict_length: Option<usize>,
+    lenient_enums: bool,
+    max_key_len: Option<usize>,
+    max_value_len: Option<usize>,
     encoding: &'static Encoding,
     err: Option<UrlencodedError>,
+    budget: Option<BudgetReservation>,
     fut: Option<LocalBoxFuture<'static, Result<T, UrlencodedError>>>,
+    reporter: Option<ExtractionReporter>,
 }
 
 #[allow(clippy::borrow_interior_mutable_const)]
@@ -251,6 +355,10 @@ impl<T> UrlEncoded<T> {
             Err(_) => return Self::err(UrlencodedError::ContentType),
         };
 
+        if payload_missing(req, payload) {
+            return Self::err(UrlencodedError::NoPayload);
+        }
+
         let mut len = None;
         if let Some(l) = req.headers().get(&CONTENT_LENGTH) {
             if let Ok(s) = l.to_str() {
@@ -264,6 +372,9 @@ impl<T> UrlEncoded<T> {
             }
         };
 
+        // the declared length only describes the form itself when the payload is not encoded
+        let strict_length = len.filter(|_| !req.headers().contains_key(&CONTENT_ENCODING));
+
         #[cfg(feature = "compress")]
         let payload = Decompress::from_headers(payload.take(), req.headers());
         #[cfg(not(feature = "compress"))]
@@ -274,8 +385,14 @@ impl<T> UrlEncoded<T> {
             stream: Some(payload),
             limit: 32_768,
             length: len,
+            strict_length,
+            lenient_enums: false,
+            max_key_len: None,
+            max_value_len: None,
             fut: None,
             err: None,
+            budget: Some(BudgetReservation::new(req)),
+            reporter: None,
         }
     }
 
@@ -285,16 +402,67 @@ impl<T> UrlEncoded<T> {
             limit: 32_768,
             fut: None,
             err: Some(err),
+            budget: None,
             length: None,
+            strict_length: None,
+            lenient_enums: false,
+            max_key_len: None,
+            max_value_len: None,
             encoding: UTF_8,
+            reporter: None,
         }
     }
 
     /// Set maximum accepted payload size. The default limit is 256kB.
+    ///
+    /// See [`FormConfig::limit`].
     pub fn limit(mut self, limit: usize) -> Self {
         self.limit = limit;
         self
     }
+
+    /// Reject payloads longer than the declared `Content-Length` with [`UrlencodedError::Parse`].
+    ///
+    /// See [`FormConfig::strict_length`].
+    pub fn strict_length(mut self, strict: bool) -> Self {
+        if !strict {
+            self.strict_length = None;
+        }
+        self
+    }
+
+    /// Match enum variants case-insensitively.
+    ///
+    /// See [`FormConfig::lenient_enums`].
+    pub fn lenient_enums(mut self, lenient: bool) -> Self {
+        self.lenient_enums = lenient;
+        self
+    }
+
+    /// Set maximum URL decoded length of each field's key, or `None` for no limit.
+    ///
+    /// See [`FormConfig::max_key_len`].
+    pub fn max_key_len(mut self, max_len: Option<usize>) -> Self {
+        self.max_key_len = max_len;
+        self
+    }
+
+    /// Set maximum URL decoded length of each field's value, or `None` for no limit.
+    ///
+    /// See [`FormConfig::max_value_len`].
+    pub fn max_value_len(mut self, max_len: Option<usize>) -> Self {
+        self.max_value_len = max_len;
+        self
+    }
+
+    /// Report fields ignored by `T` to the request's `ExtractionMetrics` hook.
+    pub(crate) fn report_unknown_fields(
+        mut self,
+        reporter: Option<ExtractionReporter>,
+    ) -> Self {
+        self.reporter = reporter;
+        self
+    }
 }
 
 impl<T> Future for UrlEncoded<T>
@@ -322,7 +490,17 @@ where
 
         // future
         let encoding = self.encoding;
+        // inflated size of encoded payloads is capped while decoding
+        #[cfg(feature = "compress")]
+        let mut stream = self.stream.take().unwrap().limit(limit);
+        #[cfg(not(feature = "compress"))]
         let mut stream = self.stream.take().unwrap();
+        let reporter = self.reporter.take();
+        let declared = self.strict_length;
+        let lenient_enums = self.lenient_enums;
+        let max_key_len = self.max_key_len;
+        let max_value_len = self.max_value_len;
+        let mut budget = self.budget.take();
 
         self.fut = Some(
             async move {
@@ -331,26 +509,50 @@ where
                 while let Some(item) = stream.next().await {
                     let chunk = item?;
 
+                    if matches!(declared, Some(len) if body.len() + chunk.len() > len) {
+                        return Err(UrlencodedError::Parse);
+                    }
+
                     if (body.len() + chunk.len()) > limit {
                         return Err(UrlencodedError::Overflow {
                             size: body.len() + chunk.len(),
                             limit,
                         });
                     } else {
+                        if let Some(ref mut budget) = budget {
+                            budget.reserve(chunk.len())?;
+                        }
+
                         body.extend_from_slice(&chunk);
                     }
                 }
 
-                if encoding == UTF_8 {
-                    serde_urlencoded::from_bytes::<T>(&body).map_err(|_| UrlencodedError::Parse)
-                } else {
-                    let body = encoding
-                        .decode_without_bom_handling_and_without_replacement(&body)
-                        .map(|s| s.into_owned())
-                        .ok_or(UrlencodedError::Parse)?;
+                // decode strictly so that mislabeled payloads are not silently mangled
+                let body = decode_form(body, encoding)?;
+
+                check_pair_lengths(&body, max_key_len, max_value_len)?;
 
-                    serde_urlencoded::from_str::<T>(&body).map_err(|_| UrlencodedError::Parse)
+                let item = from_urlencoded::<T>(&body, lenient_enums).map_err(|err| {
+                    log::debug!("Failed to deserialize URL encoded form: {}", err);
+                    UrlencodedError::Deserialize(err)
+                })?;
+
+                if let Some(reporter) = reporter {
+                    let form =
+                        serde_urlencoded::Deserializer::new(form_urlencoded::parse(&body));
+
+                    if lenient_enums {
+                        reporter.unknown_fields::<T, _>(LenientEnums(form));
+                    } else {
+                        reporter.unknown_fields::<T, _>(form);
+                    }
                 }
+
+                // buffered body is held against the budget until it is deserialized
+                drop(body);
+                drop(budget);
+
+                Ok(item)
             }
             .boxed_local(),
         );
@@ -359,6 +561,85 @@ where
     }
 }
 
+/// Checks the URL decoded length of every key and value against the given limits.
+/// Decodes the percent-decoded bytes of each field strictly in the declared charset, returning
+/// the form with its fields in UTF-8.
+fn decode_form(body: BytesMut, encoding: &'static Encoding) -> Result<Bytes, UrlencodedError> {
+    let decode = |raw: &[u8]| {
+        // `+` encodes a space
+        let raw = raw
+            .iter()
+            .map(|&b| if b == b'+' { b' ' } else { b })
+            .collect::<Vec<_>>();
+        let bytes = Cow::from(percent_decode(&raw));
+
+        encoding
+            .decode_without_bom_handling_and_without_replacement(&bytes)
+            .map(Cow::into_owned)
+            .ok_or(UrlencodedError::Encoding)
+    };
+
+    let fields = body[..]
+        .split(|&b| b == b'&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| -> Result<_, UrlencodedError> {
+            let mut parts = pair.splitn(2, |&b| b == b'=');
+            let key = decode(parts.next().unwrap_or(&[]))?;
+            let value = decode(parts.next().unwrap_or(&[]))?;
+            Ok((key, value))
+        });
+
+    // valid UTF-8 forms are deserialized as they are
+    if encoding == UTF_8 {
+        for field in fields {
+            field?;
+        }
+
+        return Ok(body.freeze());
+    }
+
+    let mut form = form_urlencoded::Serializer::new(String::new());
+    for field in fields {
+        let (key, value) = field?;
+        form.append_pair(&key, &value);
+    }
+
+    Ok(Bytes::from(form.finish()))
+}
+
+fn check_pair_lengths(
+    body: &[u8],
+    max_key_len: Option<usize>,
+    max_value_len: Option<usize>,
+) -> Result<(), UrlencodedError> {
+    if max_key_len.is_none() && max_value_len.is_none() {
+        return Ok(());
+    }
+
+    for (key, value) in form_urlencoded::parse(body) {
+        if let Some(limit) = max_key_len {
+            if key.len() > limit {
+                return Err(UrlencodedError::KeyOverflow {
+                    len: key.len(),
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = max_value_len {
+            if value.len() > limit {
+                return Err(UrlencodedError::ValueOverflow {
+                    key: key.into_owned(),
+                    len: value.len(),
+                    limit,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -395,6 +676,43 @@ mod tests {
         );
     }
 
+    #[actix_rt::test]
+    async fn test_no_payload() {
+        // requests without a body are read as an empty form
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .to_http_request();
+        let Form(s) = Form::<HashMap<String, String>>::extract(&req)
+            .await
+            .unwrap();
+        assert!(s.is_empty());
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/json"))
+            .insert_header((CONTENT_LENGTH, "0"))
+            .to_http_request();
+        let err = Form::<Info>::extract(&req).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<UrlencodedError>(),
+            Some(UrlencodedError::ContentType)
+        ));
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((CONTENT_LENGTH, "11"))
+            .to_http_request();
+
+        let err = Form::<Info>::extract(&req).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<UrlencodedError>(),
+            Some(UrlencodedError::NoPayload)
+        ));
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
     fn eq(err: UrlencodedError, other: UrlencodedError) -> bool {
         match err {
             UrlencodedError::Overflow { .. } => {
@@ -402,10 +720,175 @@ mod tests {
             }
             UrlencodedError::UnknownLength => matches!(other, UrlencodedError::UnknownLength),
             UrlencodedError::ContentType => matches!(other, UrlencodedError::ContentType),
+            UrlencodedError::Encoding => matches!(other, UrlencodedError::Encoding),
             _ => false,
         }
     }
 
+    #[actix_rt::test]
+    async fn test_form_strict_length() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((CONTENT_LENGTH, 23))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123&garbage"))
+            .app_data(FormConfig::default().strict_length(true))
+            .to_http_parts();
+
+        let err = Form::<Info>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(err.to_string(), UrlencodedError::Parse.to_string());
+
+        // payload matching the declared length is accepted
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((CONTENT_LENGTH, 23))
+            .set_payload(Bytes::from_static(b"hello=world&counter=123"))
+            .app_data(FormConfig::default().strict_length(true))
+            .to_http_parts();
+
+        let Form(s) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(s.counter, 123);
+    }
+
+    #[actix_rt::test]
+    async fn test_form_max_value_len() {
+        let payload = format!("hello={}&counter=123", "x".repeat(64));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(payload.clone())
+            .to_http_parts();
+
+        let err = UrlEncoded::<Info>::new(&req, &mut pl)
+            .max_key_len(Some(8))
+            .max_value_len(Some(32))
+            .await
+            .unwrap_err();
+        match err {
+            UrlencodedError::ValueOverflow { key, len, limit } => {
+                assert_eq!(key, "hello");
+                assert_eq!(len, 64);
+                assert_eq!(limit, 32);
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(payload)
+            .app_data(FormConfig::default().max_key_len(8).max_value_len(32))
+            .to_http_parts();
+
+        let err = Form::<Info>::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        // decoded lengths are checked, so percent-encoded values may be longer on the wire
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .set_payload(format!("hello={}&counter=123", "%41".repeat(32)))
+            .app_data(FormConfig::default().max_key_len(8).max_value_len(32))
+            .to_http_parts();
+
+        let Form(s) = Form::<Info>::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(s.hello, "A".repeat(32));
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Level {
+        Low,
+        #[serde(alias = "max")]
+        High,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Setting {
+        level: Level,
+        label: String,
+    }
+
+    #[actix_rt::test]
+    async fn test_form_lenient_enums() {
+        let extract = |body: &'static [u8], cfg: FormConfig| {
+            let (req, mut pl) = TestRequest::default()
+                .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+                .set_payload(Bytes::from_static(body))
+                .app_data(cfg)
+                .to_http_parts();
+
+            async move { Form::<Setting>::from_request(&req, &mut pl).await }
+        };
+
+        assert!(extract(b"level=LOW&label=x", FormConfig::default())
+            .await
+            .is_err());
+
+        let Form(s) = extract(
+            b"level=LOW&label=LoW",
+            FormConfig::default().lenient_enums(true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            s,
+            Setting {
+                level: Level::Low,
+                label: "LoW".to_owned()
+            }
+        );
+
+        let Form(s) = extract(
+            b"level=Max&label=x",
+            FormConfig::default().lenient_enums(true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(s.level, Level::High);
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[allow(clippy::upper_case_acronyms)]
+    enum Direction {
+        Up,
+        UP,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Move {
+        dir: Direction,
+    }
+
+    #[actix_rt::test]
+    async fn test_form_lenient_enums_ambiguous() {
+        use crate::ResponseError as _;
+
+        let extract = |body: &'static [u8]| {
+            let (req, mut pl) = TestRequest::default()
+                .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+                .set_payload(Bytes::from_static(body))
+                .to_http_parts();
+
+            async move {
+                UrlEncoded::<Move>::new(&req, &mut pl)
+                    .lenient_enums(true)
+                    .await
+            }
+        };
+
+        // exact names are not ambiguous
+        assert_eq!(extract(b"dir=Up").await.unwrap().dir, Direction::Up);
+        assert_eq!(extract(b"dir=UP").await.unwrap().dir, Direction::UP);
+
+        // the reason is carried in the error
+        let err = extract(b"dir=up").await.unwrap_err();
+        assert!(matches!(err, UrlencodedError::Deserialize(_)));
+        assert!(err
+            .to_string()
+            .contains("ambiguous variant `up`, matches both `Up` and `UP`"));
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
     #[actix_rt::test]
     async fn test_urlencoded_error() {
         let (req, mut pl) = TestRequest::default()
@@ -431,6 +914,126 @@ mod tests {
             .to_http_parts();
         let info = UrlEncoded::<Info>::new(&req, &mut pl).await;
         assert!(eq(info.err().unwrap(), UrlencodedError::ContentType));
+
+        // lone lead byte of a two byte Shift_JIS sequence
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=shift_jis",
+            ))
+            .insert_header((CONTENT_LENGTH, 22))
+            .set_payload(Bytes::from_static(b"hello=wor\x82&counter=123"))
+            .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await;
+        assert!(eq(info.err().unwrap(), UrlencodedError::Encoding));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=utf-8",
+            ))
+            .insert_header((CONTENT_LENGTH, 22))
+            .set_payload(Bytes::from_static(b"hello=wor\xff&counter=123"))
+            .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await;
+        assert!(eq(info.err().unwrap(), UrlencodedError::Encoding));
+
+        // charset is checked after percent-decoding
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=shift_jis",
+            ))
+            .insert_header((CONTENT_LENGTH, 24))
+            .set_payload(Bytes::from_static(b"hello=wor%82&counter=123"))
+            .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await;
+        assert!(eq(info.err().unwrap(), UrlencodedError::Encoding));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=utf-8",
+            ))
+            .insert_header((CONTENT_LENGTH, 24))
+            .set_payload(Bytes::from_static(b"hello=wor%FF&counter=123"))
+            .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await;
+        assert!(eq(info.err().unwrap(), UrlencodedError::Encoding));
+
+        // percent-encoded Shift_JIS is decoded
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((
+                CONTENT_TYPE,
+                "application/x-www-form-urlencoded; charset=shift_jis",
+            ))
+            .insert_header((CONTENT_LENGTH, 24))
+            .set_payload(Bytes::from_static(b"hello=%82%A0+x&counter=1"))
+            .to_http_parts();
+        let info = UrlEncoded::<Info>::new(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            info,
+            Info {
+                hello: "\u{3042} x".to_owned(),
+                counter: 1
+            }
+        );
+    }
+
+    #[cfg(feature = "compress")]
+    #[actix_rt::test]
+    async fn test_urlencoded_gzip_inflated_limit() {
+        use std::io::Write as _;
+
+        use actix_http::error::PayloadError;
+        use flate2::{write::GzEncoder, Compression};
+        use futures_util::stream;
+
+        use crate::ResponseError as _;
+
+        fn gzip(data: &[u8]) -> Vec<u8> {
+            let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+            enc.write_all(data).unwrap();
+            enc.finish().unwrap()
+        }
+
+        fn chunked_payload(data: Vec<u8>, chunk_size: usize) -> Payload {
+            let chunks = data
+                .chunks(chunk_size)
+                .map(|chunk| Ok::<_, PayloadError>(Bytes::copy_from_slice(chunk)))
+                .collect::<Vec<_>>();
+            Payload::Stream(Box::pin(stream::iter(chunks)))
+        }
+
+        let req = TestRequest::default()
+            .insert_header((CONTENT_TYPE, "application/x-www-form-urlencoded"))
+            .insert_header((CONTENT_ENCODING, "gzip"))
+            .to_http_request();
+
+        // large form within the inflated size limit
+        let hello = "a".repeat(200_000);
+        let form = format!("hello={}&counter=7", hello);
+        let mut pl = chunked_payload(gzip(form.as_bytes()), 64);
+        let info = UrlEncoded::<Info>::new(&req, &mut pl)
+            .limit(256 * 1024)
+            .await
+            .unwrap();
+        assert_eq!(info, Info { hello, counter: 7 });
+
+        // payload inflating past the limit is stopped while decoding, in small and large chunks
+        let bomb = gzip(format!("hello={}&counter=7", "a".repeat(4_000_000)).as_bytes());
+        for &chunk_size in &[1024, bomb.len()] {
+            let mut pl = chunked_payload(bomb.clone(), chunk_size);
+            let err = UrlEncoded::<Info>::new(&req, &mut pl)
+                .limit(64 * 1024)
+                .await
+                .unwrap_err();
+            assert!(matches!(
+                err,
+                UrlencodedError::Payload(PayloadError::Overflow)
+            ));
+            assert_eq!(err.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+        }
     }
 
     #[actix_rt::test]
@@ -488,6 +1091,28 @@ mod tests {
         assert_eq!(resp.body().bin_ref(), b"hello=world&counter=123");
     }
 
+    #[actix_rt::test]
+    async fn test_map_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let mut map = HashMap::new();
+        map.insert("hello", "big world");
+        map.insert("counter", "123");
+
+        let resp = map.respond_to(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(CONTENT_TYPE).unwrap(),
+            HeaderValue::from_static("application/x-www-form-urlencoded")
+        );
+
+        use crate::responder::tests::BodyTest;
+        let body = resp.body().bin_ref();
+        assert!(
+            body == b"hello=big+world&counter=123" || body == b"counter=123&hello=big+world"
+        );
+    }
+
     #[actix_rt::test]
     async fn test_with_config_in_data_wrapper() {
         let ctype = HeaderValue::from_static("application/x-www-form-urlencoded");
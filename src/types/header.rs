@@ -0,0 +1,234 @@
+//! For header extractor helper documentation, see [`Header`] and [`PrefixedHeaders`].
+
+use std::{collections::HashMap, fmt, ops};
+
+use futures_util::future::{err, ok, Ready};
+
+use crate::{
+    dev::Payload, error::ParseError, extract::FromRequest, http::header::Header as ParseHeader,
+    HttpRequest,
+};
+
+/// Extract typed headers from the request.
+///
+/// To extract a header, the inner type `T` must implement the
+/// [`Header`](crate::http::header::Header) trait.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, http::header};
+///
+/// #[get("/")]
+/// async fn index(auth: web::Header<header::Authorization>) -> String {
+///     format!("Credentials use the {} scheme", auth.scheme())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header<T>(pub T);
+
+impl<T> Header<T> {
+    /// Unwrap into the inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ops::Deref for Header<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> ops::DerefMut for Header<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Header<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<T> FromRequest for Header<T>
+where
+    T: ParseHeader,
+{
+    type Error = ParseError;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ();
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        match T::parse(req) {
+            Ok(header) => ok(Header(header)),
+            Err(e) => err(e),
+        }
+    }
+}
+
+/// Extract all headers whose name starts with a prefix into a map.
+///
+/// Keys are the header names with the prefix stripped, in lower case. Values of headers that occur
+/// more than once are joined with `", "`. The prefix defaults to `x-meta-` and can be changed with
+/// [`PrefixedHeadersConfig`]. Requests with a matching header whose value is not visible ASCII are
+/// rejected with `400 Bad Request`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+///
+/// // a request with `X-Meta-Owner: ops` yields `{"owner": "ops"}`
+/// #[get("/")]
+/// async fn index(meta: web::PrefixedHeaders) -> String {
+///     format!("{} metadata headers", meta.len())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixedHeaders(pub HashMap<String, String>);
+
+impl PrefixedHeaders {
+    /// Unwrap into the inner map.
+    pub fn into_inner(self) -> HashMap<String, String> {
+        self.0
+    }
+}
+
+impl ops::Deref for PrefixedHeaders {
+    type Target = HashMap<String, String>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ops::DerefMut for PrefixedHeaders {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl FromRequest for PrefixedHeaders {
+    type Error = ParseError;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = PrefixedHeadersConfig;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let prefix = req
+            .app_data::<Self::Config>()
+            .map_or(DEFAULT_HEADER_PREFIX, |cfg| cfg.prefix.as_str());
+
+        let mut map = HashMap::<String, String>::new();
+
+        for (name, value) in req.headers().iter() {
+            let key = match name.as_str().strip_prefix(prefix) {
+                Some(key) if !key.is_empty() => key,
+                _ => continue,
+            };
+
+            let value = match value.to_str() {
+                Ok(value) => value,
+                Err(_) => return err(ParseError::Header),
+            };
+
+            map.entry(key.to_owned())
+                .and_modify(|joined| {
+                    joined.push_str(", ");
+                    joined.push_str(value);
+                })
+                .or_insert_with(|| value.to_owned());
+        }
+
+        ok(PrefixedHeaders(map))
+    }
+}
+
+const DEFAULT_HEADER_PREFIX: &str = "x-meta-";
+
+/// [`PrefixedHeaders`] extractor configuration.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App};
+///
+/// App::new().app_data(web::PrefixedHeadersConfig::default().prefix("X-Trace-"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrefixedHeadersConfig {
+    prefix: String,
+}
+
+impl PrefixedHeadersConfig {
+    /// Set the header name prefix, matched case-insensitively.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into().to_ascii_lowercase();
+        self
+    }
+}
+
+impl Default for PrefixedHeadersConfig {
+    fn default() -> Self {
+        PrefixedHeadersConfig {
+            prefix: DEFAULT_HEADER_PREFIX.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{self, Authorization};
+    use crate::test::TestRequest;
+
+    #[actix_rt::test]
+    async fn test_header_extract() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Bearer mF_9.B5f-4.1JqM"))
+            .to_http_parts();
+
+        let auth = Header::<Authorization>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(
+            auth.into_inner(),
+            Authorization::Bearer("mF_9.B5f-4.1JqM".to_owned())
+        );
+
+        let (req, mut pl) = TestRequest::default().to_http_parts();
+        let auth = Header::<Authorization>::from_request(&req, &mut pl).await;
+        assert!(auth.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_prefixed_headers() {
+        let (req, mut pl) = TestRequest::default()
+            .insert_header(("X-Meta-Owner", "ops"))
+            .insert_header(("X-Meta-Region", "eu-west"))
+            .insert_header(("X-Other", "ignored"))
+            .to_http_parts();
+
+        let meta = PrefixedHeaders::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(meta.len(), 2);
+        assert_eq!(meta["owner"], "ops");
+        assert_eq!(meta["region"], "eu-west");
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(PrefixedHeadersConfig::default().prefix("X-Trace-"))
+            .insert_header(("X-Meta-Owner", "ops"))
+            .insert_header(("X-Trace-Id", "abc"))
+            .to_http_parts();
+
+        let meta = PrefixedHeaders::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            meta.into_inner().into_iter().collect::<Vec<_>>(),
+            vec![("id".to_owned(), "abc".to_owned())]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! For JSON helper documentation, see [`Json`].
 
 use std::{
+    collections::BTreeMap,
     fmt,
     future::Future,
     marker::PhantomData,
@@ -20,9 +21,10 @@ use actix_http::Payload;
 use crate::dev::Decompress;
 use crate::{
     error::{Error, JsonPayloadError},
-    extract::FromRequest,
+    extract::{ExtractionDebug, ExtractionReporter, FromRequest},
     http::header::CONTENT_LENGTH,
     request::HttpRequest,
+    types::payload::{payload_absent, BudgetReservation},
     web, HttpMessage, HttpResponse, Responder,
 };
 
@@ -36,6 +38,10 @@ use crate::{
 ///
 /// Use [`JsonConfig`] to configure extraction process.
 ///
+/// Requests with `Content-Length: 0` are parsed as an empty document and fail to deserialize.
+/// Other requests without a body, such as those built with `TestRequest::to_http_request` or
+/// extracted with [`FromRequest::extract`], fail with [`JsonPayloadError::NoPayload`].
+///
 /// ```
 /// use actix_web::{post, web, App};
 /// use serde::Deserialize;
@@ -128,6 +134,65 @@ impl<T: Serialize> Responder for Json<T> {
     }
 }
 
+/// Creates response with OK status code, JSON content type header, and serialized value as payload.
+///
+/// Shorthand for returning `web::Json(value)`; serialization errors are handled the same way.
+impl Responder for serde_json::Value {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        Json(self).respond_to(req)
+    }
+}
+
+/// Creates response with OK status code, JSON content type header, and the map serialized with
+/// its keys in sorted order.
+///
+/// Unlike a `HashMap` wrapped in `Json`, the same map always produces the same bytes.
+impl Responder for BTreeMap<String, serde_json::Value> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        Json(self).respond_to(req)
+    }
+}
+
+/// JSON responder for values built from client supplied data.
+///
+/// Responds like [`Json`], except that a value which fails to serialize is treated as a bad
+/// request rather than a server error: the response is `400 Bad Request` with a plain text body
+/// describing the serialization error, instead of `500 Internal Server Error`.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use actix_web::{post, web, Responder};
+///
+/// /// Echoes back fields submitted by the client.
+/// #[post("/echo")]
+/// async fn echo(fields: web::Json<HashMap<String, serde_json::Value>>) -> impl Responder {
+///     web::UntrustedJson(fields.into_inner())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct UntrustedJson<T>(pub T);
+
+impl<T> UntrustedJson<T> {
+    /// Unwrap into inner `T` value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Serialize> Responder for UntrustedJson<T> {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        match serde_json::to_string(&self.0) {
+            Ok(body) => HttpResponse::Ok()
+                .content_type(mime::APPLICATION_JSON)
+                .body(body),
+            Err(err) => HttpResponse::BadRequest()
+                .content_type(mime::TEXT_PLAIN_UTF_8)
+                .body(format!("Failed to serialize response: {}", err)),
+        }
+    }
+}
+
 /// See [here](#extractor) for example of usage as an extractor.
 impl<T> FromRequest for Json<T>
 where
@@ -182,13 +247,36 @@ where
                     req.path()
                 );
 
+                if let Some(debug) = req.app_data::<ExtractionDebug>() {
+                    if let Some((buf, limit)) = this.fut.buffered() {
+                        debug.attach_sample(&req, buf, limit);
+                    }
+                }
+
+                if let Some(reporter) = ExtractionReporter::from_req(&req) {
+                    reporter.failure::<Json<T>>(err.kind());
+                }
+
                 if let Some(err_handler) = this.err_handler.as_ref() {
                     Err((*err_handler)(err, &req))
                 } else {
                     Err(err.into())
                 }
             }
-            Ok(data) => Ok(Json(data)),
+            Ok(data) => {
+                let reporter = this.req.as_ref().and_then(ExtractionReporter::from_req);
+
+                if let Some(reporter) = reporter {
+                    if let Some((buf, _)) = this.fut.buffered() {
+                        let mut de = serde_json::Deserializer::from_slice(buf);
+                        reporter.unknown_fields::<T, _>(&mut de);
+                    }
+
+                    reporter.success::<Json<T>>();
+                }
+
+                Ok(Json(data))
+            }
         };
 
         Poll::Ready(res)
@@ -299,6 +387,7 @@ pub enum JsonBody<T> {
         #[cfg(not(feature = "compress"))]
         payload: Payload,
         buf: BytesMut,
+        budget: BudgetReservation,
         _res: PhantomData<T>,
     },
 }
@@ -329,6 +418,10 @@ where
             return JsonBody::Error(Some(JsonPayloadError::ContentType));
         }
 
+        if payload_absent(req, payload) {
+            return JsonBody::Error(Some(JsonPayloadError::NoPayload));
+        }
+
         let length = req
             .headers()
             .get(&CONTENT_LENGTH)
@@ -349,10 +442,19 @@ where
             length,
             payload,
             buf: BytesMut::with_capacity(8192),
+            budget: BudgetReservation::new(req),
             _res: PhantomData,
         }
     }
 
+    /// Returns the payload buffered so far and the limit in play, if any.
+    pub(crate) fn buffered(&self) -> Option<(&[u8], usize)> {
+        match self {
+            JsonBody::Body { buf, limit, .. } => Some((&buf[..], *limit)),
+            JsonBody::Error(_) => None,
+        }
+    }
+
     /// Set maximum accepted payload size. The default limit is 32kB.
     pub fn limit(self, limit: usize) -> Self {
         match self {
@@ -360,6 +462,7 @@ where
                 length,
                 payload,
                 buf,
+                budget,
                 ..
             } => {
                 if let Some(len) = length {
@@ -373,6 +476,7 @@ where
                     length,
                     payload,
                     buf,
+                    budget,
                     _res: PhantomData,
                 }
             }
@@ -395,6 +499,7 @@ where
                 limit,
                 buf,
                 payload,
+                budget,
                 ..
             } => loop {
                 let res = ready!(Pin::new(&mut *payload).poll_next(cx));
@@ -404,12 +509,26 @@ where
                         if (buf.len() + chunk.len()) > *limit {
                             return Poll::Ready(Err(JsonPayloadError::Overflow));
                         } else {
+                            budget.reserve(chunk.len())?;
                             buf.extend_from_slice(&chunk);
                         }
                     }
                     None => {
-                        let json = serde_json::from_slice::<T>(&buf)?;
-                        return Poll::Ready(Ok(json));
+                        let json = {
+                            let mut de = serde_json::Deserializer::from_slice(&buf);
+                            serde_path_to_error::deserialize::<_, T>(&mut de)
+                                .map_err(deserialize_error)
+                                .and_then(|json| {
+                                    de.end()?;
+                                    Ok(json)
+                                })
+                        };
+
+                        // buffered body is held against the budget until it is deserialized
+                        *buf = BytesMut::new();
+                        budget.release();
+
+                        return Poll::Ready(json);
                     }
                 }
             },
@@ -418,6 +537,18 @@ where
     }
 }
 
+/// Keep the path of the failing field, unless the error occurred at the document root.
+fn deserialize_error(err: serde_path_to_error::Error<serde_json::Error>) -> JsonPayloadError {
+    let path = err.path().to_string();
+    let source = err.into_inner();
+
+    if path == "." {
+        JsonPayloadError::Deserialize(source)
+    } else {
+        JsonPayloadError::DeserializeAt { path, source }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
@@ -464,6 +595,80 @@ mod tests {
         assert_eq!(resp.body().bin_ref(), b"{\"name\":\"test\"}");
     }
 
+    #[actix_rt::test]
+    async fn test_value_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let resp = serde_json::json!({ "ok": true }).respond_to(&req);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            header::HeaderValue::from_static("application/json")
+        );
+
+        use crate::responder::tests::BodyTest;
+        assert_eq!(resp.body().bin_ref(), b"{\"ok\":true}");
+    }
+
+    #[actix_rt::test]
+    async fn test_untrusted_json_responder() {
+        struct Unserializable;
+
+        impl Serialize for Unserializable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("unsupported value"))
+            }
+        }
+
+        use crate::responder::tests::BodyTest;
+        let req = TestRequest::default().to_http_request();
+
+        let res = Json(Unserializable).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let res = UntrustedJson(Unserializable).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            res.body().bin_ref(),
+            &b"Failed to serialize response: unsupported value"[..]
+        );
+
+        let res = UntrustedJson(MyObject {
+            name: "test".to_owned(),
+        })
+        .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.body().bin_ref(), &b"{\"name\":\"test\"}"[..]);
+    }
+
+    #[actix_rt::test]
+    async fn test_btree_map_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let map = || {
+            let mut map = BTreeMap::new();
+            map.insert("zeta".to_owned(), serde_json::json!(1));
+            map.insert("alpha".to_owned(), serde_json::json!({ "b": 2, "a": 1 }));
+            map.insert("mid".to_owned(), serde_json::json!([true, null]));
+            map
+        };
+
+        let first = map().respond_to(&req);
+        assert_eq!(first.status(), StatusCode::OK);
+        assert_eq!(
+            first.headers().get(header::CONTENT_TYPE).unwrap(),
+            header::HeaderValue::from_static("application/json")
+        );
+
+        use crate::responder::tests::BodyTest;
+        let second = map().respond_to(&req);
+        assert_eq!(first.body().bin_ref(), second.body().bin_ref());
+        assert_eq!(
+            first.body().bin_ref(),
+            &b"{\"alpha\":{\"a\":1,\"b\":2},\"mid\":[true,null],\"zeta\":1}"[..]
+        );
+    }
+
     #[actix_rt::test]
     async fn test_custom_error_responder() {
         let (req, mut pl) = TestRequest::default()
@@ -495,6 +700,60 @@ mod tests {
         assert_eq!(msg.name, "invalid request");
     }
 
+    #[actix_rt::test]
+    async fn test_no_payload() {
+        let req = TestRequest::default()
+            .insert_header((
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            ))
+            .insert_header((header::CONTENT_LENGTH, "16"))
+            .to_http_request();
+
+        let err = Json::<MyObject>::extract(&req).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<JsonPayloadError>(),
+            Some(JsonPayloadError::NoPayload)
+        ));
+
+        let mut resp = HttpResponse::from_error(err);
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let body = load_stream(resp.take_body()).await.unwrap();
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("Request has no payload"));
+
+        // requests without length headers have no body either
+        let req = TestRequest::default()
+            .insert_header((
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            ))
+            .to_http_request();
+
+        let err = Json::<MyObject>::extract(&req).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<JsonPayloadError>(),
+            Some(JsonPayloadError::NoPayload)
+        ));
+
+        // an empty body is parsed as an empty document
+        let req = TestRequest::default()
+            .insert_header((
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("application/json"),
+            ))
+            .insert_header((header::CONTENT_LENGTH, "0"))
+            .to_http_request();
+
+        let err = Json::<MyObject>::extract(&req).await.unwrap_err();
+        assert!(matches!(
+            err.as_error::<JsonPayloadError>(),
+            Some(JsonPayloadError::Deserialize(_))
+        ));
+    }
+
     #[actix_rt::test]
     async fn test_extract() {
         let (req, mut pl) = TestRequest::default()
@@ -555,6 +814,39 @@ mod tests {
         assert!(format!("{}", s.err().unwrap()).contains("Content type error"));
     }
 
+    #[actix_rt::test]
+    async fn test_deserialize_error_path() {
+        #[derive(Deserialize, Debug)]
+        struct Outer {
+            items: Vec<MyObject>,
+        }
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, mime::APPLICATION_JSON))
+            .set_payload(Bytes::from_static(
+                b"{\"items\": [{\"name\": \"a\"}, {\"name\": 42}]}",
+            ))
+            .app_data(JsonConfig::default().error_handler(|err, _| {
+                assert_eq!(err.path(), Some("items[1].name"));
+                err.into()
+            }))
+            .to_http_parts();
+
+        let s = Json::<Outer>::from_request(&req, &mut pl).await;
+        let err_str = s.err().unwrap().to_string();
+        assert!(err_str.contains("Json deserialize error at `items[1].name`"));
+
+        let (req, mut pl) = TestRequest::default()
+            .insert_header((CONTENT_TYPE, mime::APPLICATION_JSON))
+            .set_payload(Bytes::from_static(b"\"text\""))
+            .to_http_parts();
+        let json = JsonBody::<MyObject>::new(&req, &mut pl, None).await;
+        assert!(matches!(
+            json.err().unwrap(),
+            JsonPayloadError::Deserialize(_)
+        ));
+    }
+
     #[actix_rt::test]
     async fn test_json_body() {
         let (req, mut pl) = TestRequest::default().to_http_parts();
@@ -0,0 +1,137 @@
+//! For last modified responder documentation, see [`LastModified`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    http::{
+        header::{HttpDate, IfModifiedSince, IntoHeaderValue, IF_NONE_MATCH, LAST_MODIFIED},
+        Method,
+    },
+    HttpMessage, HttpRequest, HttpResponse, Responder,
+};
+
+/// Responder that sets the `Last-Modified` header and answers conditional requests.
+///
+/// `GET` and `HEAD` requests with an `If-Modified-Since` date at or after the modification time
+/// get a `304 Not Modified` response without the inner responder being run. Dates are compared at
+/// second precision, the precision of HTTP dates. `If-Modified-Since` is ignored when the request
+/// has an `If-None-Match` header, which takes precedence.
+///
+/// Other requests get the response of the inner responder, with a `Last-Modified` header added to
+/// successful responses.
+///
+/// # Examples
+/// ```
+/// use std::time::SystemTime;
+///
+/// use actix_web::{get, web, Responder};
+///
+/// #[get("/report")]
+/// async fn report() -> impl Responder {
+///     let modified = SystemTime::UNIX_EPOCH;
+///     web::LastModified("quarterly report", modified)
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LastModified<R>(pub R, pub SystemTime);
+
+impl<R> LastModified<R> {
+    /// Unwrap into inner responder.
+    pub fn into_inner(self) -> R {
+        self.0
+    }
+}
+
+impl<R: Responder> Responder for LastModified<R> {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let LastModified(responder, modified) = self;
+
+        if not_modified(req, modified) {
+            return HttpResponse::NotModified()
+                .insert_header((LAST_MODIFIED, HttpDate::from(modified)))
+                .finish();
+        }
+
+        let mut res = responder.respond_to(req);
+
+        if res.status().is_success() {
+            if let Ok(value) = HttpDate::from(modified).try_into_value() {
+                res.headers_mut().insert(LAST_MODIFIED, value);
+            }
+        }
+
+        res
+    }
+}
+
+fn not_modified(req: &HttpRequest, modified: SystemTime) -> bool {
+    if !matches!(*req.method(), Method::GET | Method::HEAD)
+        || req.headers().contains_key(IF_NONE_MATCH)
+    {
+        return false;
+    }
+
+    let since = match req.get_header::<IfModifiedSince>() {
+        Some(IfModifiedSince(since)) => SystemTime::from(since),
+        None => return false,
+    };
+
+    match (
+        modified.duration_since(UNIX_EPOCH),
+        since.duration_since(UNIX_EPOCH),
+    ) {
+        (Ok(modified), Ok(since)) => modified.as_secs() <= since.as_secs(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        http::{header::IF_MODIFIED_SINCE, StatusCode},
+        test::TestRequest,
+    };
+
+    #[test]
+    fn test_last_modified() {
+        // sub-second part is not sent and must not make the resource look modified
+        let modified = UNIX_EPOCH + Duration::from_millis(1_600_000_000_500);
+        let date = HttpDate::from(modified).to_string();
+
+        let req = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, date.as_str()))
+            .to_http_request();
+        let res = LastModified("report", modified).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(res.headers().get(LAST_MODIFIED).unwrap(), date.as_str());
+
+        let earlier = HttpDate::from(modified - Duration::from_secs(1)).to_string();
+        let req = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, earlier.as_str()))
+            .to_http_request();
+        let res = LastModified("report", modified).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(LAST_MODIFIED).unwrap(), date.as_str());
+
+        let req = TestRequest::default().to_http_request();
+        let res = LastModified("report", modified).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // If-None-Match takes precedence
+        let req = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, date.as_str()))
+            .insert_header((IF_NONE_MATCH, "\"v1\""))
+            .to_http_request();
+        let res = LastModified("report", modified).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let req = TestRequest::post()
+            .insert_header((IF_MODIFIED_SINCE, date.as_str()))
+            .to_http_request();
+        let res = LastModified("report", modified).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
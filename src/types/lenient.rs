@@ -0,0 +1,324 @@
+//! Deserializer wrapper backing the `lenient_enums` option of `FormConfig` and `QueryConfig`.
+
+use std::fmt;
+
+use serde::de::{
+    self,
+    value::{self, StringDeserializer},
+    DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+use url::form_urlencoded;
+
+/// Deserializes `T` from a URL encoded string, matching enum variants leniently if requested.
+pub(crate) fn from_urlencoded<T>(input: &[u8], lenient_enums: bool) -> Result<T, value::Error>
+where
+    T: DeserializeOwned,
+{
+    let de = serde_urlencoded::Deserializer::new(form_urlencoded::parse(input));
+
+    if lenient_enums {
+        T::deserialize(LenientEnums(de))
+    } else {
+        T::deserialize(de)
+    }
+}
+
+/// Deserializer that matches enum variant names case-insensitively.
+///
+/// The variant list serde passes to `deserialize_enum` also contains `#[serde(alias)]` names, so
+/// aliases are matched the same way. An exact match always wins; otherwise the single variant
+/// equal to the input ignoring ASCII case is used and input matching several variants is
+/// rejected as ambiguous. Everything other than enums is deserialized unchanged.
+pub(crate) struct LenientEnums<D>(pub(crate) D);
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.0.$method($($arg,)* Lenient(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer<'de> for LenientEnums<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_seq(),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0
+            .deserialize_enum(name, variants, EnumVisitor { visitor, variants })
+    }
+}
+
+/// Finds the variant `value` refers to, or returns it unchanged if no variant matches.
+fn resolve_variant<E: de::Error>(
+    value: String,
+    variants: &'static [&'static str],
+) -> Result<String, E> {
+    if variants.contains(&value.as_str()) {
+        return Ok(value);
+    }
+
+    let mut matches = variants
+        .iter()
+        .filter(|variant| variant.eq_ignore_ascii_case(&value));
+
+    match (matches.next(), matches.next()) {
+        (Some(variant), None) => Ok((*variant).to_owned()),
+        (Some(first), Some(second)) => Err(E::custom(format_args!(
+            "ambiguous variant `{}`, matches both `{}` and `{}`",
+            value, first, second
+        ))),
+        (None, _) => Ok(value),
+    }
+}
+
+/// Visitor wrapper that keeps nested values lenient.
+struct Lenient<V>(V);
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for Lenient<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_some(LenientEnums(deserializer))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_newtype_struct(LenientEnums(deserializer))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.0.visit_seq(Lenient(seq))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0.visit_map(Lenient(map))
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.0.visit_enum(data)
+    }
+}
+
+impl<'de, A> SeqAccess<'de> for Lenient<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_element_seed(Lenient(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A> MapAccess<'de> for Lenient<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.0.next_key_seed(Lenient(seed))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(Lenient(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, S> DeserializeSeed<'de> for Lenient<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = S::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.deserialize(LenientEnums(deserializer))
+    }
+}
+
+/// Visitor passed to `deserialize_enum` that rewrites the variant name before handing it on.
+struct EnumVisitor<V> {
+    visitor: V,
+    variants: &'static [&'static str],
+}
+
+impl<'de, V> Visitor<'de> for EnumVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_string(v.to_owned())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        let variant = resolve_variant::<E>(v, self.variants)?;
+        self.visitor.visit_enum(variant.into_deserializer())
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (variant, access) = data.variant::<String>()?;
+        let variant = resolve_variant::<A::Error>(variant, self.variants)?;
+
+        self.visitor.visit_enum(ResolvedEnum { variant, access })
+    }
+}
+
+/// Enum access with the variant name already read and resolved.
+struct ResolvedEnum<A> {
+    variant: String,
+    access: A,
+}
+
+impl<'de, A> EnumAccess<'de> for ResolvedEnum<A>
+where
+    A: de::VariantAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = A;
+
+    fn variant_seed<T>(self, seed: T) -> Result<(T::Value, A), Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let name: StringDeserializer<A::Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(name)?;
+        Ok((variant, self.access))
+    }
+}
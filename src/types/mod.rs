@@ -1,18 +1,52 @@
 //! Common extractors and responders.
 
 // TODO: review visibility
+mod auth;
+mod binary;
+mod body_writer;
+mod csrf;
 mod either;
+pub(crate) mod file;
+mod first_of;
 pub(crate) mod form;
+mod header;
 pub(crate) mod json;
+mod last_modified;
+mod lenient;
 mod path;
+#[cfg(feature = "uuid")]
+mod path_uuid;
 pub(crate) mod payload;
 mod query;
 pub(crate) mod readlines;
+mod redirect;
+mod remaining_payload;
+mod stream;
+mod switch_protocol;
+mod template;
 
-pub use self::either::{Either, EitherExtractError};
+pub use self::auth::{Auth, AuthConfig, BasicAuth, BearerToken, Challenge};
+pub use self::binary::Binary;
+pub use self::body_writer::{body_channel, BodyWriter, ChannelBody};
+pub use self::csrf::{CsrfConfig, CsrfToken, ExpectedCsrfToken};
+pub use self::either::{Either, Either3, Either3ExtractError, EitherExtractError};
+pub use self::first_of::{FirstOf, FirstOfExtractError};
 pub use self::form::{Form, FormConfig};
-pub use self::json::{Json, JsonConfig};
+pub use self::header::{Header, PrefixedHeaders, PrefixedHeadersConfig};
+pub use self::json::{Json, JsonConfig, UntrustedJson};
+pub use self::last_modified::LastModified;
 pub use self::path::{Path, PathConfig};
-pub use self::payload::{Payload, PayloadConfig};
-pub use self::query::{Query, QueryConfig};
+#[cfg(feature = "uuid")]
+pub use self::path_uuid::PathUuid;
+pub use self::payload::{BufferPool, Payload, PayloadBudget, PayloadConfig, PooledBytes};
+pub use self::query::{Query, QueryConfig, QueryWithRest};
 pub use self::readlines::Readlines;
+pub use self::redirect::Redirect;
+pub use self::remaining_payload::{PartialPayload, RemainingPayload};
+pub use self::stream::{StreamResponder, TryStreamBody};
+pub use self::switch_protocol::{SwitchProtocol, Upgraded};
+#[cfg(feature = "askama")]
+pub use self::template::AskamaStream;
+#[cfg(feature = "sailfish")]
+pub use self::template::SailfishStream;
+pub use self::template::{RenderStream, Template};
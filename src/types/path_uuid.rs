@@ -0,0 +1,143 @@
+//! For UUID path segment extractor documentation, see [`PathUuid`].
+
+use std::{fmt, ops};
+
+use actix_http::error::{Error, ErrorInternalServerError, ErrorNotFound};
+use futures_util::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::{dev::Payload, error::PathUuidError, FromRequest, HttpRequest};
+
+/// Extract a [`Uuid`] from a request path segment.
+///
+/// The matched route must have exactly one dynamic segment, which is parsed; use
+/// [`Path`](crate::web::Path) for routes with more segments, which are rejected with
+/// `500 Internal Server Error`. A segment that is not a valid UUID is rejected with
+/// `400 Bad Request` and the offending value in the response body. Requires the `uuid` feature.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+///
+/// #[get("/users/{id}")]
+/// async fn user(id: web::PathUuid) -> String {
+///     format!("User {}", id)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PathUuid(pub Uuid);
+
+impl PathUuid {
+    /// Unwrap into inner `Uuid` value.
+    pub fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+impl ops::Deref for PathUuid {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+impl fmt::Display for PathUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromRequest for PathUuid {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+    type Config = ();
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let mut segments = req.match_info().iter();
+
+        let value = match (segments.next(), segments.next()) {
+            (Some((_, value)), None) => value,
+            (None, _) => {
+                log::debug!(
+                    "PathUuid extractor used on a route without dynamic segments. \
+                     Request path: {:?}",
+                    req.path()
+                );
+                return ready(Err(ErrorNotFound("No path segment to extract UUID from")));
+            }
+            (Some(_), Some(_)) => {
+                log::debug!(
+                    "PathUuid extractor used on a route with more than one dynamic segment. \
+                     Request path: {:?}",
+                    req.path()
+                );
+                return ready(Err(ErrorInternalServerError(
+                    "More than one path segment to extract UUID from",
+                )));
+            }
+        };
+
+        ready(Uuid::parse_str(value).map(PathUuid).map_err(|_| {
+            log::debug!(
+                "Failed during PathUuid extractor parsing. Request path: {:?}",
+                req.path()
+            );
+            PathUuidError::new(value).into()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_router::ResourceDef;
+
+    use super::*;
+    use crate::{http::StatusCode, test::TestRequest};
+
+    #[actix_rt::test]
+    async fn test_valid_uuid() {
+        let mut req = TestRequest::with_uri("/users/67e55044-10b1-426f-9247-bb680e5fe0c8")
+            .to_srv_request();
+        ResourceDef::new("/users/{id}").match_path(req.match_info_mut());
+
+        let (req, mut pl) = req.into_parts();
+        let id = PathUuid::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            id.into_inner(),
+            Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap()
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_malformed_uuid() {
+        let mut req = TestRequest::with_uri("/users/not-a-uuid").to_srv_request();
+        ResourceDef::new("/users/{id}").match_path(req.match_info_mut());
+
+        let (req, mut pl) = req.into_parts();
+        let err = PathUuid::from_request(&req, &mut pl).await.unwrap_err();
+
+        let res = err.as_response_error().error_response();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body = std::str::from_utf8(res.body().bin_ref()).unwrap();
+        assert!(body.contains("not-a-uuid"));
+    }
+
+    #[actix_rt::test]
+    async fn test_multiple_segments() {
+        let mut req = TestRequest::with_uri(
+            "/orgs/67e55044-10b1-426f-9247-bb680e5fe0c8/users/\
+             936da01f-9abd-4d9d-80c7-02af85c822a8",
+        )
+        .to_srv_request();
+        ResourceDef::new("/orgs/{org}/users/{id}").match_path(req.match_info_mut());
+
+        // which segment holds the UUID is ambiguous
+        let (req, mut pl) = req.into_parts();
+        let err = PathUuid::from_request(&req, &mut pl).await.unwrap_err();
+
+        let res = err.as_response_error().error_response();
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
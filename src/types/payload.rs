@@ -1,9 +1,16 @@
 //! Basic binary and string payload extractors.
 
 use std::{
+    cell::RefCell,
     future::Future,
+    mem, ops,
     pin::Pin,
+    rc::Rc,
     str,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
 
@@ -78,6 +85,9 @@ impl FromRequest for Payload {
 ///
 /// Use [`PayloadConfig`] to configure extraction process.
 ///
+/// Requests without a payload, such as those built with `TestRequest::to_http_request`, resolve to
+/// empty bytes without any checks of the configuration.
+///
 /// # Examples
 /// ```
 /// use actix_web::{post, web};
@@ -95,6 +105,10 @@ impl FromRequest for Bytes {
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        if let dev::Payload::None = payload {
+            return Either::Right(ready(Ok(Bytes::new())));
+        }
+
         // allow both Config and Data<Config>
         let cfg = PayloadConfig::from_req(req);
 
@@ -115,6 +129,9 @@ impl FromRequest for Bytes {
 /// [**PayloadConfig**](PayloadConfig) allows to configure
 /// extraction process.
 ///
+/// Requests without a payload, such as those built with `TestRequest::to_http_request`, resolve to
+/// an empty string without any checks of the configuration.
+///
 /// # Examples
 /// ```
 /// use actix_web::{post, web, FromRequest};
@@ -131,6 +148,10 @@ impl FromRequest for String {
 
     #[inline]
     fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        if let dev::Payload::None = payload {
+            return Either::Right(ready(Ok(String::new())));
+        }
+
         let cfg = PayloadConfig::from_req(req);
 
         // check content-type
@@ -181,6 +202,379 @@ fn bytes_to_string(body: Bytes, encoding: &'static Encoding) -> Result<String, E
     }
 }
 
+/// Pool of reusable body buffers used by the [`PooledBytes`] extractor.
+///
+/// Register a pool through one of the `.app_data()` methods. Buffers are handed out to
+/// `PooledBytes` extractions and returned to the pool, with their allocation intact, when the
+/// extracted value is dropped. When no pool is registered, `PooledBytes` falls back to allocating a
+/// fresh buffer for every request.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web, App};
+///
+/// #[post("/")]
+/// async fn index(body: web::PooledBytes) -> String {
+///     format!("Body is {} bytes long", body.len())
+/// }
+///
+/// App::new()
+///     .app_data(web::BufferPool::new(16_384))
+///     .service(index);
+/// ```
+#[derive(Clone)]
+pub struct BufferPool {
+    buffers: Rc<RefCell<Vec<BytesMut>>>,
+    capacity: usize,
+    max_idle: usize,
+}
+
+impl BufferPool {
+    /// Create new pool that hands out buffers with `capacity` bytes preallocated.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Rc::new(RefCell::new(Vec::new())),
+            capacity,
+            max_idle: 64,
+        }
+    }
+
+    /// Set maximum number of idle buffers kept by the pool. By default 64 buffers are kept.
+    pub fn max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Number of idle buffers currently held by the pool.
+    pub fn idle(&self) -> usize {
+        self.buffers.borrow().len()
+    }
+
+    fn acquire(&self) -> BytesMut {
+        self.buffers
+            .borrow_mut()
+            .pop()
+            .unwrap_or_else(|| BytesMut::with_capacity(self.capacity))
+    }
+
+    fn release(&self, mut buf: BytesMut) {
+        let mut buffers = self.buffers.borrow_mut();
+
+        if buffers.len() < self.max_idle {
+            buf.clear();
+            buffers.push(buf);
+        }
+    }
+}
+
+/// Extract binary data from a request's payload into a buffer taken from a [`BufferPool`].
+///
+/// The buffer is returned to the pool when this value is dropped, so hot paths can read request
+/// bodies without reallocating for every request.
+///
+/// Use [`PayloadConfig`] to configure extraction process. Requests without a payload resolve to an
+/// empty buffer, like [`Bytes`].
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web};
+///
+/// #[post("/")]
+/// async fn index(body: web::PooledBytes) -> String {
+///     format!("Body {:?}!", &body[..])
+/// }
+/// ```
+pub struct PooledBytes {
+    buf: BytesMut,
+    pool: Option<BufferPool>,
+}
+
+impl PooledBytes {
+    fn new(pool: Option<BufferPool>) -> Self {
+        let buf = match pool {
+            Some(ref pool) => pool.acquire(),
+            None => BytesMut::with_capacity(8192),
+        };
+
+        PooledBytes { buf, pool }
+    }
+
+    /// Returns the number of bytes the underlying buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
+
+impl ops::Deref for PooledBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl AsRef<[u8]> for PooledBytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf
+    }
+}
+
+impl Drop for PooledBytes {
+    fn drop(&mut self) {
+        if let Some(ref pool) = self.pool {
+            pool.release(mem::take(&mut self.buf));
+        }
+    }
+}
+
+/// See [here](#examples) for example of usage as an extractor.
+impl FromRequest for PooledBytes {
+    type Config = PayloadConfig;
+    type Error = Error;
+    type Future = PooledBytesExtractFut;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        let cfg = PayloadConfig::from_req(req);
+        let pool = req.app_data::<BufferPool>().cloned();
+
+        let mut err = None;
+
+        // requests without payload resolve to an empty buffer, like `Bytes`
+        if !matches!(payload, dev::Payload::None) {
+            err = cfg.check_mimetype(req).err();
+
+            if let Some(l) = req.headers().get(&header::CONTENT_LENGTH) {
+                match l.to_str().ok().and_then(|s| s.parse::<usize>().ok()) {
+                    Some(l) if l > cfg.limit => err = Some(PayloadError::Overflow.into()),
+                    Some(_) => {}
+                    None => err = Some(PayloadError::UnknownLength.into()),
+                }
+            }
+        }
+
+        #[cfg(feature = "compress")]
+        let stream = dev::Decompress::from_headers(payload.take(), req.headers());
+        #[cfg(not(feature = "compress"))]
+        let stream = payload.take();
+
+        PooledBytesExtractFut {
+            stream,
+            body: Some(PooledBytes::new(pool)),
+            limit: cfg.limit,
+            budget: BudgetReservation::new(req),
+            err,
+        }
+    }
+}
+
+pub struct PooledBytesExtractFut {
+    #[cfg(feature = "compress")]
+    stream: dev::Decompress<dev::Payload>,
+    #[cfg(not(feature = "compress"))]
+    stream: dev::Payload,
+    body: Option<PooledBytes>,
+    limit: usize,
+    budget: BudgetReservation,
+    err: Option<Error>,
+}
+
+impl Future for PooledBytesExtractFut {
+    type Output = Result<PooledBytes, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        if let Some(err) = this.err.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        loop {
+            let res = ready!(Pin::new(&mut this.stream).poll_next(cx));
+            match res {
+                Some(chunk) => {
+                    let chunk = chunk?;
+                    let body = this.body.as_mut().unwrap();
+
+                    if body.buf.len() + chunk.len() > this.limit {
+                        return Poll::Ready(Err(PayloadError::Overflow.into()));
+                    } else {
+                        this.budget.reserve(chunk.len())?;
+                        body.buf.extend_from_slice(&chunk);
+                    }
+                }
+                None => {
+                    this.budget.release();
+                    return Poll::Ready(Ok(this.body.take().unwrap()));
+                }
+            }
+        }
+    }
+}
+
+/// Byte budget shared by all requests that buffer their payload.
+///
+/// Payload limits bound the size of a single body, but not the memory used when many requests are
+/// buffered at the same time. Register a budget through one of the `.app_data()` methods and the
+/// built-in body buffering extractors (`Bytes`, `String`, [`PooledBytes`], [`Json`](super::Json)
+/// and [`Form`](super::Form)) take bytes from it as they read the body. Extractions that would
+/// exceed the budget fail with [`PayloadError::BudgetExhausted`], which responds with
+/// `503 Service Unavailable`.
+///
+/// Bytes are returned once the buffered body has been handed out: when `Bytes`, `String` and
+/// `PooledBytes` have read the whole body, and when `Json` and `Form` have deserialized it. They
+/// are also returned when the extraction fails or is dropped. The budget bounds the memory of
+/// bodies being buffered; extracted values kept by handlers afterwards are not counted.
+///
+/// Clones share the same budget, so creating it outside the `HttpServer::new` closure shares it
+/// across all workers.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, App, HttpServer};
+///
+/// let budget = web::PayloadBudget::new(64 * 1024 * 1024);
+///
+/// HttpServer::new(move || {
+///     App::new()
+///         .app_data(budget.clone())
+///         .route("/", web::post().to(|body: web::Bytes| async move { body }))
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct PayloadBudget {
+    inner: Arc<BudgetInner>,
+}
+
+#[derive(Debug)]
+struct BudgetInner {
+    capacity: usize,
+    used: AtomicUsize,
+}
+
+impl PayloadBudget {
+    /// Create budget that allows `capacity` bytes to be buffered at once.
+    pub fn new(capacity: usize) -> Self {
+        PayloadBudget {
+            inner: Arc::new(BudgetInner {
+                capacity,
+                used: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Number of bytes that can currently be buffered.
+    pub fn available(&self) -> usize {
+        self.inner.capacity - self.inner.used.load(Ordering::Acquire)
+    }
+
+    fn try_take(&self, n: usize) -> bool {
+        let mut used = self.inner.used.load(Ordering::Acquire);
+
+        loop {
+            let new = match used.checked_add(n) {
+                Some(new) if new <= self.inner.capacity => new,
+                _ => return false,
+            };
+
+            match self.inner.used.compare_exchange_weak(
+                used,
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    fn give_back(&self, n: usize) {
+        self.inner.used.fetch_sub(n, Ordering::AcqRel);
+    }
+}
+
+/// Returns true if the request headers announce a body that `payload` does not have.
+///
+/// Requests without a body, including those with `Content-Length: 0`, get `Payload::None`; their
+/// payload is empty rather than missing.
+pub(crate) fn payload_missing(req: &HttpRequest, payload: &dev::Payload) -> bool {
+    if !matches!(payload, dev::Payload::None) {
+        return false;
+    }
+
+    let headers = req.headers();
+
+    headers.contains_key(&header::TRANSFER_ENCODING)
+        || headers
+            .get(&header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .map_or(false, |len| {
+                len.trim().parse::<u64>().map_or(true, |len| len > 0)
+            })
+}
+
+/// Returns true if `payload` has no body and the request does not declare an empty one with
+/// `Content-Length: 0`.
+///
+/// Unlike [`payload_missing`], requests without length headers also count as having no body.
+pub(crate) fn payload_absent(req: &HttpRequest, payload: &dev::Payload) -> bool {
+    if !matches!(payload, dev::Payload::None) {
+        return false;
+    }
+
+    let headers = req.headers();
+
+    headers.contains_key(&header::TRANSFER_ENCODING)
+        || headers
+            .get(&header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .map_or(true, |len| len.trim().parse::<u64>() != Ok(0))
+}
+
+/// Bytes taken from the request's [`PayloadBudget`] by a buffering extractor.
+///
+/// Returns them to the budget when released or dropped.
+pub(crate) struct BudgetReservation {
+    budget: Option<PayloadBudget>,
+    reserved: usize,
+}
+
+impl BudgetReservation {
+    pub(crate) fn new(req: &HttpRequest) -> Self {
+        BudgetReservation {
+            budget: req.app_data::<PayloadBudget>().cloned(),
+            reserved: 0,
+        }
+    }
+
+    /// Takes `n` more bytes from the budget, if one is registered.
+    pub(crate) fn reserve(&mut self, n: usize) -> Result<(), PayloadError> {
+        if let Some(ref budget) = self.budget {
+            if !budget.try_take(n) {
+                return Err(PayloadError::BudgetExhausted);
+            }
+
+            self.reserved += n;
+        }
+
+        Ok(())
+    }
+
+    /// Returns all reserved bytes to the budget.
+    pub(crate) fn release(&mut self) {
+        if let Some(ref budget) = self.budget {
+            budget.give_back(mem::take(&mut self.reserved));
+        }
+    }
+}
+
+impl Drop for BudgetReservation {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
 /// Configuration for request payloads.
 ///
 /// Applies to the built-in `Bytes` and `String` extractors. Note that the `Payload` extractor does
@@ -273,6 +667,7 @@ pub struct HttpMessageBody {
     #[cfg(not(feature = "compress"))]
     stream: dev::Payload,
     buf: BytesMut,
+    budget: BudgetReservation,
     err: Option<PayloadError>,
 }
 
@@ -308,6 +703,7 @@ impl HttpMessageBody {
             limit: DEFAULT_CONFIG_LIMIT,
             length,
             buf: BytesMut::with_capacity(8192),
+            budget: BudgetReservation::new(req),
             err,
         }
     }
@@ -344,10 +740,14 @@ impl Future for HttpMessageBody {
                     if this.buf.len() + chunk.len() > this.limit {
                         return Poll::Ready(Err(PayloadError::Overflow));
                     } else {
+                        this.budget.reserve(chunk.len())?;
                         this.buf.extend_from_slice(&chunk);
                     }
                 }
-                None => return Poll::Ready(Ok(this.buf.split().freeze())),
+                None => {
+                    this.budget.release();
+                    return Poll::Ready(Ok(this.buf.split().freeze()));
+                }
             }
         }
     }
@@ -356,6 +756,7 @@ impl Future for HttpMessageBody {
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use futures_util::StreamExt as _;
 
     use super::*;
     use crate::http::{header, StatusCode};
@@ -477,6 +878,45 @@ mod tests {
         assert_eq!(s, "hello=world");
     }
 
+    #[actix_rt::test]
+    async fn test_pooled_bytes_reuses_buffer() {
+        let pool = BufferPool::new(1024);
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(pool.clone())
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+        let body = PooledBytes::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(&body[..], b"hello=world");
+
+        let ptr = body.as_ptr();
+        let capacity = body.capacity();
+        assert!(capacity >= 1024);
+
+        drop(body);
+        assert_eq!(pool.idle(), 1);
+
+        let (req, mut pl) = TestRequest::default()
+            .app_data(pool.clone())
+            .set_payload(Bytes::from_static(b"bye"))
+            .to_http_parts();
+        let body = PooledBytes::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(&body[..], b"bye");
+        assert_eq!(body.as_ptr(), ptr);
+        assert_eq!(body.capacity(), capacity);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_pooled_bytes_limit() {
+        let (req, mut pl) = TestRequest::default()
+            .app_data(PayloadConfig::new(4))
+            .set_payload(Bytes::from_static(b"hello=world"))
+            .to_http_parts();
+        let res = PooledBytes::from_request(&req, &mut pl).await;
+        assert!(res.is_err());
+    }
+
     #[actix_rt::test]
     async fn test_message_body() {
         let (req, mut pl) = TestRequest::default()
@@ -514,4 +954,69 @@ mod tests {
             _ => unreachable!("error"),
         }
     }
+
+    #[actix_rt::test]
+    async fn test_no_payload() {
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_LENGTH, "10"))
+            .app_data(PayloadConfig::default().mimetype(mime::APPLICATION_JSON))
+            .to_http_request();
+
+        let bytes = Bytes::extract(&req).await.unwrap();
+        assert!(bytes.is_empty());
+
+        let text = String::extract(&req).await.unwrap();
+        assert!(text.is_empty());
+
+        let pooled = PooledBytes::extract(&req).await.unwrap();
+        assert!(pooled.is_empty());
+
+        let mut payload = Payload::extract(&req).await.unwrap();
+        assert!(payload.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_payload_budget() {
+        let budget = PayloadBudget::new(10);
+
+        // first upload stalls after taking 8 bytes of the budget
+        let (mut sender, inner) = actix_http::h1::Payload::create(false);
+        let (req, _) = TestRequest::default()
+            .app_data(budget.clone())
+            .to_http_parts();
+        let mut pl = dev::Payload::from(inner);
+        let mut first = Box::pin(Bytes::from_request(&req, &mut pl));
+
+        sender.feed_data(Bytes::from_static(b"12345678"));
+        let pending = futures_util::future::poll_fn(|cx| {
+            Poll::Ready(first.as_mut().poll(cx).is_pending())
+        })
+        .await;
+        assert!(pending);
+        assert_eq!(budget.available(), 2);
+
+        // concurrent second upload does not fit in what is left
+        let (req, mut pl) = TestRequest::default()
+            .app_data(budget.clone())
+            .set_payload(Bytes::from_static(b"abcdefgh"))
+            .to_http_parts();
+        let err = Bytes::from_request(&req, &mut pl).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        sender.feed_eof();
+        assert_eq!(first.await.unwrap(), Bytes::from_static(b"12345678"));
+        assert_eq!(budget.available(), 10);
+
+        // budget is returned once the first upload is read
+        let (req, mut pl) = TestRequest::default()
+            .app_data(budget.clone())
+            .set_payload(Bytes::from_static(b"abcdefgh"))
+            .to_http_parts();
+        let body = Bytes::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(body, Bytes::from_static(b"abcdefgh"));
+        assert_eq!(budget.available(), 10);
+    }
 }
@@ -4,8 +4,15 @@ use std::{fmt, ops, sync::Arc};
 
 use futures_util::future::{err, ok, Ready};
 use serde::de;
+use url::form_urlencoded;
 
-use crate::{dev::Payload, error::QueryPayloadError, Error, FromRequest, HttpRequest};
+use crate::{
+    dev::Payload,
+    error::QueryPayloadError,
+    extract::{ExtractionErrorKind, ExtractionReporter},
+    types::lenient::{from_urlencoded, LenientEnums},
+    Error, FromRequest, HttpRequest,
+};
 
 /// Extract typed information from the request's query.
 ///
@@ -123,14 +130,35 @@ where
 
     #[inline]
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        let error_handler = req
+        let (error_handler, lenient_enums) = req
             .app_data::<Self::Config>()
-            .map(|c| c.err_handler.clone())
-            .unwrap_or(None);
+            .map(|c| (c.err_handler.clone(), c.lenient_enums))
+            .unwrap_or((None, false));
 
-        serde_urlencoded::from_str::<T>(req.query_string())
-            .map(|val| ok(Query(val)))
+        let reporter = ExtractionReporter::from_req(req);
+
+        from_urlencoded::<T>(req.query_string().as_bytes(), lenient_enums)
+            .map(|val| {
+                if let Some(ref reporter) = reporter {
+                    let query = form_urlencoded::parse(req.query_string().as_bytes());
+                    let query = serde_urlencoded::Deserializer::new(query);
+
+                    if lenient_enums {
+                        reporter.unknown_fields::<T, _>(LenientEnums(query));
+                    } else {
+                        reporter.unknown_fields::<T, _>(query);
+                    }
+
+                    reporter.success::<Self>();
+                }
+
+                ok(Query(val))
+            })
             .unwrap_or_else(move |e| {
+                if let Some(ref reporter) = reporter {
+                    reporter.failure::<Self>(ExtractionErrorKind::Deserialize);
+                }
+
                 let e = QueryPayloadError::Deserialize(e);
 
                 log::debug!(
@@ -150,6 +178,127 @@ where
     }
 }
 
+/// Extract typed information from the request's query, keeping the parameters `T` does not use.
+///
+/// `T` is deserialized like with [`Query`]. Every `key=value` pair whose key is not a field of `T`
+/// is collected, decoded and in request order, into the second field. This is useful for routes
+/// that act on some parameters and forward the rest. [`QueryConfig`] applies to this extractor
+/// too.
+///
+/// Successes and failures are reported to a registered
+/// [`ExtractionMetrics`](crate::web::ExtractionMetrics) hook. The collected parameters are part of
+/// the extracted value, so they are not reported as unknown fields.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Paging {
+///     page: u32,
+/// }
+///
+/// // `/search?page=2&q=rust&lang=en` forwards `q=rust&lang=en`
+/// #[get("/search")]
+/// async fn search(query: web::QueryWithRest<Paging>) -> String {
+///     let (paging, rest) = query.into_inner();
+///     format!("page {} with {} forwarded parameters", paging.page, rest.len())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryWithRest<T>(pub T, pub Vec<(String, String)>);
+
+impl<T> QueryWithRest<T> {
+    /// Unwrap into the typed parameters and the remaining pairs.
+    pub fn into_inner(self) -> (T, Vec<(String, String)>) {
+        (self.0, self.1)
+    }
+}
+
+impl<T> ops::Deref for QueryWithRest<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for QueryWithRest<T>
+where
+    T: de::DeserializeOwned,
+{
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+    type Config = QueryConfig;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let (error_handler, lenient_enums) = req
+            .app_data::<Self::Config>()
+            .map(|c| (c.err_handler.clone(), c.lenient_enums))
+            .unwrap_or((None, false));
+
+        let reporter = ExtractionReporter::from_req(req);
+
+        let query = req.query_string().as_bytes();
+        let mut unknown = Vec::new();
+
+        let res = {
+            let de = serde_urlencoded::Deserializer::new(form_urlencoded::parse(query));
+
+            // only top level keys can be left over by a query string
+            let on_ignored = |path: serde_ignored::Path<'_>| {
+                if let serde_ignored::Path::Map {
+                    parent: serde_ignored::Path::Root,
+                    key,
+                } = path
+                {
+                    unknown.push(key);
+                }
+            };
+
+            if lenient_enums {
+                serde_ignored::deserialize(LenientEnums(de), on_ignored)
+            } else {
+                serde_ignored::deserialize(de, on_ignored)
+            }
+        };
+
+        match res {
+            Ok(val) => {
+                let rest = form_urlencoded::parse(query)
+                    .filter(|(key, _)| unknown.iter().any(|k| k == key))
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+
+                if let Some(ref reporter) = reporter {
+                    reporter.success::<Self>();
+                }
+
+                ok(QueryWithRest(val, rest))
+            }
+            Err(e) => {
+                if let Some(ref reporter) = reporter {
+                    reporter.failure::<Self>(ExtractionErrorKind::Deserialize);
+                }
+
+                let e = QueryPayloadError::Deserialize(e);
+
+                log::debug!(
+                    "Failed during QueryWithRest extractor deserialization. \
+                     Request path: {:?}",
+                    req.path()
+                );
+
+                err(match error_handler {
+                    Some(error_handler) => (error_handler)(e, req),
+                    None => e.into(),
+                })
+            }
+        }
+    }
+}
+
 /// Query extractor configuration.
 ///
 /// # Examples
@@ -182,6 +331,7 @@ where
 #[derive(Clone)]
 pub struct QueryConfig {
     err_handler: Option<Arc<dyn Fn(QueryPayloadError, &HttpRequest) -> Error + Send + Sync>>,
+    lenient_enums: bool,
 }
 
 impl QueryConfig {
@@ -193,11 +343,24 @@ impl QueryConfig {
         self.err_handler = Some(Arc::new(f));
         self
     }
+
+    /// Match enum variants case-insensitively.
+    ///
+    /// Unit variants and their `#[serde(alias)]` names are matched ignoring ASCII case and
+    /// deserialized as the variant they name. Input that matches several variants fails with
+    /// [`QueryPayloadError::Deserialize`] naming the candidates. Disabled by default.
+    pub fn lenient_enums(mut self, lenient: bool) -> Self {
+        self.lenient_enums = lenient;
+        self
+    }
 }
 
 impl Default for QueryConfig {
     fn default() -> Self {
-        QueryConfig { err_handler: None }
+        QueryConfig {
+            err_handler: None,
+            lenient_enums: false,
+        }
     }
 }
 
@@ -251,6 +414,52 @@ mod tests {
         assert_eq!(s.id, "test1");
     }
 
+    #[actix_rt::test]
+    async fn test_extract_without_payload() {
+        let req = TestRequest::with_uri("/name/user1/?id=test").to_http_request();
+        let s = Query::<Id>::extract(&req).await.unwrap();
+        assert_eq!(s.id, "test");
+
+        let req = TestRequest::with_uri("/name/user1/").to_http_request();
+        assert!(Query::<Id>::extract(&req).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_query_with_rest() {
+        let req =
+            TestRequest::with_uri("/?q=rust&id=test&lang=en&q=web%20dev").to_srv_request();
+        let (req, mut pl) = req.into_parts();
+
+        let query = QueryWithRest::<Id>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert_eq!(query.id, "test");
+
+        let (id, rest) = query.into_inner();
+        assert_eq!(id.id, "test");
+        assert_eq!(
+            rest,
+            vec![
+                ("q".to_owned(), "rust".to_owned()),
+                ("lang".to_owned(), "en".to_owned()),
+                ("q".to_owned(), "web dev".to_owned()),
+            ]
+        );
+
+        let req = TestRequest::with_uri("/?id=test").to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        let query = QueryWithRest::<Id>::from_request(&req, &mut pl)
+            .await
+            .unwrap();
+        assert!(query.1.is_empty());
+
+        let req = TestRequest::with_uri("/?q=rust").to_srv_request();
+        let (req, mut pl) = req.into_parts();
+        assert!(QueryWithRest::<Id>::from_request(&req, &mut pl)
+            .await
+            .is_err());
+    }
+
     #[actix_rt::test]
     #[should_panic]
     async fn test_tuple_panic() {
@@ -284,4 +493,78 @@ mod tests {
             StatusCode::UNPROCESSABLE_ENTITY
         );
     }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Color {
+        Red,
+        #[serde(alias = "verde")]
+        Green,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Paint {
+        color: Color,
+        name: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    enum Direction {
+        Up,
+        #[allow(clippy::upper_case_acronyms)]
+        UP,
+    }
+
+    #[derive(Deserialize, Debug)]
+    struct Move {
+        #[allow(dead_code)]
+        dir: Direction,
+    }
+
+    #[actix_rt::test]
+    async fn test_lenient_enums() {
+        let extract = |uri: &str, lenient: bool| {
+            let (req, mut pl) = TestRequest::with_uri(uri)
+                .app_data(QueryConfig::default().lenient_enums(lenient))
+                .to_http_parts();
+
+            async move { Query::<Paint>::from_request(&req, &mut pl).await }
+        };
+
+        // strict by default
+        assert!(extract("/?color=RED&name=x", false).await.is_err());
+
+        let paint = extract("/?color=rED&name=ReD", true).await.unwrap();
+        assert_eq!(
+            paint.into_inner(),
+            Paint {
+                color: Color::Red,
+                name: "ReD".to_owned()
+            }
+        );
+
+        let paint = extract("/?color=VERDE&name=x", true).await.unwrap();
+        assert_eq!(paint.color, Color::Green);
+
+        assert!(extract("/?color=blue&name=x", true).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_lenient_enums_ambiguous() {
+        let (req, mut pl) = TestRequest::with_uri("/?dir=up")
+            .app_data(QueryConfig::default().lenient_enums(true))
+            .to_http_parts();
+
+        let err = Query::<Move>::from_request(&req, &mut pl)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("ambiguous variant `up`, matches both `Up` and `UP`"));
+
+        // exact matches are not ambiguous
+        let (req, mut pl) = TestRequest::with_uri("/?dir=UP")
+            .app_data(QueryConfig::default().lenient_enums(true))
+            .to_http_parts();
+        assert!(Query::<Move>::from_request(&req, &mut pl).await.is_ok());
+    }
 }
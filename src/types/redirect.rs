@@ -0,0 +1,143 @@
+//! For redirect responder documentation, see [`Redirect`].
+
+use std::borrow::Cow;
+
+use crate::{
+    http::{header::LOCATION, StatusCode},
+    HttpRequest, HttpResponse, Responder,
+};
+
+/// Responder that redirects the client to another location.
+///
+/// Responds with `307 Temporary Redirect` by default, which keeps the request method and body.
+///
+/// Targets that are absolute paths, like `/login`, get the external path prefix of the request
+/// prepended when the application trusts the `X-Forwarded-Prefix` header of a reverse proxy; see
+/// [`App::trust_forwarded_prefix`](crate::App::trust_forwarded_prefix). Relative paths and full
+/// urls are used as is. Targets that are not valid header values, such as ones containing line
+/// breaks, are never sent; the response is a `500 Internal Server Error` instead.
+///
+/// # Examples
+/// ```
+/// use actix_web::{get, web, Responder};
+///
+/// #[get("/account")]
+/// async fn account() -> impl Responder {
+///     // redirects to `/app/login` when mounted under a trusted `/app` prefix
+///     web::Redirect::to("/login").see_other()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    to: Cow<'static, str>,
+    status: StatusCode,
+}
+
+impl Redirect {
+    /// Creates temporary redirect to `to`.
+    pub fn to(to: impl Into<Cow<'static, str>>) -> Self {
+        Redirect {
+            to: to.into(),
+            status: StatusCode::TEMPORARY_REDIRECT,
+        }
+    }
+
+    /// Uses `308 Permanent Redirect` status.
+    pub fn permanent(self) -> Self {
+        self.using_status_code(StatusCode::PERMANENT_REDIRECT)
+    }
+
+    /// Uses `307 Temporary Redirect` status.
+    pub fn temporary(self) -> Self {
+        self.using_status_code(StatusCode::TEMPORARY_REDIRECT)
+    }
+
+    /// Uses `303 See Other` status, which makes the client follow the redirect with a `GET`.
+    pub fn see_other(self) -> Self {
+        self.using_status_code(StatusCode::SEE_OTHER)
+    }
+
+    /// Uses a custom status code.
+    ///
+    /// The status should be one of the 3xx redirection codes.
+    pub fn using_status_code(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+impl Responder for Redirect {
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        let location = if self.to.starts_with('/') && !self.to.starts_with("//") {
+            format!("{}{}", req.connection_info().prefix(), self.to)
+        } else {
+            self.to.into_owned()
+        };
+
+        HttpResponse::build(self.status)
+            .insert_header((LOCATION, location))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::header::HeaderValue,
+        test::{call_service, init_service, TestRequest},
+        web, App,
+    };
+
+    #[actix_rt::test]
+    async fn test_redirect_forwarded_prefix() {
+        let srv = |trust| {
+            init_service(
+                App::new()
+                    .trust_forwarded_prefix(trust)
+                    .route("/", web::get().to(|| async { Redirect::to("/login") })),
+            )
+        };
+
+        let req = || {
+            TestRequest::default()
+                .insert_header(("x-forwarded-prefix", "/app"))
+                .to_request()
+        };
+
+        let srv_untrusted = srv(false).await;
+        let res = call_service(&srv_untrusted, req()).await;
+        assert_eq!(res.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "/login");
+
+        let srv_trusted = srv(true).await;
+        let res = call_service(&srv_trusted, req()).await;
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "/app/login");
+    }
+
+    #[test]
+    fn test_redirect_status_and_target() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = Redirect::to("https://rust-lang.org")
+            .permanent()
+            .respond_to(&req);
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            res.headers().get(LOCATION),
+            Some(&HeaderValue::from_static("https://rust-lang.org"))
+        );
+
+        let res = Redirect::to("/x").permanent().respond_to(&req);
+        assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "/x");
+
+        let res = Redirect::to("next").see_other().respond_to(&req);
+        assert_eq!(res.status(), StatusCode::SEE_OTHER);
+        assert_eq!(res.headers().get(LOCATION).unwrap(), "next");
+
+        let res = Redirect::to("/x\r\nSet-Cookie: a=b").respond_to(&req);
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!res.headers().contains_key(LOCATION));
+    }
+}
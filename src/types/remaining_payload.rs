@@ -0,0 +1,254 @@
+//! For remaining payload extractor documentation, see [`RemainingPayload`].
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use actix_http::error::PayloadError;
+use bytes::Bytes;
+use futures_core::stream::Stream;
+use futures_util::future::{ready, Ready};
+
+use crate::{dev, Error, FromRequest, HttpRequest};
+
+/// Extract the part of a request's payload stream not consumed by preceding extractors.
+///
+/// Extractors of a handler's argument tuple are set up in order, so `RemainingPayload` must come
+/// *after* every extractor that reads from the body. Most body extractors, such as `Json` or
+/// `Bytes`, consume the entire payload; when placed before `RemainingPayload` it yields nothing.
+///
+/// Extractors that only read the beginning of the body call [`RemainingPayload::split`] instead of
+/// taking the payload. The remaining stream is then held back until the [`PartialPayload`]
+/// returned by `split` is dropped, and data pushed back with [`PartialPayload::unread`] is
+/// yielded first. Keeping the `PartialPayload` alive while reading the remaining payload will
+/// never make progress.
+///
+/// # Examples
+/// ```
+/// use actix_web::{post, web};
+/// use futures_util::stream::StreamExt as _;
+///
+/// #[post("/upload/{name}")]
+/// async fn upload(
+///     name: web::Path<String>,
+///     mut body: web::RemainingPayload,
+/// ) -> actix_web::Result<String> {
+///     let mut size = 0;
+///     while let Some(chunk) = body.next().await {
+///         size += chunk?.len();
+///     }
+///
+///     Ok(format!("{} is {} bytes", name, size))
+/// }
+/// ```
+pub struct RemainingPayload(dev::Payload);
+
+impl RemainingPayload {
+    /// Takes the payload for reading its beginning, leaving the rest for later extractors.
+    ///
+    /// The payload left behind yields nothing until the returned [`PartialPayload`] is dropped.
+    pub fn split(payload: &mut dev::Payload) -> PartialPayload {
+        let shared = Rc::new(RefCell::new(Shared {
+            payload: payload.take(),
+            unread: VecDeque::new(),
+            released: false,
+            waker: None,
+        }));
+
+        *payload = dev::Payload::Stream(Box::pin(Rest(Rc::clone(&shared))));
+
+        PartialPayload(shared)
+    }
+
+    /// Unwrap to inner Payload type.
+    pub fn into_inner(self) -> dev::Payload {
+        self.0
+    }
+}
+
+impl Stream for RemainingPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    #[inline]
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.0).poll_next(cx)
+    }
+}
+
+impl FromRequest for RemainingPayload {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+    type Config = ();
+
+    #[inline]
+    fn from_request(_: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+        ready(Ok(RemainingPayload(payload.take())))
+    }
+}
+
+/// Beginning of a payload split off by [`RemainingPayload::split`].
+///
+/// Dropping it hands the unread rest of the payload to the [`RemainingPayload`] extractor.
+pub struct PartialPayload(Rc<RefCell<Shared>>);
+
+impl PartialPayload {
+    /// Pushes back data that was read but not consumed.
+    ///
+    /// The data is yielded again before the rest of the payload.
+    pub fn unread(&mut self, data: Bytes) {
+        if !data.is_empty() {
+            self.0.borrow_mut().unread.push_front(data);
+        }
+    }
+}
+
+impl Stream for PartialPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.borrow_mut().poll_next(cx)
+    }
+}
+
+impl Drop for PartialPayload {
+    fn drop(&mut self) {
+        let waker = {
+            let mut shared = self.0.borrow_mut();
+            shared.released = true;
+            shared.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+struct Shared {
+    payload: dev::Payload,
+    unread: VecDeque<Bytes>,
+    released: bool,
+    waker: Option<Waker>,
+}
+
+impl Shared {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, PayloadError>>> {
+        match self.unread.pop_front() {
+            Some(data) => Poll::Ready(Some(Ok(data))),
+            None => Pin::new(&mut self.payload).poll_next(cx),
+        }
+    }
+}
+
+/// Payload stream left behind by [`RemainingPayload::split`].
+struct Rest(Rc<RefCell<Shared>>);
+
+impl Stream for Rest {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.0.borrow_mut();
+
+        if shared.released {
+            shared.poll_next(cx)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+    use futures_util::{future::LocalBoxFuture, FutureExt as _, StreamExt as _};
+
+    use super::*;
+    use crate::{
+        test::{call_service, init_service, read_body, TestRequest},
+        web, App, HttpResponse,
+    };
+
+    /// Reads the first line of the body, leaving the rest of the payload untouched.
+    struct FirstLine(String);
+
+    impl FromRequest for FirstLine {
+        type Error = Error;
+        type Future = LocalBoxFuture<'static, Result<Self, Error>>;
+        type Config = ();
+
+        fn from_request(_: &HttpRequest, payload: &mut dev::Payload) -> Self::Future {
+            let mut head = RemainingPayload::split(payload);
+
+            async move {
+                let mut line = BytesMut::new();
+
+                while let Some(chunk) = head.next().await {
+                    let mut chunk = chunk?;
+
+                    if let Some(pos) = chunk.iter().position(|&b| b == b'\n') {
+                        line.extend_from_slice(&chunk.split_to(pos));
+                        head.unread(chunk.split_off(1));
+                        break;
+                    }
+
+                    line.extend_from_slice(&chunk);
+                }
+
+                Ok(FirstLine(String::from_utf8_lossy(&line).into_owned()))
+            }
+            .boxed_local()
+        }
+    }
+
+    async fn collect(mut body: RemainingPayload) -> Result<Bytes, Error> {
+        let mut buf = BytesMut::new();
+        while let Some(chunk) = body.next().await {
+            buf.extend_from_slice(&chunk?);
+        }
+        Ok(buf.freeze())
+    }
+
+    #[actix_rt::test]
+    async fn test_remaining_after_partial_read() {
+        let srv = init_service(App::new().route(
+            "/",
+            web::post().to(|line: FirstLine, body: RemainingPayload| async move {
+                let rest = collect(body).await?;
+                Ok::<_, Error>(HttpResponse::Ok().body(format!("{}|{:?}", line.0, rest)))
+            }),
+        ))
+        .await;
+
+        let req = TestRequest::post()
+            .uri("/")
+            .set_payload(Bytes::from_static(b"name\nrest of body"))
+            .to_request();
+        let res = call_service(&srv, req).await;
+        assert_eq!(
+            read_body(res).await,
+            Bytes::from_static(b"name|b\"rest of body\"")
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_remaining_whole_payload() {
+        let (req, mut pl) = TestRequest::default()
+            .set_payload(Bytes::from_static(b"whole body"))
+            .to_http_parts();
+
+        let body = RemainingPayload::from_request(&req, &mut pl).await.unwrap();
+        assert_eq!(
+            collect(body).await.unwrap(),
+            Bytes::from_static(b"whole body")
+        );
+
+        // nothing is left for extractors that come after
+        let body = RemainingPayload::from_request(&req, &mut pl).await.unwrap();
+        assert!(collect(body).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+//! For stream response helpers, see [`StreamResponder`] and [`TryStreamBody`].
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use mime::Mime;
+use pin_project::pin_project;
+
+use crate::{Error, HttpRequest, HttpResponse, Responder};
+
+/// Responder that streams the items of a byte stream as a chunked response body.
+///
+/// Saves reaching for [`HttpResponseBuilder::streaming`](crate::dev::HttpResponseBuilder) when a
+/// handler only needs to return a stream. The content type defaults to
+/// `application/octet-stream`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, Error, Responder};
+/// use futures_util::{stream, StreamExt as _};
+///
+/// async fn index() -> impl Responder {
+///     let lines = stream::iter(vec!["one\n", "two\n"])
+///         .map(|line| Ok::<_, Error>(web::Bytes::from_static(line.as_bytes())));
+///
+///     web::StreamResponder::new(lines).content_type(mime::TEXT_PLAIN_UTF_8)
+/// }
+/// ```
+pub struct StreamResponder<S> {
+    stream: S,
+    content_type: Mime,
+}
+
+impl<S> StreamResponder<S> {
+    /// Creates stream responder with `application/octet-stream` content type.
+    pub fn new(stream: S) -> Self {
+        StreamResponder {
+            stream,
+            content_type: mime::APPLICATION_OCTET_STREAM,
+        }
+    }
+
+    /// Sets content type of the response.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Unwraps into the response stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, E> Responder for StreamResponder<S>
+where
+    S: Stream<Item = Result<Bytes, E>> + 'static,
+    E: Into<Error> + 'static,
+{
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        let stream: Pin<Box<S>> = Box::pin(self.stream);
+
+        HttpResponse::Ok()
+            .content_type(self.content_type)
+            .streaming(stream)
+    }
+}
+
+/// Responder that streams a fallible byte stream, ending the body cleanly at the first error.
+///
+/// With [`StreamResponder`], an error item aborts the connection. Here the error is logged and the
+/// response body is terminated as if the stream had ended, so clients see a well-formed but
+/// truncated response and the connection can be reused. This suits sources like database cursors
+/// that can fail part way through. A [`truncation_marker`](Self::truncation_marker) can be set to
+/// be written as the last chunk of a truncated body.
+///
+/// The content type defaults to `application/octet-stream`.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, Error, Responder};
+/// use futures_util::stream;
+///
+/// async fn index() -> impl Responder {
+///     let rows = stream::iter(vec![
+///         Ok::<_, Error>(web::Bytes::from_static(b"row 1\n")),
+///         Err(actix_web::error::ErrorInternalServerError("cursor closed")),
+///     ]);
+///
+///     web::TryStreamBody::new(rows)
+///         .content_type(mime::TEXT_PLAIN_UTF_8)
+///         .truncation_marker("#truncated\n")
+/// }
+/// ```
+pub struct TryStreamBody<S> {
+    stream: S,
+    content_type: Mime,
+    marker: Option<Bytes>,
+}
+
+impl<S> TryStreamBody<S> {
+    /// Creates stream body with `application/octet-stream` content type.
+    pub fn new(stream: S) -> Self {
+        TryStreamBody {
+            stream,
+            content_type: mime::APPLICATION_OCTET_STREAM,
+            marker: None,
+        }
+    }
+
+    /// Sets content type of the response.
+    pub fn content_type(mut self, content_type: Mime) -> Self {
+        self.content_type = content_type;
+        self
+    }
+
+    /// Sets bytes written as the last chunk when the body is truncated by an error.
+    pub fn truncation_marker(mut self, marker: impl Into<Bytes>) -> Self {
+        self.marker = Some(marker.into());
+        self
+    }
+
+    /// Unwraps into the response stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Responder for TryStreamBody<S>
+where
+    S: Stream<Item = Result<Bytes, Error>> + 'static,
+{
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        let stream = Truncating {
+            stream: Some(self.stream),
+            marker: self.marker,
+        };
+
+        HttpResponse::Ok()
+            .content_type(self.content_type)
+            .streaming(Box::pin(stream))
+    }
+}
+
+/// Stream that ends at the first error of the inner stream.
+#[pin_project]
+struct Truncating<S> {
+    #[pin]
+    stream: Option<S>,
+    marker: Option<Bytes>,
+}
+
+impl<S> Stream for Truncating<S>
+where
+    S: Stream<Item = Result<Bytes, Error>>,
+{
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        let stream = match this.stream.as_mut().as_pin_mut() {
+            Some(stream) => stream,
+            None => return Poll::Ready(None),
+        };
+
+        match ready!(stream.poll_next(cx)) {
+            Some(Ok(chunk)) => Poll::Ready(Some(Ok(chunk))),
+            Some(Err(err)) => {
+                log::debug!("Truncating response body after stream error: {}", err);
+                this.stream.set(None);
+                Poll::Ready(this.marker.take().map(Ok))
+            }
+            None => {
+                this.stream.set(None);
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+    use crate::{
+        http::{header::CONTENT_TYPE, StatusCode},
+        test::TestRequest,
+    };
+
+    #[actix_rt::test]
+    async fn test_stream_responder() {
+        let req = TestRequest::default().to_http_request();
+
+        let chunks = stream::iter(vec!["one", "two", "three"])
+            .map(|chunk| Ok::<_, Error>(Bytes::from_static(chunk.as_bytes())));
+
+        let mut res = StreamResponder::new(chunks)
+            .content_type(mime::TEXT_PLAIN_UTF_8)
+            .respond_to(&req);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "one");
+        assert_eq!(body.next().await.unwrap().unwrap(), "two");
+        assert_eq!(body.next().await.unwrap().unwrap(), "three");
+        assert!(body.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_try_stream_body_truncates() {
+        let req = TestRequest::default().to_http_request();
+
+        let items = || {
+            stream::iter(vec![
+                Ok(Bytes::from_static(b"one")),
+                Err(crate::error::ErrorInternalServerError("cursor closed")),
+                Ok(Bytes::from_static(b"three")),
+            ])
+        };
+
+        let mut res = TryStreamBody::new(items()).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "one");
+        assert!(body.next().await.is_none());
+        assert!(body.next().await.is_none());
+
+        let mut res = TryStreamBody::new(items())
+            .truncation_marker("#truncated")
+            .respond_to(&req);
+
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "one");
+        assert_eq!(body.next().await.unwrap().unwrap(), "#truncated");
+        assert!(body.next().await.is_none());
+    }
+}
@@ -0,0 +1,75 @@
+//! For protocol upgrade responder, see [`SwitchProtocol`].
+
+use std::future::Future;
+
+pub use actix_http::h1::Upgraded;
+use actix_http::http::header::IntoHeaderValue;
+
+use crate::{HttpRequest, HttpResponse, Responder};
+
+/// Responder that switches the connection to another protocol.
+///
+/// Responds with `101 Switching Protocols` and an `Upgrade` header naming the new protocol. Once
+/// the response has been written, the raw connection is passed to the handler as an [`Upgraded`]
+/// IO object; the connection is closed when the future returned by the handler completes.
+///
+/// Upgrades are only supported on HTTP/1.1 connections. Data sent by the client before it has
+/// received the `101` response may be consumed as request payload and is not visible to the
+/// handler.
+///
+/// # Examples
+/// ```
+/// use actix_web::{web, Responder};
+///
+/// async fn upgrade() -> impl Responder {
+///     web::SwitchProtocol::new("my-protocol", |io: web::Upgraded| async move {
+///         // speak the new protocol over `io`
+///         # drop(io);
+///     })
+/// }
+/// ```
+pub struct SwitchProtocol {
+    res: HttpResponse,
+}
+
+impl SwitchProtocol {
+    /// Creates responder that switches to `protocol` and hands the connection to `handler`.
+    pub fn new<V, F, Fut>(protocol: V, handler: F) -> Self
+    where
+        V: IntoHeaderValue,
+        F: FnOnce(Upgraded) -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        let res = HttpResponse::SwitchingProtocols()
+            .upgrade(protocol)
+            .on_upgrade(handler)
+            .finish();
+
+        SwitchProtocol { res }
+    }
+}
+
+impl Responder for SwitchProtocol {
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        self.res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        http::{header, StatusCode},
+        test::TestRequest,
+    };
+
+    #[actix_rt::test]
+    async fn test_switch_protocol_response() {
+        let req = TestRequest::default().to_http_request();
+
+        let res = SwitchProtocol::new("echo", |_| async {}).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(res.headers().get(header::UPGRADE).unwrap(), "echo");
+        assert!(res.head().upgrade());
+    }
+}
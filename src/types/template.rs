@@ -0,0 +1,405 @@
+//! For streaming template documentation, see [`Template`] and [`RenderStream`].
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_http::error::ErrorInternalServerError;
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use pin_project::pin_project;
+
+use crate::{Error, HttpRequest, HttpResponse, Responder};
+
+/// Template renderer that produces its output in chunks.
+///
+/// Implemented by streaming template engines, or by adapters for engines that render to a string
+/// such as [`AskamaStream`] and [`SailfishStream`]. Return a renderer from a handler by wrapping it
+/// in a [`Template`].
+pub trait RenderStream {
+    /// Error returned when rendering fails.
+    type Error: fmt::Display;
+
+    /// Attempts to render the next chunk of output, returning `None` once rendering is complete.
+    fn poll_render(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>>;
+}
+
+/// Responder that streams the output of a [`RenderStream`] as an HTML page.
+///
+/// Chunks are sent as they are rendered using chunked transfer encoding, so large pages do not
+/// need to be buffered. The content type is `text/html; charset=utf-8`.
+///
+/// By the time rendering fails the response head has been sent, so the error can not change the
+/// status. It is logged and the body fails, which closes the connection without completing the
+/// response so that clients can tell the page is truncated. In debug builds an HTML comment
+/// describing the error is written before the body fails, to make truncated pages easy to spot.
+///
+/// # Examples
+/// ```
+/// use std::{pin::Pin, task::{Context, Poll}};
+/// use actix_web::web::{self, Bytes, RenderStream};
+///
+/// struct Rows(u32);
+///
+/// impl RenderStream for Rows {
+///     type Error = std::convert::Infallible;
+///
+///     fn poll_render(
+///         mut self: Pin<&mut Self>,
+///         _: &mut Context<'_>,
+///     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+///         if self.0 == 0 {
+///             return Poll::Ready(None);
+///         }
+///
+///         self.0 -= 1;
+///         Poll::Ready(Some(Ok(Bytes::from(format!("<p>row {}</p>", self.0)))))
+///     }
+/// }
+///
+/// async fn index() -> web::Template<Rows> {
+///     web::Template::new(Rows(1000))
+/// }
+/// ```
+pub struct Template<R> {
+    renderer: R,
+}
+
+impl<R> Template<R> {
+    /// Creates responder streaming the output of `renderer`.
+    pub fn new(renderer: R) -> Self {
+        Template { renderer }
+    }
+
+    /// Unwraps into the renderer.
+    pub fn into_inner(self) -> R {
+        self.renderer
+    }
+}
+
+impl<R> Responder for Template<R>
+where
+    R: RenderStream + 'static,
+{
+    fn respond_to(self, _: &HttpRequest) -> HttpResponse {
+        let body = Rendering {
+            renderer: Some(self.renderer),
+            error: None,
+        };
+
+        HttpResponse::Ok()
+            .content_type(mime::TEXT_HTML_UTF_8)
+            .streaming(Box::pin(body))
+    }
+}
+
+/// Stream of rendered chunks that fails at the first rendering error.
+#[pin_project]
+struct Rendering<R> {
+    #[pin]
+    renderer: Option<R>,
+    error: Option<Error>,
+}
+
+impl<R: RenderStream> Stream for Rendering<R> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        let renderer = match this.renderer.as_mut().as_pin_mut() {
+            Some(renderer) => renderer,
+            None => return Poll::Ready(None),
+        };
+
+        match ready!(renderer.poll_render(cx)) {
+            Some(Ok(chunk)) => Poll::Ready(Some(Ok(chunk))),
+            Some(Err(err)) => {
+                log::error!("Template rendering failed mid-stream: {}", err);
+                this.renderer.set(None);
+
+                let msg = err.to_string();
+                let error =
+                    ErrorInternalServerError(format!("Template rendering failed: {}", msg));
+
+                if cfg!(debug_assertions) {
+                    *this.error = Some(error);
+
+                    // `--` would end the comment early
+                    let comment = format!(
+                        "<!-- template rendering failed: {} -->",
+                        msg.replace("--", "- -")
+                    );
+                    Poll::Ready(Some(Ok(Bytes::from(comment))))
+                } else {
+                    Poll::Ready(Some(Err(error)))
+                }
+            }
+            None => {
+                this.renderer.set(None);
+                Poll::Ready(None)
+            }
+        }
+    }
+}
+
+/// Size of the chunks rendered output of non-streaming engines is split into.
+#[cfg(any(feature = "askama", feature = "sailfish"))]
+const CHUNK_SIZE: usize = 8192;
+
+/// Splits the next chunk off the front of `rendered`.
+#[cfg(any(feature = "askama", feature = "sailfish"))]
+fn next_chunk(rendered: &mut Bytes) -> Option<Bytes> {
+    if rendered.is_empty() {
+        None
+    } else {
+        let len = rendered.len().min(CHUNK_SIZE);
+        Some(rendered.split_to(len))
+    }
+}
+
+/// [`RenderStream`] adapter for [askama](https://docs.rs/askama) templates.
+///
+/// Askama renders to a string, so the template is rendered when the first chunk is polled and
+/// then sent in chunks.
+#[cfg(feature = "askama")]
+pub struct AskamaStream<T> {
+    template: Option<T>,
+    rendered: Bytes,
+}
+
+#[cfg(feature = "askama")]
+impl<T: askama::Template> AskamaStream<T> {
+    /// Creates renderer for `template`.
+    pub fn new(template: T) -> Self {
+        AskamaStream {
+            template: Some(template),
+            rendered: Bytes::new(),
+        }
+    }
+}
+
+#[cfg(feature = "askama")]
+impl<T: askama::Template + Unpin> RenderStream for AskamaStream<T> {
+    type Error = askama::Error;
+
+    fn poll_render(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(template) = this.template.take() {
+            match template.render() {
+                Ok(rendered) => this.rendered = Bytes::from(rendered),
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+
+        Poll::Ready(next_chunk(&mut this.rendered).map(Ok))
+    }
+}
+
+/// [`RenderStream`] adapter for [sailfish](https://docs.rs/sailfish) templates.
+///
+/// Sailfish renders to a string, so the template is rendered when the first chunk is polled and
+/// then sent in chunks.
+#[cfg(feature = "sailfish")]
+pub struct SailfishStream<T> {
+    template: Option<T>,
+    rendered: Bytes,
+}
+
+#[cfg(feature = "sailfish")]
+impl<T: sailfish::TemplateOnce> SailfishStream<T> {
+    /// Creates renderer for `template`.
+    pub fn new(template: T) -> Self {
+        SailfishStream {
+            template: Some(template),
+            rendered: Bytes::new(),
+        }
+    }
+}
+
+#[cfg(feature = "sailfish")]
+impl<T: sailfish::TemplateOnce + Unpin> RenderStream for SailfishStream<T> {
+    type Error = sailfish::runtime::RenderError;
+
+    fn poll_render(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(template) = this.template.take() {
+            match template.render_once() {
+                Ok(rendered) => this.rendered = Bytes::from(rendered),
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+
+        Poll::Ready(next_chunk(&mut this.rendered).map(Ok))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use futures_util::StreamExt as _;
+
+    use super::*;
+    use crate::{
+        http::{header::CONTENT_TYPE, StatusCode},
+        test::TestRequest,
+    };
+
+    /// Renderer yielding a fixed list of chunks and errors.
+    struct FakeRenderer(VecDeque<Result<&'static str, &'static str>>);
+
+    impl RenderStream for FakeRenderer {
+        type Error = &'static str;
+
+        fn poll_render(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+            Poll::Ready(self.0.pop_front().map(|res| res.map(Bytes::from_static)))
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_template_streams_chunks() {
+        let req = TestRequest::default().to_http_request();
+
+        let renderer = FakeRenderer(vec![Ok("<html>"), Ok("<p>hi</p>"), Ok("</html>")].into());
+        let mut res = Template::new(renderer).respond_to(&req);
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8"
+        );
+
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "<html>");
+        assert_eq!(body.next().await.unwrap().unwrap(), "<p>hi</p>");
+        assert_eq!(body.next().await.unwrap().unwrap(), "</html>");
+        assert!(body.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_template_mid_stream_failure() {
+        let req = TestRequest::default().to_http_request();
+
+        let renderer =
+            FakeRenderer(vec![Ok("<html>"), Err("missing -- field"), Ok("</html>")].into());
+        let mut res = Template::new(renderer).respond_to(&req);
+
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "<html>");
+
+        if cfg!(debug_assertions) {
+            assert_eq!(
+                body.next().await.unwrap().unwrap(),
+                "<!-- template rendering failed: missing - - field -->"
+            );
+        }
+
+        let err = body.next().await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("missing -- field"));
+        assert!(body.next().await.is_none());
+    }
+
+    /// Value whose rendering always fails.
+    #[cfg(any(feature = "askama", feature = "sailfish"))]
+    struct Broken;
+
+    /// Reads the body of a response whose rendering failed, returning the error.
+    #[cfg(any(feature = "askama", feature = "sailfish"))]
+    async fn render_error(mut res: HttpResponse) -> Error {
+        let mut body = res.take_body();
+
+        if cfg!(debug_assertions) {
+            let comment = body.next().await.unwrap().unwrap();
+            assert!(comment.starts_with(b"<!-- template rendering failed"));
+        }
+
+        let err = body.next().await.unwrap().unwrap_err();
+        assert!(body.next().await.is_none());
+        err
+    }
+
+    #[cfg(feature = "askama")]
+    impl fmt::Display for Broken {
+        fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[cfg(feature = "askama")]
+    #[actix_rt::test]
+    async fn test_askama_stream() {
+        #[derive(askama::Template)]
+        #[template(source = "<p>{{ name }}</p>", ext = "html")]
+        struct Page<T: fmt::Display> {
+            name: T,
+        }
+
+        let req = TestRequest::default().to_http_request();
+
+        let template = AskamaStream::new(Page { name: "world" });
+        let mut res = Template::new(template).respond_to(&req);
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "<p>world</p>");
+        assert!(body.next().await.is_none());
+
+        let template = AskamaStream::new(Page { name: Broken });
+        let res = Template::new(template).respond_to(&req);
+        let err = render_error(res).await;
+        assert!(err.to_string().starts_with("Template rendering failed"));
+    }
+
+    #[cfg(feature = "sailfish")]
+    impl sailfish::runtime::Render for Broken {
+        fn render(
+            &self,
+            _: &mut sailfish::runtime::Buffer,
+        ) -> Result<(), sailfish::runtime::RenderError> {
+            Err(sailfish::runtime::RenderError::Msg(
+                "broken value".to_owned(),
+            ))
+        }
+    }
+
+    #[cfg(feature = "sailfish")]
+    #[actix_rt::test]
+    async fn test_sailfish_stream() {
+        #[derive(sailfish::TemplateOnce)]
+        #[template(path = "template_stream.stpl")]
+        struct Page<T: sailfish::runtime::Render> {
+            name: T,
+        }
+
+        let req = TestRequest::default().to_http_request();
+
+        let template = SailfishStream::new(Page { name: "world" });
+        let mut res = Template::new(template).respond_to(&req);
+        let mut body = res.take_body();
+        assert_eq!(body.next().await.unwrap().unwrap(), "<p>world</p>");
+        assert!(body.next().await.is_none());
+
+        let template = SailfishStream::new(Page { name: Broken });
+        let res = Template::new(template).respond_to(&req);
+        let err = render_error(res).await;
+        assert!(err.to_string().contains("broken value"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use actix_http::http::Method;
 use actix_router::IntoPattern;
+use futures_util::future::Either;
 use std::future::Future;
 
 pub use actix_http::Response as HttpResponse;
@@ -16,11 +17,20 @@ use crate::route::Route;
 use crate::scope::Scope;
 use crate::service::WebService;
 
+pub use crate::authorization::{AuthContext, Decision, Permissions};
+pub use crate::blocking::BlockingConfig;
 pub use crate::config::ServiceConfig;
 pub use crate::data::Data;
+pub use crate::extract::{
+    ExtractionCounter, ExtractionCounts, ExtractionDebug, ExtractionErrorKind,
+    ExtractionFailure, ExtractionMetrics,
+};
 pub use crate::request::HttpRequest;
 pub use crate::request_data::ReqData;
+pub use crate::responder::ResponsePostProcessors;
+pub use crate::sharded::{Sharded, ShardedHandle};
 pub use crate::types::*;
+pub use crate::FirstOf;
 
 /// Create resource for a specific path.
 ///
@@ -275,11 +285,21 @@ pub fn service<T: IntoPattern>(path: T) -> WebService {
 
 /// Execute blocking function on a thread pool, returns future that resolves
 /// to result of the function execution.
+///
+/// When called while a handler runs, the function is subject to the [`BlockingConfig`]
+/// registered closest to the handler: it waits for a free slot and fails with
+/// [`BlockingError::Overloaded`] when the configured limits are exceeded. Functions started
+/// elsewhere, e.g. from spawned tasks, are not limited; use [`BlockingConfig::block`] there.
 pub fn block<F, R>(f: F) -> impl Future<Output = Result<R, BlockingError>>
 where
     F: FnOnce() -> R + Send + 'static,
     R: Send + 'static,
 {
-    let fut = actix_rt::task::spawn_blocking(f);
-    async { fut.await.map_err(|_| BlockingError) }
+    match BlockingConfig::current() {
+        Some(config) => Either::Left(config.block(f)),
+        None => {
+            let fut = actix_rt::task::spawn_blocking(f);
+            Either::Right(async { fut.await.map_err(|_| BlockingError::Gone) })
+        }
+    }
 }
@@ -0,0 +1,87 @@
+//! Runs in its own test binary since it sends a shutdown signal to the whole process.
+
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::{net, process, thread, time::Duration};
+
+use actix_web::{test, web, App, HttpResponse, HttpServer};
+
+#[test]
+fn test_tasks_cancelled_on_graceful_shutdown() {
+    let addr = test::unused_addr();
+    let runs = Arc::new(AtomicUsize::new(0));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+
+    let server = {
+        let runs = runs.clone();
+        let stopped = stopped.clone();
+
+        thread::spawn(move || {
+            let sys = actix_rt::System::new();
+
+            sys.block_on(async move {
+                let srv = HttpServer::new(|| {
+                    App::new().route(
+                        "/slow",
+                        web::get().to(|| async {
+                            actix_rt::time::sleep(Duration::from_secs(2)).await;
+                            HttpResponse::Ok().finish()
+                        }),
+                    )
+                })
+                .workers(1)
+                .shutdown_timeout(10)
+                .spawn_periodic(Duration::from_millis(50), move || {
+                    let runs = runs.clone();
+                    async move {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                    }
+                })
+                .bind(addr)
+                .unwrap()
+                .run();
+
+                tx.send(()).unwrap();
+                srv.await.unwrap();
+                stopped.store(true, Ordering::SeqCst);
+            });
+        })
+    };
+
+    rx.recv().unwrap();
+    thread::sleep(Duration::from_millis(300));
+    assert!(runs.load(Ordering::SeqCst) > 0);
+
+    // request in flight keeps graceful shutdown going
+    let mut stream = net::TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    let status = process::Command::new("kill")
+        .arg("-TERM")
+        .arg(process::id().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // tasks are cancelled while the server is still shutting down
+    thread::sleep(Duration::from_millis(300));
+    let cancelled_at = runs.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(runs.load(Ordering::SeqCst), cancelled_at);
+    assert!(!stopped.load(Ordering::SeqCst));
+
+    // in-flight request is still completed
+    let mut res = String::new();
+    stream.read_to_string(&mut res).unwrap();
+    assert!(res.starts_with("HTTP/1.1 200 OK"));
+
+    server.join().unwrap();
+    assert!(stopped.load(Ordering::SeqCst));
+}
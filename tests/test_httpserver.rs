@@ -1,5 +1,6 @@
+use std::io::{Read, Write};
 use std::sync::mpsc;
-use std::{thread, time::Duration};
+use std::{net, thread, time::Duration};
 
 #[cfg(feature = "openssl")]
 extern crate tls_openssl as openssl;
@@ -71,6 +72,149 @@ async fn test_start() {
     let _ = sys.stop();
 }
 
+#[actix_rt::test]
+async fn test_worker_recycle() {
+    let addr = test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let sys = actix_rt::System::new();
+
+        sys.block_on(async {
+            let srv = HttpServer::new(|| {
+                App::new().service(
+                    web::resource("/").route(web::to(|| HttpResponse::Ok().body("test"))),
+                )
+            })
+            .workers(2)
+            .worker_max_requests(2)
+            .disable_signals()
+            .bind(format!("{}", addr))
+            .unwrap();
+
+            let stats = srv.worker_stats();
+            let srv = srv.run();
+
+            let _ = tx.send((srv, stats, actix_rt::System::current()));
+        });
+
+        let _ = sys.run();
+    });
+    let (srv, stats, sys) = rx.recv().unwrap();
+
+    // every request uses a new connection; none may be dropped while workers are recycled
+    for _ in 0..10 {
+        let mut stream = net::TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+
+        let mut res = String::new();
+        stream.read_to_string(&mut res).unwrap();
+        assert!(
+            res.starts_with("HTTP/1.1 200 OK"),
+            "unexpected response: {}",
+            res
+        );
+        assert!(res.ends_with("test"));
+    }
+
+    // both workers are replaced, one at a time
+    assert!(stats.recycles() >= 2);
+
+    // stop
+    let _ = srv.stop(false);
+
+    thread::sleep(Duration::from_millis(100));
+    let _ = sys.stop();
+}
+
+#[actix_rt::test]
+async fn test_sharded_data() {
+    use std::cell::Cell;
+
+    #[derive(Default)]
+    struct Hits(Cell<usize>);
+
+    let addr = test::unused_addr();
+    let (tx, rx) = mpsc::channel();
+    let handle = web::ShardedHandle::<Hits>::new();
+
+    let shards = handle.clone();
+    thread::spawn(move || {
+        let sys = actix_rt::System::new();
+
+        sys.block_on(async {
+            let srv = HttpServer::new(move || {
+                App::new()
+                    .sharded_data(&shards, Hits::default)
+                    .route(
+                        "/",
+                        web::get().to(|hits: web::Sharded<Hits>| {
+                            hits.0.set(hits.0.get() + 1);
+                            HttpResponse::Ok()
+                        }),
+                    )
+                    .route(
+                        "/total",
+                        web::get().to(|hits: web::Sharded<Hits>| async move {
+                            let (shards, total): (usize, usize) = web::Sharded::collect(
+                                hits.handle(),
+                                |(shards, total), hits| (shards + 1, total + hits.0.get()),
+                            )
+                            .await;
+
+                            format!("{}/{}", total, shards)
+                        }),
+                    )
+            })
+            .workers(2)
+            .disable_signals()
+            .bind(format!("{}", addr))
+            .unwrap()
+            .run();
+
+            let _ = tx.send((srv, actix_rt::System::current()));
+        });
+
+        let _ = sys.run();
+    });
+    let (srv, sys) = rx.recv().unwrap();
+
+    let get = |path: &str| {
+        let mut stream = net::TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+
+        let mut res = String::new();
+        stream.read_to_string(&mut res).unwrap();
+        assert!(
+            res.starts_with("HTTP/1.1 200 OK"),
+            "unexpected response: {}",
+            res
+        );
+        res
+    };
+
+    // every request uses a new connection, spreading them over both workers
+    for _ in 0..10 {
+        get("/");
+    }
+
+    assert_eq!(handle.len(), 2);
+    assert!(get("/total").ends_with("10/2"));
+
+    // stop
+    let _ = srv.stop(false);
+
+    thread::sleep(Duration::from_millis(100));
+    let _ = sys.stop();
+}
+
 #[cfg(feature = "openssl")]
 fn ssl_acceptor() -> std::io::Result<SslAcceptorBuilder> {
     use openssl::{
@@ -151,3 +295,124 @@ async fn test_start_ssl() {
     thread::sleep(Duration::from_millis(100));
     let _ = sys.stop();
 }
+
+#[actix_rt::test]
+async fn test_bind_addr_in_use() {
+    use actix_web::BindErrorKind;
+
+    let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap();
+
+    let errs = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .bind(addr)
+        .err()
+        .unwrap();
+
+    assert_eq!(errs.errors().len(), 1);
+    assert_eq!(errs.errors()[0].addr, Some(addr));
+    assert!(matches!(errs.errors()[0].kind, BindErrorKind::AddrInUse));
+
+    let err = std::io::Error::from(errs);
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+}
+
+#[actix_rt::test]
+#[cfg(feature = "openssl")]
+async fn test_bind_openssl_reports_all_errors() {
+    use actix_web::BindErrorKind;
+    use openssl::{pkey::PKey, rsa::Rsa};
+
+    let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap();
+
+    // private key that does not belong to the certificate
+    let mut builder = ssl_acceptor().unwrap();
+    let other_key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+    builder.set_private_key(&other_key).unwrap();
+
+    let errs = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .bind_openssl(addr, builder)
+        .err()
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].addr, Some(addr));
+    assert!(matches!(errs[0].kind, BindErrorKind::AddrInUse));
+    assert_eq!(errs[1].addr, None);
+    assert!(matches!(errs[1].kind, BindErrorKind::Tls(_)));
+}
+
+#[actix_rt::test]
+#[cfg(feature = "rustls")]
+async fn test_bind_rustls_reports_all_errors() {
+    use actix_web::BindErrorKind;
+    use rustls::{Certificate, NoClientAuth, PrivateKey, ServerConfig};
+
+    let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap();
+
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let other = rcgen::generate_simple_self_signed(vec!["localhost".to_owned()]).unwrap();
+    let rustls_config = |key: &rcgen::Certificate| {
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config
+            .set_single_cert(
+                vec![Certificate(cert.serialize_der().unwrap())],
+                PrivateKey(key.serialize_private_key_der()),
+            )
+            .unwrap();
+        config
+    };
+
+    // private key that does not belong to the certificate
+    let errs = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .bind_rustls(addr, rustls_config(&other))
+        .err()
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].addr, Some(addr));
+    assert!(matches!(errs[0].kind, BindErrorKind::AddrInUse));
+    assert_eq!(errs[1].addr, None);
+    assert!(matches!(errs[1].kind, BindErrorKind::Tls(_)));
+
+    let srv = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .bind_rustls("127.0.0.1:0", rustls_config(&cert))
+        .unwrap();
+    assert_eq!(srv.addrs_with_scheme()[0].1, "https");
+}
+
+#[actix_rt::test]
+async fn test_collect_bind_errors() {
+    use actix_web::BindErrorKind;
+
+    let taken = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let taken2 = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = taken.local_addr().unwrap();
+    let addr2 = taken2.local_addr().unwrap();
+
+    let srv = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .collect_bind_errors()
+        .bind(addr)
+        .unwrap()
+        .bind("127.0.0.1:0")
+        .unwrap()
+        .bind(addr2)
+        .unwrap();
+    assert_eq!(srv.addrs().len(), 1);
+
+    let errs = srv.check_binds().err().unwrap().into_inner();
+    assert_eq!(errs.len(), 2);
+    assert_eq!(errs[0].addr, Some(addr));
+    assert!(matches!(errs[0].kind, BindErrorKind::AddrInUse));
+    assert_eq!(errs[1].addr, Some(addr2));
+    assert!(matches!(errs[1].kind, BindErrorKind::AddrInUse));
+
+    let srv = HttpServer::new(|| App::new().route("/", web::to(HttpResponse::Ok)))
+        .collect_bind_errors()
+        .bind("127.0.0.1:0")
+        .unwrap();
+    assert!(srv.check_binds().is_ok());
+}
@@ -154,6 +154,46 @@ async fn test_body_gzip() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 }
 
+#[actix_rt::test]
+async fn test_body_gzip_save_data() {
+    let srv = test::start_with(test::config().h1(), || {
+        App::new()
+            .wrap(Compress::new(ContentEncoding::Br).honor_save_data(true))
+            .service(web::resource("/").route(web::to(|| HttpResponse::Ok().body(STR))))
+    });
+
+    // client does not accept the configured encoding
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .append_header((ACCEPT_ENCODING, "gzip"))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert!(!response.headers().contains_key(CONTENT_ENCODING));
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+
+    // save-data hint makes the middleware fall back to an accepted encoding
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .append_header((ACCEPT_ENCODING, "gzip"))
+        .append_header(("save-data", "on"))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+
+    let bytes = response.body().await.unwrap();
+    let mut e = GzDecoder::new(&bytes[..]);
+    let mut dec = Vec::new();
+    e.read_to_end(&mut dec).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+}
+
 #[actix_rt::test]
 async fn test_body_gzip2() {
     let srv = test::start_with(test::config().h1(), || {
@@ -350,6 +390,68 @@ async fn test_body_chunked_implicit() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 }
 
+#[actix_rt::test]
+async fn test_body_gzip_unsized() {
+    let srv = test::start_with(test::config().h1(), || {
+        App::new()
+            .wrap(Compress::new(ContentEncoding::Gzip))
+            .service(web::resource("/").route(web::get().to(move || {
+                // content length of the uncompressed body is stale once compressed
+                HttpResponse::Ok()
+                    .insert_header((CONTENT_LENGTH, STR.len()))
+                    .streaming(TestBody::new(Bytes::from_static(STR.as_ref()), 24))
+            })))
+    });
+
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .append_header((ACCEPT_ENCODING, "gzip"))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert!(!response.headers().contains_key(CONTENT_LENGTH));
+    assert_eq!(
+        response.headers().get(TRANSFER_ENCODING).unwrap(),
+        &b"chunked"[..]
+    );
+
+    // read response
+    let bytes = response.body().await.unwrap();
+
+    // decode
+    let mut e = GzDecoder::new(&bytes[..]);
+    let mut dec = Vec::new();
+    e.read_to_end(&mut dec).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+}
+
+#[actix_rt::test]
+async fn test_body_gzip_unsized_disabled() {
+    let srv = test::start_with(test::config().h1(), || {
+        App::new()
+            .wrap(Compress::new(ContentEncoding::Gzip).compress_unsized(false))
+            .service(web::resource("/").route(web::get().to(move || {
+                HttpResponse::Ok()
+                    .streaming(TestBody::new(Bytes::from_static(STR.as_ref()), 24))
+            })))
+    });
+
+    let mut response = srv
+        .get("/")
+        .no_decompress()
+        .append_header((ACCEPT_ENCODING, "gzip"))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert!(!response.headers().contains_key(CONTENT_ENCODING));
+
+    let bytes = response.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
 #[actix_rt::test]
 async fn test_body_br_streaming() {
     let srv = test::start_with(test::config().h1(), || {
@@ -907,3 +1009,131 @@ async fn test_normalize() {
     let response = srv.get("/one/").send().await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[actix_rt::test]
+async fn test_switch_protocol_echo() {
+    use std::{net, time::Duration};
+
+    use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
+    use futures_util::future::poll_fn;
+
+    let srv = test::start_with(test::config().h1(), || {
+        App::new().service(web::resource("/echo").to(|| async {
+            web::SwitchProtocol::new("echo", |mut io: web::Upgraded| async move {
+                let mut buf = [0; 1];
+                let mut read_buf = ReadBuf::new(&mut buf);
+                poll_fn(|cx| Pin::new(&mut io).poll_read(cx, &mut read_buf))
+                    .await
+                    .unwrap();
+                let data = read_buf.filled().to_vec();
+
+                poll_fn(|cx| Pin::new(&mut io).poll_write(cx, &data))
+                    .await
+                    .unwrap();
+                poll_fn(|cx| Pin::new(&mut io).poll_flush(cx))
+                    .await
+                    .unwrap();
+            })
+        }))
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+        .write_all(b"GET /echo HTTP/1.1\r\nconnection: upgrade\r\nupgrade: echo\r\n\r\n")
+        .unwrap();
+
+    let mut head = Vec::new();
+    let mut byte = [0; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        stream.read_exact(&mut byte).unwrap();
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8(head).unwrap().to_lowercase();
+    assert!(head.starts_with("http/1.1 101 switching protocols\r\n"));
+    assert!(head.contains("upgrade: echo\r\n"));
+
+    // bytes written after the handshake reach the handler and are echoed back
+    stream.write_all(b"x").unwrap();
+    let mut data = Vec::new();
+    stream.read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"x");
+}
+
+#[actix_rt::test]
+async fn test_keep_alive_policy() {
+    use actix_web::{dev::KeepAlivePolicy, Responder as _};
+
+    let srv = test::start_with(test::config().h1(), || {
+        App::new()
+            .service(
+                web::scope("/events")
+                    .app_data(KeepAlivePolicy::Timeout(60))
+                    .route("", web::get().to(HttpResponse::Ok)),
+            )
+            .service(
+                web::scope("/api")
+                    .app_data(KeepAlivePolicy::Timeout(2))
+                    .route("", web::get().to(HttpResponse::Ok))
+                    .route(
+                        "/close",
+                        web::get()
+                            .to(|| async { "bye".with_extension(KeepAlivePolicy::Close) }),
+                    ),
+            )
+    });
+
+    let res = srv.get("/events").send().await.unwrap();
+    assert_eq!(res.headers().get("keep-alive").unwrap(), "timeout=60");
+
+    let res = srv.get("/api").send().await.unwrap();
+    assert_eq!(res.headers().get("keep-alive").unwrap(), "timeout=2");
+
+    // the response's own policy wins over the scope's
+    let res = srv.get("/api/close").send().await.unwrap();
+    assert!(res.headers().get("keep-alive").is_none());
+    assert_eq!(res.headers().get("connection").unwrap(), "close");
+}
+
+#[actix_rt::test]
+async fn test_form_content_length_zero() {
+    use std::{collections::HashMap, net};
+
+    let srv = test::start_with(test::config().h1(), || {
+        App::new().route(
+            "/",
+            web::post().to(|form: web::Form<HashMap<String, String>>| {
+                HttpResponse::Ok().body(form.len().to_string())
+            }),
+        )
+    });
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST / HTTP/1.1\r\n\
+          Content-Type: application/x-www-form-urlencoded\r\n\
+          Content-Length: 0\r\n\
+          Connection: close\r\n\
+          \r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK"));
+    assert!(data.ends_with("\r\n\r\n0"));
+
+    // content type is still checked for empty bodies
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST / HTTP/1.1\r\n\
+          Content-Type: text/plain\r\n\
+          Content-Length: 0\r\n\
+          Connection: close\r\n\
+          \r\n",
+    );
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 400 Bad Request"));
+    assert!(data.contains("Content type error"));
+}